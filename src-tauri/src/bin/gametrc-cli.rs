@@ -0,0 +1,514 @@
+// gametrc-cli.rs — Headless companion to the desktop app.
+//
+// Works directly against the same games.db the app uses, so the library can be
+// scripted or managed over SSH:
+//
+//   gametrc-cli list --status Playing
+//   gametrc-cli search zelda --sort Rating --desc
+//   gametrc-cli add "Hollow Knight" --platform PC --status Backlog --genre Metroidvania
+//   gametrc-cli export --output library.json
+//   gametrc-cli export --format csv --status Completed > completed.csv
+//   gametrc-cli export --format markdown --output ~/Vault/Games.md
+//   gametrc-cli export --format html --platform Switch --status Wishlist --sort Title -o wishlist.html
+//
+// Pass `--db <path>` (or set GAMETRC_DB) to point at a different database.
+//
+// RUST NOTE: `clap`'s derive API turns these structs/enums into a full argument
+// parser — field names become flags and doc comments become `--help` text.
+
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rusqlite::Connection;
+
+use gametrc_lib::db;
+use gametrc_lib::export;
+use gametrc_lib::error::{CmdResult, CommandError};
+use gametrc_lib::filesync;
+use gametrc_lib::images;
+use gametrc_lib::models::{
+    ControllerSupport, CoopMode, DeckStatus, Game, GameInput, GameStatus, HtmlExportOptions, InputMethod, MarkdownGrouping,
+    PlaytimeUnit, ProtonTier, QueryField, SearchFilter, SortField,
+};
+
+#[derive(Parser)]
+#[command(name = "gametrc-cli", version, about = "Manage your GameTrc library from the terminal")]
+struct Cli {
+    /// Path to games.db (defaults to the desktop app's database)
+    #[arg(long, env = "GAMETRC_DB", global = true)]
+    db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List games, most recently updated first
+    List {
+        /// Only show games with this status (e.g. Playing, Backlog)
+        #[arg(long, value_parser = parse_status)]
+        status: Option<GameStatus>,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search by title, franchise and notes
+    Search {
+        query: String,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add a game to the library
+    Add {
+        title: String,
+        #[arg(long, default_value = "PC")]
+        platform: String,
+        #[arg(long, value_parser = parse_status, default_value = "Backlog")]
+        status: GameStatus,
+        #[arg(long)]
+        franchise: Option<String>,
+        /// Release date as YYYY-MM-DD
+        #[arg(long)]
+        release_date: Option<String>,
+        /// Repeat for several genres: --genre RPG --genre Action
+        #[arg(long)]
+        genre: Vec<String>,
+        /// 1 – 10
+        #[arg(long)]
+        rating: Option<f64>,
+        /// Playtime in hours (stored to the minute)
+        #[arg(long)]
+        hours: Option<f64>,
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// Export games (to stdout unless --output is given). Takes the same
+    /// filters as `search`, so only the matching games are written.
+    Export {
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Only export games whose title, franchise or notes contain this
+        #[arg(long)]
+        query: Option<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+}
+
+/// The SearchFilter fields, shared by every command that selects games.
+#[derive(Args)]
+struct FilterArgs {
+    /// Fields the query searches, comma-separated: Title, Franchise, Notes,
+    /// Developer, Publisher (default: Title,Franchise,Notes)
+    #[arg(long = "in", value_parser = parse_query_field, value_delimiter = ',')]
+    query_fields: Vec<QueryField>,
+    /// Typo-tolerant title matching for the query, best match first
+    #[arg(long)]
+    fuzzy: bool,
+    /// Only games with one of these statuses, comma-separated (e.g. Playing,Backlog)
+    #[arg(long, value_parser = parse_status, value_delimiter = ',')]
+    status: Vec<GameStatus>,
+    /// Only games on one of these platforms, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    platform: Vec<String>,
+    /// Matches part of the franchise name
+    #[arg(long)]
+    franchise: Option<String>,
+    /// Only games with one of these genres, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    genre: Vec<String>,
+    #[arg(long)]
+    min_rating: Option<f64>,
+    #[arg(long)]
+    max_rating: Option<f64>,
+    /// Released in or after this year
+    #[arg(long)]
+    min_year: Option<i32>,
+    /// Released in or before this year
+    #[arg(long)]
+    max_year: Option<i32>,
+    #[arg(long)]
+    min_hours: Option<f64>,
+    #[arg(long)]
+    max_hours: Option<f64>,
+    /// Progress percent, 0 – 100
+    #[arg(long)]
+    min_progress: Option<f64>,
+    #[arg(long)]
+    max_progress: Option<f64>,
+    /// Added on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    added_after: Option<String>,
+    /// Added on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    added_before: Option<String>,
+    /// Completed on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    completed_after: Option<String>,
+    /// Completed on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    completed_before: Option<String>,
+    /// Leave out games with these statuses (comma-separated)
+    #[arg(long, value_parser = parse_status, value_delimiter = ',')]
+    exclude_status: Vec<GameStatus>,
+    /// Leave out games on these platforms (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    exclude_platform: Vec<String>,
+    /// Leave out games with any of these genres (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    exclude_genre: Vec<String>,
+    /// Only games found by the installed-games scan
+    #[arg(long)]
+    installed: bool,
+    /// Borked, Bronze, Silver, Gold or Platinum — this tier or better on ProtonDB
+    #[arg(long, value_parser = parse_tier)]
+    min_protondb_tier: Option<ProtonTier>,
+    /// Unsupported, Playable or Verified — this Steam Deck status or better
+    #[arg(long, value_parser = parse_deck_status)]
+    min_deck_status: Option<DeckStatus>,
+    /// Full, Partial or None
+    #[arg(long, value_parser = parse_controller)]
+    controller_support: Option<ControllerSupport>,
+    /// KeyboardMouse, Gamepad, Wheel, Touch or Other
+    #[arg(long, value_parser = parse_input)]
+    input_method: Option<InputMethod>,
+    /// None, Local, Online or LocalAndOnline (Local also matches LocalAndOnline)
+    #[arg(long, value_parser = parse_coop)]
+    coop: Option<CoopMode>,
+    /// At least this many local (couch) players
+    #[arg(long)]
+    min_local_players: Option<i32>,
+    /// At least this many online players
+    #[arg(long)]
+    min_online_players: Option<i32>,
+    /// Only games with a session played with this person
+    #[arg(long)]
+    played_with: Option<String>,
+    /// Only games with a playthrough tagged with this challenge (e.g. Nuzlocke)
+    #[arg(long)]
+    challenge: Option<String>,
+    /// Also list archived games
+    #[arg(long)]
+    include_archived: bool,
+    /// Only favorite games
+    #[arg(long)]
+    favorite: bool,
+    /// Title, Franchise, ReleaseDate, Rating, PlaytimeHours, ProgressPercent, UpdatedAt, CreatedAt, CompletedAt, SequenceInFranchise, Random
+    #[arg(long, value_parser = parse_sort)]
+    sort: Option<SortField>,
+    /// Sort descending instead of ascending
+    #[arg(long)]
+    desc: bool,
+    /// Secondary sort for ties, same fields as --sort
+    #[arg(long, value_parser = parse_sort)]
+    then_sort: Option<SortField>,
+    /// Secondary sort descending instead of ascending
+    #[arg(long)]
+    then_desc: bool,
+    /// Seed for --sort Random, to get the same shuffle again
+    #[arg(long)]
+    seed: Option<i64>,
+}
+
+impl FilterArgs {
+    fn into_filter(self, query: Option<String>) -> SearchFilter {
+        SearchFilter {
+            query,
+            query_fields:       self.query_fields,
+            fuzzy:              self.fuzzy.then_some(true),
+            status:             self.status,
+            platform:           self.platform,
+            franchise:          self.franchise,
+            genre:              self.genre,
+            min_rating:         self.min_rating,
+            max_rating:         self.max_rating,
+            min_release_year:   self.min_year,
+            max_release_year:   self.max_year,
+            min_playtime_hours: self.min_hours,
+            max_playtime_hours: self.max_hours,
+            min_progress:       self.min_progress,
+            max_progress:       self.max_progress,
+            added_after:        self.added_after,
+            added_before:       self.added_before,
+            completed_after:    self.completed_after,
+            completed_before:   self.completed_before,
+            exclude_statuses:   self.exclude_status,
+            exclude_platforms:  self.exclude_platform,
+            exclude_genres:     self.exclude_genre,
+            installed:          self.installed.then_some(true),
+            min_protondb_tier:  self.min_protondb_tier,
+            min_deck_status:    self.min_deck_status,
+            controller_support: self.controller_support,
+            input_method:       self.input_method,
+            coop_mode:          self.coop,
+            min_local_players:  self.min_local_players,
+            min_online_players: self.min_online_players,
+            played_with:        self.played_with,
+            challenge:          self.challenge,
+            include_archived:   self.include_archived,
+            favorite:           self.favorite.then_some(true),
+            favorites_first:    false,
+            sort_by:            self.sort,
+            sort_asc:           (self.sort.is_some() || self.desc).then_some(!self.desc),
+            then_by:            self.then_sort,
+            then_asc:           Some(!self.then_desc),
+            random_seed:        self.seed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ExportFormat {
+    Json,
+    Csv,
+    /// Grouped by status
+    Markdown,
+    /// Standalone page with embedded covers (needs --output)
+    Html,
+    /// Excel workbook (needs --output)
+    Xlsx,
+    /// CSV using Backloggd's statuses, ratings and platform names
+    Backloggd,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> CmdResult<()> {
+    let db_path = cli.db.unwrap_or_else(db::default_db_path);
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+        // The app keeps its images folder next to the database
+        images::init(parent.join("images"));
+    }
+    // Edits are stamped with the same device id the app uses
+    if let Ok(device) = filesync::device_id(&db::default_local_dir()) {
+        db::set_device_id(device);
+    }
+    let conn = Connection::open(&db_path)?;
+    db::init_db(&conn)?;
+
+    match cli.command {
+        Command::List { status, json } => {
+            let games = match status {
+                Some(status) => db::search_games(&conn, status_filter(status))?,
+                None => db::get_all_games(&conn)?,
+            };
+            print_games(&games, json, db::get_settings(&conn)?.playtime_unit)
+        }
+        Command::Search { query, filter, json } => {
+            let games = db::search_games(&conn, filter.into_filter(Some(query)))?;
+            print_games(&games, json, db::get_settings(&conn)?.playtime_unit)
+        }
+        Command::Add { title, platform, status, franchise, release_date, genre, rating, hours, notes } => {
+            let input = GameInput {
+                title,
+                franchise,
+                sequence_in_franchise: None,
+                release_date,
+                platform,
+                status,
+                progress_percent: None,
+                playtime_minutes: hours.map(|h| (h * 60.0).round() as i64),
+                rating,
+                notes,
+                cover_art_path: None,
+                screenshots: vec![],
+                developer: None,
+                publisher: None,
+                genres: genre,
+                controller_support: None,
+                input_method: None,
+                max_local_players: None,
+                max_online_players: None,
+                coop_mode: None,
+                original_title: None,
+                sort_title: None,
+            };
+            input.validate()?;
+            let game = db::add_game(&conn, input)?;
+            emit(&format!("Added #{} {}", game.id, game.title))
+        }
+        Command::Export { output, format, query, filter } => {
+            let games = export::select_games(&conn, Some(filter.into_filter(query)))?;
+
+            // HTML and Excel are written straight to a file.
+            if matches!(format, ExportFormat::Html | ExportFormat::Xlsx) {
+                let path = output
+                    .ok_or_else(|| CommandError::validation("output", "HTML and XLSX exports need --output"))?;
+                if format == ExportFormat::Html {
+                    export::write_html(&path, &games, &HtmlExportOptions::default(), db::get_settings(&conn)?.playtime_unit)?;
+                } else {
+                    export::write_xlsx(&path, &games, &db::get_play_sessions(&conn, None)?)?;
+                }
+                eprintln!("Exported {} games to {}", games.len(), path.display());
+                return Ok(());
+            }
+
+            let text = match format {
+                ExportFormat::Csv       => export::to_csv(&games, &[])?,
+                ExportFormat::Markdown  => export::to_markdown(&games, MarkdownGrouping::Status, db::get_settings(&conn)?.playtime_unit),
+                ExportFormat::Backloggd => export::to_backloggd_csv(&games)?,
+                _                       => to_json(&games)?,
+            };
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, text)?;
+                    eprintln!("Exported {} games to {}", games.len(), path.display());
+                }
+                None => emit(text.trim_end())?,
+            }
+            Ok(())
+        }
+    }
+}
+
+fn status_filter(status: GameStatus) -> SearchFilter {
+    SearchFilter {
+        query:              None,
+        query_fields:       vec![],
+        fuzzy:              None,
+        status:             vec![status],
+        platform:           vec![],
+        franchise:          None,
+        genre:              vec![],
+        min_rating:         None,
+        max_rating:         None,
+        min_release_year:   None,
+        max_release_year:   None,
+        min_playtime_hours: None,
+        max_playtime_hours: None,
+        min_progress:       None,
+        max_progress:       None,
+        added_after:        None,
+        added_before:       None,
+        completed_after:    None,
+        completed_before:   None,
+        exclude_statuses:   vec![],
+        exclude_platforms:  vec![],
+        exclude_genres:     vec![],
+        installed:          None,
+        min_protondb_tier:  None,
+        min_deck_status:    None,
+        controller_support: None,
+        input_method:       None,
+        coop_mode:          None,
+        min_local_players:  None,
+        min_online_players: None,
+        played_with:        None,
+        challenge:          None,
+        include_archived:   false,
+        favorite:           None,
+        favorites_first:    false,
+        sort_by:            None,
+        sort_asc:           None,
+        then_by:            None,
+        then_asc:           None,
+        random_seed:        None,
+    }
+}
+
+fn print_games(games: &[Game], json: bool, unit: PlaytimeUnit) -> CmdResult<()> {
+    if json {
+        return emit(&to_json(games)?);
+    }
+    let mut table = format!(
+        "{:>5}  {:<40}  {:<14}  {:<10}  {:>6}  {:>9}",
+        "ID", "TITLE", "PLATFORM", "STATUS", "RATING", "PLAYTIME"
+    );
+    for g in games {
+        // RUST NOTE: writing into a String can't fail, so the Result is ignored.
+        let _ = write!(
+            table,
+            "\n{:>5}  {:<40}  {:<14}  {:<10}  {:>6}  {:>9}",
+            g.id,
+            truncate(&g.title, 40),
+            truncate(&g.platform, 14),
+            g.status.as_str(),
+            g.rating.map(|r| format!("{r:.1}")).unwrap_or_default(),
+            g.playtime_minutes.map(|m| unit.format(m)).unwrap_or_default(),
+        );
+    }
+    emit(&table)
+}
+
+/// Print to stdout. A closed pipe (`gametrc-cli list | head`) isn't an error.
+fn emit(text: &str) -> CmdResult<()> {
+    match writeln!(io::stdout().lock(), "{text}") {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.map_err(Into::into),
+    }
+}
+
+fn to_json(games: &[Game]) -> CmdResult<String> {
+    serde_json::to_string_pretty(games).map_err(|e| CommandError::Internal(e.to_string()))
+}
+
+/// Cut a string to `max` characters (not bytes) so multi-byte titles don't panic.
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(max - 1).collect();
+        format!("{cut}…")
+    }
+}
+
+/// Strict status parsing — unlike `GameStatus::from_str`, typos are an error
+/// instead of silently becoming NotStarted.
+fn parse_status(s: &str) -> Result<GameStatus, String> {
+    [
+        GameStatus::NotStarted, GameStatus::Playing, GameStatus::Completed,
+        GameStatus::Dropped, GameStatus::Backlog, GameStatus::Wishlist,
+    ]
+    .into_iter()
+    .find(|status| status.as_str().eq_ignore_ascii_case(s))
+    .ok_or_else(|| "expected one of NotStarted, Playing, Completed, Dropped, Backlog, Wishlist".to_string())
+}
+
+fn parse_tier(s: &str) -> Result<ProtonTier, String> {
+    ProtonTier::parse(s).ok_or_else(|| "expected one of Borked, Bronze, Silver, Gold, Platinum".to_string())
+}
+
+fn parse_deck_status(s: &str) -> Result<DeckStatus, String> {
+    DeckStatus::parse(s).ok_or_else(|| "expected one of Unsupported, Playable, Verified".to_string())
+}
+
+fn parse_controller(s: &str) -> Result<ControllerSupport, String> {
+    ControllerSupport::parse(s).ok_or_else(|| "expected one of Full, Partial, None".to_string())
+}
+
+fn parse_input(s: &str) -> Result<InputMethod, String> {
+    InputMethod::parse(s).ok_or_else(|| "expected one of KeyboardMouse, Gamepad, Wheel, Touch, Other".to_string())
+}
+
+fn parse_coop(s: &str) -> Result<CoopMode, String> {
+    CoopMode::parse(s).ok_or_else(|| "expected one of None, Local, Online, LocalAndOnline".to_string())
+}
+
+fn parse_query_field(s: &str) -> Result<QueryField, String> {
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+        .map_err(|_| "expected one of Title, Franchise, Notes, Developer, Publisher".to_string())
+}
+
+fn parse_sort(s: &str) -> Result<SortField, String> {
+    // SortField's serde names are its variant names, so reuse them.
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+        .map_err(|_| "expected one of Title, Franchise, ReleaseDate, Rating, PlaytimeHours, ProgressPercent, UpdatedAt, CreatedAt, CompletedAt, SequenceInFranchise, Random".to_string())
+}
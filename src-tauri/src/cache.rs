@@ -0,0 +1,137 @@
+// cache.rs — Provider responses kept on disk.
+//
+// Metadata searches, store pages and cover downloads are cached by URL in
+// app_cache_dir/responses/, one file per response. Within its TTL a cached
+// response is used instead of asking the provider again, so a bulk import
+// that looks up the same title twice only hits the network once. Past its
+// TTL it's only used as a fallback when the provider can't be reached,
+// which keeps lookups working offline.
+//
+// `http::get_json_cached` / `http::get_bytes_cached` are the way in; this
+// module only knows about files. Until `init` is called (e.g. in the CLI)
+// nothing is cached.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use crate::error::CmdResult;
+use crate::models::CacheStats;
+
+/// Search results change as stores add games; a week is plenty.
+pub const SEARCH_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Store pages (developer, genres, description, …).
+pub const DETAILS_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Cover art and other images hardly ever change at the same URL.
+pub const IMAGE_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Resolve the cache directory: app_cache_dir/responses/
+pub fn get_cache_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_cache_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("responses")
+}
+
+/// Turn caching on, storing responses in `dir`. Call once at startup.
+pub fn init(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        warn!(dir = %dir.display(), error = %e, "Couldn't create the response cache, caching is off");
+        return;
+    }
+    let _ = DIR.set(dir);
+}
+
+/// A cached response and whether it's still within the TTL it was read with.
+pub struct Cached {
+    pub body:  Vec<u8>,
+    pub fresh: bool,
+}
+
+/// The response cached under `key`, however old. Check `fresh` before
+/// preferring it over the network.
+pub fn read(key: &str, ttl: Duration) -> Option<Cached> {
+    let path = path_for(key)?;
+    let age = fs::metadata(&path).and_then(|m| m.modified()).ok()?.elapsed().unwrap_or_default();
+    let body = fs::read(&path).ok()?;
+    debug!(key, age_secs = age.as_secs(), "Cache hit");
+    Some(Cached { body, fresh: age < ttl })
+}
+
+/// Cache `body` under `key`. Failing to write only costs a future request.
+pub fn write(key: &str, body: &[u8]) {
+    let Some(path) = path_for(key) else { return };
+    // Write then rename, so a crash never leaves half a response behind.
+    let tmp = path.with_extension("tmp");
+    if let Err(e) = fs::write(&tmp, body).and_then(|()| fs::rename(&tmp, &path)) {
+        warn!(key, error = %e, "Couldn't cache response");
+    }
+}
+
+/// How much is cached.
+pub fn stats() -> CacheStats {
+    let mut stats = CacheStats::default();
+    let mut oldest: Option<SystemTime> = None;
+    for (_, size, modified) in entries() {
+        stats.entries += 1;
+        stats.size_bytes += size;
+        oldest = Some(oldest.map_or(modified, |o| o.min(modified)));
+    }
+    stats.oldest = oldest.map(|t| DateTime::<Utc>::from(t).to_rfc3339());
+    stats
+}
+
+/// Delete every cached response, or only those older than `older_than`.
+/// Returns what was removed.
+pub fn clear(older_than: Option<Duration>) -> CmdResult<CacheStats> {
+    let mut removed = CacheStats::default();
+    let cutoff = older_than.and_then(|age| SystemTime::now().checked_sub(age));
+    for (path, size, modified) in entries() {
+        if cutoff.is_some_and(|cutoff| modified >= cutoff) {
+            continue;
+        }
+        fs::remove_file(&path)?;
+        removed.entries += 1;
+        removed.size_bytes += size;
+    }
+    info!(entries = removed.entries, bytes = removed.size_bytes, "Cleared response cache");
+    Ok(removed)
+}
+
+/// Every cached file with its size and modification time.
+fn entries() -> Vec<(PathBuf, u64, SystemTime)> {
+    let Some(dir) = DIR.get() else { return vec![] };
+    let Ok(read_dir) = fs::read_dir(dir) else { return vec![] };
+    read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then_some((entry.path(), meta.len(), meta.modified().ok()?))
+        })
+        .collect()
+}
+
+fn path_for(key: &str) -> Option<PathBuf> {
+    DIR.get().map(|dir| file_for(dir, key))
+}
+
+/// Cache files are named by a hash of the key (the URL).
+fn file_for(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{:016x}", fnv1a(key.as_bytes())))
+}
+
+/// A hash that's stable across runs and versions, unlike std's `DefaultHasher`.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    // RUST NOTE: FNV-1a — XOR each byte in, then multiply by the FNV prime.
+    // `wrapping_mul` lets the multiplication overflow instead of panicking.
+    bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,232 @@
+// card.rs — Render a game as a shareable "game card" PNG.
+//
+// A card is 1200 × 630 — the size link previews on social media use — with
+// the cover on the left and the game on the right:
+//
+//   ┌──────────┬──────────────────────────────┐
+//   │          │ COMPLETED                    │
+//   │  cover   │ Hades                        │
+//   │          │ PC                           │
+//   │          │ RATING        PLAYTIME       │
+//   │          │ 9.5 / 10      61h 12m        │
+//   │          │ Completed 2021-02-14  GameTrc│
+//   └──────────┴──────────────────────────────┘
+//
+// Colours follow the app's dark theme (styles.css). Text is drawn with a sans
+// serif font from the system (see `load_font`), so nothing needs bundling.
+// The drawing helpers in the second half work on any canvas.
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::imageops;
+use image::{Rgba, RgbaImage};
+
+use crate::error::{CmdResult, CommandError};
+use crate::images;
+use crate::models::{Game, GameStatus, PlaytimeUnit};
+
+pub const WIDTH: u32 = 1200;
+pub const HEIGHT: u32 = 630;
+const PADDING: u32 = 48;
+const COVER_WIDTH: u32 = 400;
+
+pub const BACKGROUND: Rgba<u8> = Rgba([0x0d, 0x0f, 0x14, 0xff]);
+pub const PANEL: Rgba<u8> = Rgba([0x1a, 0x1e, 0x28, 0xff]);
+pub const TEXT: Rgba<u8> = Rgba([0xe8, 0xea, 0xf0, 0xff]);
+pub const TEXT_2: Rgba<u8> = Rgba([0x90, 0x98, 0xab, 0xff]);
+pub const TEXT_3: Rgba<u8> = Rgba([0x5a, 0x61, 0x70, 0xff]);
+pub const ACCENT: Rgba<u8> = Rgba([0xf5, 0x9e, 0x0b, 0xff]);
+
+/// Draw `game`'s card. `unit` is how the playtime is written.
+pub fn render(game: &Game, unit: PlaytimeUnit) -> CmdResult<RgbaImage> {
+    let font = load_font()?;
+    let mut canvas = RgbaImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+
+    let cover_height = HEIGHT - 2 * PADDING;
+    draw_cover(&mut canvas, game, PADDING, PADDING, COVER_WIDTH, cover_height, &font);
+
+    let left = (PADDING + COVER_WIDTH + 56) as f32;
+    let right = (WIDTH - PADDING) as f32;
+    let mut y = PADDING as f32 + 24.0;
+
+    // Status, as a tinted pill like the app's badges.
+    let color = status_color(&game.status);
+    let label = game.status.label().to_uppercase();
+    let label_width = text_width(&font, 24.0, &label);
+    fill_rect(&mut canvas, left as u32, y as u32, label_width as u32 + 32, 44, with_alpha(color, 0x22));
+    draw_text(&mut canvas, &font, 24.0, left + 16.0, y + 31.0, &label, color);
+    y += 44.0 + 40.0;
+
+    for line in wrap(&font, 64.0, &game.title, right - left, 2) {
+        y += 68.0;
+        draw_text(&mut canvas, &font, 64.0, left, y, &line, TEXT);
+    }
+    y += 52.0;
+    draw_text(&mut canvas, &font, 32.0, left, y, &game.platform, TEXT_2);
+
+    // The numbers, side by side.
+    let stats: Vec<(&str, String)> = [
+        game.rating.map(|r| ("RATING", format!("{r:.1} / 10"))),
+        game.playtime_minutes.filter(|m| *m > 0).map(|m| ("PLAYTIME", unit.format(m))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let stats_top = (HEIGHT - PADDING) as f32 - 150.0;
+    let mut x = left;
+    for (label, value) in stats {
+        draw_text(&mut canvas, &font, 22.0, x, stats_top, label, TEXT_3);
+        draw_text(&mut canvas, &font, 48.0, x, stats_top + 58.0, &value, ACCENT);
+        x += text_width(&font, 48.0, &value).max(text_width(&font, 22.0, label)) + 72.0;
+    }
+
+    let bottom = (HEIGHT - PADDING) as f32 - 8.0;
+    if let Some(completed) = game.completed_at.as_deref().filter(|_| game.status == GameStatus::Completed) {
+        let day = completed.get(..10).unwrap_or(completed);
+        draw_text(&mut canvas, &font, 24.0, left, bottom, &format!("Completed {day}"), TEXT_2);
+    }
+    draw_text(&mut canvas, &font, 24.0, right - text_width(&font, 24.0, "GameTrc"), bottom, "GameTrc", TEXT_3);
+
+    Ok(canvas)
+}
+
+/// `game`'s cover, cropped to fill the box; without one, the title's first
+/// letter.
+pub fn draw_cover(canvas: &mut RgbaImage, game: &Game, x: u32, y: u32, width: u32, height: u32, font: &FontVec) {
+    // A cover that can't be read is drawn like a missing one.
+    match game.cover_art_path.as_deref().and_then(|path| images::decode(path).ok()) {
+        Some(cover) => {
+            let cover = images::fill(&cover, width, height);
+            imageops::overlay(canvas, &cover, i64::from(x), i64::from(y));
+        }
+        None => {
+            fill_rect(canvas, x, y, width, height, PANEL);
+            let letter = game.title.chars().next().unwrap_or('?').to_uppercase().to_string();
+            let size = height as f32 * 0.375;
+            let letter_width = text_width(font, size, &letter);
+            let baseline = (y + height / 2) as f32 + size * 0.35;
+            draw_text(canvas, font, size, x as f32 + (width as f32 - letter_width) / 2.0, baseline, &letter, TEXT_3);
+        }
+    }
+}
+
+fn status_color(status: &GameStatus) -> Rgba<u8> {
+    match status {
+        GameStatus::Playing    => Rgba([0x3b, 0x82, 0xf6, 0xff]),
+        GameStatus::Completed  => Rgba([0x22, 0xc5, 0x5e, 0xff]),
+        GameStatus::Dropped    => Rgba([0xef, 0x44, 0x44, 0xff]),
+        GameStatus::Backlog    => Rgba([0xa8, 0x55, 0xf7, 0xff]),
+        GameStatus::Wishlist   => Rgba([0xf9, 0x73, 0x16, 0xff]),
+        GameStatus::NotStarted => TEXT_3,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Drawing
+// ---------------------------------------------------------------------------
+
+/// Sans-serif families to draw text with, in order of preference. Each OS
+/// has at least one.
+const FONT_FAMILIES: &[&str] = &[
+    "Segoe UI", "Helvetica Neue", "Helvetica", "Arial", "Noto Sans", "DejaVu Sans", "Liberation Sans", "Cantarell", "Ubuntu",
+];
+
+/// A bold sans-serif system font.
+pub fn load_font() -> CmdResult<FontVec> {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    let families: Vec<fontdb::Family> = FONT_FAMILIES
+        .iter()
+        .map(|name| fontdb::Family::Name(name))
+        .chain([fontdb::Family::SansSerif])
+        .collect();
+    let query = fontdb::Query { families: &families, weight: fontdb::Weight::BOLD, ..fontdb::Query::default() };
+    let id = fonts
+        .query(&query)
+        .or_else(|| fonts.faces().next().map(|face| face.id))
+        .ok_or_else(|| CommandError::Internal("No fonts found on this system to draw text with".into()))?;
+    fonts
+        .with_face_data(id, |data, index| FontVec::try_from_vec_and_index(data.to_vec(), index).ok())
+        .flatten()
+        .ok_or_else(|| CommandError::Internal("Couldn't read the system font".into()))
+}
+
+/// Draw `text` with its baseline at `y`. Returns how wide it was.
+pub fn draw_text(canvas: &mut RgbaImage, font: &FontVec, size: f32, x: f32, y: f32, text: &str, color: Rgba<u8>) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut caret = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(size, ab_glyph::point(caret, y));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else { continue };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + i64::from(gx);
+            let py = bounds.min.y as i64 + i64::from(gy);
+            if px >= 0 && py >= 0 && (px as u32) < canvas.width() && (py as u32) < canvas.height() {
+                let alpha = (coverage * f32::from(color[3])) as u8;
+                blend(canvas.get_pixel_mut(px as u32, py as u32), with_alpha(color, alpha));
+            }
+        });
+    }
+    caret - x
+}
+
+pub fn text_width(font: &FontVec, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum()
+}
+
+/// Break `text` into lines no wider than `width`, at most `max_lines` of
+/// them; what doesn't fit ends the last line with "…".
+pub fn wrap(font: &FontVec, size: f32, text: &str, width: f32, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let joined = lines.last().map(|line| format!("{line} {word}"));
+        match joined {
+            Some(joined) if text_width(font, size, &joined) <= width => *lines.last_mut().expect("joined a line") = joined,
+            Some(joined) if lines.len() >= max_lines => {
+                *lines.last_mut().expect("joined a line") = ellipsize(font, size, &joined, width);
+                break;
+            }
+            // A single word wider than the line is cut.
+            _ if text_width(font, size, word) > width => lines.push(ellipsize(font, size, word, width)),
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+fn ellipsize(font: &FontVec, size: f32, text: &str, width: f32) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() && text_width(font, size, &format!("{}…", chars.iter().collect::<String>())) > width {
+        chars.pop();
+    }
+    format!("{}…", chars.iter().collect::<String>().trim_end())
+}
+
+pub fn fill_rect(canvas: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(canvas.height()) {
+        for px in x..(x + width).min(canvas.width()) {
+            blend(canvas.get_pixel_mut(px, py), color);
+        }
+    }
+}
+
+fn with_alpha(color: Rgba<u8>, alpha: u8) -> Rgba<u8> {
+    Rgba([color[0], color[1], color[2], alpha])
+}
+
+/// Paint `color` over `pixel` by its alpha. The canvas is opaque throughout.
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>) {
+    let alpha = u16::from(color[3]);
+    for i in 0..3 {
+        pixel[i] = ((u16::from(color[i]) * alpha + u16::from(pixel[i]) * (255 - alpha)) / 255) as u8;
+    }
+}
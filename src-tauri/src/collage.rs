@@ -0,0 +1,80 @@
+// collage.rs — Tile the covers of many games into one image.
+//
+// A mosaic of a year's finished games, or of the whole backlog, to share or
+// to print. Covers are laid out left to right, top to bottom, in the order
+// the games come in, on the same dark background as game cards (card.rs):
+//
+//   ┌─────────────────────────────────┐
+//   │ Finished in 2024                │   heading, if given
+//   │ ┌────┐ ┌────┐ ┌────┐ ┌────┐     │
+//   │ │    │ │    │ │    │ │    │     │   `columns` tiles per row,
+//   │ └────┘ └────┘ └────┘ └────┘     │   each 3:4 like box art
+//   │ Hades  Celeste …                │   captions, if asked for
+//   └─────────────────────────────────┘
+//
+// Games without a cover get the same first-letter tile as on a card.
+
+use image::RgbaImage;
+
+use crate::card::{self, BACKGROUND, TEXT, TEXT_2};
+use crate::error::{CmdResult, CommandError};
+use crate::jobs::JobHandle;
+use crate::models::{CollageLayout, Game};
+
+/// The largest width or height a collage may have, in pixels.
+const MAX_SIDE: u32 = 16_384;
+
+/// Render `games` as a grid per `layout`.
+pub fn render(games: &[Game], layout: &CollageLayout, job: &JobHandle) -> CmdResult<RgbaImage> {
+    if games.is_empty() {
+        return Err(CommandError::validation("filter", "no games match"));
+    }
+    if !(1..=100).contains(&layout.columns) {
+        return Err(CommandError::validation("columns", "must be between 1 and 100"));
+    }
+    if !(32..=1000).contains(&layout.tile_width) {
+        return Err(CommandError::validation("tileWidth", "must be between 32 and 1000 pixels"));
+    }
+
+    let tile_width = layout.tile_width;
+    let tile_height = tile_width * 4 / 3;
+    let gap = layout.gap.min(tile_width);
+    let columns = layout.columns.min(games.len() as u32);
+    let rows = (games.len() as u32).div_ceil(columns);
+    let caption_size = (tile_width as f32 * 0.08).max(12.0);
+    let caption_height = if layout.captions { (caption_size * 1.8) as u32 } else { 0 };
+    let heading_size = (tile_width as f32 * 0.2).clamp(24.0, 72.0);
+    let heading_height = if layout.heading.is_some() { (heading_size * 1.6) as u32 + gap } else { 0 };
+
+    let width = u64::from(gap) + u64::from(columns) * u64::from(tile_width + gap);
+    let height = u64::from(gap + heading_height) + u64::from(rows) * u64::from(tile_height + caption_height + gap);
+    if width > u64::from(MAX_SIDE) || height > u64::from(MAX_SIDE) {
+        return Err(CommandError::validation(
+            "layout",
+            format!("the collage would be {width} × {height} pixels; use smaller tiles or fewer games"),
+        ));
+    }
+
+    let font = card::load_font()?;
+    let mut canvas = RgbaImage::from_pixel(width as u32, height as u32, BACKGROUND);
+    if let Some(heading) = &layout.heading {
+        let line = card::wrap(&font, heading_size, heading, (width as u32 - 2 * gap) as f32, 1);
+        let baseline = gap as f32 + heading_size * 1.2;
+        card::draw_text(&mut canvas, &font, heading_size, gap as f32, baseline, &line.concat(), TEXT);
+    }
+
+    for (i, game) in games.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, games.len(), game.title.as_str());
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let x = gap + column * (tile_width + gap);
+        let y = gap + heading_height + row * (tile_height + caption_height + gap);
+        card::draw_cover(&mut canvas, game, x, y, tile_width, tile_height, &font);
+        if layout.captions {
+            let caption = card::wrap(&font, caption_size, &game.title, tile_width as f32, 1).concat();
+            let baseline = (y + tile_height) as f32 + caption_size * 1.4;
+            card::draw_text(&mut canvas, &font, caption_size, x as f32, baseline, &caption, TEXT_2);
+        }
+    }
+    Ok(canvas)
+}
@@ -10,96 +10,331 @@
 //
 // `tauri::State<AppState>` is dependency injection — Tauri injects the shared
 // application state (our database connection) into each command automatically.
+//
+// Every command is `async` and hands its rusqlite / filesystem / network work to
+// Tauri's blocking thread pool via `spawn_blocking`. That keeps the IPC handler
+// free while a slow query or download runs, so the webview never freezes.
 
-use tauri::State;
-use std::sync::Mutex;
+use tauri::{Manager, State};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use rusqlite::Connection;
+use serde::Serialize;
 
-use crate::models::{Game, GameInput, GameStats, SearchFilter};
-use crate::db;
+use crate::models::{
+    Achievement, AchievementSummary, ActiveSession, BackupReason, BundleSummary, CacheStats, ChangeAction,
+    CollageLayout, CommandInfo, ConflictResolution, ConflictStrategy, CoverSource, CredentialStatus, CropRect,
+    Diagnostics, ExportColumn, ExternalId, FileSyncResolution, FileSyncStatus, Game, GameGroup, GameInput, GamePage,
+    GameStats, GameStatus, GameSummary, GroupBy, Highlights, Hook, HookInput, HookRun, HtmlExportOptions,
+    ImageEvictionReport, ImageKind, ImageSize, ImportPreview, ImporterPlugin, InstallScanReport, Job, JobStatus,
+    LibraryReport, LogEntry, MarkdownGrouping, MergeReport, NowPlaying, OnThisDay, Person, PersonStats,
+    PersonalBest, PersonalBestInput, PlaySession, Playthrough, PlaythroughInput, PlaytimeAdjustment, ProcessedImage,
+    Purchase, RateLimit, RefreshManifest, RelinkReport, SaveBackup, SaveLocation, ScreenshotImport, SearchFilter,
+    Selected, SessionSource, Settings, Soundtrack, SoundtrackInput, SyncConflict, SyncReport, TitleImportEntry,
+    TrackerFormat, UsageCounter, UsageInsights, WebDavAction, WebDavDirection, WebDavSyncReport,
+};
+use crate::cache;
+use crate::compat;
+use crate::covers;
+use crate::credentials;
+use crate::db::{self, ImportOutcome};
+use crate::diagnostics;
+use crate::events;
+use crate::export;
+use crate::hooks;
+use crate::http;
+use crate::import;
+use crate::library;
+use crate::saves;
+use crate::installs;
+use crate::metadata;
+use crate::preview::PreviewRecorder;
+use crate::filesync::{self, FileSync};
+use crate::humble;
+use crate::itch;
+use crate::jobs::{JobHandle, JobQueue};
+use crate::plugins;
+use crate::psn;
+use crate::ratelimit;
+use crate::refresh;
+use crate::retroarch;
+use crate::steam;
+use crate::timer;
+use crate::trackers;
+use crate::webdav;
+use crate::xbox;
+use crate::error::{CmdResult, CommandError};
 
 /// RUST NOTE: This is our shared application state.
 /// `Mutex<Connection>` ensures only one thread accesses the DB at a time.
 /// Tauri manages multiple threads for IPC, so this is essential.
+/// The `Arc` lets a command hand its own reference to a background thread.
 pub struct AppState {
-    pub db: Mutex<Connection>,
+    pub db:       Arc<Mutex<Connection>>,
+    pub jobs:     Arc<JobQueue>,   // imports, syncs and other long tasks
+    pub filesync: Arc<FileSync>,   // sync-safe mode for a games.db in a cloud-drive folder
 }
 
-// ---------------------------------------------------------------------------
-// Error handling
-// ---------------------------------------------------------------------------
-
-// RUST NOTE: Tauri commands must return `Result<T, E>` where E implements
-// `serde::Serialize` so errors can be sent back to JavaScript as JSON.
-// `rusqlite::Error` doesn't implement Serialize, so we wrap it in our own type.
-
-#[derive(Debug, serde::Serialize)]
-pub struct CommandError(String);
-
-impl From<rusqlite::Error> for CommandError {
-    fn from(e: rusqlite::Error) -> Self {
-        CommandError(e.to_string())
-    }
+/// Run `f` against the shared connection on the blocking thread pool.
+///
+/// RUST NOTE: `spawn_blocking` requires a `'static` closure, so we clone the
+/// `Arc` (cheap — it only bumps a reference count) and move it into the task.
+/// Mutex::lock() returns a LockResult. If a thread panicked while holding the
+/// lock it becomes "poisoned". We convert that to CommandError::Internal.
+async fn with_db<T, F>(state: &AppState, f: F) -> CmdResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> CmdResult<T> + Send + 'static,
+{
+    let db = Arc::clone(&state.db);
+    blocking(move || {
+        let conn = db::lock(&db)?;
+        f(&conn)
+    })
+    .await
 }
 
-impl From<crate::images::ImageError> for CommandError {
-    fn from(e: crate::images::ImageError) -> Self {
-        CommandError(e.to_string())
+/// Run any blocking closure (file copies, HTTP downloads, …) off the IPC thread.
+async fn blocking<T, F>(f: F) -> CmdResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> CmdResult<T> + Send + 'static,
+{
+    let result = tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| CommandError::Internal(format!("Background task failed: {e}")))?;
+    if let Err(e) = &result {
+        tracing::warn!(code = e.code(), error = %e, "Command failed");
     }
+    result
 }
 
-// Shorthand type alias — `CmdResult<T>` is `Result<T, CommandError>`
-type CmdResult<T> = Result<T, CommandError>;
-
-// Macro to lock the Mutex and propagate the error if poisoned
-// RUST NOTE: Mutex::lock() returns a LockResult. If a thread panicked while
-// holding the lock it becomes "poisoned". We convert that to our CommandError.
-macro_rules! db {
-    ($state:expr) => {
-        $state
-            .db
-            .lock()
-            .map_err(|e| CommandError(format!("DB lock poisoned: {e}")))?
-    };
+/// Run `work` as a `kind` job on the queue and wait for what it returns. The
+/// caller gets its result as before; everyone else can follow the job's
+/// progress through `jobs://updated` and cancel it with `cancel_job`.
+async fn run_job<T, F>(state: &AppState, kind: &'static str, work: F) -> CmdResult<T>
+where
+    T: Serialize + Send + 'static,
+    F: FnOnce(&JobHandle) -> CmdResult<T> + Send + 'static,
+{
+    let jobs = Arc::clone(&state.jobs);
+    blocking(move || jobs.enqueue(kind, work)?.wait()).await
 }
 
 // ---------------------------------------------------------------------------
 // Game CRUD
 // ---------------------------------------------------------------------------
 
-/// Fetch every game, ordered by most recently updated.
+/// Fetch every game, ordered by most recently updated. Archived games are
+/// left out unless `includeArchived` is true.
+///
+/// `fields` optionally limits each game to those fields (the id is always
+/// included) to keep the payload small for big libraries.
+///
+/// Example JS call:
+///   const games = await invoke("get_all_games", { fields: ["title", "status", "cover_art_path"] });
 #[tauri::command]
-pub fn get_all_games(state: State<AppState>) -> CmdResult<Vec<Game>> {
-    let conn = db!(state);
-    db::get_all_games(&conn).map_err(Into::into)
+pub async fn get_all_games(
+    state: State<'_, AppState>,
+    fields: Option<Vec<String>>,
+    include_archived: Option<bool>,
+) -> CmdResult<Selected<Vec<Game>>> {
+    let mut games = with_db(&state, |conn| db::get_all_games(conn).map_err(Into::into)).await?;
+    if include_archived != Some(true) {
+        games.retain(|game| !game.archived);
+    }
+    select_fields(games, fields.as_deref())
 }
 
-/// Fetch a single game by its database ID.
+/// Fetch a single game by its database ID. Takes the same `fields` as get_all_games.
 #[tauri::command]
-pub fn get_game(state: State<AppState>, id: i64) -> CmdResult<Option<Game>> {
-    let conn = db!(state);
-    db::get_game(&conn, id).map_err(Into::into)
+pub async fn get_game(state: State<'_, AppState>, id: i64, fields: Option<Vec<String>>) -> CmdResult<Selected<Option<Game>>> {
+    let game = with_db(&state, move |conn| db::get_game(conn, id).map_err(Into::into)).await?;
+    select_fields(game, fields.as_deref())
 }
 
 /// Insert a new game and return the created record (with its assigned id).
+/// Emits `games://changed` with action `Added`.
 #[tauri::command]
-pub fn add_game(state: State<AppState>, input: GameInput) -> CmdResult<Game> {
-    let conn = db!(state);
-    db::add_game(&conn, input).map_err(Into::into)
+pub async fn add_game(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    input: GameInput,
+) -> CmdResult<Game> {
+    input.validate()?;
+    let game = with_db(&state, move |conn| {
+        check_playing_limit(conn, None, &input)?;
+        Ok(db::add_game(conn, input)?)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, vec![game.id]);
+    hooks::games_added(&[game.id]);
+    Ok(game)
 }
 
 /// Update an existing game and return the updated record.
+/// Emits `games://changed` with action `Updated`; moving the game to
+/// Completed also runs the user's GameCompleted hooks.
 #[tauri::command]
-pub fn update_game(state: State<AppState>, id: i64, input: GameInput) -> CmdResult<Game> {
-    let conn = db!(state);
-    db::update_game(&conn, id, input).map_err(Into::into)
+pub async fn update_game(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    input: GameInput,
+) -> CmdResult<Game> {
+    input.validate()?;
+    let (game, old_status) = with_db(&state, move |conn| {
+        check_playing_limit(conn, Some(id), &input)?;
+        let before = db::get_game(conn, id)?;
+        let old_minutes = before.as_ref().and_then(|g| g.playtime_minutes);
+        let tx = conn.unchecked_transaction()?;
+        let game = db::update_game(&tx, id, input).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Game {id}")),
+            e => e.into(),
+        })?;
+        // A playtime edited here is a hand correction; keep a record of it.
+        if game.playtime_minutes != old_minutes {
+            db::add_playtime_adjustment(&tx, id, old_minutes, game.playtime_minutes, None)?;
+        }
+        db::count_usage(&tx, UsageCounter::Edit)?;
+        tx.commit()?;
+        Ok((game, before.map(|g| g.status)))
+    })
+    .await?;
+    if game.status == GameStatus::Completed && old_status.is_some_and(|s| s != GameStatus::Completed) {
+        hooks::game_completed(id);
+    }
+    events::games_changed(&app, ChangeAction::Updated, vec![id]);
+    Ok(game)
+}
+
+/// With the `playing_limit` setting, refuse to move a game into Playing when
+/// that many others already are. A game that's already Playing can always be
+/// saved.
+fn check_playing_limit(conn: &Connection, id: Option<i64>, input: &GameInput) -> CmdResult<()> {
+    let limit = db::get_settings(conn)?.playing_limit;
+    if input.status != GameStatus::Playing || limit == 0 {
+        return Ok(());
+    }
+    if let Some(game) = id.map(|id| db::get_game(conn, id)).transpose()?.flatten() {
+        if game.status == GameStatus::Playing {
+            return Ok(());
+        }
+    }
+    let playing = db::count_playing(conn, id)?;
+    if playing >= i64::from(limit) {
+        return Err(CommandError::validation(
+            "status",
+            format!("You're already playing {playing} games (your limit is {limit}); finish or shelve one first"),
+        ));
+    }
+    Ok(())
 }
 
 /// Delete a game. Returns true if a row was deleted, false if id wasn't found.
+/// Emits `games://changed` with action `Deleted` when something was removed.
+#[tauri::command]
+pub async fn delete_game(app: tauri::AppHandle, state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    let deleted = with_db(&state, move |conn| db::delete_game(conn, id).map_err(Into::into)).await?;
+    if deleted {
+        events::games_changed(&app, ChangeAction::Deleted, vec![id]);
+    }
+    Ok(deleted)
+}
+
+/// Pin a game to the top of the dashboard, or unpin it. Returns the game.
+///
+/// Example JS call:
+///   const { favorite } = await invoke("toggle_favorite", { id: 12 });
+#[tauri::command]
+pub async fn toggle_favorite(app: tauri::AppHandle, state: State<'_, AppState>, id: i64) -> CmdResult<Game> {
+    let game = with_db(&state, move |conn| {
+        db::toggle_favorite(conn, id).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Game {id}")),
+            e => e.into(),
+        })
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![id]);
+    Ok(game)
+}
+
+/// Archive games (e.g. bundle shovelware you'll never play) or bring them
+/// back. Archived games aren't deleted, just hidden from get_all_games,
+/// searches and stats unless asked for (`includeArchived`). Returns how many
+/// games changed.
+///
+/// Example JS call:
+///   await invoke("set_archived", { ids: [12, 13, 14], archived: true });
+#[tauri::command]
+pub async fn set_archived(app: tauri::AppHandle, state: State<'_, AppState>, ids: Vec<i64>, archived: bool) -> CmdResult<usize> {
+    let game_ids = ids.clone();
+    let changed = with_db(&state, move |conn| Ok(db::set_archived(conn, &game_ids, archived)?)).await?;
+    events::games_changed(&app, ChangeAction::Updated, ids);
+    Ok(changed)
+}
+
+/// Add a nickname or regional name that search should also find the game by.
+/// Returns the updated game.
+///
+/// Example JS call:
+///   const game = await invoke("add_game_alias", { gameId: 12, alias: "BotW" });
+#[tauri::command]
+pub async fn add_game_alias(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    alias: String,
+) -> CmdResult<Game> {
+    let alias = alias.trim().to_string();
+    if alias.is_empty() {
+        return Err(CommandError::validation("alias", "must not be empty"));
+    }
+    let game = with_db(&state, move |conn| {
+        if db::get_game(conn, game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {game_id}")));
+        }
+        db::add_game_alias(conn, game_id, &alias)?;
+        db::get_game(conn, game_id)?.ok_or_else(|| CommandError::NotFound(format!("Game {game_id}")))
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    Ok(game)
+}
+
+/// Returns false if the game had no such alias.
 #[tauri::command]
-pub fn delete_game(state: State<AppState>, id: i64) -> CmdResult<bool> {
-    let conn = db!(state);
-    db::delete_game(&conn, id).map_err(Into::into)
+pub async fn remove_game_alias(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    alias: String,
+) -> CmdResult<bool> {
+    let removed = with_db(&state, move |conn| Ok(db::remove_game_alias(conn, game_id, alias.trim())?)).await?;
+    if removed {
+        events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    }
+    Ok(removed)
+}
+
+/// Keep only `fields` (plus `id`) of every game in `value` — a game, a list
+/// of games or an Option of either. With no `fields` the games go out whole.
+fn select_fields<T: Serialize>(value: T, fields: Option<&[String]>) -> CmdResult<Selected<T>> {
+    let Some(fields) = fields else { return Ok(Selected::All(value)) };
+    let mut json = serde_json::to_value(&value).map_err(|e| CommandError::Internal(e.to_string()))?;
+    let games: Vec<&mut serde_json::Map<String, serde_json::Value>> = match &mut json {
+        serde_json::Value::Array(items) => items.iter_mut().filter_map(|g| g.as_object_mut()).collect(),
+        serde_json::Value::Object(game) => vec![game],
+        _ => vec![],
+    };
+    for game in games {
+        // A typo would otherwise quietly return games without the field.
+        if let Some(unknown) = fields.iter().find(|f| !game.contains_key(f.as_str())) {
+            return Err(CommandError::validation("fields", format!("Unknown field \"{unknown}\"")));
+        }
+        game.retain(|key, _| key == "id" || fields.contains(key));
+    }
+    Ok(Selected::Fields(json))
 }
 
 // ---------------------------------------------------------------------------
@@ -113,91 +348,2244 @@ pub fn delete_game(state: State<AppState>, id: i64) -> CmdResult<bool> {
 ///     filter: { query: "zelda", status: "Completed", sort_by: "Rating", sort_asc: false }
 ///   })
 #[tauri::command]
-pub fn search_games(state: State<AppState>, filter: SearchFilter) -> CmdResult<Vec<Game>> {
-    let conn = db!(state);
-    db::search_games(&conn, filter).map_err(Into::into)
+pub async fn search_games(
+    state: State<'_, AppState>,
+    filter: SearchFilter,
+    fields: Option<Vec<String>>,
+) -> CmdResult<Selected<Vec<Game>>> {
+    let games = with_db(&state, move |conn| db::search_games(conn, filter).map_err(Into::into)).await?;
+    select_fields(games, fields.as_deref())
+}
+
+/// One page of search results, for infinite scrolling. `filter` is optional —
+/// leave it out to page through every game in get_all_games order. Pass the
+/// returned `next_cursor` to get the following page; `limit` defaults to 50
+/// (at most 500).
+///
+/// Example JS call:
+///   let page = await invoke("search_games_page", { filter: { sort_by: "Title" }, limit: 100 });
+///   page = await invoke("search_games_page", { filter: { sort_by: "Title" }, limit: 100,
+///                                              cursor: page.next_cursor });
+#[tauri::command]
+pub async fn search_games_page(
+    state: State<'_, AppState>,
+    filter: Option<SearchFilter>,
+    limit: Option<u32>,
+    cursor: Option<String>,
+) -> CmdResult<GamePage> {
+    let limit = limit.unwrap_or(50).clamp(1, 500);
+    with_db(&state, move |conn| {
+        db::search_games_page(conn, filter.unwrap_or_default(), limit, cursor.as_deref())
+    })
+    .await
+}
+
+/// Like `search_games`, but returns `GameSummary`s (id, title, cover, status,
+/// rating, platform) for grid and list views. Leave `filter` out for every game.
+///
+/// Example JS call:
+///   const cards = await invoke("search_game_summaries", { filter: { status: "Backlog" } });
+#[tauri::command]
+pub async fn search_game_summaries(
+    state: State<'_, AppState>,
+    filter: Option<SearchFilter>,
+) -> CmdResult<Vec<GameSummary>> {
+    with_db(&state, move |conn| Ok(db::search_game_summaries(conn, filter.unwrap_or_default())?)).await
+}
+
+/// Like `search_games_page`, but the page holds `GameSummary`s.
+#[tauri::command]
+pub async fn search_game_summaries_page(
+    state: State<'_, AppState>,
+    filter: Option<SearchFilter>,
+    limit: Option<u32>,
+    cursor: Option<String>,
+) -> CmdResult<GamePage<GameSummary>> {
+    let limit = limit.unwrap_or(50).clamp(1, 500);
+    with_db(&state, move |conn| {
+        db::search_game_summaries_page(conn, filter.unwrap_or_default(), limit, cursor.as_deref())
+    })
+    .await
+}
+
+/// Search results split into groups with a header and count each, for the
+/// grouped list views. `filter` is optional — leave it out to group everything.
+///
+/// Example JS call:
+///   const groups = await invoke("get_games_grouped", {
+///     groupBy: "Franchise", filter: { status: ["Playing", "Backlog"], sort_by: "SequenceInFranchise" }
+///   });
+///   // → [{ key: "Zelda", label: "Zelda", count: 4, games: [...] }, …]
+#[tauri::command]
+pub async fn get_games_grouped(
+    state: State<'_, AppState>,
+    group_by: GroupBy,
+    filter: Option<SearchFilter>,
+) -> CmdResult<Vec<GameGroup>> {
+    with_db(&state, move |conn| Ok(db::get_games_grouped(conn, group_by, filter)?)).await
 }
 
 // ---------------------------------------------------------------------------
 // Stats & dashboard
 // ---------------------------------------------------------------------------
 
-/// Aggregate statistics for the dashboard.
+/// Aggregate statistics for the dashboard, pinned games first.
+#[tauri::command]
+pub async fn get_stats(state: State<'_, AppState>) -> CmdResult<GameStats> {
+    with_db(&state, |conn| db::get_stats(conn).map_err(Into::into)).await
+}
+
+/// Data for the dashboard's widgets: the 10 most played games, each genre's
+/// best-rated game, the longest wait from purchase to completion, the oldest
+/// untouched backlog entry and the shortest completed game.
+///
+/// Example JS call:
+///   const h = await invoke("get_highlights");
+///   // { most_played: [{ game: { id: 2, title: "Hades", … }, value: 3672 }, …],
+///   //   top_rated_by_genre: [{ genre: "Roguelike", game: { … } }, …],
+///   //   longest_to_finish: { game: { … }, value: 1204 }, … }
+#[tauri::command]
+pub async fn get_highlights(state: State<'_, AppState>) -> CmdResult<Highlights> {
+    with_db(&state, |conn| Ok(db::get_highlights(conn)?)).await
+}
+
+/// "On this day": games completed, started or added on this calendar date in
+/// earlier years, most recent first. `date` ('YYYY-MM-DD') defaults to today
+/// on this computer.
+///
+/// Example JS call:
+///   const memories = await invoke("get_on_this_day");
+///   // [{ game: { id: 2, title: "Hades", … }, event: "Completed", date: "2021-02-14", years_ago: 3 }, …]
+#[tauri::command]
+pub async fn get_on_this_day(state: State<'_, AppState>, date: Option<String>) -> CmdResult<Vec<OnThisDay>> {
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| CommandError::validation("date", "must be YYYY-MM-DD"))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    with_db(&state, move |conn| Ok(db::get_on_this_day(conn, date)?)).await
+}
+
+/// "Your tracker habits": app launches, games added and edits, in total and
+/// per week for the last `weeks` weeks (default 12). The counters are kept on
+/// this computer only — they aren't exported, synced or sent anywhere.
+///
+/// Example JS call:
+///   const habits = await invoke("get_usage_insights", { weeks: 8 });
+///   // { tracking_since: "2024-01-03", days_used: 41, launches: 57, games_added: 120, edits: 36,
+///   //   weeks: [{ week_start: "2024-03-04", launches: 5, games_added: 2, edits: 3 }, …] }
+#[tauri::command]
+pub async fn get_usage_insights(state: State<'_, AppState>, weeks: Option<u32>) -> CmdResult<UsageInsights> {
+    let weeks = weeks.unwrap_or(12);
+    if !(1..=520).contains(&weeks) {
+        return Err(CommandError::validation("weeks", "must be between 1 and 520"));
+    }
+    let today = chrono::Local::now().date_naive();
+    with_db(&state, move |conn| Ok(db::get_usage_insights(conn, today, weeks)?)).await
+}
+
+/// The Now Playing shelf: every game in Playing, most recently played or
+/// edited first. Cap how many can be in Playing with the `playing_limit`
+/// setting; add_game / update_game then refuse one more.
+///
+/// Example JS call:
+///   const shelf = await invoke("get_now_playing");
+///   // [{ game_id: 2, title: "Hollow Knight", last_activity: "2024-06-01T21:30:00Z", … }]
 #[tauri::command]
-pub fn get_stats(state: State<AppState>) -> CmdResult<GameStats> {
-    let conn = db!(state);
-    db::get_stats(&conn).map_err(Into::into)
+pub async fn get_now_playing(state: State<'_, AppState>) -> CmdResult<Vec<NowPlaying>> {
+    with_db(&state, |conn| Ok(db::get_now_playing(conn)?)).await
 }
 
 // ---------------------------------------------------------------------------
 // Utilities
 // ---------------------------------------------------------------------------
 
+/// Every command the frontend can call — name, section, description, example
+/// and arguments (named as `invoke` takes them, with their JSON kind) — for
+/// the command palette and scripting console.
+///
+/// Example JS call:
+///   const commands = await invoke("list_commands");
+///   // [{ name: "log_play_session", category: "Play sessions", summary: "Log time spent playing…",
+///   //    args: [{ name: "gameId", kind: "integer", rust_type: "i64", optional: false }, …],
+///   //    returns: "PlaySession", … }, …]
+#[tauri::command]
+pub async fn list_commands() -> CmdResult<Vec<CommandInfo>> {
+    Ok(crate::registry::commands().to_vec())
+}
+
 /// Returns all distinct platform names stored in the DB (for filter dropdowns).
 #[tauri::command]
-pub fn get_platforms(state: State<AppState>) -> CmdResult<Vec<String>> {
-    let conn = db!(state);
-    let mut stmt = conn
-        .prepare("SELECT DISTINCT platform FROM games ORDER BY platform")
-        .map_err(|e| CommandError(e.to_string()))?;
-    let platforms = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| CommandError(e.to_string()))?
-        .collect::<Result<Vec<String>, _>>()
-        .map_err(|e| CommandError(e.to_string()))?;
-    Ok(platforms)
+pub async fn get_platforms(state: State<'_, AppState>) -> CmdResult<Vec<String>> {
+    with_db(&state, |conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT platform FROM games ORDER BY platform")?;
+        let platforms = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(platforms)
+    })
+    .await
 }
 
 /// Returns all distinct franchise names (for franchise grouping and autocomplete).
 #[tauri::command]
-pub fn get_franchises(state: State<AppState>) -> CmdResult<Vec<String>> {
-    let conn = db!(state);
-    let mut stmt = conn
-        .prepare(
+pub async fn get_franchises(state: State<'_, AppState>) -> CmdResult<Vec<String>> {
+    with_db(&state, |conn| {
+        let mut stmt = conn.prepare(
             "SELECT DISTINCT franchise FROM games
              WHERE franchise IS NOT NULL ORDER BY franchise"
-        )
-        .map_err(|e| CommandError(e.to_string()))?;
-    let franchises = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| CommandError(e.to_string()))?
-        .collect::<Result<Vec<String>, _>>()
-        .map_err(|e| CommandError(e.to_string()))?;
-    Ok(franchises)
+        )?;
+        let franchises = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(franchises)
+    })
+    .await
 }
 
 /// Returns all distinct genre names (for filter dropdowns and autocomplete).
 #[tauri::command]
-pub fn get_genres(state: State<AppState>) -> CmdResult<Vec<String>> {
-    let conn = db!(state);
-    let mut stmt = conn
-        .prepare(
+pub async fn get_genres(state: State<'_, AppState>) -> CmdResult<Vec<String>> {
+    with_db(&state, |conn| {
+        let mut stmt = conn.prepare(
             "SELECT DISTINCT genre FROM game_genres ORDER BY genre"
-        )
-        .map_err(|e| CommandError(e.to_string()))?;
-    let genres = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| CommandError(e.to_string()))?
-        .collect::<Result<Vec<String>, _>>()
-        .map_err(|e| CommandError(e.to_string()))?;
-    Ok(genres)
+        )?;
+        let genres = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(genres)
+    })
+    .await
+}
+
+/// Show a file or folder, selected, in the system file manager (Explorer,
+/// Finder, …) — e.g. a screenshot from the gallery. Only paths in the app data
+/// folder (covers, screenshots, logs, …) or in a folder the library knows a
+/// game by (an install or a save location) are revealed; anything else is
+/// refused. A stored image file name works too.
+///
+/// Example JS call:
+///   await invoke("reveal_in_folder", { path: game.screenshots[0] });
+#[tauri::command]
+pub async fn reveal_in_folder(app: tauri::AppHandle, state: State<'_, AppState>, path: String) -> CmdResult<()> {
+    let game_dirs = with_db(&state, |conn| Ok(db::get_game_directories(conn)?)).await?;
+    blocking(move || {
+        let app_data = app.path().app_data_dir().map_err(|e| CommandError::Internal(e.to_string()))?;
+        let path = crate::images::resolve(path);
+        let target = std::fs::canonicalize(&path).map_err(|_| CommandError::NotFound(format!("File {path}")))?;
+        // Compared canonicalized, so "..", symlinks and letter case on
+        // Windows can't sneak a path out of its folder.
+        let allowed = std::iter::once(app_data)
+            .chain(game_dirs.into_iter().map(std::path::PathBuf::from))
+            .filter_map(|root| std::fs::canonicalize(root).ok())
+            .any(|root| target.starts_with(root));
+        if !allowed {
+            return Err(CommandError::validation("path", "must be in the app data folder or a game's folder"));
+        }
+        tauri_plugin_opener::reveal_item_in_dir(&target)
+            .map_err(|e| CommandError::Internal(format!("Couldn't open the file manager: {e}")))
+    })
+    .await
 }
 
 // ---------------------------------------------------------------------------
-// Image processing
+// Export
 // ---------------------------------------------------------------------------
 
-/// Process a cover image: copy a local file or download a remote URL.
+/// Write games as CSV to `path` and return how many rows were written.
 ///
-/// Takes either a local filesystem path or an http(s):// URL.
-/// Saves the image to app_data_dir/images/ with a unique filename.
-/// Returns the absolute path to the saved image, which should be stored in the DB.
+/// `columns` picks and orders the columns (all of them when omitted);
+/// `filter` limits the export to matching games (the whole library when omitted).
 ///
 /// Example JS call:
-///   const savedPath = await invoke("process_cover_image", { input: "https://example.com/cover.jpg" });
-///   // or
-///   const savedPath = await invoke("process_cover_image", { input: "/home/user/Pictures/game.png" });
+///   const path = await window.__TAURI__.dialog.save({ filters: [{ name: "CSV", extensions: ["csv"] }] });
+///   await invoke("export_csv", { path, columns: ["Title", "Platform", "Rating"], filter: { status: "Completed" } });
+#[tauri::command]
+pub async fn export_csv(
+    state: State<'_, AppState>,
+    path: String,
+    columns: Option<Vec<ExportColumn>>,
+    filter: Option<SearchFilter>,
+) -> CmdResult<usize> {
+    let games = with_db(&state, move |conn| export::select_games(conn, filter)).await?;
+    blocking(move || {
+        let csv = export::to_csv(&games, &columns.unwrap_or_default())?;
+        std::fs::write(&path, csv)?;
+        tracing::info!(path, rows = games.len(), "Exported CSV");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Write games as a grouped Markdown document (e.g. for an Obsidian vault).
+///
+/// `group_by` is "Status" (default) or "Franchise"; `filter` works like in
+/// `export_csv`. Returns the number of games written.
+///
+/// Example JS call:
+///   await invoke("export_markdown", { path, groupBy: "Franchise", filter: null });
+#[tauri::command]
+pub async fn export_markdown(
+    state: State<'_, AppState>,
+    path: String,
+    group_by: Option<MarkdownGrouping>,
+    filter: Option<SearchFilter>,
+) -> CmdResult<usize> {
+    let (games, unit) = with_db(&state, move |conn| {
+        Ok((export::select_games(conn, filter)?, db::get_settings(conn)?.playtime_unit))
+    })
+    .await?;
+    blocking(move || {
+        let markdown = export::to_markdown(&games, group_by.unwrap_or(MarkdownGrouping::Status), unit);
+        std::fs::write(&path, markdown)?;
+        tracing::info!(path, games = games.len(), "Exported Markdown");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Export games as a CSV that follows Backloggd's conventions (statuses,
+/// play types, 5-star ratings, IGDB platform names) for mirroring the library.
+///
+/// Example JS call:
+///   await invoke("export_backloggd", { path: "/home/me/backloggd.csv", filter: null });
+#[tauri::command]
+pub async fn export_backloggd(
+    state: State<'_, AppState>,
+    path: String,
+    filter: Option<SearchFilter>,
+) -> CmdResult<usize> {
+    let games = with_db(&state, move |conn| export::select_games(conn, filter)).await?;
+    blocking(move || {
+        std::fs::write(&path, export::to_backloggd_csv(&games)?)?;
+        tracing::info!(path, games = games.len(), "Exported Backloggd CSV");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Export soundtracks (of every game, or just `gameId`'s) as CSV.
+/// Returns the number of rows written.
+///
+/// Example JS call:
+///   await invoke("export_soundtracks", { path: "/home/me/osts.csv" });
+#[tauri::command]
+pub async fn export_soundtracks(state: State<'_, AppState>, path: String, game_id: Option<i64>) -> CmdResult<usize> {
+    let soundtracks = with_db(&state, move |conn| Ok(db::get_soundtracks(conn, game_id)?)).await?;
+    blocking(move || {
+        std::fs::write(&path, export::soundtracks_to_csv(&soundtracks)?)?;
+        tracing::info!(path, rows = soundtracks.len(), "Exported soundtracks");
+        Ok(soundtracks.len())
+    })
+    .await
+}
+
+/// Export games to an Excel workbook with "Games", "Sessions" and "Stats" sheets.
+/// Dates and numbers are written as typed cells, ready for pivot tables.
+///
+/// Example JS call:
+///   await invoke("export_xlsx", { path: "/home/me/games.xlsx", filter: null });
+#[tauri::command]
+pub async fn export_xlsx(
+    state: State<'_, AppState>,
+    path: String,
+    filter: Option<SearchFilter>,
+) -> CmdResult<usize> {
+    let (games, sessions) = with_db(&state, move |conn| {
+        Ok((export::select_games(conn, filter)?, db::get_play_sessions(conn, None)?))
+    })
+    .await?;
+    blocking(move || {
+        export::write_xlsx(std::path::Path::new(&path), &games, &sessions)?;
+        tracing::info!(path, games = games.len(), "Exported XLSX");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Render games into a standalone HTML page that can be hosted or shared.
+///
+/// `options.covers` is "Embed" (default — one self-contained file), "Link"
+/// (covers copied into a `<name>_covers/` folder beside the page) or "None".
+/// Notes are only included when `options.include_notes` is true.
+///
+/// Example JS call:
+///   await invoke("export_html", { path, options: { title: "Sam's Switch games", covers: "Link" },
+///                                 filter: { platform: "Switch" } });
+#[tauri::command]
+pub async fn export_html(
+    state: State<'_, AppState>,
+    path: String,
+    options: Option<HtmlExportOptions>,
+    filter: Option<SearchFilter>,
+) -> CmdResult<usize> {
+    let (games, unit) = with_db(&state, move |conn| {
+        Ok((export::select_games(conn, filter)?, db::get_settings(conn)?.playtime_unit))
+    })
+    .await?;
+    blocking(move || {
+        export::write_html(std::path::Path::new(&path), &games, &options.unwrap_or_default(), unit)?;
+        tracing::info!(path, games = games.len(), "Exported HTML");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Back up the whole library to one file: a JSON dump of every game, session,
+/// playthrough, setting, … — or, with `includeImages`, a zip holding the dump
+/// plus every cover and screenshot. API keys aren't included (they live in the
+/// OS credential store). With a `passphrase` the file is encrypted, for
+/// backups kept on cloud storage you don't trust; it can't be restored
+/// without that passphrase.
+///
+/// Example JS call:
+///   const path = await window.__TAURI__.dialog.save({ filters: [{ name: "Backup", extensions: ["zip"] }] });
+///   const { games, images } = await invoke("export_library", { path, includeImages: true });
+///   await invoke("export_library", { path: "/cloud/gametrc.bak", includeImages: true, passphrase: "correct horse" });
+#[tauri::command]
+pub async fn export_library(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    include_images: Option<bool>,
+    passphrase: Option<String>,
+) -> CmdResult<LibraryReport> {
+    let images_dir = match include_images {
+        Some(true) => Some(crate::images::get_images_dir(&app)?),
+        _ => None,
+    };
+    let db = Arc::clone(&state.db);
+    run_job(&state, "library_export", move |_| {
+        library::export(&*db::lock(&db)?, std::path::Path::new(&path), images_dir.as_deref(), passphrase.as_deref())
+    })
+    .await
+}
+
+/// Replace the library with a backup written by `export_library` (JSON or
+/// zip; a zip's images are restored too). Everything currently in the
+/// library is discarded. An encrypted backup needs its `passphrase`; without
+/// it, or with the wrong one, this fails with a "passphrase" validation error.
+///
+/// Example JS call:
+///   const { games } = await invoke("import_library", { path: "/home/me/gametrc-backup.zip" });
+///   await invoke("import_library", { path: "/cloud/gametrc.bak", passphrase: "correct horse" });
+#[tauri::command]
+pub async fn import_library(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    passphrase: Option<String>,
+) -> CmdResult<LibraryReport> {
+    let images_dir = crate::images::get_images_dir(&app)?;
+    let db = Arc::clone(&state.db);
+    let removed = with_db(&state, |conn| Ok(db::get_game_ids(conn)?)).await?;
+    let report = run_job(&state, "library_import", move |_| {
+        library::import(&*db::lock(&db)?, std::path::Path::new(&path), &images_dir, passphrase.as_deref())
+    })
+    .await?;
+    library_replaced(&app, &state, removed).await?;
+    Ok(report)
+}
+
+/// After the whole library was replaced (import, WebDAV pull): apply the
+/// restored settings and tell the frontend every game changed.
+async fn library_replaced(app: &tauri::AppHandle, state: &AppState, removed: Vec<i64>) -> CmdResult<()> {
+    let (added, settings) = with_db(state, |conn| Ok((db::get_game_ids(conn)?, db::get_settings(conn)?))).await?;
+    ratelimit::configure(&settings.rate_limits);
+    events::games_changed(app, ChangeAction::Deleted, removed);
+    events::games_changed(app, ChangeAction::Added, added);
+    Ok(())
+}
+
+/// Sync the library with the WebDAV folder in the `webdav_url` setting (e.g.
+/// a Nextcloud folder), signing in as `webdav_username` with the
+/// "webdav_password" credential.
+///
+/// `direction` defaults to "Auto": push if only this library changed since
+/// the last sync, pull if only the server's copy did. If both changed the
+/// result is "Conflict" and nothing is touched; sync again with "Push" or
+/// "Pull" to pick a side. A pull replaces the whole library.
+///
+/// Example JS call:
+///   const { action } = await invoke("webdav_sync", { direction: "Auto" });
+#[tauri::command]
+pub async fn webdav_sync(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    direction: Option<WebDavDirection>,
+) -> CmdResult<WebDavSyncReport> {
+    let images_dir = crate::images::get_images_dir(&app)?;
+    let removed = with_db(&state, |conn| Ok(db::get_game_ids(conn)?)).await?;
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "webdav_sync", move |job| {
+        webdav::sync(&db, &images_dir, direction.unwrap_or_default(), job)
+    })
+    .await?;
+    if report.action == WebDavAction::Pulled {
+        library_replaced(&app, &state, removed).await?;
+    }
+    Ok(report)
+}
+
+/// Sync-safe mode's view of games.db: whether another device opened it since
+/// this one last did, whether it was replaced on disk while open (also sent
+/// as a `sync://db-changed` event), and any conflicted copies next to it.
+///
+/// Example JS call:
+///   const { changed_on_disk, conflicted_copies } = await invoke("get_file_sync_status");
+#[tauri::command]
+pub async fn get_file_sync_status(state: State<'_, AppState>) -> CmdResult<FileSyncStatus> {
+    let filesync = Arc::clone(&state.filesync);
+    blocking(move || filesync.status()).await
+}
+
+/// Settle a games.db that was replaced on disk while open: "Reload" uses the
+/// file on disk, "Merge" uses it and merges this app's games back in
+/// (`strategy` decides how games both have are treated, default "FillEmpty"),
+/// "KeepMine" overwrites it with this app's database.
+///
+/// Example JS call:
+///   await invoke("resolve_file_sync", { resolution: "Merge", strategy: "FillEmpty" });
+#[tauri::command]
+pub async fn resolve_file_sync(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    resolution: FileSyncResolution,
+    strategy: Option<ConflictStrategy>,
+) -> CmdResult<MergeReport> {
+    let removed = with_db(&state, |conn| Ok(db::get_game_ids(conn)?)).await?;
+    let (filesync, db) = (Arc::clone(&state.filesync), Arc::clone(&state.db));
+    let report = blocking(move || filesync.resolve(&db, resolution, strategy.unwrap_or(ConflictStrategy::FillEmpty))).await?;
+    library_replaced(&app, &state, removed).await?;
+    Ok(report)
+}
+
+/// Merge the games of another database file — typically a cloud drive's
+/// "conflicted copy" from `get_file_sync_status` — into the library, matching
+/// games by title. `deleteAfter` removes the file once merged.
+///
+/// Example JS call:
+///   await invoke("merge_database", { path: copies[0], strategy: "FillEmpty", deleteAfter: true });
+#[tauri::command]
+pub async fn merge_database(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    strategy: Option<ConflictStrategy>,
+    delete_after: Option<bool>,
+) -> CmdResult<MergeReport> {
+    let report = with_db(&state, move |conn| {
+        let report = filesync::merge_file(conn, std::path::Path::new(&path), strategy.unwrap_or(ConflictStrategy::FillEmpty))?;
+        if delete_after == Some(true) {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(report)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.created_ids.clone());
+    hooks::games_added(&report.created_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    events::games_changed(&app, ChangeAction::Deleted, report.deleted_ids.clone());
+    Ok(report)
+}
+
+/// Games a merge found edited differently in the two copies, newest first.
+/// Each lists the fields that differ, with this library's and the other
+/// copy's value. Resolved conflicts are included on request.
+///
+/// Example JS call:
+///   const conflicts = await invoke("get_sync_conflicts", {});
+///   // [{ id: 3, game_id: 12, title: "Hades", source: "games (conflicted copy).db",
+///   //    fields: [{ field: "status", local: "Playing", remote: "Completed" }], … }]
+#[tauri::command]
+pub async fn get_sync_conflicts(state: State<'_, AppState>, include_resolved: Option<bool>) -> CmdResult<Vec<SyncConflict>> {
+    with_db(&state, move |conn| Ok(db::get_sync_conflicts(conn, include_resolved.unwrap_or(false))?)).await
+}
+
+/// Settle a sync conflict. `KeepLocal` leaves the game as it is, `KeepRemote`
+/// takes the other copy's value for every conflicting field, and
+/// `MergeFields` takes it only for the fields listed. Returns the game.
+///
+/// Example JS call:
+///   await invoke("resolve_sync_conflict", { id: 3, resolution: "KeepRemote" });
+///   await invoke("resolve_sync_conflict", { id: 4, resolution: { MergeFields: ["status", "rating"] } });
+#[tauri::command]
+pub async fn resolve_sync_conflict(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    resolution: ConflictResolution,
+) -> CmdResult<Game> {
+    let game = with_db(&state, move |conn| filesync::resolve_conflict(conn, id, &resolution)).await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![game.id]);
+    Ok(game)
+}
+
+/// Tile the covers of the games matching `filter` — by default, the whole
+/// library — into one PNG at `path`, in the order they're listed. `layout`
+/// sets the columns, tile size and gaps, captions and a heading (see
+/// CollageLayout). Returns how many games it shows.
+///
+/// Example JS call:
+///   await invoke("export_collage", {
+///     path: "/home/me/finished-2024.png",
+///     filter: { status: "Completed", completed_after: "2024-01-01", completed_before: "2024-12-31" },
+///     layout: { columns: 8, heading: "Finished in 2024" },
+///   });
+#[tauri::command]
+pub async fn export_collage(
+    state: State<'_, AppState>,
+    path: String,
+    filter: Option<SearchFilter>,
+    layout: Option<CollageLayout>,
+) -> CmdResult<usize> {
+    let games = with_db(&state, move |conn| export::select_games(conn, filter)).await?;
+    run_job(&state, "collage_export", move |job| {
+        let collage = crate::collage::render(&games, &layout.unwrap_or_default(), job)?;
+        collage
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| CommandError::Internal(format!("Couldn't save the collage: {e}")))?;
+        tracing::info!(path, games = games.len(), "Exported cover collage");
+        Ok(games.len())
+    })
+    .await
+}
+
+/// Render a game's card — cover, title, status, rating and playtime — as a
+/// 1200 × 630 PNG, for sharing a completion on social media. It's saved to
+/// `path`, or to the `cards` folder in app data if that's left out; returns
+/// where.
+///
+/// Example JS call:
+///   const png = await invoke("render_game_card", { id: 12 });
+#[tauri::command]
+pub async fn render_game_card(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    path: Option<String>,
+) -> CmdResult<String> {
+    let (game, unit) = with_db(&state, move |conn| {
+        let game = db::get_game(conn, id)?.ok_or_else(|| CommandError::NotFound(format!("Game {id}")))?;
+        Ok((game, db::get_settings(conn)?.playtime_unit))
+    })
+    .await?;
+    blocking(move || {
+        let path = match path {
+            Some(path) => std::path::PathBuf::from(path),
+            None => {
+                let dir = app.path().app_data_dir().map_err(|e| CommandError::Internal(e.to_string()))?.join("cards");
+                std::fs::create_dir_all(&dir)?;
+                dir.join(format!("game-{id}.png"))
+            }
+        };
+        crate::card::render(&game, unit)?
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| CommandError::Internal(format!("Couldn't save the card: {e}")))?;
+        tracing::info!(id, path = %path.display(), "Rendered game card");
+        Ok(path.to_string_lossy().into_owned())
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+/// Step 1 of importing a title list: look every line up, but don't save anything.
+///
+/// `text` is newline-separated titles (blank lines, `#` comments and list
+/// bullets are ignored). Each entry comes back with the metadata the configured
+/// provider found (or `null`) and `existing_id` if the game is already in the
+/// library, so the user can review the matches before confirming.
+///
+/// Example JS call:
+///   const preview = await invoke("preview_title_list", { text: "Hades\nCeleste\nOuter Wilds" });
+#[tauri::command]
+pub async fn preview_title_list(state: State<'_, AppState>, text: String) -> CmdResult<Vec<TitleImportEntry>> {
+    let titles = import::parse_title_list(&text);
+    if titles.is_empty() {
+        return Err(CommandError::validation("text", "No titles found"));
+    }
+    let settings = with_db(&state, |conn| Ok(db::get_settings(conn)?)).await?;
+
+    // One provider request per title — a single failed lookup shouldn't sink the whole list.
+    let found = blocking(move || {
+        let provider = metadata::provider(settings.metadata_provider);
+        Ok(titles
+            .into_iter()
+            .map(|title| {
+                let metadata = provider.as_ref().and_then(|p| {
+                    p.lookup(&title)
+                        .inspect_err(|e| tracing::warn!(title, error = %e, "Metadata lookup failed"))
+                        .ok()
+                        .flatten()
+                });
+                (title, metadata)
+            })
+            .collect::<Vec<_>>())
+    })
+    .await?;
+
+    with_db(&state, move |conn| {
+        found
+            .into_iter()
+            .map(|(query, metadata)| {
+                let existing_id = match db::find_game_by_title(conn, &query)? {
+                    Some(id) => Some(id),
+                    None => match &metadata {
+                        Some(m) => db::find_game_by_title(conn, &m.title)?,
+                        None => None,
+                    },
+                };
+                Ok(TitleImportEntry { query, metadata, existing_id })
+            })
+            .collect()
+    })
+    .await
+}
+
+/// Step 2 of importing a title list: create Backlog entries for the confirmed lines.
+///
+/// Pass back the entries from `preview_title_list` the user wants to keep.
+/// Covers are downloaded; a cover that fails to download just leaves the game
+/// without one. `platform` defaults to "PC". Entries matching a library game
+/// (those with an `existing_id`) are handled by `onConflict`: "Skip" (the
+/// default), "Overwrite", "FillEmpty" or "Duplicate". The same option works
+/// for every importer. Returns the new and matched games.
+///
+/// Example JS call:
+///   const games = await invoke("import_title_list", { entries: preview, platform: "PC",
+///                                                     onConflict: "FillEmpty" });
+#[tauri::command]
+pub async fn import_title_list(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    entries: Vec<TitleImportEntry>,
+    platform: Option<String>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<Vec<Game>> {
+    let platform = platform.unwrap_or_else(|| "PC".to_string());
+    let image_app = app.clone();
+    let inputs = run_job(&state, "title_import", move |job| {
+        let total = entries.len();
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                job.check_cancelled()?;
+                job.progress(i, total, entry.query.as_str());
+                let url = entry.metadata.as_ref().and_then(|m| m.cover_url.clone());
+                let cover = url.as_deref().and_then(|url| crate::images::process_image(&image_app, url, ImageKind::Cover).ok());
+                // Remember where the cover and the match came from, so both can be refreshed later.
+                let source = url.as_deref().zip(cover.as_deref()).map(|(url, cover)| covers::source(url, cover));
+                let link = entry.metadata.as_ref().map(|m| (m.source.clone(), m.source_id.clone()));
+                let input = import::title_entry_to_input(entry, &platform, cover);
+                input.validate()?;
+                Ok((input, link, source))
+            })
+            .collect::<CmdResult<Vec<TitleEntry>>>()
+    })
+    .await?;
+
+    let (games, outcomes) = with_db(&state, move |conn| {
+        let outcomes = add_title_entries(conn, inputs, on_conflict.unwrap_or_default())?;
+        let games = outcomes
+            .iter()
+            .map(|(id, _)| db::get_game(conn, *id)?.ok_or_else(|| CommandError::NotFound(format!("Game {id}"))))
+            .collect::<CmdResult<Vec<Game>>>()?;
+        Ok((games, outcomes))
+    })
+    .await?;
+    tracing::info!(games = games.len(), "Imported title list");
+    let ids_with = |wanted| outcomes.iter().filter(|(_, o)| *o == wanted).map(|(id, _)| *id).collect::<Vec<i64>>();
+    let created = ids_with(ImportOutcome::Created);
+    hooks::games_added(&created);
+    events::games_changed(&app, ChangeAction::Added, created);
+    events::games_changed(&app, ChangeAction::Updated, ids_with(ImportOutcome::Updated));
+    Ok(games)
+}
+
+/// Dry run of `import_title_list`: what the same entries would create, with
+/// nothing saved and no covers downloaded.
+///
+/// Example JS call:
+///   const { created, skipped } = await invoke("preview_title_import", { entries, platform: "PC" });
+#[tauri::command]
+pub async fn preview_title_import(
+    state: State<'_, AppState>,
+    entries: Vec<TitleImportEntry>,
+    platform: Option<String>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<ImportPreview> {
+    let platform = platform.unwrap_or_else(|| "PC".to_string());
+    with_db(&state, move |conn| {
+        let inputs = entries
+            .into_iter()
+            .map(|entry| {
+                let link = entry.metadata.as_ref().map(|m| (m.source.clone(), m.source_id.clone()));
+                let input = import::title_entry_to_input(entry, &platform, None);
+                input.validate()?;
+                Ok((input, link, None))
+            })
+            .collect::<CmdResult<Vec<_>>>()?;
+
+        let mut recorder = PreviewRecorder::start(conn)?;
+        // Never committed: dropping `tx` rolls the inserts back.
+        let tx = conn.unchecked_transaction()?;
+        for (id, _) in add_title_entries(&tx, inputs, on_conflict.unwrap_or_default())? {
+            recorder.record(&tx, id)?;
+        }
+        Ok(recorder.finish())
+    })
+    .await
+}
+
+/// A confirmed title-list entry: the game, the (source, id) its metadata came
+/// from and where its cover was downloaded from.
+type TitleEntry = (GameInput, Option<(String, String)>, Option<CoverSource>);
+
+/// The writes behind `import_title_list`: import each game, linked to where
+/// its metadata and cover came from.
+fn add_title_entries(
+    conn: &Connection,
+    inputs: Vec<TitleEntry>,
+    on_conflict: ConflictStrategy,
+) -> CmdResult<Vec<(i64, ImportOutcome)>> {
+    inputs
+        .into_iter()
+        .map(|(input, link, cover)| {
+            let link = link.as_ref().map(|(source, id)| (source.as_str(), id.as_str()));
+            let (id, outcome) = db::import_game(conn, input, link, on_conflict)?;
+            if let Some(cover) = cover {
+                db::set_cover_source(conn, id, &cover)?;
+            }
+            Ok((id, outcome))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Play sessions
+// ---------------------------------------------------------------------------
+
+/// Log time spent playing a game; its playtime goes up by the same amount.
+/// `started_at` (RFC 3339) defaults to now. `game_version` is the patch the
+/// session was played on, if known; `person_ids` are the people who played along.
+///
+/// Example JS call:
+///   await invoke("log_play_session", { gameId: 12, minutes: 90, gameVersion: "1.6.2", personIds: [3] });
+#[tauri::command]
+pub async fn log_play_session(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    minutes: i64,
+    started_at: Option<String>,
+    game_version: Option<String>,
+    person_ids: Option<Vec<i64>>,
+) -> CmdResult<PlaySession> {
+    if minutes <= 0 {
+        return Err(CommandError::validation("minutes", "Must be greater than 0"));
+    }
+    let started_at = started_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let session = with_db(&state, move |conn| {
+        if db::get_game(conn, game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {game_id}")));
+        }
+        let version = game_version.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        let mut session = db::add_play_session(conn, game_id, &started_at, minutes, SessionSource::Manual, version)?;
+        if let Some(ids) = person_ids.filter(|ids| !ids.is_empty()) {
+            db::set_session_people(conn, session.id, &ids)?;
+            session.people = db::get_people(conn)?.into_iter().filter(|p| ids.contains(&p.id)).collect();
+        }
+        Ok(session)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    hooks::session_ended(&session);
+    backup_after_session(&app, &state, game_id).await?;
+    Ok(session)
+}
+
+/// Correct a game's playtime by hand. The old and new values and the reason
+/// are kept (see `get_playtime_adjustments`), and once corrected, a first
+/// Steam/PSN sync won't top the game back up to the service's total.
+///
+/// Example JS call:
+///   await invoke("adjust_playtime", { gameId: 12, minutes: 2400, reason: "Left it running overnight" });
+#[tauri::command]
+pub async fn adjust_playtime(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    minutes: Option<i64>,
+    reason: Option<String>,
+) -> CmdResult<PlaytimeAdjustment> {
+    if minutes.is_some_and(|m| m < 0) {
+        return Err(CommandError::validation("minutes", "must not be negative"));
+    }
+    let adjustment = with_db(&state, move |conn| {
+        db::adjust_playtime(conn, game_id, minutes, reason.as_deref()).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Game {game_id}")),
+            e => e.into(),
+        })
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    Ok(adjustment)
+}
+
+/// A game's playtime corrections, newest first.
+#[tauri::command]
+pub async fn get_playtime_adjustments(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<PlaytimeAdjustment>> {
+    with_db(&state, move |conn| Ok(db::get_playtime_adjustments(conn, game_id)?)).await
+}
+
+/// Start the live session timer for a game. It ticks (`sessions://tick`)
+/// every minute until `stop_session`.
+///
+/// Example JS call:
+///   const session = await invoke("start_session", { gameId: 12, gameVersion: "1.6.2" });
+#[tauri::command]
+pub async fn start_session(
+    state: State<'_, AppState>,
+    game_id: i64,
+    game_version: Option<String>,
+) -> CmdResult<ActiveSession> {
+    with_db(&state, move |conn| timer::start(conn, game_id, game_version.as_deref())).await
+}
+
+/// Pause the running session; paused time isn't counted as play.
+#[tauri::command]
+pub async fn pause_session(state: State<'_, AppState>) -> CmdResult<ActiveSession> {
+    with_db(&state, timer::pause).await
+}
+
+#[tauri::command]
+pub async fn resume_session(state: State<'_, AppState>) -> CmdResult<ActiveSession> {
+    with_db(&state, timer::resume).await
+}
+
+/// Stop the timer and log the time played as a session. Returns null (and
+/// logs nothing) if it ran for less than a minute.
+///
+/// Example JS call:
+///   const session = await invoke("stop_session");
+#[tauri::command]
+pub async fn stop_session(app: tauri::AppHandle, state: State<'_, AppState>) -> CmdResult<Option<PlaySession>> {
+    let session = with_db(&state, timer::stop).await?;
+    if let Some(session) = &session {
+        events::games_changed(&app, ChangeAction::Updated, vec![session.game_id]);
+        hooks::session_ended(session);
+        backup_after_session(&app, &state, session.game_id).await?;
+    }
+    Ok(session)
+}
+
+/// The session the timer is running, or null.
 #[tauri::command]
-pub fn process_cover_image(app: tauri::AppHandle, input: String) -> CmdResult<String> {
-    crate::images::process_image(&app, &input).map_err(Into::into)
-}
\ No newline at end of file
+pub async fn get_active_session(state: State<'_, AppState>) -> CmdResult<Option<ActiveSession>> {
+    with_db(&state, timer::current).await
+}
+
+/// A finished session is a good moment to snapshot the saves. The session is
+/// already recorded, so a failed backup is only logged.
+async fn backup_after_session(app: &tauri::AppHandle, state: &AppState, game_id: i64) -> CmdResult<()> {
+    let (auto_backup, has_saves) = with_db(state, move |conn| {
+        Ok((db::get_settings(conn)?.auto_backup_saves, !db::get_save_locations(conn, game_id)?.is_empty()))
+    })
+    .await?;
+    if auto_backup && has_saves {
+        if let Ok(backup) = take_backup(app, state, game_id, BackupReason::SessionEnd).await {
+            prune_backups(state, game_id).await.ok();
+            tracing::info!(game_id, backup_id = backup.id, "Backed up saves after session");
+        }
+    }
+    Ok(())
+}
+
+/// Set the game version a session was played on (None / "" clears it).
+///
+/// Example JS call:
+///   await invoke("set_session_game_version", { sessionId: 7, gameVersion: "Patch 14" });
+#[tauri::command]
+pub async fn set_session_game_version(
+    state: State<'_, AppState>,
+    session_id: i64,
+    game_version: Option<String>,
+) -> CmdResult<()> {
+    with_db(&state, move |conn| {
+        let version = game_version.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        if !db::set_session_game_version(conn, session_id, version)? {
+            return Err(CommandError::NotFound(format!("Play session {session_id}")));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Replace the people recorded for a session.
+///
+/// Example JS call:
+///   await invoke("set_session_people", { sessionId: 7, personIds: [3, 5] });
+#[tauri::command]
+pub async fn set_session_people(state: State<'_, AppState>, session_id: i64, person_ids: Vec<i64>) -> CmdResult<()> {
+    with_db(&state, move |conn| {
+        if !db::set_session_people(conn, session_id, &person_ids)? {
+            return Err(CommandError::NotFound(format!("Play session {session_id}")));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Sessions for one game, newest first (every game's if `gameId` is omitted).
+#[tauri::command]
+pub async fn get_play_sessions(state: State<'_, AppState>, game_id: Option<i64>) -> CmdResult<Vec<PlaySession>> {
+    with_db(&state, move |conn| Ok(db::get_play_sessions(conn, game_id)?)).await
+}
+
+// ---------------------------------------------------------------------------
+// Playthroughs
+// ---------------------------------------------------------------------------
+
+/// Record a run through a game. A game's replay count is its number of
+/// playthroughs after the first; flag New Game+ runs with `new_game_plus` and
+/// challenge runs with a `challenge` tag and optional `ruleset`.
+///
+/// Example JS call:
+///   await invoke("add_playthrough", { input: {
+///     game_id: 12, started_at: "2024-03-01", finished_at: "2024-03-20", new_game_plus: true,
+///     challenge: "Nuzlocke", ruleset: "First encounter per route only; fainted = released"
+///   }});
+#[tauri::command]
+pub async fn add_playthrough(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    input: PlaythroughInput,
+) -> CmdResult<Playthrough> {
+    input.validate()?;
+    let playthrough = with_db(&state, move |conn| {
+        if db::get_game(conn, input.game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {}", input.game_id)));
+        }
+        Ok(db::add_playthrough(conn, &input)?)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![playthrough.game_id]);
+    Ok(playthrough)
+}
+
+#[tauri::command]
+pub async fn update_playthrough(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    input: PlaythroughInput,
+) -> CmdResult<Playthrough> {
+    input.validate()?;
+    let playthrough = with_db(&state, move |conn| {
+        db::update_playthrough(conn, id, &input).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Playthrough {id}")),
+            e => e.into(),
+        })
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![playthrough.game_id]);
+    Ok(playthrough)
+}
+
+#[tauri::command]
+pub async fn delete_playthrough(app: tauri::AppHandle, state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    let game_id = with_db(&state, move |conn| Ok(db::delete_playthrough(conn, id)?)).await?;
+    if let Some(game_id) = game_id {
+        events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    }
+    Ok(game_id.is_some())
+}
+
+/// Every challenge tag used on a playthrough (for autocomplete and the
+/// `challenge` search filter).
+#[tauri::command]
+pub async fn get_challenges(state: State<'_, AppState>) -> CmdResult<Vec<String>> {
+    with_db(&state, |conn| Ok(db::get_challenges(conn)?)).await
+}
+
+/// A game's playthroughs, oldest first.
+#[tauri::command]
+pub async fn get_playthroughs(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<Playthrough>> {
+    with_db(&state, move |conn| Ok(db::get_playthroughs(conn, game_id)?)).await
+}
+
+// ---------------------------------------------------------------------------
+// Personal bests
+// ---------------------------------------------------------------------------
+
+/// Record a speedrun time. The fastest one shows up as the game's `best_run`;
+/// it's kept apart from playtime_hours.
+///
+/// Example JS call:
+///   await invoke("add_personal_best", { input: {
+///     game_id: 12, category: "Any%", time_ms: 1843250, achieved_on: "2024-05-02",
+///     video_url: "https://youtu.be/…"
+///   }});
+#[tauri::command]
+pub async fn add_personal_best(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    input: PersonalBestInput,
+) -> CmdResult<PersonalBest> {
+    input.validate()?;
+    let best = with_db(&state, move |conn| {
+        if db::get_game(conn, input.game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {}", input.game_id)));
+        }
+        Ok(db::add_personal_best(conn, &input)?)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![best.game_id]);
+    Ok(best)
+}
+
+#[tauri::command]
+pub async fn update_personal_best(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    input: PersonalBestInput,
+) -> CmdResult<PersonalBest> {
+    input.validate()?;
+    let best = with_db(&state, move |conn| {
+        db::update_personal_best(conn, id, &input).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Personal best {id}")),
+            e => e.into(),
+        })
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, vec![best.game_id]);
+    Ok(best)
+}
+
+#[tauri::command]
+pub async fn delete_personal_best(app: tauri::AppHandle, state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    let game_id = with_db(&state, move |conn| Ok(db::delete_personal_best(conn, id)?)).await?;
+    if let Some(game_id) = game_id {
+        events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    }
+    Ok(game_id.is_some())
+}
+
+/// A game's personal bests, by category and fastest first.
+#[tauri::command]
+pub async fn get_personal_bests(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<PersonalBest>> {
+    with_db(&state, move |conn| Ok(db::get_personal_bests(conn, game_id)?)).await
+}
+
+// ---------------------------------------------------------------------------
+// People
+// ---------------------------------------------------------------------------
+
+/// Add someone to play with. Adding a name that already exists returns that person.
+///
+/// Example JS call:
+///   const sam = await invoke("add_person", { name: "Sam" });
+#[tauri::command]
+pub async fn add_person(state: State<'_, AppState>, name: String) -> CmdResult<Person> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(CommandError::validation("name", "must not be empty"));
+    }
+    with_db(&state, move |conn| Ok(db::add_person(conn, &name)?)).await
+}
+
+/// Remove a person (and them from every session they were recorded in).
+#[tauri::command]
+pub async fn delete_person(state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    with_db(&state, move |conn| Ok(db::delete_person(conn, id)?)).await
+}
+
+#[tauri::command]
+pub async fn get_people(state: State<'_, AppState>) -> CmdResult<Vec<Person>> {
+    with_db(&state, |conn| Ok(db::get_people(conn)?)).await
+}
+
+/// Sessions, minutes and games shared with each person, most time first.
+/// Filter games by person with `played_with` in `search_games`.
+///
+/// Example JS call:
+///   const [top] = await invoke("get_people_stats");  // { name: "Sam", total_minutes: 5400, … }
+#[tauri::command]
+pub async fn get_people_stats(state: State<'_, AppState>) -> CmdResult<Vec<PersonStats>> {
+    with_db(&state, |conn| Ok(db::get_people_stats(conn)?)).await
+}
+
+// ---------------------------------------------------------------------------
+// Soundtracks
+// ---------------------------------------------------------------------------
+
+/// Add a soundtrack to a game.
+///
+/// Example JS call:
+///   await invoke("add_soundtrack", { input: {
+///     game_id: 12, title: "Hades Original Soundtrack", owned: true, format: "Vinyl",
+///     favorite_tracks: ["In the Blood", "Good Riddance"], link: "https://supergiantgames.bandcamp.com"
+///   }});
+#[tauri::command]
+pub async fn add_soundtrack(state: State<'_, AppState>, input: SoundtrackInput) -> CmdResult<Soundtrack> {
+    input.validate()?;
+    with_db(&state, move |conn| {
+        if db::get_game(conn, input.game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {}", input.game_id)));
+        }
+        Ok(db::add_soundtrack(conn, &input)?)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_soundtrack(state: State<'_, AppState>, id: i64, input: SoundtrackInput) -> CmdResult<Soundtrack> {
+    input.validate()?;
+    with_db(&state, move |conn| {
+        db::update_soundtrack(conn, id, &input).map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound(format!("Soundtrack {id}")),
+            e => e.into(),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_soundtrack(state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    with_db(&state, move |conn| Ok(db::delete_soundtrack(conn, id)?)).await
+}
+
+/// One game's soundtracks, or every soundtrack when `gameId` is omitted.
+#[tauri::command]
+pub async fn get_soundtracks(state: State<'_, AppState>, game_id: Option<i64>) -> CmdResult<Vec<Soundtrack>> {
+    with_db(&state, move |conn| Ok(db::get_soundtracks(conn, game_id)?)).await
+}
+
+/// Find soundtracks by soundtrack title, favorite track or game title.
+///
+/// Example JS call:
+///   const osts = await invoke("search_soundtracks", { query: "hollow", ownedOnly: true });
+#[tauri::command]
+pub async fn search_soundtracks(
+    state: State<'_, AppState>,
+    query: String,
+    owned_only: Option<bool>,
+) -> CmdResult<Vec<Soundtrack>> {
+    with_db(&state, move |conn| {
+        Ok(db::search_soundtracks(conn, query.trim(), owned_only.unwrap_or(false))?)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Save backups
+// ---------------------------------------------------------------------------
+
+/// Register a folder holding a game's save files.
+///
+/// Example JS call:
+///   await invoke("add_save_location", { gameId: 12, path: "C:\\Users\\me\\Saved Games\\Hades" });
+#[tauri::command]
+pub async fn add_save_location(state: State<'_, AppState>, game_id: i64, path: String) -> CmdResult<SaveLocation> {
+    if !std::path::Path::new(&path).is_dir() {
+        return Err(CommandError::validation("path", "Must be an existing folder"));
+    }
+    with_db(&state, move |conn| {
+        if db::get_game(conn, game_id)?.is_none() {
+            return Err(CommandError::NotFound(format!("Game {game_id}")));
+        }
+        Ok(db::add_save_location(conn, game_id, &path)?)
+    })
+    .await
+}
+
+/// Stop backing up a save folder. Existing backups are kept.
+#[tauri::command]
+pub async fn remove_save_location(state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    with_db(&state, move |conn| Ok(db::remove_save_location(conn, id)?)).await
+}
+
+#[tauri::command]
+pub async fn get_save_locations(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<SaveLocation>> {
+    with_db(&state, move |conn| Ok(db::get_save_locations(conn, game_id)?)).await
+}
+
+/// Zip every registered save folder of a game into app data. Only the newest
+/// `save_backups_to_keep` backups (a setting) are kept.
+///
+/// Example JS call:
+///   const backup = await invoke("backup_saves", { gameId: 12 });
+#[tauri::command]
+pub async fn backup_saves(app: tauri::AppHandle, state: State<'_, AppState>, game_id: i64) -> CmdResult<SaveBackup> {
+    let backup = take_backup(&app, &state, game_id, BackupReason::Manual).await?;
+    prune_backups(&state, game_id).await?;
+    Ok(backup)
+}
+
+/// Put a backup's files back, replacing the current contents of its save
+/// folders. The current saves are backed up first, so a restore can be undone.
+///
+/// Example JS call:
+///   await invoke("restore_saves", { gameId: 12, backupId: 40 });
+#[tauri::command]
+pub async fn restore_saves(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    backup_id: i64,
+) -> CmdResult<Vec<String>> {
+    let backup = with_db(&state, move |conn| {
+        db::get_save_backups(conn, game_id)?
+            .into_iter()
+            .find(|b| b.id == backup_id)
+            .ok_or_else(|| CommandError::NotFound(format!("Save backup {backup_id}")))
+    })
+    .await?;
+
+    // Missing save folders just mean there's nothing to keep.
+    match take_backup(&app, &state, game_id, BackupReason::PreRestore).await {
+        Ok(_) | Err(CommandError::ValidationFailed { .. }) => {}
+        Err(e) => return Err(e),
+    }
+    let restored = blocking(move || saves::restore_backup(std::path::Path::new(&backup.archive_path))).await?;
+
+    // Pruning waits until after the restore so it can't delete the archive being restored.
+    prune_backups(&state, game_id).await?;
+    Ok(restored)
+}
+
+/// A game's save backups, newest first.
+#[tauri::command]
+pub async fn get_save_backups(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<SaveBackup>> {
+    with_db(&state, move |conn| Ok(db::get_save_backups(conn, game_id)?)).await
+}
+
+/// Zip a game's save folders and record the backup.
+async fn take_backup(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    game_id: i64,
+    reason: BackupReason,
+) -> CmdResult<SaveBackup> {
+    let locations: Vec<String> = with_db(state, move |conn| {
+        Ok(db::get_save_locations(conn, game_id)?.into_iter().map(|l| l.path).collect())
+    })
+    .await?;
+    if locations.is_empty() {
+        return Err(CommandError::validation("gameId", "No save folders registered for this game"));
+    }
+
+    let dir = saves::get_backups_dir(app, game_id)?;
+    let archive = blocking(move || saves::create_backup(&dir, &locations)).await?;
+    with_db(state, move |conn| {
+        let path = archive.path.to_string_lossy();
+        Ok(db::add_save_backup(conn, game_id, &path, archive.size_bytes, archive.file_count, reason)?)
+    })
+    .await
+}
+
+/// Apply the retention setting, deleting archives that fall outside it.
+async fn prune_backups(state: &AppState, game_id: i64) -> CmdResult<()> {
+    let stale = with_db(state, move |conn| {
+        let keep = db::get_settings(conn)?.save_backups_to_keep;
+        Ok(db::prune_save_backups(conn, game_id, keep)?)
+    })
+    .await?;
+    for path in stale {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!(path, error = %e, "Could not delete old save backup");
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// External accounts (Steam, …)
+// ---------------------------------------------------------------------------
+
+/// Link a game to its id on another service, e.g. `{ source: "Steam", externalId: "1145360" }`.
+#[tauri::command]
+pub async fn link_external_id(
+    state: State<'_, AppState>,
+    game_id: i64,
+    source: String,
+    external_id: String,
+) -> CmdResult<()> {
+    if external_id.trim().is_empty() {
+        return Err(CommandError::validation("external_id", "Must not be empty"));
+    }
+    with_db(&state, move |conn| Ok(db::link_external_id(conn, game_id, &source, external_id.trim())?)).await
+}
+
+/// Remove a game's link to `source`. Returns false if it wasn't linked.
+#[tauri::command]
+pub async fn unlink_external_id(state: State<'_, AppState>, game_id: i64, source: String) -> CmdResult<bool> {
+    with_db(&state, move |conn| Ok(db::unlink_external_id(conn, game_id, &source)?)).await
+}
+
+/// A game's links, including what each service last reported.
+#[tauri::command]
+pub async fn get_external_ids(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<ExternalId>> {
+    with_db(&state, move |conn| Ok(db::get_external_ids(conn, Some(game_id), None)?)).await
+}
+
+/// Pull playtime for every Steam-linked game now, instead of waiting for the
+/// background sync. Needs the `steam_api_key` credential and `steam_id` in settings.
+///
+/// With `dryRun: true` nothing is saved and `preview` lists the games that
+/// would be created, updated (with field diffs) or skipped. The other syncs
+/// and import_retroarch_playlist take `dryRun` too.
+///
+/// Example JS call:
+///   const { updated_ids, added_minutes } = await invoke("sync_steam_playtime");
+///   const { preview } = await invoke("sync_steam_playtime", { dryRun: true });
+#[tauri::command]
+pub async fn sync_steam_playtime(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "steam_sync", move |job| steam::sync_playtime(&db, dry_run.unwrap_or(false), job)).await?;
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Fetch ProtonDB tiers for Steam-linked games (all of them, or just
+/// `gameId`). Filter on them with `min_protondb_tier` in `search_games`.
+///
+/// Example JS call:
+///   const { checked, updated_ids } = await invoke("sync_protondb");
+#[tauri::command]
+pub async fn sync_protondb(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: Option<i64>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "protondb_sync", move |job| compat::sync_protondb(&db, game_id, job)).await?;
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Fetch Valve's Steam Deck compatibility status for Steam-linked games (all
+/// of them, or just `gameId`). Filter on it with `min_deck_status` — e.g. a
+/// "Deck-ready backlog" is `{ status: "Backlog", min_deck_status: "Playable" }`.
+///
+/// Example JS call:
+///   const { checked, updated_ids } = await invoke("sync_deck_status");
+#[tauri::command]
+pub async fn sync_deck_status(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: Option<i64>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "deck_status_sync", move |job| compat::sync_deck_status(&db, game_id, job)).await?;
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Re-check every provider's records that are older than its staleness
+/// window (`protondb_stale_days`, `deck_status_stale_days`, `cover_stale_days`)
+/// now, instead of waiting for the background refresh. Covers are only
+/// downloaded again if the server says they changed. Returns the manifest of
+/// what changed.
+///
+/// Example JS call:
+///   const { providers } = await invoke("refresh_stale_metadata");
+///   // providers: [{ provider: "ProtonDB", stale_after_days: 7, checked: 12, updated_ids: [4], error: null }, …]
+#[tauri::command]
+pub async fn refresh_stale_metadata(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> CmdResult<RefreshManifest> {
+    let (job_app, db) = (app.clone(), Arc::clone(&state.db));
+    let manifest = run_job(&state, "metadata_refresh", move |job| refresh::refresh_stale(&job_app, &db, job)).await?;
+    events::games_changed(&app, ChangeAction::Updated, refresh::updated_ids(&manifest));
+    Ok(manifest)
+}
+
+/// Manifests of recent metadata refreshes (background or manual), newest first.
+///
+/// Example JS call:
+///   const runs = await invoke("get_metadata_refreshes", { limit: 10 });
+#[tauri::command]
+pub async fn get_metadata_refreshes(
+    state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> CmdResult<Vec<RefreshManifest>> {
+    with_db(&state, move |conn| Ok(db::get_refresh_manifests(conn, limit.unwrap_or(20))?)).await
+}
+
+/// Copy the screenshots the Steam client saved for a Steam-linked game into
+/// the library. Screenshots imported earlier are skipped (and counted), so
+/// this can be run again to pick up new ones. Returns the game with its
+/// updated screenshot list.
+///
+/// Example JS call:
+///   const { game, added, skipped } = await invoke("import_steam_screenshots", { gameId: 12 });
+#[tauri::command]
+pub async fn import_steam_screenshots(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+) -> CmdResult<ScreenshotImport> {
+    let (settings, app_id, existing) = with_db(&state, move |conn| {
+        db::get_game(conn, game_id)?.ok_or_else(|| CommandError::NotFound(format!("Game {game_id}")))?;
+        let link = db::get_external_ids(conn, Some(game_id), Some(steam::SOURCE))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| CommandError::validation("game_id", "Game isn't linked to a Steam app id"))?;
+        Ok((db::get_settings(conn)?, link.external_id, db::screenshot_hashes(conn, game_id)?))
+    })
+    .await?;
+
+    let image_app = app.clone();
+    let (saved, duplicates) = run_job(&state, "steam_screenshot_import", move |job| {
+        let found = steam::find_screenshots(settings.steam_path.as_deref(), settings.steam_id.as_deref(), &app_id)?;
+        new_screenshots(&image_app, &found, existing, job)
+    })
+    .await?;
+
+    let mut report = with_db(&state, move |conn| Ok(db::add_screenshots(conn, game_id, &saved)?)).await?;
+    report.skipped += duplicates;
+    events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    Ok(report)
+}
+
+/// Import trophy titles, trophies and PS4/PS5 playtime from PlayStation Network.
+/// Needs the `psn_npsso` credential. Titles not yet in the library are added;
+/// `onConflict` (see import_title_list) handles ones matching a library game by title.
+///
+/// Example JS call:
+///   const { imported_ids, updated_ids } = await invoke("sync_psn");
+#[tauri::command]
+pub async fn sync_psn(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "psn_sync", move |job| {
+        psn::sync(&db, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Import the Xbox library, gamerscore and achievement unlocks through OpenXBL.
+/// Needs the `xbox_api_key` credential. Titles not yet in the library are added;
+/// `onConflict` (see import_title_list) handles ones matching a library game by title.
+///
+/// Example JS call:
+///   const { imported_ids, updated_ids } = await invoke("sync_xbox");
+#[tauri::command]
+pub async fn sync_xbox(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "xbox_sync", move |job| {
+        xbox::sync(&db, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Import the games bought or claimed on itch.io, with their covers and
+/// store pages (see `get_external_ids`). Needs the `itch_api_key` credential.
+/// Games not yet in the library are added as DRM-free PC games; `onConflict`
+/// (see import_title_list) handles ones matching a library game by title.
+///
+/// Example JS call:
+///   const { imported_ids, updated_ids } = await invoke("sync_itch", { dryRun: true });
+#[tauri::command]
+pub async fn sync_itch(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let job_app = app.clone();
+    let report = run_job(&state, "itch_sync", move |job| {
+        itch::sync(&job_app, &db, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Import the games bought through Humble Bundle — bundles, Humble Choice
+/// months and store purchases — recording each order with its bundle name and
+/// date (see `get_bundles`). Needs the `humble_session` credential.
+/// `onConflict` (see import_title_list) handles games matching a library game
+/// by title.
+///
+/// Example JS call:
+///   const { imported_ids, updated_ids } = await invoke("sync_humble", { dryRun: true });
+#[tauri::command]
+pub async fn sync_humble(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "humble_sync", move |job| {
+        humble::sync(&db, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Like `sync_humble`, from a library export file (the orders as JSON, as
+/// saved by library export scripts) instead of the session cookie.
+///
+/// Example JS call:
+///   const report = await invoke("import_humble_export", { path: "/home/me/humble-orders.json" });
+#[tauri::command]
+pub async fn import_humble_export(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "humble_import", move |job| {
+        let path = std::path::Path::new(&path);
+        humble::import_export(&db, path, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Every order a game came in (bundle, date), oldest first.
+#[tauri::command]
+pub async fn get_purchases(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<Purchase>> {
+    with_db(&state, move |conn| Ok(db::get_purchases(conn, game_id)?)).await
+}
+
+/// Every bundle bought, newest first, with how many of its games have been
+/// played — for keeping an eye on bundles bought and never touched.
+///
+/// Example JS call:
+///   const bundles = await invoke("get_bundles");
+///   // [{ store: "Humble", bundle: "Humble Indie Bundle 20", purchased_at: "2019-05-01", games: 8, played: 2 }, …]
+#[tauri::command]
+pub async fn get_bundles(state: State<'_, AppState>) -> CmdResult<Vec<BundleSummary>> {
+    with_db(&state, move |conn| Ok(db::get_bundles(conn)?)).await
+}
+
+/// A game's trophies / achievements from every linked service.
+#[tauri::command]
+pub async fn get_achievements(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<Achievement>> {
+    with_db(&state, move |conn| Ok(db::get_achievements(conn, game_id)?)).await
+}
+
+/// Unlocked / total achievements and points (gamerscore) per service for a game.
+#[tauri::command]
+pub async fn get_achievement_summary(state: State<'_, AppState>, game_id: i64) -> CmdResult<Vec<AchievementSummary>> {
+    with_db(&state, move |conn| Ok(db::get_achievement_summaries(conn, game_id)?)).await
+}
+
+/// Import the games in a RetroArch playlist (.lpl), with their system and ROM
+/// path. If RetroArch's runtime logs are found (by default in the `logs` folder
+/// next to the playlist), their playtime is merged in too. Importing the same
+/// playlist again only adds new games and new playtime. `onConflict` (see
+/// import_title_list) handles ROMs matching an unlinked library game by title.
+///
+/// Example JS call:
+///   await invoke("import_retroarch_playlist",
+///                { path: "/home/me/.config/retroarch/playlists/Nintendo - Super Nintendo Entertainment System.lpl" });
+#[tauri::command]
+pub async fn import_retroarch_playlist(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    logs_dir: Option<String>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let report = run_job(&state, "retroarch_import", move |job| {
+        let logs_dir = logs_dir.as_deref().map(std::path::Path::new);
+        let path = std::path::Path::new(&path);
+        retroarch::import_playlist(&db, path, logs_dir, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Import the CSV export of another game tracker: each game with its status,
+/// rating, playtime and notes, as far as the tracker records them.
+/// `format` is "HowLongToBeat" (Options → Export on your profile), "Darkadia"
+/// or "Completionator". `platform` (default "PC") is used for rows without
+/// one; `onConflict` (see import_title_list) handles games already in the
+/// library.
+///
+/// Example JS call:
+///   const report = await invoke("import_tracker_export", {
+///     path: "/home/me/Downloads/hltb-games.csv", format: "HowLongToBeat", dryRun: true,
+///   });
+#[tauri::command]
+pub async fn import_tracker_export(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    format: TrackerFormat,
+    platform: Option<String>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let platform = platform.unwrap_or_else(|| "PC".to_string());
+    let report = run_job(&state, "tracker_import", move |job| {
+        let path = std::path::Path::new(&path);
+        trackers::import(&db, format, path, &platform, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// The importer plugins installed in the app data folder's `plugins/` (see
+/// plugins.rs for how to write one). Plugins that fail to load are left out
+/// and logged. A plugin runs with the user's own permissions, not in a
+/// sandbox.
+///
+/// Example JS call:
+///   const plugins = await invoke("list_importer_plugins");
+///   // [{ id: "backloggd", name: "Backloggd", input: "File", file_types: ["csv"], … }]
+#[tauri::command]
+pub async fn list_importer_plugins(app: tauri::AppHandle) -> CmdResult<Vec<ImporterPlugin>> {
+    blocking(move || plugins::list(&plugins::get_plugins_dir(&app)?)).await
+}
+
+/// Run importer plugin `id` and import the games it returns. `path` is the
+/// file to import from, for plugins whose input is "File"; `platform`
+/// (default "PC") is used for games without one; `onConflict` (see
+/// import_title_list) handles games already in the library. The plugin's
+/// answer is validated as a whole first — an invalid game fails the import
+/// with a VALIDATION_FAILED error naming it (e.g. field "games[3].rating").
+///
+/// Example JS call:
+///   const report = await invoke("run_importer_plugin", {
+///     id: "backloggd", path: "/home/me/Downloads/backloggd.csv", dryRun: true,
+///   });
+#[tauri::command]
+pub async fn run_importer_plugin(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    path: Option<String>,
+    platform: Option<String>,
+    dry_run: Option<bool>,
+    on_conflict: Option<ConflictStrategy>,
+) -> CmdResult<SyncReport> {
+    let db = Arc::clone(&state.db);
+    let dir = plugins::get_plugins_dir(&app)?;
+    let platform = platform.unwrap_or_else(|| "PC".to_string());
+    let report = run_job(&state, "plugin_import", move |job| {
+        let path = path.as_deref().map(std::path::Path::new);
+        plugins::run(&dir, &db, &id, path, &platform, dry_run.unwrap_or(false), on_conflict.unwrap_or_default(), job)
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Added, report.imported_ids.clone());
+    hooks::games_added(&report.imported_ids);
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+/// Imports, syncs and other long tasks, newest first — queued and running
+/// ones with their current progress. `status` narrows it down, e.g. "Running".
+/// Follow changes live with `listen("jobs://updated", …)`.
+///
+/// Example JS call:
+///   const jobs = await invoke("get_jobs", { status: "Running" });
+///   // [{ id: 41, kind: "psn_sync", status: "Running", done: 12, total: 80, message: "Hades", … }]
+#[tauri::command]
+pub async fn get_jobs(
+    state: State<'_, AppState>,
+    status: Option<JobStatus>,
+    limit: Option<u32>,
+) -> CmdResult<Vec<Job>> {
+    let jobs = Arc::clone(&state.jobs);
+    blocking(move || jobs.jobs(status, limit.unwrap_or(50))).await
+}
+
+/// Stop a queued or running job. A running job stops at its next checkpoint
+/// (work in progress is rolled back); the command that started it fails with
+/// code "CANCELLED". Returns false if the job had already finished.
+///
+/// Example JS call:
+///   await invoke("cancel_job", { id: 41 });
+#[tauri::command]
+pub async fn cancel_job(state: State<'_, AppState>, id: i64) -> CmdResult<bool> {
+    let jobs = Arc::clone(&state.jobs);
+    blocking(move || jobs.cancel(id)).await
+}
+
+// ---------------------------------------------------------------------------
+// Installed games
+// ---------------------------------------------------------------------------
+
+/// Look for games installed through Steam, Epic and GOG and mark the matching
+/// library entries as installed (anything no longer found is unmarked).
+/// Installs with no matching game come back in `missing`, ready to be added.
+///
+/// Example JS call:
+///   const { installed, missing } = await invoke("scan_installed_games");
+#[tauri::command]
+pub async fn scan_installed_games(app: tauri::AppHandle, state: State<'_, AppState>) -> CmdResult<InstallScanReport> {
+    let settings = with_db(&state, |conn| Ok(db::get_settings(conn)?)).await?;
+    let found = blocking(move || Ok(installs::scan(settings.steam_path.as_deref()))).await?;
+
+    let (report, touched) = with_db(&state, move |conn| {
+        let mut found = found;
+        db::match_installs(conn, &mut found)?;
+        let touched = db::replace_installs(conn, &found)?;
+        let (installed, missing) = found.into_iter().partition(|i| i.game_id.is_some());
+        Ok((InstallScanReport { installed, missing }, touched))
+    })
+    .await?;
+    events::games_changed(&app, ChangeAction::Updated, touched);
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// Return the user's settings (defaults for anything never saved).
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> CmdResult<Settings> {
+    with_db(&state, |conn| Ok(db::get_settings(conn)?)).await
+}
+
+/// Replace the user's settings and return them.
+///
+/// Example JS call:
+///   await invoke("update_settings", { settings: { metadata_provider: "Steam" } });
+#[tauri::command]
+pub async fn update_settings(state: State<'_, AppState>, settings: Settings) -> CmdResult<Settings> {
+    http::check_settings(&settings)?;
+    with_db(&state, move |conn| {
+        db::save_settings(conn, &settings)?;
+        ratelimit::configure(&settings.rate_limits);
+        http::configure(&settings);
+        Ok(settings)
+    })
+    .await
+}
+
+/// How fast each external service may be called, with the built-in default.
+/// Override one with the `rate_limits` setting, e.g. after upgrading to a
+/// paid OpenXBL plan: `{ ...settings, rate_limits: { Xbox: 20 } }`.
+///
+/// Example JS call:
+///   const limits = await invoke("get_rate_limits");
+///   // [{ provider: "Xbox", hosts: ["xbl.io"], default_per_minute: 2, per_minute: 2 }, …]
+#[tauri::command]
+pub async fn get_rate_limits() -> CmdResult<Vec<RateLimit>> {
+    Ok(ratelimit::limits())
+}
+
+// ---------------------------------------------------------------------------
+// Credentials
+// ---------------------------------------------------------------------------
+// API keys and tokens are kept in the OS credential store, not in settings.
+// `key` is one of the names listed by get_credential_status.
+
+/// Store a secret, e.g. the Steam Web API key. An empty `secret` removes it.
+///
+/// Example JS call:
+///   await invoke("set_credential", { key: "steam_api_key", secret: "0123ABCD…" });
+#[tauri::command]
+pub async fn set_credential(key: String, secret: String) -> CmdResult<()> {
+    blocking(move || {
+        credentials::set(&key, &secret)?;
+        if key == credentials::PROXY_PASSWORD {
+            http::reconnect();
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// The stored secret, or null if it isn't set.
+///
+/// Example JS call:
+///   const npsso = await invoke("get_credential", { key: "psn_npsso" });
+#[tauri::command]
+pub async fn get_credential(key: String) -> CmdResult<Option<String>> {
+    blocking(move || credentials::get(&key)).await
+}
+
+/// Remove a stored secret.
+///
+/// Example JS call:
+///   await invoke("delete_credential", { key: "xbox_api_key" });
+#[tauri::command]
+pub async fn delete_credential(key: String) -> CmdResult<()> {
+    blocking(move || {
+        credentials::delete(&key)?;
+        if key == credentials::PROXY_PASSWORD {
+            http::reconnect();
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Every secret the app can use and whether it's set, for the settings screen.
+///
+/// Example JS call:
+///   const creds = await invoke("get_credential_status");
+///   // [{ key: "steam_api_key", provider: "Steam", label: "Steam Web API key", configured: true }, …]
+#[tauri::command]
+pub async fn get_credential_status() -> CmdResult<Vec<CredentialStatus>> {
+    blocking(|| Ok(credentials::statuses())).await
+}
+
+// ---------------------------------------------------------------------------
+// Hooks
+// ---------------------------------------------------------------------------
+// The user's own commands, run with the event as JSON on stdin when a game
+// is added, completed or a session ends (see hooks.rs). Kept on this device.
+
+/// Every hook on this device, in the order they run.
+///
+/// Example JS call:
+///   const hooks = await invoke("get_hooks");
+///   // [{ id: "5f0c…", event: "SessionEnded", command: "/home/me/bin/journal", args: [], enabled: true, … }]
+#[tauri::command]
+pub async fn get_hooks() -> CmdResult<Vec<Hook>> {
+    Ok(hooks::list())
+}
+
+/// Register a command to run on `event` ("GameAdded", "GameCompleted" or
+/// "SessionEnded"). `command` is a program on PATH or the path to one;
+/// `args` are passed as given, so a shell line needs "sh" and "-c".
+///
+/// Example JS call:
+///   const hook = await invoke("add_hook", { input: {
+///     event: "SessionEnded", command: "sh", args: ["-c", "cat >> ~/gaming-journal.jsonl"],
+///   } });
+#[tauri::command]
+pub async fn add_hook(input: HookInput) -> CmdResult<Hook> {
+    blocking(move || hooks::add(input)).await
+}
+
+/// Replace a hook's event, command, arguments and whether it's enabled
+/// (omitted = enabled).
+///
+/// Example JS call:
+///   await invoke("update_hook", { id, input: { ...hook, enabled: false } });
+#[tauri::command]
+pub async fn update_hook(id: String, input: HookInput) -> CmdResult<Hook> {
+    blocking(move || hooks::update(&id, input)).await
+}
+
+#[tauri::command]
+pub async fn delete_hook(id: String) -> CmdResult<()> {
+    blocking(move || hooks::delete(&id)).await
+}
+
+/// Run a hook now, with game `gameId` as the event's game, and wait for it —
+/// to check it works before it runs for real. The payload has `test: true`.
+///
+/// Example JS call:
+///   const run = await invoke("test_hook", { id, gameId: 12 });
+///   // { exit_code: 0, timed_out: false, duration_ms: 41, stdout: "", stderr: "" }
+#[tauri::command]
+pub async fn test_hook(state: State<'_, AppState>, id: String, game_id: i64) -> CmdResult<HookRun> {
+    let game = with_db(&state, move |conn| db::get_game(conn, game_id)?.ok_or_else(|| CommandError::NotFound(format!("Game {game_id}")))).await?;
+    blocking(move || hooks::test(&id, &game)).await
+}
+
+// ---------------------------------------------------------------------------
+// Response cache
+// ---------------------------------------------------------------------------
+
+/// How many provider responses (searches, store pages, covers) are cached on
+/// disk and how much space they take.
+///
+/// Example JS call:
+///   const { entries, size_bytes, oldest } = await invoke("get_cache_stats");
+#[tauri::command]
+pub async fn get_cache_stats() -> CmdResult<CacheStats> {
+    blocking(|| Ok(cache::stats())).await
+}
+
+/// Delete cached provider responses — all of them, or only those older than
+/// `olderThanDays`. Returns what was removed.
+///
+/// Example JS call:
+///   await invoke("clear_metadata_cache", { olderThanDays: 30 });
+#[tauri::command]
+pub async fn clear_metadata_cache(older_than_days: Option<u32>) -> CmdResult<CacheStats> {
+    let older_than = older_than_days.map(|days| std::time::Duration::from_secs(u64::from(days) * 24 * 3600));
+    blocking(move || cache::clear(older_than)).await
+}
+
+// ---------------------------------------------------------------------------
+// Image processing
+// ---------------------------------------------------------------------------
+
+/// Process a cover image: copy a local file or download a remote URL.
+///
+/// Takes either a local filesystem path or an http(s):// URL.
+/// Saves the image to app_data_dir/images/ with a unique filename.
+/// Returns the absolute path to the saved image, which should be stored in the DB.
+/// `kind` ("Cover", the default, or "Screenshot") decides how it's stored:
+/// covers are cropped to portrait and shrunk, screenshots kept as they are.
+///
+/// Example JS call:
+///   const savedPath = await invoke("process_cover_image", { input: "https://example.com/cover.jpg" });
+///   // or
+///   const savedPath = await invoke("process_cover_image", { input: "/home/user/Pictures/shot.png", kind: "Screenshot" });
+#[tauri::command]
+pub async fn process_cover_image(app: tauri::AppHandle, input: String, kind: Option<ImageKind>) -> CmdResult<String> {
+    blocking(move || crate::images::process_image(&app, &input, kind.unwrap_or_default()).map_err(Into::into)).await
+}
+
+/// `process_cover_image` for many inputs in one call — e.g. files dropped on
+/// the window. Returns a result per input, in the same order; one failing
+/// doesn't stop the rest. `kind` is as for `process_cover_image`.
+///
+/// Example JS call:
+///   const results = await invoke("process_images", { inputs: droppedPaths, kind: "Screenshot" });
+///   // [{ input: "/home/user/a.png", path: "/…/images/3f2c….png", error: null },
+///   //  { input: "/home/user/notes.txt", path: null, error: "IO error: …" }]
+#[tauri::command]
+pub async fn process_images(
+    app: tauri::AppHandle,
+    inputs: Vec<String>,
+    kind: Option<ImageKind>,
+) -> CmdResult<Vec<ProcessedImage>> {
+    let kind = kind.unwrap_or_default();
+    blocking(move || {
+        Ok(inputs
+            .into_iter()
+            .map(|input| match crate::images::process_image(&app, &input, kind) {
+                Ok(path) => ProcessedImage { input, path: Some(path), error: None },
+                Err(e) => ProcessedImage { input, path: None, error: Some(e.to_string()) },
+            })
+            .collect())
+    })
+    .await
+}
+
+/// Crop and/or resize an image — the pixel work behind the cover-crop UI.
+/// `cropRect` is in the image's own pixels; `targetSize` is the size to scale
+/// the (cropped) image to. The result is written to a new file, leaving the
+/// original for any game that still uses it; returns the new path, to save
+/// with update_game.
+///
+/// Example JS call:
+///   const cropped = await invoke("transform_image", {
+///     path: game.cover_art_path,
+///     cropRect: { x: 120, y: 0, width: 600, height: 900 },
+///     targetSize: { width: 400, height: 600 },
+///   });
+#[tauri::command]
+pub async fn transform_image(
+    app: tauri::AppHandle,
+    path: String,
+    crop_rect: Option<CropRect>,
+    target_size: Option<ImageSize>,
+) -> CmdResult<String> {
+    blocking(move || crate::images::transform(&app, &path, crop_rect, target_size)).await
+}
+
+/// Attach every image in a folder (PNG, JPEG, WebP or GIF; subfolders aren't
+/// searched) to a game as screenshots, in file name order. Each is copied into
+/// the images folder like `process_cover_image` does. Exact copies of
+/// screenshots the game has are skipped and counted, so a folder can be
+/// imported again after more were added to it. Runs as a job, so a big folder
+/// reports progress.
+///
+/// Example JS call:
+///   const { game, added, skipped } = await invoke("import_screenshots", {
+///     gameId: 12, folderPath: "C:/Users/me/Pictures/Hades",
+///   });
+#[tauri::command]
+pub async fn import_screenshots(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    game_id: i64,
+    folder_path: String,
+) -> CmdResult<ScreenshotImport> {
+    let folder = std::path::PathBuf::from(folder_path.trim());
+    if !folder.is_dir() {
+        return Err(CommandError::validation("folder_path", "must be a folder"));
+    }
+    let existing = with_db(&state, move |conn| {
+        db::get_game(conn, game_id)?.ok_or_else(|| CommandError::NotFound(format!("Game {game_id}")))?;
+        Ok(db::screenshot_hashes(conn, game_id)?)
+    })
+    .await?;
+
+    let image_app = app.clone();
+    let (saved, duplicates) = run_job(&state, "screenshot_import", move |job| {
+        let found = crate::images::images_in(&folder)?;
+        new_screenshots(&image_app, &found, existing, job)
+    })
+    .await?;
+
+    let mut report = with_db(&state, move |conn| Ok(db::add_screenshots(conn, game_id, &saved)?)).await?;
+    report.skipped += duplicates;
+    events::games_changed(&app, ChangeAction::Updated, vec![game_id]);
+    Ok(report)
+}
+
+/// Copy the files in `found` that aren't among a game's screenshots (`existing`,
+/// path → content hash) into the images folder, comparing by content before
+/// anything is copied. Returns the saved paths and how many were duplicates.
+fn new_screenshots(
+    app: &tauri::AppHandle,
+    found: &[std::path::PathBuf],
+    existing: HashMap<String, String>,
+    job: &JobHandle,
+) -> CmdResult<(Vec<String>, usize)> {
+    let mut seen: HashSet<String> = existing.into_values().collect();
+    let mut saved = Vec::new();
+    let mut duplicates = 0;
+    for (i, path) in found.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, found.len(), path.to_string_lossy());
+        let path = path.to_string_lossy();
+        if !seen.insert(crate::images::content_hash(&path)?) {
+            duplicates += 1;
+            continue;
+        }
+        saved.push(crate::images::process_image(app, &path, ImageKind::Screenshot)?);
+    }
+    Ok((saved, duplicates))
+}
+
+/// Put a cover or screenshot on the system clipboard as an image — not its
+/// path — ready to paste into a chat or a document.
+///
+/// Example JS call:
+///   await invoke("copy_image_to_clipboard", { path: game.cover_art_path });
+#[tauri::command]
+pub async fn copy_image_to_clipboard(app: tauri::AppHandle, path: String) -> CmdResult<()> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    blocking(move || {
+        let pixels = crate::images::decode(&path)?;
+        let (width, height) = pixels.dimensions();
+        let image = tauri::image::Image::new_owned(pixels.into_raw(), width, height);
+        app.clipboard()
+            .write_image(&image)
+            .map_err(|e| CommandError::Internal(format!("Couldn't copy to the clipboard: {e}")))?;
+        tracing::info!(path, width, height, "Copied image to the clipboard");
+        Ok(())
+    })
+    .await
+}
+
+/// Repair cover and screenshot paths after the app data folder moved (e.g.
+/// to a new machine): each path whose file is gone is pointed at the file of
+/// the same name in the current images folder. Images found nowhere come
+/// back in `missing`, with their paths untouched.
+///
+/// Example JS call:
+///   const { relinked, missing } = await invoke("relink_images");
+#[tauri::command]
+pub async fn relink_images(app: tauri::AppHandle, state: State<'_, AppState>) -> CmdResult<RelinkReport> {
+    let images_dir = crate::images::get_images_dir(&app)?;
+    let report = with_db(&state, move |conn| crate::images::relink(conn, &images_dir)).await?;
+    events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+    Ok(report)
+}
+
+/// Delete images no game uses any more, oldest first, until the images folder
+/// fits the `max_images_mb` setting. Covers and screenshots in use are never
+/// deleted; `over_cap_bytes` says how far they alone are over the cap. Also
+/// runs on startup.
+///
+/// Example JS call:
+///   const { removed, freed_bytes, over_cap_bytes } = await invoke("evict_images");
+#[tauri::command]
+pub async fn evict_images(app: tauri::AppHandle, state: State<'_, AppState>) -> CmdResult<ImageEvictionReport> {
+    let db = Arc::clone(&state.db);
+    run_job(&state, "image_eviction", move |_| crate::images::evict_unused(&app, &db)).await
+}
+
+// ---------------------------------------------------------------------------
+// Diagnostics
+// ---------------------------------------------------------------------------
+
+/// Version, database, images, backups and which providers are set up, in
+/// one payload for the about screen and support requests. Secrets are never
+/// included, only whether each is set.
+///
+/// Example JS call:
+///   const info = await invoke("get_diagnostics");
+///   // { app_version: "1.2.0", db_path: "…/games.db", db_size_bytes: 1843200, schema_version: 17,
+///   //   image_count: 412, images_size_bytes: 98304000, backups: { … }, providers: [ … ], … }
+#[tauri::command]
+pub async fn get_diagnostics(app: tauri::AppHandle, state: State<'_, AppState>) -> CmdResult<Diagnostics> {
+    let db = Arc::clone(&state.db);
+    blocking(move || diagnostics::collect(&app, &db)).await
+}
+
+/// Return the most recent log lines, oldest first, for attaching to bug reports.
+///
+/// `level` is the least severe level to include ("error", "warn", "info",
+/// "debug", "trace"); omit it to get everything. `limit` defaults to 200.
+///
+/// Example JS call:
+///   const logs = await invoke("get_recent_logs", { level: "warn", limit: 50 });
+#[tauri::command]
+pub async fn get_recent_logs(
+    app: tauri::AppHandle,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> CmdResult<Vec<LogEntry>> {
+    blocking(move || {
+        let log_dir = crate::logging::get_log_dir(&app);
+        crate::logging::recent_logs(&log_dir, level.as_deref(), limit.unwrap_or(200))
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::InvalidInput => CommandError::validation("level", e.to_string()),
+                _ => e.into(),
+            })
+    })
+    .await
+}
@@ -0,0 +1,120 @@
+// compat.rs — How well Steam-linked games run on Linux and the Steam Deck.
+//
+// Two sources, both keyed by Steam app id:
+//   ProtonDB   — crowd-sourced tier (Platinum, Gold, …) for running through Proton
+//   Steam Deck — Valve's own Deck Verified review (Verified / Playable / Unsupported)
+//
+// Each rating is stored in `game_compat` (one row per game and source) with
+// the time it was checked, so the library can be filtered without hitting the
+// network.
+
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::http;
+use crate::jobs::JobHandle;
+use crate::models::{DeckStatus, ExternalId, ProtonTier, SyncReport};
+use crate::steam;
+
+/// `source` values used in `game_compat`.
+pub const PROTONDB: &str = "ProtonDB";
+pub const STEAM_DECK: &str = "SteamDeck";
+
+const PROTONDB_URL: &str = "https://www.protondb.com/api/v1/reports/summaries";
+const DECK_URL: &str = "https://store.steampowered.com/saleaction/ajaxgetdeckappcompatibilityreport";
+
+#[derive(Deserialize)]
+struct ProtonSummary {
+    tier: String,   // "platinum", "gold", …, or "pending" with too few reports
+}
+
+#[derive(Deserialize)]
+struct DeckResponse {
+    results: Option<DeckResults>,   // missing (or `[]`) for apps Valve hasn't reviewed
+}
+
+#[derive(Deserialize)]
+struct DeckResults {
+    resolved_category: u8,          // 0 unknown, 1 unsupported, 2 playable, 3 verified
+}
+
+/// Fetch the ProtonDB tier of every Steam-linked game (or just `game_id`).
+pub fn sync_protondb(db: &Mutex<Connection>, game_id: Option<i64>, job: &JobHandle) -> CmdResult<SyncReport> {
+    let links = db::get_external_ids(&*db::lock(db)?, game_id, Some(steam::SOURCE))?;
+    sync_ratings(db, &links, PROTONDB, fetch_protondb, job)
+}
+
+/// Fetch the Steam Deck compatibility status of every Steam-linked game (or just `game_id`).
+pub fn sync_deck_status(db: &Mutex<Connection>, game_id: Option<i64>, job: &JobHandle) -> CmdResult<SyncReport> {
+    let links = db::get_external_ids(&*db::lock(db)?, game_id, Some(steam::SOURCE))?;
+    sync_ratings(db, &links, STEAM_DECK, fetch_deck_status, job)
+}
+
+/// Re-check `source` (`PROTONDB` or `STEAM_DECK`) for the Steam-linked games
+/// whose rating is missing or was last checked before `checked_before`.
+pub fn refresh_stale(db: &Mutex<Connection>, source: &str, checked_before: &str, job: &JobHandle) -> CmdResult<SyncReport> {
+    let fetch = match source {
+        PROTONDB => fetch_protondb,
+        STEAM_DECK => fetch_deck_status,
+        _ => return Err(CommandError::validation("source", format!("Unknown compatibility source {source}"))),
+    };
+    let links = db::get_stale_compat_links(&*db::lock(db)?, source, steam::SOURCE, checked_before)?;
+    sync_ratings(db, &links, source, fetch, job)
+}
+
+fn fetch_protondb(app_id: &str) -> CmdResult<Option<&'static str>> {
+    let url = format!("{PROTONDB_URL}/{app_id}.json");
+    // No summary yet means nobody has reported on the game.
+    let summary = http::get_json_if_found::<ProtonSummary>(&url)?;
+    Ok(summary.and_then(|s| ProtonTier::parse(&s.tier)).map(|t| t.as_str()))
+}
+
+fn fetch_deck_status(app_id: &str) -> CmdResult<Option<&'static str>> {
+    let response: serde_json::Value = http::get_json(DECK_URL, &[("nAppID", app_id), ("l", "english")])?;
+    let category = serde_json::from_value::<DeckResponse>(response)
+        .ok()
+        .and_then(|r| r.results)
+        .map(|r| r.resolved_category);
+    Ok(category.and_then(DeckStatus::from_category).map(|s| s.as_str()))
+}
+
+/// Look up one rating per linked game with `fetch` and store it under
+/// `source`. A lookup that fails is logged and skipped so one bad app id
+/// doesn't stop the rest. `updated_ids` lists games whose rating changed.
+fn sync_ratings(
+    db: &Mutex<Connection>,
+    links: &[ExternalId],
+    source: &str,
+    fetch: fn(&str) -> CmdResult<Option<&'static str>>,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    // Network first, without the DB lock held.
+    let mut ratings = Vec::with_capacity(links.len());
+    for (i, link) in links.iter().enumerate() {
+        // Nothing is stored until every lookup is done, so cancelling keeps nothing.
+        job.check_cancelled()?;
+        job.progress(i, links.len(), format!("{source}: app {}", link.external_id));
+        match fetch(&link.external_id) {
+            Ok(rating) => ratings.push((link.game_id, rating)),
+            Err(e) => warn!(source, app_id = link.external_id, error = %e, "Compatibility lookup failed"),
+        }
+    }
+
+    let mut report = SyncReport { checked: ratings.len(), ..SyncReport::default() };
+    let mut conn = db::lock(db)?;
+    let tx = conn.transaction()?;
+    for (game_id, rating) in ratings {
+        if db::set_compat_rating(&tx, game_id, source, rating)? {
+            report.updated_ids.push(game_id);
+        }
+    }
+    tx.commit()?;
+
+    info!(source, checked = report.checked, changed = report.updated_ids.len(), "Compatibility ratings synced");
+    Ok(report)
+}
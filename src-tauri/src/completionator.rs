@@ -0,0 +1,67 @@
+// completionator.rs — Read a Completionator collection export (CSV); see
+// trackers.rs.
+//
+// Completionator exports one row per game in the collection, with its
+// progress as a word and the time played as "H:MM":
+//
+//   Name,Platform,Region,Edition,Ownership,Progress Status,Now Playing,Rating,
+//   Playtime,Date Added,Date Completed,Notes,…
+//   Hades,PC,,,Owned,Completed,No,9,61:12,2021-01-02,2021-02-14,
+//
+// Each row maps to a game:
+//
+//   Now Playing / Progress Status → status (see `status`)
+//   Ownership "Wishlist"          → Wishlist, unless it's been played
+//   Rating                        → rating, already 1 – 10
+//   Playtime                      → playtime
+//   Date Added / Date Completed   → a playthrough, for finished games
+
+use crate::import;
+use crate::models::GameStatus;
+use crate::trackers::{self, Row, TrackerEntry, TrackerExport};
+
+pub struct Completionator;
+
+impl TrackerExport for Completionator {
+    fn name(&self) -> &'static str {
+        "Completionator"
+    }
+
+    fn title_columns(&self) -> &'static [&'static str] {
+        &["name", "title", "game"]
+    }
+
+    fn entry(&self, row: &Row, fallback_platform: &str) -> Option<TrackerEntry> {
+        let title = row.get(self.title_columns())?;
+        let progress = row.get(&["progress status", "progress", "status"]).unwrap_or_default().to_lowercase();
+        let wishlist = row.get(&["ownership"]).is_some_and(|o| o.eq_ignore_ascii_case("wishlist"));
+        let status = if row.flag("now playing") {
+            GameStatus::Playing
+        } else {
+            status(&progress, wishlist)
+        };
+
+        let mut input = import::remote_title_input(title, row.get(&["platform"]).unwrap_or(fallback_platform), status.clone());
+        input.rating = row.get(&["rating", "score"]).and_then(|score| trackers::parse_rating(score, 10.0));
+        input.playtime_minutes = row.get(&["playtime", "time played"]).and_then(trackers::parse_duration).filter(|m| *m > 0);
+        input.notes = row.get(&["notes"]).map(str::to_string);
+        let finished_at = row.get(&["date completed", "completed", "completion date"]).and_then(trackers::parse_date);
+        Some(TrackerEntry {
+            started_at: finished_at.is_some().then(|| row.get(&["date added", "added"]).and_then(trackers::parse_date)).flatten(),
+            finished_at: finished_at.filter(|_| status == GameStatus::Completed),
+            ..TrackerEntry::new(input)
+        })
+    }
+}
+
+/// Completionator's progress words → a status.
+fn status(progress: &str, wishlist: bool) -> GameStatus {
+    match progress {
+        "beaten" | "completed" | "mastered" | "100%" => GameStatus::Completed,
+        "unfinished" | "in progress" | "playing"     => GameStatus::Playing,
+        "abandoned" | "dropped"                      => GameStatus::Dropped,
+        _ if wishlist                                => GameStatus::Wishlist,
+        "unplayed" | "backlog"                       => GameStatus::Backlog,
+        _                                            => GameStatus::NotStarted,
+    }
+}
@@ -0,0 +1,88 @@
+// covers.rs — Keep downloaded covers in step with where they came from.
+//
+// A cover downloaded from a URL (a title-list import, an itch.io sync)
+// remembers it in `cover_sources`, along with the server's ETag /
+// Last-Modified and a hash of the bytes. The metadata refresh (refresh.rs)
+// asks for it again with If-None-Match / If-Modified-Since:
+//
+//   304 Not Modified          → nothing is downloaded or written
+//   200 with the same bytes   → nothing is written (servers without validators)
+//   200 with new bytes        → saved as a new cover; the old file is left
+//                               for `images::evict`
+//
+// so refreshing doesn't write a new UUID-named copy of every cover each time.
+// A cover the user has since replaced isn't refreshed: a source only counts
+// while the game's cover is still the file it was saved as.
+
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::cache;
+use crate::db;
+use crate::error::CmdResult;
+use crate::http;
+use crate::images;
+use crate::jobs::JobHandle;
+use crate::models::{CoverSource, ImageKind, SyncReport};
+
+/// Provider name in refresh manifests.
+pub const SOURCE: &str = "Covers";
+
+/// What to remember about `cover`, just saved from `url`. The download is
+/// still in the response cache, so it's hashed without asking the server
+/// again. Validators aren't known yet; the first refresh picks them up.
+pub fn source(url: &str, cover: &str) -> CoverSource {
+    CoverSource {
+        url:           url.to_string(),
+        cover:         images::to_stored(cover),
+        etag:          None,
+        last_modified: None,
+        hash:          http::get_bytes_cached(url, cache::IMAGE_TTL).ok().map(|bytes| images::bytes_hash(&bytes)),
+    }
+}
+
+/// Re-check the covers last checked before `checked_before` (RFC 3339). A
+/// cover that fails to download is logged and skipped. `updated_ids` lists
+/// games whose cover changed.
+pub fn refresh_stale(app: &AppHandle, db: &Mutex<Connection>, checked_before: &str, job: &JobHandle) -> CmdResult<SyncReport> {
+    let sources = db::get_stale_cover_sources(&*db::lock(db)?, checked_before)?;
+    let mut report = SyncReport { checked: sources.len(), ..SyncReport::default() };
+    for (i, (game_id, source)) in sources.into_iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, report.checked, source.url.as_str());
+        let url = source.url.clone();
+        match refresh_cover(app, db, game_id, source) {
+            Ok(true) => report.updated_ids.push(game_id),
+            Ok(false) => {}
+            Err(e) => warn!(game_id, url, error = %e, "Cover refresh failed"),
+        }
+    }
+    info!(checked = report.checked, changed = report.updated_ids.len(), "Covers refreshed");
+    Ok(report)
+}
+
+/// Ask for one cover again; true if the game got a new one.
+fn refresh_cover(app: &AppHandle, db: &Mutex<Connection>, game_id: i64, mut source: CoverSource) -> CmdResult<bool> {
+    // Network first, without the DB lock held.
+    let download = http::get_bytes_if_changed(&source.url, source.etag.as_deref(), source.last_modified.as_deref())?;
+    let Some(download) = download else {
+        db::set_cover_source(&*db::lock(db)?, game_id, &source)?;
+        return Ok(false);
+    };
+    let hash = images::bytes_hash(&download.body);
+    source.etag = download.etag;
+    source.last_modified = download.last_modified;
+    if source.hash.as_deref() == Some(hash.as_str()) {
+        db::set_cover_source(&*db::lock(db)?, game_id, &source)?;
+        return Ok(false);
+    }
+
+    // The new bytes are in the response cache now, so this doesn't download them again.
+    let cover = images::process_image(app, &source.url, ImageKind::Cover)?;
+    let previous = std::mem::replace(&mut source.cover, images::to_stored(&cover));
+    source.hash = Some(hash);
+    Ok(db::replace_cover(&*db::lock(db)?, game_id, &previous, &source)?)
+}
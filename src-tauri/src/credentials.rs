@@ -0,0 +1,139 @@
+// credentials.rs — API keys and tokens, kept in the OS credential store.
+//
+// Secrets used to be ordinary settings, i.e. plaintext in games.db where any
+// backup or export of the database carried them along. They now live in the
+// platform's keychain:
+//   macOS   — Keychain
+//   Windows — Credential Manager
+//   Linux   — Secret Service (GNOME Keyring, KWallet, …)
+//
+// Each secret is stored under the service "GameTrc" with its key (e.g.
+// "steam_api_key") as the account name. `migrate_from_settings` moves secrets
+// saved by older versions out of the settings table on startup.
+
+use rusqlite::Connection;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::models::CredentialStatus;
+
+const SERVICE: &str = "GameTrc";
+
+/// Steam Web API key, from steamcommunity.com/dev/apikey.
+pub const STEAM_API_KEY: &str = "steam_api_key";
+/// PSN NPSSO token, from ca.account.sony.com/api/v1/ssocookie.
+pub const PSN_NPSSO: &str = "psn_npsso";
+/// OpenXBL API key, from xbl.io.
+pub const XBOX_API_KEY: &str = "xbox_api_key";
+/// itch.io API key, from itch.io/user/settings/api-keys.
+pub const ITCH_API_KEY: &str = "itch_api_key";
+/// Humble Bundle session cookie (_simpleauth_sess), from the browser while
+/// signed in on humblebundle.com.
+pub const HUMBLE_SESSION: &str = "humble_session";
+/// Password (or app password) for the WebDAV server the library syncs to.
+pub const WEBDAV_PASSWORD: &str = "webdav_password";
+/// Password for the proxy in the `proxy_url` setting, if it needs one.
+pub const PROXY_PASSWORD: &str = "proxy_password";
+
+struct Known {
+    key:      &'static str,
+    provider: &'static str,
+    label:    &'static str,
+}
+
+/// Every secret the app knows how to use. A new provider adds a row.
+const KNOWN: &[Known] = &[
+    Known { key: STEAM_API_KEY,   provider: "Steam",   label: "Steam Web API key" },
+    Known { key: PSN_NPSSO,       provider: "PSN",     label: "PSN NPSSO token" },
+    Known { key: XBOX_API_KEY,    provider: "Xbox",    label: "OpenXBL API key" },
+    Known { key: ITCH_API_KEY,    provider: "itch.io", label: "itch.io API key" },
+    Known { key: HUMBLE_SESSION,  provider: "Humble",  label: "Humble session cookie" },
+    Known { key: WEBDAV_PASSWORD, provider: "WebDAV",  label: "WebDAV password" },
+    Known { key: PROXY_PASSWORD,  provider: "Proxy",   label: "Proxy password" },
+];
+
+/// The secret stored under `key`, if any.
+pub fn get(key: &str) -> CmdResult<Option<String>> {
+    match entry(key)?.get_password() {
+        Ok(secret) => Ok(Some(secret).filter(|s| !s.trim().is_empty())),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(store_error(e)),
+    }
+}
+
+/// Store `secret` under `key`, replacing what was there. A blank secret
+/// deletes it instead.
+pub fn set(key: &str, secret: &str) -> CmdResult<()> {
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return delete(key);
+    }
+    entry(key)?.set_password(secret).map_err(store_error)?;
+    info!(key, "Saved credential");
+    Ok(())
+}
+
+/// Remove the secret stored under `key`. Removing one that isn't there is fine.
+pub fn delete(key: &str) -> CmdResult<()> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {
+            info!(key, "Deleted credential");
+            Ok(())
+        }
+        Err(e) => Err(store_error(e)),
+    }
+}
+
+/// Which secrets are set, without revealing them — for the settings screen.
+/// A secret that can't be read (store locked or missing) counts as not set.
+pub fn statuses() -> Vec<CredentialStatus> {
+    KNOWN
+        .iter()
+        .map(|k| CredentialStatus {
+            key:        k.key.to_string(),
+            provider:   k.provider.to_string(),
+            label:      k.label.to_string(),
+            configured: get(k.key).ok().flatten().is_some(),
+        })
+        .collect()
+}
+
+/// The secret under `key`, or a validation error telling the user to set it.
+pub fn require(key: &str) -> CmdResult<String> {
+    let label = known(key)?.label;
+    get(key)?.ok_or_else(|| CommandError::validation(key, format!("Set a {label} first")))
+}
+
+/// Move secrets older versions kept in the settings table into the credential
+/// store. A secret is only removed from the table once it's safely stored, so
+/// nothing is lost if the store isn't available.
+pub fn migrate_from_settings(conn: &Connection) -> CmdResult<()> {
+    for k in KNOWN {
+        let Some(secret) = db::get_setting_string(conn, k.key)? else { continue };
+        if !secret.trim().is_empty() {
+            if let Err(e) = set(k.key, &secret) {
+                warn!(key = k.key, error = %e, "Couldn't move credential out of settings");
+                continue;
+            }
+        }
+        db::delete_setting(conn, k.key)?;
+        info!(key = k.key, "Moved credential from settings to the credential store");
+    }
+    Ok(())
+}
+
+fn known(key: &str) -> CmdResult<&'static Known> {
+    KNOWN
+        .iter()
+        .find(|k| k.key == key)
+        .ok_or_else(|| CommandError::validation("key", format!("Unknown credential {key}")))
+}
+
+fn entry(key: &str) -> CmdResult<keyring::Entry> {
+    keyring::Entry::new(SERVICE, known(key)?.key).map_err(store_error)
+}
+
+fn store_error(e: keyring::Error) -> CommandError {
+    CommandError::Internal(format!("Credential store: {e}"))
+}
@@ -0,0 +1,64 @@
+// darkadia.rs — Read a Darkadia collection export (CSV); see trackers.rs.
+//
+// Darkadia's export has one row per copy of a game, with the game's progress
+// as 0/1 columns and a 0 – 5 star rating in half steps:
+//
+//   Name,Added,Loved,Owned,Played,Playing,Finished,Mastered,Dominated,Shelved,
+//   Rating,Notes,Tags,Platform,Copy label,Copy release,Copy box,Copy media,…
+//   Hades,2021-01-02,1,1,1,0,1,0,0,0,4.5,,roguelike,PC,Steam,…
+//
+// Each row maps to a game:
+//
+//   progress   → status: Playing; Finished, Mastered or Dominated (Completed);
+//                Shelved (Dropped); Played (Playing — started, not done);
+//                Owned (Backlog); otherwise Wishlist
+//   Rating     → rating, 5 stars scaled to 1 – 10
+//   Loved      → favorite
+//   Added      → when the playthrough started, for games it finished
+//
+// A game owned on two platforms is two rows; each becomes its own game,
+// since GameTrc keeps one game per platform.
+
+use crate::import;
+use crate::models::GameStatus;
+use crate::trackers::{self, Row, TrackerEntry, TrackerExport};
+
+pub struct Darkadia;
+
+impl TrackerExport for Darkadia {
+    fn name(&self) -> &'static str {
+        "Darkadia"
+    }
+
+    fn title_columns(&self) -> &'static [&'static str] {
+        &["name"]
+    }
+
+    fn entry(&self, row: &Row, fallback_platform: &str) -> Option<TrackerEntry> {
+        let title = row.get(self.title_columns())?;
+        let finished = row.flag("finished") || row.flag("mastered") || row.flag("dominated");
+        let status = if row.flag("playing") {
+            GameStatus::Playing
+        } else if finished {
+            GameStatus::Completed
+        } else if row.flag("shelved") {
+            GameStatus::Dropped
+        } else if row.flag("played") {
+            GameStatus::Playing
+        } else if row.flag("owned") {
+            GameStatus::Backlog
+        } else {
+            GameStatus::Wishlist
+        };
+
+        let platform = row.get(&["platform", "platforms"]).unwrap_or(fallback_platform);
+        let mut input = import::remote_title_input(title, platform, status);
+        input.rating = row.get(&["rating"]).and_then(|stars| trackers::parse_rating(stars, 5.0));
+        input.notes = row.get(&["notes"]).map(str::to_string);
+        Some(TrackerEntry {
+            started_at: finished.then(|| row.get(&["added"]).and_then(trackers::parse_date)).flatten(),
+            favorite:   row.flag("loved"),
+            ..TrackerEntry::new(input)
+        })
+    }
+}
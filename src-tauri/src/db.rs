@@ -4,26 +4,39 @@
 // Each Tauri command locks the connection via a Mutex, runs its query,
 // and immediately releases the lock — so there's no concurrency issue.
 
-use rusqlite::{Connection, Result, params};
+use rusqlite::{Connection, OptionalExtension, Result, params, params_from_iter};
+use rusqlite::types::Value;
 use tauri::AppHandle;
 use tauri::Manager;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use chrono::Utc;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use tracing::{debug, info, warn};
 
+use crate::error::{CmdResult, CommandError};
 use crate::models::{
-    CountEntry, Game, GameInput, GameStats, GameStatus, SearchFilter,
-    SortField, StatusBreakdown,
+    Achievement, ActiveSession, AchievementSummary, BackupReason, BulkAddError, BulkAddReport, BundleSummary, ConflictStrategy, ControllerSupport, CoopMode, CountEntry, CoverSource, DayEvent, DbGeneration, DeckStatus, DetectedInstall, ExternalId, FieldConflict, Game, GameGroup, GameInput, GamePage, GameSummary, GenreHighlight, Highlight, Highlights, GameStats, GameStatus, GroupBy, ImageRef, InputMethod, InstallUsage, Job, JobStatus, NowPlaying, OnThisDay, Person, PersonStats, PersonalBest, PersonalBestInput, PlaySession, PlaytimeAdjustment, Playthrough, PlaythroughInput, ProtonTier, Purchase, RecordVersion, RefreshManifest, ScreenshotImport, SearchFilter,
+    RemoteAchievement, SaveBackup, SaveLocation, SessionSource, Soundtrack, SoundtrackFormat, SoundtrackInput, Settings, SortField, StatusBreakdown, SyncConflict, UsageCounter, UsageInsights, UsageWeek, WebDavState,
 };
+use crate::images::{self, CoverDetails};
+use crate::import;
+use crate::search::{self, SearchQuery};
+use crate::titles;
 
 // ---------------------------------------------------------------------------
 // Setup
 // ---------------------------------------------------------------------------
 
 /// Resolve the path to games.db inside the OS-appropriate app data directory.
-/// e.g. on Windows: C:\Users\<user>\AppData\Roaming\gametrc\games.db
-///      on macOS:   ~/Library/Application Support/gametrc/games.db
-///      on Linux:   ~/.local/share/gametrc/games.db
+/// e.g. on Windows: C:\Users\<user>\AppData\Roaming\me.hushm.gametrc\games.db
+///      on macOS:   ~/Library/Application Support/me.hushm.gametrc/games.db
+///      on Linux:   ~/.local/share/me.hushm.gametrc/games.db
 pub fn get_db_path(app: &AppHandle) -> PathBuf {
+    // GAMETRC_DB moves the database, e.g. into a cloud-drive folder (see filesync.rs)
+    if let Some(path) = std::env::var_os("GAMETRC_DB").filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
     // RUST NOTE: `unwrap_or_else` is like `unwrap()` but runs a closure if the
     // value is an Err. It's safer than a plain `unwrap()` which would panic.
     app.path()
@@ -32,9 +45,45 @@ pub fn get_db_path(app: &AppHandle) -> PathBuf {
         .join("games.db")
 }
 
+/// Same location as `get_db_path`, resolved without a running Tauri app.
+/// Used by the CLI, which has no AppHandle. Tauri's app data dir is the OS data
+/// dir joined with the bundle identifier from tauri.conf.json.
+pub fn default_db_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_IDENTIFIER)
+        .join("games.db")
+}
+
+/// The app's machine-local data folder (Tauri's `app_local_data_dir`), for the CLI.
+pub fn default_local_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_IDENTIFIER)
+}
+
+/// Lock the shared connection. A poisoned lock (a thread panicked while
+/// holding it) becomes `CommandError::Internal` instead of a panic here too.
+pub fn lock(db: &Mutex<Connection>) -> CmdResult<MutexGuard<'_, Connection>> {
+    db.lock().map_err(|e| CommandError::Internal(format!("DB lock poisoned: {e}")))
+}
+
+/// Must match `identifier` in tauri.conf.json.
+const APP_IDENTIFIER: &str = "me.hushm.gametrc";
+
 /// Create all tables and indexes if they don't already exist.
 /// `execute_batch` runs multiple SQL statements in one shot.
 pub fn init_db(conn: &Connection) -> Result<()> {
+    let fresh: bool = conn.query_row(
+        "SELECT NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'games')",
+        [],
+        |row| row.get(0),
+    )?;
+    // A database from an earlier version that lacks a table MIGRATIONS change
+    // gets it as it was before them, so they still apply.
+    if !fresh {
+        conn.execute_batch(UNMIGRATED_TABLES)?;
+    }
     conn.execute_batch("
         PRAGMA journal_mode = WAL;           -- better concurrent read performance
         PRAGMA foreign_keys = ON;            -- enforce FK constraints
@@ -49,21 +98,41 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             status                TEXT    NOT NULL DEFAULT 'Backlog',
             progress_percent      REAL    CHECK(progress_percent IS NULL OR
                                                 (progress_percent >= 0 AND progress_percent <= 100)),
-            playtime_hours        REAL    CHECK(playtime_hours IS NULL OR playtime_hours >= 0),
+            playtime_minutes      INTEGER CHECK(playtime_minutes IS NULL OR playtime_minutes >= 0),
             rating                REAL    CHECK(rating IS NULL OR (rating >= 1 AND rating <= 10)),
             notes                 TEXT,
             cover_art_path        TEXT,
             developer             TEXT,
             publisher             TEXT,
             created_at            TEXT    NOT NULL,
-            updated_at            TEXT    NOT NULL
+            updated_at            TEXT    NOT NULL,
+            controller_support    TEXT,
+            input_method          TEXT,
+            max_local_players     INTEGER,
+            max_online_players    INTEGER,
+            coop_mode             TEXT,
+            original_title        TEXT,
+            sort_title            TEXT,
+            normalized_title      TEXT,     -- duplicate-matching key, see titles.rs
+            completed_at          TEXT,
+            revision              INTEGER NOT NULL DEFAULT 1,   -- change tracking, see below
+            device_id             TEXT,
+            modified_at           TEXT,
+            archived              INTEGER NOT NULL DEFAULT 0,
+            favorite              INTEGER NOT NULL DEFAULT 0,
+            dominant_color        TEXT,
+            accent_color          TEXT,
+            cover_blurhash        TEXT,
+            cover_poster_path     TEXT
         );
 
         -- Screenshots are stored as a separate table (one-to-many)
         CREATE TABLE IF NOT EXISTS game_screenshots (
-            id      INTEGER PRIMARY KEY AUTOINCREMENT,
-            game_id INTEGER NOT NULL,
-            path    TEXT    NOT NULL,
+            id       INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id  INTEGER NOT NULL,
+            path     TEXT    NOT NULL,
+            hash     TEXT,
+            blurhash TEXT,
             FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
         );
 
@@ -75,13 +144,513 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
         );
 
+        -- User preferences: one row per Settings field, value stored as JSON
+        CREATE TABLE IF NOT EXISTS settings (
+            key   TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        -- Play sessions: manual logs plus playtime pulled from Steam etc.
+        CREATE TABLE IF NOT EXISTS play_sessions (
+            id               INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id          INTEGER NOT NULL,
+            started_at       TEXT    NOT NULL,
+            duration_minutes INTEGER NOT NULL CHECK(duration_minutes >= 0),
+            source           TEXT    NOT NULL DEFAULT 'Manual',
+            created_at       TEXT    NOT NULL,
+            game_version     TEXT,
+            idle_minutes     INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- A game's id on other services, plus what they last reported
+        CREATE TABLE IF NOT EXISTS game_external_ids (
+            game_id                 INTEGER NOT NULL,
+            source                  TEXT    NOT NULL,
+            external_id             TEXT    NOT NULL,
+            remote_playtime_minutes INTEGER,
+            remote_recent_minutes   INTEGER,
+            remote_last_played      TEXT,
+            synced_at               TEXT,
+            url                     TEXT,
+            drm_free                INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (game_id, source),
+            UNIQUE (source, external_id),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Trophies / achievements imported from PSN, Xbox, …
+        CREATE TABLE IF NOT EXISTS achievements (
+            id             INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id        INTEGER NOT NULL,
+            source         TEXT    NOT NULL,
+            external_id    TEXT    NOT NULL,
+            name           TEXT    NOT NULL,
+            description    TEXT,
+            icon_url       TEXT,
+            kind           TEXT,     -- trophy grade, if the service has them
+            points         INTEGER,  -- gamerscore, if the service has it
+            hidden         INTEGER NOT NULL DEFAULT 0,
+            unlocked_at    TEXT,
+            rarity_percent REAL,
+            UNIQUE (game_id, source, external_id),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Where a game is installed, as found by the launcher scanner
+        CREATE TABLE IF NOT EXISTS game_installs (
+            game_id      INTEGER NOT NULL,
+            launcher     TEXT    NOT NULL,
+            install_path TEXT    NOT NULL,
+            size_bytes   INTEGER,
+            detected_at  TEXT    NOT NULL,
+            PRIMARY KEY (game_id, launcher),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Folders holding a game's save files, and the zips taken of them
+        CREATE TABLE IF NOT EXISTS save_locations (
+            id         INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id    INTEGER NOT NULL,
+            path       TEXT    NOT NULL,
+            created_at TEXT    NOT NULL,
+            UNIQUE (game_id, path),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS save_backups (
+            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id      INTEGER NOT NULL,
+            archive_path TEXT    NOT NULL,
+            size_bytes   INTEGER NOT NULL,
+            file_count   INTEGER NOT NULL,
+            reason       TEXT    NOT NULL,
+            created_at   TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Game soundtracks / OSTs; favorite_tracks is a JSON array of names
+        CREATE TABLE IF NOT EXISTS soundtracks (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id         INTEGER NOT NULL,
+            title           TEXT    NOT NULL,
+            owned           INTEGER NOT NULL DEFAULT 0,
+            format          TEXT,
+            favorite_tracks TEXT    NOT NULL DEFAULT '[]',
+            link            TEXT,
+            created_at      TEXT    NOT NULL,
+            updated_at      TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Third-party compatibility ratings (ProtonDB tier, …), one per source
+        CREATE TABLE IF NOT EXISTS game_compat (
+            game_id    INTEGER NOT NULL,
+            source     TEXT    NOT NULL,
+            rating     TEXT,              -- NULL = checked, but no rating yet
+            checked_at TEXT    NOT NULL,
+            PRIMARY KEY (game_id, source),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Imports, syncs and other long tasks run through the job queue
+        CREATE TABLE IF NOT EXISTS jobs (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind        TEXT    NOT NULL,
+            status      TEXT    NOT NULL DEFAULT 'Queued',
+            done        INTEGER NOT NULL DEFAULT 0,
+            total       INTEGER NOT NULL DEFAULT 0,
+            message     TEXT,
+            result      TEXT,              -- JSON
+            error       TEXT,
+            created_at  TEXT    NOT NULL,
+            started_at  TEXT,
+            finished_at TEXT
+        );
+
+        -- One row per background metadata refresh; providers is a JSON list of
+        -- what each provider checked and changed
+        CREATE TABLE IF NOT EXISTS metadata_refreshes (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at  TEXT    NOT NULL,
+            finished_at TEXT    NOT NULL,
+            providers   TEXT    NOT NULL DEFAULT '[]'
+        );
+
+        -- Separate runs through a game (replays, New Game+)
+        CREATE TABLE IF NOT EXISTS playthroughs (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id       INTEGER NOT NULL,
+            started_at    TEXT,
+            finished_at   TEXT,
+            new_game_plus INTEGER NOT NULL DEFAULT 0,
+            game_version  TEXT,
+            notes         TEXT,
+            created_at    TEXT    NOT NULL,
+            challenge     TEXT,
+            ruleset       TEXT,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Speedrun personal bests, one row per run kept
+        CREATE TABLE IF NOT EXISTS personal_bests (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id     INTEGER NOT NULL,
+            category    TEXT    NOT NULL,
+            time_ms     INTEGER NOT NULL CHECK(time_ms > 0),
+            achieved_on TEXT,
+            video_url   TEXT,
+            created_at  TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- People I play with, and which sessions they were part of
+        CREATE TABLE IF NOT EXISTS people (
+            id         INTEGER PRIMARY KEY AUTOINCREMENT,
+            name       TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+            created_at TEXT    NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS session_people (
+            session_id INTEGER NOT NULL,
+            person_id  INTEGER NOT NULL,
+            PRIMARY KEY (session_id, person_id),
+            FOREIGN KEY (session_id) REFERENCES play_sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (person_id)  REFERENCES people(id)        ON DELETE CASCADE
+        );
+
+        -- Nicknames and regional names that search also matches (e.g. BotW)
+        CREATE TABLE IF NOT EXISTS game_aliases (
+            id      INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id INTEGER NOT NULL,
+            alias   TEXT    NOT NULL COLLATE NOCASE,
+            UNIQUE (game_id, alias),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Who opened this file last (one row), see filesync.rs
+        CREATE TABLE IF NOT EXISTS db_generation (
+            id         INTEGER PRIMARY KEY CHECK (id = 1),
+            generation INTEGER NOT NULL,
+            device     TEXT,
+            written_at TEXT
+        );
+
+        -- When each game field was last edited and on which device, for
+        -- field-level merges (see filesync.rs). `field` is a GameInput key.
+        CREATE TABLE IF NOT EXISTS game_field_versions (
+            game_id     INTEGER NOT NULL,
+            field       TEXT    NOT NULL,
+            revision    INTEGER NOT NULL,
+            device_id   TEXT,
+            modified_at TEXT    NOT NULL,
+            PRIMARY KEY (game_id, field),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Deleted games, so a merge with a copy that still has them doesn't
+        -- bring them back (see filesync.rs). Merges match games by title, so
+        -- these are keyed the same way.
+        CREATE TABLE IF NOT EXISTS game_tombstones (
+            normalized_title TEXT    PRIMARY KEY,
+            title            TEXT    NOT NULL,
+            revision         INTEGER NOT NULL,
+            device_id        TEXT,
+            deleted_at       TEXT    NOT NULL
+        );
+
+        -- Hand corrections to a game's playtime_minutes, kept as an audit trail
+        CREATE TABLE IF NOT EXISTS playtime_adjustments (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id     INTEGER NOT NULL,
+            old_minutes INTEGER,
+            new_minutes INTEGER,
+            reason      TEXT,
+            created_at  TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Where and when a game was bought: one row per order it came in, so a
+        -- game bought in two bundles has two
+        CREATE TABLE IF NOT EXISTS purchases (
+            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id      INTEGER NOT NULL,
+            store        TEXT    NOT NULL,
+            order_id     TEXT    NOT NULL,
+            bundle       TEXT,
+            purchased_at TEXT,     -- 'YYYY-MM-DD'
+            UNIQUE (game_id, store, order_id),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- The session the live timer is running (at most one row). Kept on disk
+        -- so a crash doesn't lose it: heartbeat_at is when the app was last seen
+        -- running, and is where a leftover session gets closed
+        CREATE TABLE IF NOT EXISTS active_session (
+            id             INTEGER PRIMARY KEY CHECK (id = 1),
+            game_id        INTEGER NOT NULL,
+            started_at     TEXT    NOT NULL,
+            paused_at      TEXT,
+            paused_seconds INTEGER NOT NULL DEFAULT 0,
+            game_version   TEXT,
+            heartbeat_at   TEXT    NOT NULL,
+            idle_seconds   INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Games a merge found edited differently on both sides; `fields`
+        -- holds the conflicting values as JSON
+        CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id     INTEGER NOT NULL,
+            source      TEXT    NOT NULL,
+            fields      TEXT    NOT NULL,
+            created_at  TEXT    NOT NULL,
+            resolved_at TEXT,
+            resolution  TEXT,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- What the last WebDAV sync saw (one row): the remote file's ETag or
+        -- Last-Modified, and a hash of the library as pushed or pulled
+        CREATE TABLE IF NOT EXISTS webdav_sync (
+            id             INTEGER PRIMARY KEY CHECK (id = 1),
+            remote_version TEXT,
+            library_hash   TEXT,
+            synced_at      TEXT
+        );
+
+        -- Where a downloaded cover came from, with the server's ETag /
+        -- Last-Modified, so refreshing only downloads it again if it changed
+        CREATE TABLE IF NOT EXISTS cover_sources (
+            game_id       INTEGER PRIMARY KEY,
+            url           TEXT    NOT NULL,
+            cover         TEXT    NOT NULL,   -- the stored cover it was saved as
+            etag          TEXT,
+            last_modified TEXT,
+            hash          TEXT,               -- SHA-256 of the downloaded bytes
+            checked_at    TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        -- Counters of how the app is used, per local day: launches, games
+        -- added, edits. Only ever read on this computer (get_usage_insights);
+        -- not exported, not synced, never sent anywhere
+        CREATE TABLE IF NOT EXISTS usage_counters (
+            day   TEXT    NOT NULL,   -- 'YYYY-MM-DD'
+            kind  TEXT    NOT NULL,   -- 'launch', 'game_added', 'edit'
+            count INTEGER NOT NULL,
+            PRIMARY KEY (day, kind)
+        );
+
         -- Indexes for the most common queries
         CREATE INDEX IF NOT EXISTS idx_games_title     ON games(title COLLATE NOCASE);
         CREATE INDEX IF NOT EXISTS idx_games_status    ON games(status);
         CREATE INDEX IF NOT EXISTS idx_games_franchise ON games(franchise);
         CREATE INDEX IF NOT EXISTS idx_games_platform  ON games(platform);
         CREATE INDEX IF NOT EXISTS idx_games_rating    ON games(rating);
+        CREATE INDEX IF NOT EXISTS idx_sessions_game   ON play_sessions(game_id, started_at);
+        CREATE INDEX IF NOT EXISTS idx_aliases_alias   ON game_aliases(alias);
     ")?;
+    // The tables above are already the latest schema; only a database made
+    // by an earlier version needs migrating.
+    if fresh {
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_games_normalized ON games(normalized_title);")?;
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() as i64)?;
+    }
+    run_migrations(conn)?;
+    backfill_normalized_titles(conn)?;
+    info!("Database schema ready");
+    Ok(())
+}
+
+/// The tables MIGRATIONS change, as they were before the first of them.
+const UNMIGRATED_TABLES: &str = "
+        CREATE TABLE IF NOT EXISTS games (
+            id                    INTEGER PRIMARY KEY AUTOINCREMENT,
+            title                 TEXT    NOT NULL,
+            franchise             TEXT,
+            sequence_in_franchise INTEGER,
+            release_date          TEXT,     -- 'YYYY-MM-DD'
+            platform              TEXT    NOT NULL DEFAULT 'PC',
+            status                TEXT    NOT NULL DEFAULT 'Backlog',
+            progress_percent      REAL    CHECK(progress_percent IS NULL OR
+                                                (progress_percent >= 0 AND progress_percent <= 100)),
+            playtime_hours        REAL    CHECK(playtime_hours IS NULL OR playtime_hours >= 0),
+            rating                REAL    CHECK(rating IS NULL OR (rating >= 1 AND rating <= 10)),
+            notes                 TEXT,
+            cover_art_path        TEXT,
+            developer             TEXT,
+            publisher             TEXT,
+            created_at            TEXT    NOT NULL,
+            updated_at            TEXT    NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS game_screenshots (
+            id      INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id INTEGER NOT NULL,
+            path    TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS play_sessions (
+            id               INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id          INTEGER NOT NULL,
+            started_at       TEXT    NOT NULL,
+            duration_minutes INTEGER NOT NULL CHECK(duration_minutes >= 0),
+            source           TEXT    NOT NULL DEFAULT 'Manual',
+            created_at       TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS game_external_ids (
+            game_id                 INTEGER NOT NULL,
+            source                  TEXT    NOT NULL,
+            external_id             TEXT    NOT NULL,
+            remote_playtime_minutes INTEGER,
+            remote_recent_minutes   INTEGER,
+            remote_last_played      TEXT,
+            synced_at               TEXT,
+            PRIMARY KEY (game_id, source),
+            UNIQUE (source, external_id),
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS playthroughs (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id       INTEGER NOT NULL,
+            started_at    TEXT,
+            finished_at   TEXT,
+            new_game_plus INTEGER NOT NULL DEFAULT 0,
+            game_version  TEXT,
+            notes         TEXT,
+            created_at    TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS playtime_adjustments (
+            id         INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id    INTEGER NOT NULL,
+            old_hours  REAL,
+            new_hours  REAL,
+            reason     TEXT,
+            created_at TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS active_session (
+            id             INTEGER PRIMARY KEY CHECK (id = 1),
+            game_id        INTEGER NOT NULL,
+            started_at     TEXT    NOT NULL,
+            paused_at      TEXT,
+            paused_seconds INTEGER NOT NULL DEFAULT 0,
+            game_version   TEXT,
+            heartbeat_at   TEXT    NOT NULL,
+            FOREIGN KEY (game_id) REFERENCES games(id) ON DELETE CASCADE
+        );
+";
+
+/// Changes to tables that already exist in users' databases, which
+/// CREATE TABLE IF NOT EXISTS can't express. `PRAGMA user_version` stores how
+/// many have been applied, so each runs exactly once. Only ever append here,
+/// and make the same change to the CREATE TABLE in `init_db`: a new database
+/// is created as the latest schema, with every migration counted as applied.
+/// A migration that is the first to change a table also adds the table's
+/// shape before it to UNMIGRATED_TABLES.
+const MIGRATIONS: &[&str] = &[
+    // 1 — the game version / patch a session was played on
+    "ALTER TABLE play_sessions ADD COLUMN game_version TEXT;",
+    // 2 — controller support and the input method a game was played with
+    "ALTER TABLE games ADD COLUMN controller_support TEXT;
+     ALTER TABLE games ADD COLUMN input_method TEXT;",
+    // 3 — multiplayer player counts and co-op mode
+    "ALTER TABLE games ADD COLUMN max_local_players INTEGER;
+     ALTER TABLE games ADD COLUMN max_online_players INTEGER;
+     ALTER TABLE games ADD COLUMN coop_mode TEXT;",
+    // 4 — challenge-run tag and ruleset on playthroughs
+    "ALTER TABLE playthroughs ADD COLUMN challenge TEXT;
+     ALTER TABLE playthroughs ADD COLUMN ruleset TEXT;",
+    // 5 — original (e.g. Japanese) title and an explicit sort key
+    "ALTER TABLE games ADD COLUMN original_title TEXT;
+     ALTER TABLE games ADD COLUMN sort_title TEXT;",
+    // 6 — hidden duplicate-matching key, see titles.rs (filled by backfill_normalized_titles)
+    "ALTER TABLE games ADD COLUMN normalized_title TEXT;
+     CREATE INDEX IF NOT EXISTS idx_games_normalized ON games(normalized_title);",
+    // 7 — when a game was completed; for existing games the last edit is the best guess
+    "ALTER TABLE games ADD COLUMN completed_at TEXT;
+     UPDATE games SET completed_at = updated_at WHERE status = 'Completed';",
+    // 8 — change tracking: bumped on every edit, with the device that made it
+    "ALTER TABLE games ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+     ALTER TABLE games ADD COLUMN device_id TEXT;
+     ALTER TABLE games ADD COLUMN modified_at TEXT;
+     UPDATE games SET modified_at = updated_at;",
+    // 9 — archived games are hidden from default views and stats
+    "ALTER TABLE games ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;",
+    // 10 — favorites, pinned to the top of the dashboard
+    "ALTER TABLE games ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;",
+    // 11 — idle time noticed during timed sessions
+    "ALTER TABLE play_sessions ADD COLUMN idle_minutes INTEGER NOT NULL DEFAULT 0;
+     ALTER TABLE active_session ADD COLUMN idle_seconds INTEGER NOT NULL DEFAULT 0;",
+    // 12 — playtime in whole minutes rather than fractional hours
+    "ALTER TABLE games ADD COLUMN playtime_minutes INTEGER CHECK(playtime_minutes IS NULL OR playtime_minutes >= 0);
+     UPDATE games SET playtime_minutes = CAST(ROUND(playtime_hours * 60) AS INTEGER);
+     ALTER TABLE games DROP COLUMN playtime_hours;
+     ALTER TABLE playtime_adjustments ADD COLUMN old_minutes INTEGER;
+     ALTER TABLE playtime_adjustments ADD COLUMN new_minutes INTEGER;
+     UPDATE playtime_adjustments SET old_minutes = CAST(ROUND(old_hours * 60) AS INTEGER),
+                                     new_minutes = CAST(ROUND(new_hours * 60) AS INTEGER);
+     ALTER TABLE playtime_adjustments DROP COLUMN old_hours;
+     ALTER TABLE playtime_adjustments DROP COLUMN new_hours;
+     UPDATE game_field_versions SET field = 'playtime_minutes' WHERE field = 'playtime_hours';",
+    // 13 — the store page a linked copy came from, and whether it's DRM-free
+    "ALTER TABLE game_external_ids ADD COLUMN url TEXT;
+     ALTER TABLE game_external_ids ADD COLUMN drm_free INTEGER NOT NULL DEFAULT 0;",
+    // 14 — a content hash per screenshot, to skip adding the same one twice
+    "ALTER TABLE game_screenshots ADD COLUMN hash TEXT;",
+    // 15 — colours taken from the cover, to theme the detail page
+    "ALTER TABLE games ADD COLUMN dominant_color TEXT;
+     ALTER TABLE games ADD COLUMN accent_color TEXT;",
+    // 16 — blurhash placeholders for covers and screenshots
+    "ALTER TABLE games ADD COLUMN cover_blurhash TEXT;
+     ALTER TABLE game_screenshots ADD COLUMN blurhash TEXT;",
+    // 17 — a still frame of an animated cover
+    "ALTER TABLE games ADD COLUMN cover_poster_path TEXT;",
+];
+
+/// The schema version that moved playtime from hours to minutes; library
+/// exports from before it are converted on restore.
+pub const PLAYTIME_MINUTES_SCHEMA: i64 = 12;
+
+fn run_migrations(conn: &Connection) -> Result<()> {
+    let applied: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (version, sql) in MIGRATIONS.iter().enumerate().skip(applied as usize) {
+        let version = version as i64 + 1;
+        // RUST NOTE: `unchecked_transaction` opens a transaction from a shared
+        // `&Connection`; the migration and its version bump commit together.
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        info!(version, "Applied database migration");
+    }
+    Ok(())
+}
+
+/// Fill `normalized_title` for rows written before the column existed.
+fn backfill_normalized_titles(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, title FROM games WHERE normalized_title IS NULL")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    for (id, title) in &rows {
+        conn.execute(
+            "UPDATE games SET normalized_title = ?1 WHERE id = ?2",
+            params![titles::normalize(title), id],
+        )?;
+    }
+    if !rows.is_empty() {
+        info!(count = rows.len(), "Normalized game titles");
+    }
     Ok(())
 }
 
@@ -90,69 +659,113 @@ pub fn init_db(conn: &Connection) -> Result<()> {
 // ---------------------------------------------------------------------------
 
 fn fetch_game_by_id(conn: &Connection, id: i64) -> Result<Option<Game>> {
-    let result = conn.query_row(
+    Ok(fetch_games(conn, &[id])?.pop())
+}
+
+/// The games with these ids, in the order given (ids with no game are left
+/// out). A fixed number of queries however many games are asked for: the
+/// ids go in as one JSON array, unpacked by `json_each`.
+fn fetch_games(conn: &Connection, ids: &[i64]) -> Result<Vec<Game>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let ids_json = serde_json::Value::from(ids).to_string();
+    let mut stmt = conn.prepare_cached(
         "SELECT id, title, franchise, sequence_in_franchise, release_date, platform,
-                status, progress_percent, playtime_hours, rating, notes, cover_art_path,
-                developer, publisher, created_at, updated_at
-         FROM games WHERE id = ?1",
-        params![id],
-        // RUST NOTE: This closure maps a database row to a Game struct.
-        // row.get::<column_index, Type>() extracts a typed column value.
-        |row| {
-            Ok(Game {
-                id:                    row.get(0)?,
-                title:                 row.get(1)?,
-                franchise:             row.get(2)?,
-                sequence_in_franchise: row.get(3)?,
-                release_date:          row.get(4)?,
-                platform:              row.get(5)?,
-                status: GameStatus::from_str(&row.get::<_, String>(6)?),
-                progress_percent:      row.get(7)?,
-                playtime_hours:        row.get(8)?,
-                rating:                row.get(9)?,
-                notes:                 row.get(10)?,
-                cover_art_path:        row.get(11)?,
-                screenshots:           vec![],  // filled below
-                developer:             row.get(12)?,
-                publisher:             row.get(13)?,
-                genres:                vec![],  // filled below
-                created_at:            row.get(14)?,
-                updated_at:            row.get(15)?,
-            })
-        },
-    );
+                status, progress_percent, playtime_minutes, rating, notes, cover_art_path,
+                developer, publisher, created_at, updated_at,
+                EXISTS (SELECT 1 FROM game_installs i WHERE i.game_id = games.id),
+                (SELECT SUM(size_bytes) FROM game_installs i WHERE i.game_id = games.id),
+                (SELECT rating FROM game_compat c WHERE c.game_id = games.id AND c.source = 'ProtonDB'),
+                (SELECT rating FROM game_compat c WHERE c.game_id = games.id AND c.source = 'SteamDeck'),
+                controller_support, input_method, max_local_players, max_online_players, coop_mode,
+                MAX((SELECT COUNT(*) FROM playthroughs p WHERE p.game_id = games.id) - 1, 0),
+                (SELECT json_object('category', pb.category, 'time_ms', pb.time_ms)
+                 FROM personal_bests pb WHERE pb.game_id = games.id ORDER BY pb.time_ms LIMIT 1),
+                original_title, sort_title, completed_at, archived, favorite,
+                dominant_color, accent_color, cover_blurhash, cover_poster_path
+         FROM games WHERE id IN (SELECT value FROM json_each(?1))",
+    )?;
+    let mut games: HashMap<i64, Game> = stmt
+        .query_map(params![ids_json], |row| Ok((row.get(0)?, game_from_row(row)?)))?
+        .collect::<Result<_>>()?;
 
-    match result {
-        Ok(mut game) => {
-            game.screenshots = fetch_screenshots(conn, id)?;
-            game.genres      = fetch_genres(conn, id)?;
-            Ok(Some(game))
+    let mut stmt = conn.prepare_cached(
+        "SELECT game_id, path, blurhash FROM game_screenshots
+         WHERE game_id IN (SELECT value FROM json_each(?1)) ORDER BY id"
+    )?;
+    let mut rows = stmt.query(params![ids_json])?;
+    while let Some(row) = rows.next()? {
+        if let Some(game) = games.get_mut(&row.get(0)?) {
+            game.screenshots.push(images::resolve(row.get(1)?));
+            game.screenshot_blurhashes.push(row.get(2)?);
         }
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e),
     }
+    let genres = "SELECT game_id, genre FROM game_genres
+                  WHERE game_id IN (SELECT value FROM json_each(?1)) ORDER BY genre";
+    for (id, genre) in pairs(conn, genres, &ids_json)? {
+        games.entry(id).and_modify(|game| game.genres.push(genre));
+    }
+    let aliases = "SELECT game_id, alias FROM game_aliases
+                   WHERE game_id IN (SELECT value FROM json_each(?1)) ORDER BY alias";
+    for (id, alias) in pairs(conn, aliases, &ids_json)? {
+        games.entry(id).and_modify(|game| game.aliases.push(alias));
+    }
+    Ok(ids.iter().filter_map(|id| games.remove(id)).collect())
 }
 
-fn fetch_screenshots(conn: &Connection, game_id: i64) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare(
-        "SELECT path FROM game_screenshots WHERE game_id = ?1 ORDER BY id"
-    )?;
-    // RUST NOTE: `query_map` returns an iterator of Results. We collect them,
-    // then use `collect::<Result<Vec<_>, _>>()` to turn Vec<Result<T>> into Result<Vec<T>>.
-    let paths = stmt
-        .query_map(params![game_id], |row| row.get(0))?
-        .collect::<Result<Vec<String>>>()?;
-    Ok(paths)
+/// The (game id, text) rows `sql` returns for the ids in `ids_json`.
+fn pairs(conn: &Connection, sql: &str, ids_json: &str) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let rows = stmt.query_map(params![ids_json], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
 }
 
-fn fetch_genres(conn: &Connection, game_id: i64) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare(
-        "SELECT genre FROM game_genres WHERE game_id = ?1 ORDER BY genre"
-    )?;
-    let genres = stmt
-        .query_map(params![game_id], |row| row.get(0))?
-        .collect::<Result<Vec<String>>>()?;
-    Ok(genres)
+/// A row of `fetch_games`' main query as a Game, without the lists filled in.
+fn game_from_row(row: &rusqlite::Row) -> Result<Game> {
+    // RUST NOTE: row.get::<column_index, Type>() extracts a typed column value.
+    Ok(Game {
+        id:                    row.get(0)?,
+        title:                 row.get(1)?,
+        original_title:        row.get(27)?,
+        sort_title:            row.get(28)?,
+        franchise:             row.get(2)?,
+        sequence_in_franchise: row.get(3)?,
+        release_date:          row.get(4)?,
+        platform:              row.get(5)?,
+        status: GameStatus::from_str(&row.get::<_, String>(6)?),
+        progress_percent:      row.get(7)?,
+        playtime_minutes:      row.get(8)?,
+        rating:                row.get(9)?,
+        notes:                 row.get(10)?,
+        cover_art_path:        row.get::<_, Option<String>>(11)?.map(images::resolve),
+        dominant_color:        row.get(32)?,
+        accent_color:          row.get(33)?,
+        cover_blurhash:        row.get(34)?,
+        cover_poster_path:     row.get::<_, Option<String>>(35)?.map(images::resolve),
+        screenshots:           vec![],  // filled by fetch_games
+        screenshot_blurhashes: vec![],  // filled by fetch_games
+        developer:             row.get(12)?,
+        publisher:             row.get(13)?,
+        genres:                vec![],  // filled by fetch_games
+        aliases:               vec![],  // filled by fetch_games
+        installed:             row.get(16)?,
+        install_size_bytes:    row.get::<_, Option<i64>>(17)?.map(|b| b as u64),
+        protondb_tier:         row.get::<_, Option<String>>(18)?.and_then(|t| ProtonTier::parse(&t)),
+        deck_status:           row.get::<_, Option<String>>(19)?.and_then(|d| DeckStatus::parse(&d)),
+        controller_support:    row.get::<_, Option<String>>(20)?.and_then(|c| ControllerSupport::parse(&c)),
+        input_method:          row.get::<_, Option<String>>(21)?.and_then(|i| InputMethod::parse(&i)),
+        max_local_players:     row.get(22)?,
+        max_online_players:    row.get(23)?,
+        coop_mode:             row.get::<_, Option<String>>(24)?.and_then(|c| CoopMode::parse(&c)),
+        replay_count:          row.get(25)?,
+        best_run:              row.get::<_, Option<String>>(26)?.and_then(|j| serde_json::from_str(&j).ok()),
+        created_at:            row.get(14)?,
+        updated_at:            row.get(15)?,
+        completed_at:          row.get(29)?,
+        archived:              row.get(30)?,
+        favorite:              row.get(31)?,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -166,14 +779,7 @@ pub fn get_all_games(conn: &Connection) -> Result<Vec<Game>> {
     let ids: Vec<i64> = stmt
         .query_map([], |row| row.get(0))?
         .collect::<Result<Vec<i64>>>()?;
-
-    let mut games = Vec::new();
-    for id in ids {
-        if let Some(game) = fetch_game_by_id(conn, id)? {
-            games.push(game);
-        }
-    }
-    Ok(games)
+    fetch_games(conn, &ids)
 }
 
 pub fn get_game(conn: &Connection, id: i64) -> Result<Option<Game>> {
@@ -182,34 +788,8 @@ pub fn get_game(conn: &Connection, id: i64) -> Result<Option<Game>> {
 
 pub fn add_game(conn: &Connection, input: GameInput) -> Result<Game> {
     let now = Utc::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT INTO games (title, franchise, sequence_in_franchise, release_date,
-            platform, status, progress_percent, playtime_hours, rating, notes,
-            cover_art_path, developer, publisher, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
-        params![
-            input.title,
-            input.franchise,
-            input.sequence_in_franchise,
-            input.release_date,
-            input.platform,
-            input.status.as_str(),
-            input.progress_percent,
-            input.playtime_hours,
-            input.rating,
-            input.notes,
-            input.cover_art_path,
-            input.developer,
-            input.publisher,
-            now,
-            now,
-        ],
-    )?;
-
-    let new_id = conn.last_insert_rowid();
-    insert_screenshots(conn, new_id, &input.screenshots)?;
-    insert_genres(conn, new_id, &input.genres)?;
+    let new_id = atomically(conn, || insert_game(conn, &input, &now))?;
+    info!(id = new_id, title = %input.title, "Added game");
 
     // RUST NOTE: `?` at the end of a Result-returning expression is the "early return
     // on error" operator — equivalent to `unwrap()` but propagates the error to the caller
@@ -217,15 +797,99 @@ pub fn add_game(conn: &Connection, input: GameInput) -> Result<Game> {
     fetch_game_by_id(conn, new_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
 }
 
+/// Insert the game row plus its screenshots and genres; returns the new id.
+/// Not atomic on its own — callers wrap it in `atomically`.
+fn insert_game(conn: &Connection, input: &GameInput, now: &str) -> Result<i64> {
+    conn.prepare_cached(
+        "INSERT INTO games (title, franchise, sequence_in_franchise, release_date,
+            platform, status, progress_percent, playtime_minutes, rating, notes,
+            cover_art_path, developer, publisher, created_at, updated_at,
+            controller_support, input_method, max_local_players, max_online_players, coop_mode,
+            original_title, sort_title, normalized_title, completed_at, device_id, modified_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20,
+                 ?21, ?22, ?23, CASE WHEN ?6 = 'Completed' THEN ?14 END, ?24, ?14)",
+    )?
+    .execute(params![
+        input.title,
+        input.franchise,
+        input.sequence_in_franchise,
+        input.release_date,
+        input.platform,
+        input.status.as_str(),
+        input.progress_percent,
+        input.playtime_minutes,
+        input.rating,
+        input.notes,
+        input.cover_art_path.as_deref().map(images::to_stored),
+        input.developer,
+        input.publisher,
+        now,
+        now,
+        input.controller_support.map(|c| c.as_str()),
+        input.input_method.map(|i| i.as_str()),
+        input.max_local_players,
+        input.max_online_players,
+        input.coop_mode.map(|c| c.as_str()),
+        input.original_title,
+        input.sort_title,
+        titles::normalize(&input.title),
+        device_id(),
+    ])?;
+
+    let new_id = conn.last_insert_rowid();
+    count_usage(conn, UsageCounter::GameAdded)?;
+    store_cover_details(conn, new_id, input.cover_art_path.as_deref())?;
+    insert_screenshots(conn, new_id, &input.screenshots, &HashMap::new(), &mut HashSet::new())?;
+    insert_genres(conn, new_id, &input.genres)?;
+    Ok(new_id)
+}
+
 pub fn update_game(conn: &Connection, id: i64, input: GameInput) -> Result<Game> {
     let now = Utc::now().to_rfc3339();
+    atomically(conn, || {
+        let changed = write_game(conn, id, &input, &now)?;
+        record_edit(conn, id, &changed, &now)
+    })?;
+    info!(id, title = %input.title, "Updated game");
+
+    fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+}
+
+/// Write a merge's result over game `id` like `update_game`, but without
+/// stamping it as an edit made here: the merge sets the stamps of the fields
+/// it took itself (`set_field_versions`), and the rest weren't edited.
+pub fn write_merged_game(conn: &Connection, id: i64, input: GameInput) -> Result<Game> {
+    let now = Utc::now().to_rfc3339();
+    atomically(conn, || write_game(conn, id, &input, &now))?;
+    fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+}
+
+/// Set one of the flags in import::FLAGS on game `id` as a merge took it,
+/// unstamped like `write_merged_game`.
+pub fn write_merged_flag(conn: &Connection, id: i64, name: &str, value: bool) -> Result<()> {
+    if import::FLAGS.contains(&name) {
+        // The name is one of the FLAGS, all of them games columns.
+        conn.execute(&format!("UPDATE games SET \"{name}\" = ?2 WHERE id = ?1"), params![id, value])?;
+    }
+    Ok(())
+}
+
+/// Overwrite game `id` with `input`. Returns the fields that changed.
+fn write_game(conn: &Connection, id: i64, input: &GameInput, now: &str) -> Result<Vec<String>> {
+    let before = fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    let changed = import::changed_fields(&import::game_to_input(&before), input);
 
     let rows = conn.execute(
         "UPDATE games SET
             title = ?1, franchise = ?2, sequence_in_franchise = ?3,
             release_date = ?4, platform = ?5, status = ?6, progress_percent = ?7,
-            playtime_hours = ?8, rating = ?9, notes = ?10, cover_art_path = ?11,
-            developer = ?12, publisher = ?13, updated_at = ?14
+            playtime_minutes = ?8, rating = ?9, notes = ?10, cover_art_path = ?11,
+            developer = ?12, publisher = ?13, updated_at = ?14,
+            controller_support = ?16, input_method = ?17, max_local_players = ?18,
+            max_online_players = ?19, coop_mode = ?20, original_title = ?21, sort_title = ?22,
+            normalized_title = ?23,
+            -- Keep the first completion date; leaving Completed clears it.
+            completed_at = CASE WHEN ?6 = 'Completed' THEN COALESCE(completed_at, ?14) END
          WHERE id = ?15",
         params![
             input.title,
@@ -235,195 +899,2370 @@ pub fn update_game(conn: &Connection, id: i64, input: GameInput) -> Result<Game>
             input.platform,
             input.status.as_str(),
             input.progress_percent,
-            input.playtime_hours,
+            input.playtime_minutes,
             input.rating,
             input.notes,
-            input.cover_art_path,
+            input.cover_art_path.as_deref().map(images::to_stored),
             input.developer,
             input.publisher,
             now,
             id,
+            input.controller_support.map(|c| c.as_str()),
+            input.input_method.map(|i| i.as_str()),
+            input.max_local_players,
+            input.max_online_players,
+            input.coop_mode.map(|c| c.as_str()),
+            input.original_title,
+            input.sort_title,
+            titles::normalize(&input.title),
         ],
     )?;
 
     if rows == 0 {
         return Err(rusqlite::Error::QueryReturnedNoRows);
     }
+    if changed.iter().any(|field| field == "cover_art_path") {
+        store_cover_details(conn, id, input.cover_art_path.as_deref())?;
+    }
 
     // Replace related rows: delete old ones, insert new ones
+    let known = screenshot_files(conn, id)?;
     conn.execute("DELETE FROM game_screenshots WHERE game_id = ?1", params![id])?;
     conn.execute("DELETE FROM game_genres      WHERE game_id = ?1", params![id])?;
-    insert_screenshots(conn, id, &input.screenshots)?;
+    let skipped = insert_screenshots(conn, id, &input.screenshots, &known, &mut HashSet::new())?;
+    if skipped > 0 {
+        info!(id, skipped, "Dropped duplicate screenshots");
+    }
     insert_genres(conn, id, &input.genres)?;
+    Ok(changed)
+}
 
-    fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+/// Archive or unarchive games; returns how many changed. Archived games stay
+/// in the library but drop out of default views and stats.
+pub fn set_archived(conn: &Connection, ids: &[i64], archived: bool) -> Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    let changed = atomically(conn, || {
+        let mut changed = 0;
+        for &id in ids {
+            let rows = conn.execute(
+                "UPDATE games SET archived = ?2, updated_at = ?3 WHERE id = ?1 AND archived != ?2",
+                params![id, archived, now],
+            )?;
+            if rows > 0 {
+                record_edit(conn, id, &["archived".to_string()], &now)?;
+                changed += rows;
+            }
+        }
+        Ok(changed)
+    })?;
+    info!(changed, archived, "Set archived");
+    Ok(changed)
+}
+
+/// Pin or unpin a game; returns it.
+pub fn toggle_favorite(conn: &Connection, id: i64) -> Result<Game> {
+    let now = Utc::now().to_rfc3339();
+    atomically(conn, || {
+        let rows = conn.execute("UPDATE games SET favorite = NOT favorite, updated_at = ?2 WHERE id = ?1", params![id, now])?;
+        if rows == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        record_edit(conn, id, &["favorite".to_string()], &now)
+    })?;
+    let game = fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    info!(id, favorite = game.favorite, "Toggled favorite");
+    Ok(game)
 }
 
+/// Delete a game, leaving a tombstone stamped like an edit so merges know.
 pub fn delete_game(conn: &Connection, id: i64) -> Result<bool> {
-    let rows = conn.execute("DELETE FROM games WHERE id = ?1", params![id])?;
-    Ok(rows > 0)
+    let Some(version) = get_record_version(conn, id)? else { return Ok(false) };
+    let deleted = RecordVersion {
+        revision:    version.revision + 1,
+        device_id:   device_id().map(str::to_string),
+        modified_at: Utc::now().to_rfc3339(),
+    };
+    delete_game_with(conn, id, &deleted)?;
+    info!(id, "Deleted game");
+    Ok(true)
 }
 
-fn insert_screenshots(conn: &Connection, game_id: i64, paths: &[String]) -> Result<()> {
-    for path in paths {
-        conn.execute(
-            "INSERT INTO game_screenshots (game_id, path) VALUES (?1, ?2)",
-            params![game_id, path],
-        )?;
-    }
-    Ok(())
+/// Delete game `id`, recording `deleted` (the stamp of the delete) as its
+/// tombstone — another device's, when a merge carries its delete over.
+pub fn delete_game_with(conn: &Connection, id: i64, deleted: &RecordVersion) -> Result<()> {
+    atomically(conn, || {
+        let title: String = conn.query_row("SELECT title FROM games WHERE id = ?1", params![id], |row| row.get(0))?;
+        set_tombstone(conn, &title, deleted)?;
+        conn.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+        Ok(())
+    })
 }
 
-fn insert_genres(conn: &Connection, game_id: i64, genres: &[String]) -> Result<()> {
-    for genre in genres {
-        conn.execute(
-            "INSERT INTO game_genres (game_id, genre) VALUES (?1, ?2)",
-            params![game_id, genre],
-        )?;
-    }
-    Ok(())
+/// Id of a game whose title matches `title` once both are normalized (case,
+/// punctuation, diacritics, roman numerals — see titles.rs), if there is one.
+/// Used by importers to flag games that are already in the library.
+pub fn find_game_by_title(conn: &Connection, title: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM games WHERE normalized_title = ?1 ORDER BY id LIMIT 1",
+        params![titles::normalize(title)],
+        |row| row.get(0),
+    )
+    .optional()
 }
 
-// ---------------------------------------------------------------------------
-// Search & filter
-// ---------------------------------------------------------------------------
+/// Every game's id and title.
+pub fn game_titles(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT id, title FROM games")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
 
-pub fn search_games(conn: &Connection, filter: SearchFilter) -> Result<Vec<Game>> {
-    // We build the SQL query dynamically based on which filters are set.
-    // RUST NOTE: `String::new()` creates an empty owned String on the heap.
-    let mut conditions: Vec<String> = Vec::new();
+/// File names of every image the library uses (covers and screenshots), so
+/// image eviction knows what it must keep.
+pub fn get_image_file_names(conn: &Connection) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT cover_art_path FROM games WHERE cover_art_path IS NOT NULL
+         UNION
+         SELECT cover_poster_path FROM games WHERE cover_poster_path IS NOT NULL
+         UNION
+         SELECT path FROM game_screenshots",
+    )?;
+    let paths = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>>>()?;
+    Ok(paths
+        .iter()
+        .filter_map(|p| std::path::Path::new(p).file_name()?.to_str().map(str::to_string))
+        .collect())
+}
 
-    if filter.query.is_some() {
-        conditions.push(
-            "(g.title LIKE ?_q OR g.franchise LIKE ?_q OR g.notes LIKE ?_q)".to_string()
-        );
-    }
-    if filter.status.is_some()    { conditions.push("g.status = ?_s".to_string()); }
-    if filter.platform.is_some()  { conditions.push("g.platform = ?_p".to_string()); }
-    if filter.franchise.is_some() { conditions.push("g.franchise LIKE ?_f".to_string()); }
-    if filter.genre.is_some() {
-        conditions.push(
-            "EXISTS (SELECT 1 FROM game_genres gg WHERE gg.game_id = g.id AND gg.genre = ?_g)".to_string()
-        );
-    }
-    if filter.min_rating.is_some() { conditions.push("g.rating >= ?_r".to_string()); }
+/// Every stored image path: covers (`screenshot_id` None) and screenshots.
+pub fn get_image_refs(conn: &Connection) -> Result<Vec<ImageRef>> {
+    let mut stmt = conn.prepare(
+        "SELECT g.id, g.title, NULL, g.cover_art_path FROM games g WHERE g.cover_art_path IS NOT NULL
+         UNION ALL
+         SELECT g.id, g.title, s.id, s.path FROM game_screenshots s JOIN games g ON g.id = s.game_id
+         ORDER BY 1, 3",
+    )?;
+    let refs = stmt
+        .query_map([], |row| {
+            Ok(ImageRef {
+                game_id:       row.get(0)?,
+                title:         row.get(1)?,
+                screenshot_id: row.get(2)?,
+                path:          row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(refs)
+}
 
-    let where_clause = if conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
+/// Point one stored image (a cover, or the screenshot `screenshot_id`) at `path`.
+pub fn set_image_path(conn: &Connection, image: &ImageRef, path: &str) -> Result<()> {
+    match image.screenshot_id {
+        Some(id) => conn.execute("UPDATE game_screenshots SET path = ?2 WHERE id = ?1", params![id, path])?,
+        None => conn.execute("UPDATE games SET cover_art_path = ?2 WHERE id = ?1", params![image.game_id, path])?,
     };
+    Ok(())
+}
 
-    let order_clause = build_order_clause(&filter);
-
-    // rusqlite doesn't support named params in execute_batch, so we use positional params.
-    // Rebuild with clean positional markers:
-    let sql = format!(
-        "SELECT DISTINCT g.id FROM games g {where_clause} {order_clause}"
-    );
-
-    // Collect query parameters in order
-    // RUST NOTE: `Box<dyn rusqlite::ToSql>` is a trait object — a dynamically-dispatched
-    // value that implements `ToSql`. This lets us mix different types (String, f64, etc.)
-    // in a single Vec.
-    let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-    let query_like = filter.query.as_ref().map(|q| format!("%{q}%"));
-    let status_str = filter.status.as_ref().map(|s| s.as_str().to_string());
-    let franchise_like = filter.franchise.as_ref().map(|f| format!("%{f}%"));
-
-    // Rebuild SQL with real positional params (rusqlite uses ?1, ?2, …)
-    let mut param_idx = 1usize;
-    let mut final_conditions: Vec<String> = Vec::new();
-
-    if let Some(ref q) = query_like {
-        final_conditions.push(format!(
-            "(g.title LIKE ?{p} OR g.franchise LIKE ?{p} OR g.notes LIKE ?{p})",
-            p = param_idx
-        ));
-        param_values.push(Box::new(q.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref s) = status_str {
-        final_conditions.push(format!("g.status = ?{}", param_idx));
-        param_values.push(Box::new(s.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref p) = filter.platform {
-        final_conditions.push(format!("g.platform = ?{}", param_idx));
-        param_values.push(Box::new(p.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref f) = franchise_like {
-        final_conditions.push(format!("g.franchise LIKE ?{}", param_idx));
-        param_values.push(Box::new(f.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref g) = filter.genre {
-        final_conditions.push(format!(
-            "EXISTS (SELECT 1 FROM game_genres gg WHERE gg.game_id = g.id AND gg.genre = ?{})",
-            param_idx
-        ));
-        param_values.push(Box::new(g.clone()));
-        param_idx += 1;
-    }
-    if let Some(r) = filter.min_rating {
-        final_conditions.push(format!("g.rating >= ?{}", param_idx));
-        param_values.push(Box::new(r));
-    }
-
-    let where_str = if final_conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", final_conditions.join(" AND "))
+/// Work out and save the colours and blurhash of a game's cover, and its
+/// poster frame if it's animated (and the `animated_cover_posters` setting is
+/// on); none without a cover, or if it can't be read.
+fn store_cover_details(conn: &Connection, id: i64, cover: Option<&str>) -> Result<()> {
+    let details = cover.and_then(|cover| match images::cover_details(cover) {
+        Ok(details) => Some(details),
+        Err(e) => {
+            warn!(id, error = %e, "Couldn't read the cover's colours");
+            None
+        }
+    });
+    let poster = match cover {
+        Some(cover) if images::is_animated(cover) && get_settings(conn)?.animated_cover_posters => {
+            images::make_poster(cover).map_err(|e| warn!(id, error = %e, "Couldn't save a poster frame")).ok()
+        }
+        _ => None,
     };
+    conn.execute(
+        "UPDATE games SET dominant_color = ?2, accent_color = ?3, cover_blurhash = ?4, cover_poster_path = ?5
+         WHERE id = ?1",
+        params![
+            id,
+            details.as_ref().map(|d| &d.dominant),
+            details.as_ref().map(|d| &d.accent),
+            details.as_ref().map(|d| &d.blurhash),
+            poster,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Games whose cover's colours or blurhash haven't been worked out (covers
+/// set before they were), with the stored cover path.
+pub fn covers_without_details(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, cover_art_path FROM games
+         WHERE cover_art_path IS NOT NULL AND (dominant_color IS NULL OR cover_blurhash IS NULL)"
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect();
+    rows
+}
+
+/// Save details worked out from `cover`, unless the game's cover has
+/// changed since.
+pub fn set_cover_details(conn: &Connection, id: i64, cover: &str, details: &CoverDetails) -> Result<()> {
+    conn.execute(
+        "UPDATE games SET dominant_color = ?3, accent_color = ?4, cover_blurhash = ?5
+         WHERE id = ?1 AND cover_art_path = ?2",
+        params![id, cover, details.dominant, details.accent, details.blurhash],
+    )?;
+    Ok(())
+}
+
+/// Screenshots added before blurhashes were kept: (row id, stored path).
+pub fn screenshots_without_blurhash(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare("SELECT id, path FROM game_screenshots WHERE blurhash IS NULL")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect();
+    rows
+}
+
+pub fn set_screenshot_blurhash(conn: &Connection, id: i64, blurhash: &str) -> Result<()> {
+    conn.execute("UPDATE game_screenshots SET blurhash = ?2 WHERE id = ?1", params![id, blurhash])?;
+    Ok(())
+}
+
+/// Append screenshots to a game without touching the ones it already has.
+/// Exact copies of one it has (or of each other) are skipped and counted.
+pub fn add_screenshots(conn: &Connection, game_id: i64, paths: &[String]) -> Result<ScreenshotImport> {
+    let skipped = atomically(conn, || {
+        let known = screenshot_files(conn, game_id)?;
+        let mut seen: HashSet<String> = known.values().map(|file| file.hash.clone()).collect();
+        let skipped = insert_screenshots(conn, game_id, paths, &known, &mut seen)?;
+        if skipped < paths.len() {
+            let now = Utc::now().to_rfc3339();
+            conn.execute("UPDATE games SET updated_at = ?1 WHERE id = ?2", params![now, game_id])?;
+            record_edit(conn, game_id, &["screenshots".to_string()], &now)?;
+        }
+        Ok(skipped)
+    })?;
+    info!(game_id, added = paths.len() - skipped, skipped, "Added screenshots");
+    let game = fetch_game_by_id(conn, game_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    Ok(ScreenshotImport { game, added: paths.len() - skipped, skipped })
+}
+
+/// Run `write` so that either all of its changes land or none do.
+///
+/// Uses a SAVEPOINT rather than BEGIN because savepoints nest: importers call
+/// add_game inside their own transaction, and BEGIN would fail there.
+fn atomically<T>(conn: &Connection, write: impl FnOnce() -> Result<T>) -> Result<T> {
+    conn.execute_batch("SAVEPOINT game_write")?;
+    match write() {
+        Ok(value) => {
+            conn.execute_batch("RELEASE game_write")?;
+            Ok(value)
+        }
+        Err(e) => {
+            // Report the original failure, not a problem rolling back.
+            if let Err(rollback) = conn.execute_batch("ROLLBACK TO game_write; RELEASE game_write") {
+                warn!(error = %rollback, "Failed to roll back game write");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// What's been read from a screenshot's file, kept so it's only read once.
+struct ScreenshotFile {
+    hash:     String,
+    blurhash: Option<String>,
+}
+
+/// Insert `paths` as screenshots, skipping any whose content hash is in
+/// `seen` (or repeats an earlier path's). `known` saves re-reading files
+/// already on record. Returns how many were skipped. A file that can't be
+/// read is added unhashed.
+fn insert_screenshots(
+    conn: &Connection,
+    game_id: i64,
+    paths: &[String],
+    known: &HashMap<String, ScreenshotFile>,
+    seen: &mut HashSet<String>,
+) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO game_screenshots (game_id, path, hash, blurhash) VALUES (?1, ?2, ?3, ?4)"
+    )?;
+    let mut skipped = 0;
+    for path in paths {
+        let stored = images::to_stored(path);
+        let file = known.get(&stored);
+        let hash = file.map(|f| f.hash.clone()).or_else(|| images::content_hash(&stored).ok());
+        if hash.as_ref().is_some_and(|hash| !seen.insert(hash.clone())) {
+            skipped += 1;
+            continue;
+        }
+        let blurhash = file
+            .and_then(|f| f.blurhash.clone())
+            .or_else(|| images::decode(&stored).ok().and_then(|image| images::blurhash(&image)));
+        stmt.execute(params![game_id, stored, hash, blurhash])?;
+    }
+    Ok(skipped)
+}
+
+/// Stored path → content hash of each of `game_id`'s screenshots; see
+/// `screenshot_files`.
+pub fn screenshot_hashes(conn: &Connection, game_id: i64) -> Result<HashMap<String, String>> {
+    Ok(screenshot_files(conn, game_id)?.into_iter().map(|(path, file)| (path, file.hash)).collect())
+}
+
+/// Stored path → what's on record for each of `game_id`'s screenshots. Ones
+/// added before hashes were kept are hashed now, and the hash saved; missing
+/// files are left out.
+fn screenshot_files(conn: &Connection, game_id: i64) -> Result<HashMap<String, ScreenshotFile>> {
+    let mut stmt = conn.prepare_cached("SELECT id, path, hash, blurhash FROM game_screenshots WHERE game_id = ?1")?;
+    let rows = stmt
+        .query_map(params![game_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    let mut files = HashMap::with_capacity(rows.len());
+    for (id, path, hash, blurhash) in rows {
+        let hash = match hash {
+            Some(hash) => hash,
+            None => {
+                let Ok(hash) = images::content_hash(&path) else { continue };
+                conn.execute("UPDATE game_screenshots SET hash = ?2 WHERE id = ?1", params![id, hash])?;
+                hash
+            }
+        };
+        files.insert(path, ScreenshotFile { hash, blurhash });
+    }
+    Ok(files)
+}
+
+fn insert_genres(conn: &Connection, game_id: i64, genres: &[String]) -> Result<()> {
+    let mut stmt = conn.prepare_cached("INSERT INTO game_genres (game_id, genre) VALUES (?1, ?2)")?;
+    for genre in genres {
+        stmt.execute(params![game_id, genre])?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Bulk insert
+// ---------------------------------------------------------------------------
+
+/// Batches at least this big rebuild the `games` indexes once at the end
+/// instead of updating them on every insert.
+const BULK_REINDEX_THRESHOLD: usize = 500;
+
+/// Add many games in one transaction — much faster than calling `add_game`
+/// per row, which commits (and syncs to disk) every time.
+///
+/// A row that fails validation or its insert is rolled back on its own and
+/// reported in `errors`; the other rows are still added. Nothing is fetched
+/// back, so callers that need full games look them up by `created_ids`.
+pub fn bulk_add_games(conn: &Connection, inputs: &[GameInput]) -> Result<BulkAddReport> {
+    let now = Utc::now().to_rfc3339();
+    let mut report = BulkAddReport::default();
+
+    atomically(conn, || {
+        // Dropping and recreating the indexes inside the transaction means a
+        // failure rolls them back along with everything else. The definitions
+        // are read from the schema so they can't drift from init_db.
+        let indexes: Vec<String> = if inputs.len() >= BULK_REINDEX_THRESHOLD {
+            let indexes = conn
+                .prepare(
+                    "SELECT name, sql FROM sqlite_master
+                     WHERE type = 'index' AND tbl_name = 'games' AND sql IS NOT NULL",
+                )?
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>>>()?;
+            for (name, _) in &indexes {
+                conn.execute_batch(&format!("DROP INDEX \"{name}\""))?;
+            }
+            indexes.into_iter().map(|(_, sql)| sql).collect()
+        } else {
+            Vec::new()
+        };
+
+        for (index, input) in inputs.iter().enumerate() {
+            // Each row gets its own savepoint so a failure only undoes that row.
+            let result = input
+                .validate()
+                .map_err(|e| e.to_string())
+                .and_then(|()| atomically(conn, || insert_game(conn, input, &now)).map_err(|e| e.to_string()));
+            match result {
+                Ok(id) => report.created_ids.push(id),
+                Err(message) => report.errors.push(BulkAddError { index, title: input.title.clone(), message }),
+            }
+        }
+
+        for sql in &indexes {
+            conn.execute_batch(sql)?;
+        }
+        Ok(())
+    })?;
+
+    info!(added = report.created_ids.len(), failed = report.errors.len(), "Bulk added games");
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Aliases
+// ---------------------------------------------------------------------------
+
+/// Add a nickname for a game. Adding one it already has is a no-op.
+pub fn add_game_alias(conn: &Connection, game_id: i64, alias: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO game_aliases (game_id, alias) VALUES (?1, ?2)",
+        params![game_id, alias],
+    )?;
+    Ok(())
+}
+
+/// Returns false if the game had no such alias.
+pub fn remove_game_alias(conn: &Connection, game_id: i64, alias: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "DELETE FROM game_aliases WHERE game_id = ?1 AND alias = ?2",
+        params![game_id, alias],
+    )?;
+    Ok(rows > 0)
+}
+
+// ---------------------------------------------------------------------------
+// Search & filter
+// ---------------------------------------------------------------------------
+
+pub fn search_games(conn: &Connection, filter: SearchFilter) -> Result<Vec<Game>> {
+    let ids: Vec<i64> = query_game_ids(conn, &SearchQuery::new(&filter))?.into_iter().map(|(id, _)| id).collect();
+    let mut games = fetch_games(conn, &ids)?;
+    if let Some(query) = filter.query.as_ref().filter(|_| filter.fuzzy == Some(true)) {
+        games = search::rank_fuzzy(games, query, filter.sort_by.is_none());
+    }
+    Ok(games)
+}
+
+/// One page of `search_games`, continuing after `cursor` (from the previous
+/// page's `next_cursor`).
+///
+/// Keyset pagination: the cursor holds the sort key values of the last game
+/// on the page, and the next page is "rows that sort after those". Unlike
+/// OFFSET it stays fast deep into the list, and games added or edited while
+/// scrolling don't make rows repeat or go missing.
+pub fn search_games_page(conn: &Connection, filter: SearchFilter, limit: u32, cursor: Option<&str>) -> CmdResult<GamePage> {
+    let (ids, next_cursor) = page_ids(conn, &filter, limit, cursor)?;
+    Ok(GamePage { games: fetch_games(conn, &ids)?, next_cursor })
+}
+
+/// `search_games_page` returning summaries.
+pub fn search_game_summaries_page(
+    conn: &Connection,
+    filter: SearchFilter,
+    limit: u32,
+    cursor: Option<&str>,
+) -> CmdResult<GamePage<GameSummary>> {
+    let (ids, next_cursor) = page_ids(conn, &filter, limit, cursor)?;
+    Ok(GamePage { games: fetch_summaries(conn, &ids)?, next_cursor })
+}
+
+/// Ids for one page plus the cursor for the next.
+fn page_ids(conn: &Connection, filter: &SearchFilter, limit: u32, cursor: Option<&str>) -> CmdResult<(Vec<i64>, Option<String>)> {
+    if filter.fuzzy == Some(true) && filter.query.is_some() {
+        return Err(CommandError::validation("fuzzy", "Fuzzy results are ranked after the query and can't be paged"));
+    }
+    if filter.sort_by == Some(SortField::Random) && filter.random_seed.is_none() {
+        return Err(CommandError::validation("random_seed", "Paging a random sort needs a seed"));
+    }
+    let query = SearchQuery::new(filter);
+    let after = cursor.map(search::decode_cursor).transpose()?;
+    if after.as_ref().is_some_and(|keys| keys.len() != query.sort_key_count()) {
+        return Err(CommandError::validation("cursor", "Cursor doesn't belong to this sort"));
+    }
+
+    // Ask for one extra row to learn whether there's a next page.
+    let query = query.page(limit + 1, after.as_deref());
+    let mut rows = query_game_ids(conn, &query)?;
+    let next_cursor = if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        rows.last().map(|(_, keys)| search::encode_cursor(keys))
+    } else {
+        None
+    };
+    Ok((rows.into_iter().map(|(id, _)| id).collect(), next_cursor))
+}
+
+/// `search_games` returning summaries. Pass `SearchFilter::default()` for
+/// every game in get_all_games order.
+pub fn search_game_summaries(conn: &Connection, filter: SearchFilter) -> Result<Vec<GameSummary>> {
+    if filter.fuzzy == Some(true) && filter.query.is_some() {
+        // Fuzzy ranking looks at aliases and original titles, so it needs full games.
+        return Ok(search_games(conn, filter)?.iter().map(GameSummary::from).collect());
+    }
+    let ids: Vec<i64> = query_game_ids(conn, &SearchQuery::new(&filter))?.into_iter().map(|(id, _)| id).collect();
+    fetch_summaries(conn, &ids)
+}
+
+/// Summaries for `ids`, in the same order.
+fn fetch_summaries(conn: &Connection, ids: &[i64]) -> Result<Vec<GameSummary>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, title, cover_art_path, status, rating, platform, favorite, cover_blurhash, cover_poster_path
+         FROM games WHERE id IN (SELECT value FROM json_each(?1))"
+    )?;
+    let mut summaries: HashMap<i64, GameSummary> = stmt
+        .query_map(params![serde_json::Value::from(ids).to_string()], |row| {
+            Ok((row.get(0)?, GameSummary {
+                id:             row.get(0)?,
+                title:          row.get(1)?,
+                cover_art_path: row.get::<_, Option<String>>(2)?.map(images::resolve),
+                cover_blurhash: row.get(7)?,
+                cover_poster_path: row.get::<_, Option<String>>(8)?.map(images::resolve),
+                status: GameStatus::from_str(&row.get::<_, String>(3)?),
+                rating:         row.get(4)?,
+                platform:       row.get(5)?,
+                favorite:       row.get(6)?,
+            }))
+        })?
+        .collect::<Result<_>>()?;
+    Ok(ids.iter().filter_map(|id| summaries.remove(id)).collect())
+}
+
+/// Ids of the games matching `filter`, in sort order, each with its sort key
+/// values (only filled in when paging).
+fn query_game_ids(conn: &Connection, query: &SearchQuery) -> Result<Vec<(i64, Vec<Value>)>> {
+    // RUST NOTE: `prepare_cached` keeps the compiled statement in the
+    // connection's LRU cache keyed by its SQL, so the next search with the
+    // same filters set (whatever their values) skips parsing and planning.
+    let mut stmt = conn.prepare_cached(&query.sql())?;
+    let key_count = query.key_count();
+    let rows = stmt
+        .query_map(params_from_iter(query.params()), |row| {
+            let key_values = (1..=key_count).map(|i| row.get::<_, Value>(i)).collect::<Result<Vec<_>>>()?;
+            Ok((row.get::<_, i64>(0)?, key_values))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    debug!(results = rows.len(), "search_games");
+    Ok(rows)
+}
+
+/// Games matching `filter` (or all games) split into groups. Statuses come in
+/// display order, platforms and franchises alphabetically with games that
+/// have no franchise last; within a group the filter's sort order is kept.
+pub fn get_games_grouped(conn: &Connection, group_by: GroupBy, filter: Option<SearchFilter>) -> Result<Vec<GameGroup>> {
+    let games = match filter {
+        Some(filter) => search_games(conn, filter)?,
+        None => get_all_games(conn)?,
+    };
+
+    let mut buckets: Vec<(Option<String>, Vec<Game>)> = Vec::new();
+    for game in games {
+        let key = match group_by {
+            GroupBy::Status    => Some(game.status.as_str().to_string()),
+            GroupBy::Platform  => Some(game.platform.clone()),
+            GroupBy::Franchise => game.franchise.clone().filter(|f| !f.trim().is_empty()),
+        };
+        match buckets.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push(game),
+            None => buckets.push((key, vec![game])),
+        }
+    }
+
+    match group_by {
+        GroupBy::Status => buckets.sort_by_key(|(key, _)| {
+            GameStatus::DISPLAY_ORDER.iter().position(|s| Some(s.as_str()) == key.as_deref())
+        }),
+        // `None` (no franchise) sorts after every name.
+        _ => buckets.sort_by_key(|(key, _)| (key.is_none(), key.as_ref().map(|k| k.to_lowercase()))),
+    }
+
+    Ok(buckets
+        .into_iter()
+        .map(|(key, games)| {
+            let label = match (&key, group_by) {
+                (Some(k), GroupBy::Status) => GameStatus::from_str(k).label().to_string(),
+                (Some(k), _)               => k.clone(),
+                (None, _)                  => "Standalone".to_string(),
+            };
+            GameGroup { key, label, count: games.len(), games }
+        })
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Play sessions
+// ---------------------------------------------------------------------------
+
+/// Record a session and add its minutes to the game's playtime.
+pub fn add_play_session(
+    conn: &Connection,
+    game_id: i64,
+    started_at: &str,
+    duration_minutes: i64,
+    source: SessionSource,
+    game_version: Option<&str>,
+) -> Result<PlaySession> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO play_sessions (game_id, started_at, duration_minutes, source, game_version, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![game_id, started_at, duration_minutes, source.as_str(), game_version, now],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE games SET playtime_minutes = COALESCE(playtime_minutes, 0) + ?1, updated_at = ?2
+         WHERE id = ?3",
+        params![duration_minutes, now, game_id],
+    )?;
+    record_edit(conn, game_id, &["playtime_minutes".to_string()], &now)?;
+    info!(game_id, duration_minutes, source = source.as_str(), "Logged play session");
+
+    Ok(PlaySession {
+        id,
+        game_id,
+        started_at: started_at.to_string(),
+        duration_minutes,
+        source,
+        game_version: game_version.map(str::to_string),
+        idle_minutes: 0,
+        people: vec![],
+        created_at: now,
+    })
+}
+
+/// Record (or clear) the game version a logged session was played on.
+pub fn set_session_game_version(conn: &Connection, session_id: i64, game_version: Option<&str>) -> Result<bool> {
+    Ok(conn.execute(
+        "UPDATE play_sessions SET game_version = ?1 WHERE id = ?2",
+        params![game_version, session_id],
+    )? > 0)
+}
+
+/// Note how much of a session the timer found the player idle.
+pub fn set_session_idle_minutes(conn: &Connection, session_id: i64, idle_minutes: i64) -> Result<bool> {
+    Ok(conn.execute(
+        "UPDATE play_sessions SET idle_minutes = ?1 WHERE id = ?2",
+        params![idle_minutes, session_id],
+    )? > 0)
+}
+
+/// Sessions for one game (or every game when `game_id` is None), newest first.
+pub fn get_play_sessions(conn: &Connection, game_id: Option<i64>) -> Result<Vec<PlaySession>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.game_id, s.started_at, s.duration_minutes, s.source, s.game_version, s.created_at,
+                s.idle_minutes,
+                (SELECT json_group_array(json_object('id', p.id, 'name', p.name))
+                 FROM session_people sp JOIN people p ON p.id = sp.person_id
+                 WHERE sp.session_id = s.id)
+         FROM play_sessions s WHERE ?1 IS NULL OR s.game_id = ?1
+         ORDER BY s.started_at DESC",
+    )?;
+    let sessions = stmt
+        .query_map(params![game_id], |row| {
+            Ok(PlaySession {
+                id:               row.get(0)?,
+                game_id:          row.get(1)?,
+                started_at:       row.get(2)?,
+                duration_minutes: row.get(3)?,
+                source:           SessionSource::parse(&row.get::<_, String>(4)?),
+                game_version:     row.get(5)?,
+                idle_minutes:     row.get(7)?,
+                people:           serde_json::from_str(&row.get::<_, String>(8)?).unwrap_or_default(),
+                created_at:       row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(sessions)
+}
+
+/// Set a game's playtime by hand, recording the correction.
+pub fn adjust_playtime(conn: &Connection, game_id: i64, minutes: Option<i64>, reason: Option<&str>) -> Result<PlaytimeAdjustment> {
+    let now = Utc::now().to_rfc3339();
+    let old_minutes: Option<i64> = conn.query_row(
+        "SELECT playtime_minutes FROM games WHERE id = ?1",
+        params![game_id],
+        |row| row.get(0),
+    )?;
+    atomically(conn, || {
+        conn.execute(
+            "UPDATE games SET playtime_minutes = ?1, updated_at = ?2 WHERE id = ?3",
+            params![minutes, now, game_id],
+        )?;
+        record_edit(conn, game_id, &["playtime_minutes".to_string()], &now)?;
+        add_playtime_adjustment(conn, game_id, old_minutes, minutes, reason)
+    })
+}
+
+/// Record that a game's playtime was corrected from `old_minutes` to
+/// `new_minutes`. The playtime itself must already be updated.
+pub fn add_playtime_adjustment(
+    conn: &Connection,
+    game_id: i64,
+    old_minutes: Option<i64>,
+    new_minutes: Option<i64>,
+    reason: Option<&str>,
+) -> Result<PlaytimeAdjustment> {
+    let now = Utc::now().to_rfc3339();
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    conn.execute(
+        "INSERT INTO playtime_adjustments (game_id, old_minutes, new_minutes, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![game_id, old_minutes, new_minutes, reason, now],
+    )?;
+    info!(game_id, ?old_minutes, ?new_minutes, "Adjusted playtime");
+    Ok(PlaytimeAdjustment {
+        id: conn.last_insert_rowid(),
+        game_id,
+        old_minutes,
+        new_minutes,
+        reason: reason.map(str::to_string),
+        created_at: now,
+    })
+}
+
+/// A game's playtime corrections, newest first.
+pub fn get_playtime_adjustments(conn: &Connection, game_id: i64) -> Result<Vec<PlaytimeAdjustment>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, game_id, old_minutes, new_minutes, reason, created_at FROM playtime_adjustments
+         WHERE game_id = ?1 ORDER BY id DESC",
+    )?;
+    let adjustments = stmt
+        .query_map(params![game_id], |row| {
+            Ok(PlaytimeAdjustment {
+                id:          row.get(0)?,
+                game_id:     row.get(1)?,
+                old_minutes: row.get(2)?,
+                new_minutes: row.get(3)?,
+                reason:      row.get(4)?,
+                created_at:  row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(adjustments)
+}
+
+/// The open timer session, if there is one. `elapsed_seconds` is left at 0;
+/// timer.rs works it out.
+pub fn get_active_session(conn: &Connection) -> Result<Option<ActiveSession>> {
+    conn.query_row(
+        "SELECT a.game_id, g.title, a.started_at, a.paused_at, a.paused_seconds, a.game_version, a.heartbeat_at,
+                a.idle_seconds
+         FROM active_session a JOIN games g ON g.id = a.game_id
+         WHERE a.id = 1",
+        [],
+        |row| {
+            Ok(ActiveSession {
+                game_id:         row.get(0)?,
+                title:           row.get(1)?,
+                started_at:      row.get(2)?,
+                paused_at:       row.get(3)?,
+                paused_seconds:  row.get(4)?,
+                game_version:    row.get(5)?,
+                heartbeat_at:    row.get(6)?,
+                idle_seconds:    row.get(7)?,
+                elapsed_seconds: 0,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Open the timer session. Fails if one is already open.
+pub fn start_active_session(conn: &Connection, game_id: i64, started_at: &str, game_version: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO active_session (id, game_id, started_at, game_version, heartbeat_at) VALUES (1, ?1, ?2, ?3, ?2)",
+        params![game_id, started_at, game_version],
+    )?;
+    Ok(())
+}
+
+/// Pause (`paused_at` = Some) or resume (None) the timer session, adding
+/// `paused_seconds` to the time it has spent paused.
+pub fn set_active_session_paused(conn: &Connection, paused_at: Option<&str>, paused_seconds: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE active_session SET paused_at = ?1, paused_seconds = paused_seconds + ?2, heartbeat_at = ?3 WHERE id = 1",
+        params![paused_at, paused_seconds, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Note that the app is still running with the session open, adding any
+/// idle time noticed since the last heartbeat.
+pub fn touch_active_session(conn: &Connection, at: &str, idle_seconds: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE active_session SET heartbeat_at = ?1, idle_seconds = idle_seconds + ?2 WHERE id = 1",
+        params![at, idle_seconds],
+    )?;
+    Ok(())
+}
+
+pub fn clear_active_session(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM active_session", [])?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Playthroughs
+// ---------------------------------------------------------------------------
+
+const PLAYTHROUGH_SELECT: &str =
+    "SELECT id, game_id, started_at, finished_at, new_game_plus, challenge, ruleset, game_version, notes,
+            created_at
+     FROM playthroughs";
+
+fn row_to_playthrough(row: &rusqlite::Row) -> Result<Playthrough> {
+    Ok(Playthrough {
+        id:            row.get(0)?,
+        game_id:       row.get(1)?,
+        started_at:    row.get(2)?,
+        finished_at:   row.get(3)?,
+        new_game_plus: row.get(4)?,
+        challenge:     row.get(5)?,
+        ruleset:       row.get(6)?,
+        game_version:  row.get(7)?,
+        notes:         row.get(8)?,
+        created_at:    row.get(9)?,
+    })
+}
+
+/// The trimmed challenge tag, or None if it's blank.
+fn challenge_tag(input: &PlaythroughInput) -> Option<&str> {
+    input.challenge.as_deref().map(str::trim).filter(|c| !c.is_empty())
+}
+
+pub fn add_playthrough(conn: &Connection, input: &PlaythroughInput) -> Result<Playthrough> {
+    conn.execute(
+        "INSERT INTO playthroughs (game_id, started_at, finished_at, new_game_plus, challenge, ruleset,
+                                   game_version, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            input.game_id, input.started_at, input.finished_at, input.new_game_plus,
+            challenge_tag(input), input.ruleset, input.game_version, input.notes, Utc::now().to_rfc3339(),
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    info!(id, game_id = input.game_id, "Added playthrough");
+    conn.query_row(&format!("{PLAYTHROUGH_SELECT} WHERE id = ?1"), params![id], row_to_playthrough)
+}
+
+/// Errors with `QueryReturnedNoRows` if there's no playthrough `id`.
+pub fn update_playthrough(conn: &Connection, id: i64, input: &PlaythroughInput) -> Result<Playthrough> {
+    let changed = conn.execute(
+        "UPDATE playthroughs SET game_id = ?1, started_at = ?2, finished_at = ?3, new_game_plus = ?4,
+                challenge = ?5, ruleset = ?6, game_version = ?7, notes = ?8
+         WHERE id = ?9",
+        params![
+            input.game_id, input.started_at, input.finished_at, input.new_game_plus,
+            challenge_tag(input), input.ruleset, input.game_version, input.notes, id,
+        ],
+    )?;
+    if changed == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    conn.query_row(&format!("{PLAYTHROUGH_SELECT} WHERE id = ?1"), params![id], row_to_playthrough)
+}
+
+/// Delete a playthrough and return the game it belonged to (None if it didn't exist).
+/// Every challenge tag used so far (for autocomplete), ignoring case.
+pub fn get_challenges(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT challenge FROM playthroughs WHERE challenge IS NOT NULL
+         GROUP BY challenge COLLATE NOCASE ORDER BY challenge COLLATE NOCASE"
+    )?;
+    let challenges = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<String>>>()?;
+    Ok(challenges)
+}
+
+pub fn delete_playthrough(conn: &Connection, id: i64) -> Result<Option<i64>> {
+    let game_id = conn
+        .query_row("SELECT game_id FROM playthroughs WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()?;
+    conn.execute("DELETE FROM playthroughs WHERE id = ?1", params![id])?;
+    Ok(game_id)
+}
+
+/// A game's playthroughs, oldest first (undated ones last).
+pub fn get_playthroughs(conn: &Connection, game_id: i64) -> Result<Vec<Playthrough>> {
+    let mut stmt = conn.prepare(&format!(
+        "{PLAYTHROUGH_SELECT} WHERE game_id = ?1 ORDER BY started_at IS NULL, started_at, id"
+    ))?;
+    let playthroughs = stmt
+        .query_map(params![game_id], row_to_playthrough)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(playthroughs)
+}
+
+// ---------------------------------------------------------------------------
+// Personal bests
+// ---------------------------------------------------------------------------
+
+const PERSONAL_BEST_SELECT: &str =
+    "SELECT id, game_id, category, time_ms, achieved_on, video_url, created_at FROM personal_bests";
+
+fn row_to_personal_best(row: &rusqlite::Row) -> Result<PersonalBest> {
+    Ok(PersonalBest {
+        id:          row.get(0)?,
+        game_id:     row.get(1)?,
+        category:    row.get(2)?,
+        time_ms:     row.get(3)?,
+        achieved_on: row.get(4)?,
+        video_url:   row.get(5)?,
+        created_at:  row.get(6)?,
+    })
+}
+
+pub fn add_personal_best(conn: &Connection, input: &PersonalBestInput) -> Result<PersonalBest> {
+    conn.execute(
+        "INSERT INTO personal_bests (game_id, category, time_ms, achieved_on, video_url, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            input.game_id, input.category.trim(), input.time_ms, input.achieved_on,
+            input.video_url, Utc::now().to_rfc3339(),
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.query_row(&format!("{PERSONAL_BEST_SELECT} WHERE id = ?1"), params![id], row_to_personal_best)
+}
+
+/// Errors with `QueryReturnedNoRows` if there's no personal best `id`.
+pub fn update_personal_best(conn: &Connection, id: i64, input: &PersonalBestInput) -> Result<PersonalBest> {
+    let changed = conn.execute(
+        "UPDATE personal_bests SET game_id = ?1, category = ?2, time_ms = ?3, achieved_on = ?4, video_url = ?5
+         WHERE id = ?6",
+        params![input.game_id, input.category.trim(), input.time_ms, input.achieved_on, input.video_url, id],
+    )?;
+    if changed == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    conn.query_row(&format!("{PERSONAL_BEST_SELECT} WHERE id = ?1"), params![id], row_to_personal_best)
+}
+
+/// Delete a personal best and return its game (None if it didn't exist).
+pub fn delete_personal_best(conn: &Connection, id: i64) -> Result<Option<i64>> {
+    let game_id = conn
+        .query_row("SELECT game_id FROM personal_bests WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()?;
+    conn.execute("DELETE FROM personal_bests WHERE id = ?1", params![id])?;
+    Ok(game_id)
+}
+
+/// A game's personal bests grouped by category, fastest first within each.
+pub fn get_personal_bests(conn: &Connection, game_id: i64) -> Result<Vec<PersonalBest>> {
+    let mut stmt = conn.prepare(&format!(
+        "{PERSONAL_BEST_SELECT} WHERE game_id = ?1 ORDER BY category COLLATE NOCASE, time_ms"
+    ))?;
+    let bests = stmt
+        .query_map(params![game_id], row_to_personal_best)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(bests)
+}
+
+// ---------------------------------------------------------------------------
+// People
+// ---------------------------------------------------------------------------
+
+/// Add a person, or return the existing one with the same name (ignoring case).
+pub fn add_person(conn: &Connection, name: &str) -> Result<Person> {
+    conn.execute(
+        "INSERT INTO people (name, created_at) VALUES (?1, ?2) ON CONFLICT(name) DO NOTHING",
+        params![name, Utc::now().to_rfc3339()],
+    )?;
+    conn.query_row(
+        "SELECT id, name FROM people WHERE name = ?1",
+        params![name],
+        |row| Ok(Person { id: row.get(0)?, name: row.get(1)? }),
+    )
+}
+
+pub fn delete_person(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM people WHERE id = ?1", params![id])? > 0)
+}
+
+pub fn get_people(conn: &Connection) -> Result<Vec<Person>> {
+    let mut stmt = conn.prepare("SELECT id, name FROM people ORDER BY name COLLATE NOCASE")?;
+    let people = stmt
+        .query_map([], |row| Ok(Person { id: row.get(0)?, name: row.get(1)? }))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(people)
+}
+
+/// Replace the people recorded for a session. Returns false if there's no such session.
+pub fn set_session_people(conn: &Connection, session_id: i64, person_ids: &[i64]) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM play_sessions WHERE id = ?1)",
+        params![session_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(false);
+    }
+    conn.execute("DELETE FROM session_people WHERE session_id = ?1", params![session_id])?;
+    for person_id in person_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO session_people (session_id, person_id) VALUES (?1, ?2)",
+            params![session_id, person_id],
+        )?;
+    }
+    Ok(true)
+}
+
+/// Time played with each person, most first. People with no sessions are included with zeros.
+pub fn get_people_stats(conn: &Connection) -> Result<Vec<PersonStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.id, p.name, COUNT(s.id), COALESCE(SUM(s.duration_minutes), 0), COUNT(DISTINCT s.game_id),
+                (SELECT g.title FROM session_people sp2
+                 JOIN play_sessions s2 ON s2.id = sp2.session_id
+                 JOIN games g ON g.id = s2.game_id
+                 WHERE sp2.person_id = p.id
+                 GROUP BY g.id ORDER BY SUM(s2.duration_minutes) DESC LIMIT 1)
+         FROM people p
+         LEFT JOIN session_people sp ON sp.person_id = p.id
+         LEFT JOIN play_sessions s ON s.id = sp.session_id
+         GROUP BY p.id
+         ORDER BY 4 DESC, p.name COLLATE NOCASE",
+    )?;
+    let stats = stmt
+        .query_map([], |row| {
+            Ok(PersonStats {
+                id:            row.get(0)?,
+                name:          row.get(1)?,
+                sessions:      row.get(2)?,
+                total_minutes: row.get(3)?,
+                games:         row.get(4)?,
+                top_game:      row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(stats)
+}
+
+// ---------------------------------------------------------------------------
+// Soundtracks
+// ---------------------------------------------------------------------------
+
+const SOUNDTRACK_SELECT: &str =
+    "SELECT s.id, s.game_id, g.title, s.title, s.owned, s.format, s.favorite_tracks, s.link,
+            s.created_at, s.updated_at
+     FROM soundtracks s JOIN games g ON g.id = s.game_id";
+
+fn row_to_soundtrack(row: &rusqlite::Row) -> Result<Soundtrack> {
+    Ok(Soundtrack {
+        id:              row.get(0)?,
+        game_id:         row.get(1)?,
+        game_title:      row.get(2)?,
+        title:           row.get(3)?,
+        owned:           row.get(4)?,
+        format:          row.get::<_, Option<String>>(5)?.map(|f| SoundtrackFormat::parse(&f)),
+        favorite_tracks: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+        link:            row.get(7)?,
+        created_at:      row.get(8)?,
+        updated_at:      row.get(9)?,
+    })
+}
+
+fn tracks_json(tracks: &[String]) -> String {
+    serde_json::to_string(tracks).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn add_soundtrack(conn: &Connection, input: &SoundtrackInput) -> Result<Soundtrack> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO soundtracks (game_id, title, owned, format, favorite_tracks, link, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+        params![
+            input.game_id, input.title.trim(), input.owned, input.format.map(|f| f.as_str()),
+            tracks_json(&input.favorite_tracks), input.link, now,
+        ],
+    )?;
+    get_soundtrack(conn, conn.last_insert_rowid())
+}
+
+/// Errors with `QueryReturnedNoRows` if there's no soundtrack `id`.
+pub fn update_soundtrack(conn: &Connection, id: i64, input: &SoundtrackInput) -> Result<Soundtrack> {
+    let changed = conn.execute(
+        "UPDATE soundtracks SET game_id = ?1, title = ?2, owned = ?3, format = ?4, favorite_tracks = ?5,
+                link = ?6, updated_at = ?7
+         WHERE id = ?8",
+        params![
+            input.game_id, input.title.trim(), input.owned, input.format.map(|f| f.as_str()),
+            tracks_json(&input.favorite_tracks), input.link, Utc::now().to_rfc3339(), id,
+        ],
+    )?;
+    if changed == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    get_soundtrack(conn, id)
+}
+
+pub fn delete_soundtrack(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM soundtracks WHERE id = ?1", params![id])? > 0)
+}
+
+fn get_soundtrack(conn: &Connection, id: i64) -> Result<Soundtrack> {
+    conn.query_row(&format!("{SOUNDTRACK_SELECT} WHERE s.id = ?1"), params![id], row_to_soundtrack)
+}
+
+/// Soundtracks of one game, or of every game, ordered by game then title.
+pub fn get_soundtracks(conn: &Connection, game_id: Option<i64>) -> Result<Vec<Soundtrack>> {
+    let mut stmt = conn.prepare(&format!(
+        "{SOUNDTRACK_SELECT} WHERE ?1 IS NULL OR s.game_id = ?1
+         ORDER BY g.title COLLATE NOCASE, s.title COLLATE NOCASE"
+    ))?;
+    let soundtracks = stmt
+        .query_map(params![game_id], row_to_soundtrack)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(soundtracks)
+}
+
+/// Soundtracks whose title, favorite tracks or game title contain `query`.
+pub fn search_soundtracks(conn: &Connection, query: &str, owned_only: bool) -> Result<Vec<Soundtrack>> {
+    let mut stmt = conn.prepare(&format!(
+        "{SOUNDTRACK_SELECT}
+         WHERE (s.title LIKE ?1 OR s.favorite_tracks LIKE ?1 OR g.title LIKE ?1)
+           AND (?2 = 0 OR s.owned = 1)
+         ORDER BY g.title COLLATE NOCASE, s.title COLLATE NOCASE"
+    ))?;
+    let soundtracks = stmt
+        .query_map(params![format!("%{query}%"), owned_only], row_to_soundtrack)?
+        .collect::<Result<Vec<_>>>()?;
+    debug!(results = soundtracks.len(), "search_soundtracks");
+    Ok(soundtracks)
+}
+
+// ---------------------------------------------------------------------------
+// Save backups
+// ---------------------------------------------------------------------------
+
+pub fn add_save_location(conn: &Connection, game_id: i64, path: &str) -> Result<SaveLocation> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO save_locations (game_id, path, created_at) VALUES (?1, ?2, ?3)",
+        params![game_id, path, now],
+    )?;
+    Ok(SaveLocation { id: conn.last_insert_rowid(), game_id, path: path.to_string(), created_at: now })
+}
+
+pub fn remove_save_location(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM save_locations WHERE id = ?1", params![id])? > 0)
+}
+
+pub fn get_save_locations(conn: &Connection, game_id: i64) -> Result<Vec<SaveLocation>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, game_id, path, created_at FROM save_locations WHERE game_id = ?1 ORDER BY id"
+    )?;
+    let locations = stmt
+        .query_map(params![game_id], |row| {
+            Ok(SaveLocation {
+                id:         row.get(0)?,
+                game_id:    row.get(1)?,
+                path:       row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(locations)
+}
+
+pub fn add_save_backup(
+    conn: &Connection,
+    game_id: i64,
+    archive_path: &str,
+    size_bytes: u64,
+    file_count: i64,
+    reason: BackupReason,
+) -> Result<SaveBackup> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO save_backups (game_id, archive_path, size_bytes, file_count, reason, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![game_id, archive_path, size_bytes as i64, file_count, reason.as_str(), now],
+    )?;
+    Ok(SaveBackup {
+        id: conn.last_insert_rowid(),
+        game_id,
+        archive_path: archive_path.to_string(),
+        size_bytes,
+        file_count,
+        reason,
+        created_at: now,
+    })
+}
+
+/// A game's backups, newest first.
+pub fn get_save_backups(conn: &Connection, game_id: i64) -> Result<Vec<SaveBackup>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, game_id, archive_path, size_bytes, file_count, reason, created_at
+         FROM save_backups WHERE game_id = ?1 ORDER BY created_at DESC, id DESC"
+    )?;
+    let backups = stmt
+        .query_map(params![game_id], |row| {
+            Ok(SaveBackup {
+                id:           row.get(0)?,
+                game_id:      row.get(1)?,
+                archive_path: row.get(2)?,
+                size_bytes:   row.get::<_, i64>(3)? as u64,
+                file_count:   row.get(4)?,
+                reason:       BackupReason::parse(&row.get::<_, String>(5)?),
+                created_at:   row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(backups)
+}
+
+/// How many save backups there are in all, their total size and when the
+/// newest was made.
+pub fn get_save_backup_totals(conn: &Connection) -> Result<(i64, i64, Option<String>)> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0), MAX(created_at) FROM save_backups",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+}
+
+/// Forget all but the newest `keep` backups of a game (0 = keep all) and
+/// return the archive paths that should now be deleted from disk.
+pub fn prune_save_backups(conn: &Connection, game_id: i64, keep: u32) -> Result<Vec<String>> {
+    if keep == 0 {
+        return Ok(vec![]);
+    }
+    let old: Vec<SaveBackup> = get_save_backups(conn, game_id)?.into_iter().skip(keep as usize).collect();
+    for backup in &old {
+        conn.execute("DELETE FROM save_backups WHERE id = ?1", params![backup.id])?;
+    }
+    if !old.is_empty() {
+        info!(game_id, pruned = old.len(), "Pruned old save backups");
+    }
+    Ok(old.into_iter().map(|b| b.archive_path).collect())
+}
+
+// ---------------------------------------------------------------------------
+// External ids (Steam app ids, …)
+// ---------------------------------------------------------------------------
+
+/// Link a game to its id on `source`. Re-linking replaces the old id and
+/// forgets the previous sync numbers. Linking the same external id to a second
+/// game fails with a UNIQUE constraint violation.
+pub fn link_external_id(conn: &Connection, game_id: i64, source: &str, external_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO game_external_ids (game_id, source, external_id) VALUES (?1, ?2, ?3)
+         ON CONFLICT(game_id, source) DO UPDATE SET
+            external_id = excluded.external_id, remote_playtime_minutes = NULL,
+            remote_recent_minutes = NULL, remote_last_played = NULL, synced_at = NULL",
+        params![game_id, source, external_id],
+    )?;
+    info!(game_id, source, external_id, "Linked external id");
+    Ok(())
+}
+
+pub fn unlink_external_id(conn: &Connection, game_id: i64, source: &str) -> Result<bool> {
+    let rows = conn.execute(
+        "DELETE FROM game_external_ids WHERE game_id = ?1 AND source = ?2",
+        params![game_id, source],
+    )?;
+    Ok(rows > 0)
+}
+
+/// Links for one game, or for every game on `source` (pass `game_id: None`).
+pub fn get_external_ids(conn: &Connection, game_id: Option<i64>, source: Option<&str>) -> Result<Vec<ExternalId>> {
+    let mut stmt = conn.prepare(
+        "SELECT game_id, source, external_id, remote_playtime_minutes, remote_recent_minutes,
+                remote_last_played, synced_at, url, drm_free
+         FROM game_external_ids
+         WHERE (?1 IS NULL OR game_id = ?1) AND (?2 IS NULL OR source = ?2)
+         ORDER BY game_id, source",
+    )?;
+    let ids = stmt
+        .query_map(params![game_id, source], |row| {
+            Ok(ExternalId {
+                game_id:                 row.get(0)?,
+                source:                  row.get(1)?,
+                external_id:             row.get(2)?,
+                remote_playtime_minutes: row.get(3)?,
+                remote_recent_minutes:   row.get(4)?,
+                remote_last_played:      row.get(5)?,
+                synced_at:               row.get(6)?,
+                url:                     row.get(7)?,
+                drm_free:                row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ids)
+}
+
+/// Record where a linked copy can be downloaded from and whether it's DRM-free.
+pub fn set_external_page(conn: &Connection, game_id: i64, source: &str, url: Option<&str>, drm_free: bool) -> Result<()> {
+    conn.execute(
+        "UPDATE game_external_ids SET url = ?1, drm_free = ?2 WHERE game_id = ?3 AND source = ?4",
+        params![url, drm_free, game_id, source],
+    )?;
+    Ok(())
+}
+
+/// Id of the game linked to `external_id` on `source`, if any.
+pub fn find_linked_game(conn: &Connection, source: &str, external_id: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT game_id FROM game_external_ids WHERE source = ?1 AND external_id = ?2",
+        params![source, external_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+// ---------------------------------------------------------------------------
+// Purchases
+// ---------------------------------------------------------------------------
+
+/// Record that `game_id` came in order `order_id` on `store`. Importing the
+/// same order again changes nothing; returns whether a purchase was added.
+pub fn add_purchase(
+    conn: &Connection,
+    game_id: i64,
+    store: &str,
+    order_id: &str,
+    bundle: Option<&str>,
+    purchased_at: Option<&str>,
+) -> Result<bool> {
+    let rows = conn.execute(
+        "INSERT OR IGNORE INTO purchases (game_id, store, order_id, bundle, purchased_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![game_id, store, order_id, bundle, purchased_at],
+    )?;
+    Ok(rows > 0)
+}
+
+/// Every order a game came in, oldest first.
+pub fn get_purchases(conn: &Connection, game_id: i64) -> Result<Vec<Purchase>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, game_id, store, order_id, bundle, purchased_at FROM purchases
+         WHERE game_id = ?1 ORDER BY purchased_at IS NULL, purchased_at, id",
+    )?;
+    let purchases = stmt
+        .query_map(params![game_id], |row| {
+            Ok(Purchase {
+                id:           row.get(0)?,
+                game_id:      row.get(1)?,
+                store:        row.get(2)?,
+                order_id:     row.get(3)?,
+                bundle:       row.get(4)?,
+                purchased_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(purchases)
+}
+
+/// Every bundle bought, newest first, with how many of its games have been
+/// played at all — started, finished or dropped, or with playtime logged.
+pub fn get_bundles(conn: &Connection) -> Result<Vec<BundleSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.store, p.bundle, MIN(p.purchased_at), COUNT(DISTINCT p.game_id),
+                COUNT(DISTINCT CASE WHEN g.status IN ('Playing', 'Completed', 'Dropped')
+                                      OR COALESCE(g.playtime_minutes, 0) > 0 THEN g.id END)
+         FROM purchases p JOIN games g ON g.id = p.game_id
+         WHERE p.bundle IS NOT NULL
+         GROUP BY p.store, p.bundle
+         ORDER BY MIN(p.purchased_at) DESC, p.bundle",
+    )?;
+    let bundles = stmt
+        .query_map([], |row| {
+            Ok(BundleSummary {
+                store:        row.get(0)?,
+                bundle:       row.get(1)?,
+                purchased_at: row.get(2)?,
+                games:        row.get(3)?,
+                played:       row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(bundles)
+}
+
+/// What `import_game` did with an incoming game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportOutcome {
+    Created,     // added as a new game
+    Updated,     // merged into a matching game, which changed
+    Unchanged,   // matched a game and left it as it was
+}
+
+/// Find the library game for an imported title, creating it if needed. Every
+/// importer goes through here so conflicts are handled the same everywhere.
+///
+/// Tries the `(source, external_id)` link first — that's the same game, so it's
+/// returned untouched. Next a game with the same title: `strategy` decides
+/// whether it's kept, overwritten, gap-filled or ignored in favour of a new
+/// game (see ConflictStrategy); a kept or merged game gets linked. Otherwise
+/// `input` is added as a new game.
+pub fn import_game(
+    conn: &Connection,
+    input: GameInput,
+    link: Option<(&str, &str)>,
+    strategy: ConflictStrategy,
+) -> Result<(i64, ImportOutcome)> {
+    if let Some((source, external_id)) = link {
+        if let Some(id) = find_linked_game(conn, source, external_id)? {
+            return Ok((id, ImportOutcome::Unchanged));
+        }
+    }
+
+    let existing = match strategy {
+        ConflictStrategy::Duplicate => None,
+        _ => find_game_by_title(conn, &input.title)?,
+    };
+    let (id, outcome) = match existing {
+        Some(id) => {
+            let game = fetch_game_by_id(conn, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            match import::merge_input(&game, input, strategy) {
+                Some(merged) => {
+                    update_game(conn, id, merged)?;
+                    (id, ImportOutcome::Updated)
+                }
+                None => (id, ImportOutcome::Unchanged),
+            }
+        }
+        None => (add_game(conn, input)?.id, ImportOutcome::Created),
+    };
+
+    if let Some((source, external_id)) = link {
+        // Keep an existing link to a different id (e.g. another edition) rather than fail.
+        if get_external_ids(conn, Some(id), Some(source))?.is_empty() {
+            link_external_id(conn, id, source, external_id)?;
+        }
+    }
+    debug!(id, ?outcome, ?strategy, "Imported game");
+    Ok((id, outcome))
+}
+
+/// Merge a service's reported lifetime playtime into a linked game.
+/// Returns the minutes added to the game (0 if nothing changed).
+///
+/// Steam-style services only report a running total, so we work from the
+/// change since the last sync. Anything the user logged by hand since then was
+/// (presumably) played through that service too, so it's subtracted — only the
+/// remainder becomes a new session. On the very first sync there's no previous
+/// total; the game is just topped up to the remote total — unless the user
+/// has corrected its playtime by hand, which then stands and only later
+/// changes are added.
+pub fn merge_remote_playtime(
+    conn: &Connection,
+    link: &ExternalId,
+    total_minutes: i64,
+    recent_minutes: Option<i64>,
+    last_played: Option<String>,
+    source: SessionSource,
+) -> Result<i64> {
+    let now = Utc::now().to_rfc3339();
+
+    let added = match (link.remote_playtime_minutes, &link.synced_at) {
+        (Some(previous), Some(synced_at)) => {
+            let local: i64 = conn.query_row(
+                "SELECT COALESCE(SUM(duration_minutes), 0) FROM play_sessions
+                 WHERE game_id = ?1 AND source IN ('Manual', 'Timer') AND created_at > ?2",
+                params![link.game_id, synced_at],
+                |row| row.get(0),
+            )?;
+            (total_minutes - previous - local).max(0)
+        }
+        _ => {
+            let (local, adjusted): (Option<i64>, bool) = conn.query_row(
+                "SELECT playtime_minutes, EXISTS (SELECT 1 FROM playtime_adjustments WHERE game_id = ?1)
+                 FROM games WHERE id = ?1",
+                params![link.game_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            if adjusted { 0 } else { (total_minutes - local.unwrap_or(0)).max(0) }
+        }
+    };
+
+    conn.execute(
+        "UPDATE game_external_ids SET remote_playtime_minutes = ?1, remote_recent_minutes = ?2,
+                remote_last_played = ?3, synced_at = ?4
+         WHERE game_id = ?5 AND source = ?6",
+        params![total_minutes, recent_minutes, last_played, now, link.game_id, link.source],
+    )?;
+
+    if added > 0 {
+        let started_at = last_played.unwrap_or_else(|| now.clone());
+        add_play_session(conn, link.game_id, &started_at, added, source, None)?;
+    }
+    debug!(game_id = link.game_id, total_minutes, added, "Merged remote playtime");
+    Ok(added)
+}
+
+// ---------------------------------------------------------------------------
+// Compatibility ratings
+// ---------------------------------------------------------------------------
+
+/// Store the rating `source` gives a game (None = no rating yet).
+/// Returns true if it differs from what was stored before.
+pub fn set_compat_rating(conn: &Connection, game_id: i64, source: &str, rating: Option<&str>) -> Result<bool> {
+    let previous: Option<Option<String>> = conn
+        .query_row(
+            "SELECT rating FROM game_compat WHERE game_id = ?1 AND source = ?2",
+            params![game_id, source],
+            |row| row.get(0),
+        )
+        .optional()?;
+    conn.execute(
+        "INSERT INTO game_compat (game_id, source, rating, checked_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(game_id, source) DO UPDATE SET rating = excluded.rating, checked_at = excluded.checked_at",
+        params![game_id, source, rating, Utc::now().to_rfc3339()],
+    )?;
+    Ok(previous.flatten().as_deref() != rating)
+}
+
+/// Games linked on `link_source` whose `source` rating is missing or was last
+/// checked before `checked_before` (RFC 3339), oldest first.
+pub fn get_stale_compat_links(
+    conn: &Connection,
+    source: &str,
+    link_source: &str,
+    checked_before: &str,
+) -> Result<Vec<ExternalId>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.game_id, e.source, e.external_id, e.remote_playtime_minutes, e.remote_recent_minutes,
+                e.remote_last_played, e.synced_at, e.url, e.drm_free
+         FROM game_external_ids e
+         LEFT JOIN game_compat c ON c.game_id = e.game_id AND c.source = ?1
+         WHERE e.source = ?2 AND (c.checked_at IS NULL OR c.checked_at < ?3)
+         ORDER BY c.checked_at IS NOT NULL, c.checked_at, e.game_id",
+    )?;
+    let ids = stmt
+        .query_map(params![source, link_source, checked_before], |row| {
+            Ok(ExternalId {
+                game_id:                 row.get(0)?,
+                source:                  row.get(1)?,
+                external_id:             row.get(2)?,
+                remote_playtime_minutes: row.get(3)?,
+                remote_recent_minutes:   row.get(4)?,
+                remote_last_played:      row.get(5)?,
+                synced_at:               row.get(6)?,
+                url:                     row.get(7)?,
+                drm_free:                row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ids)
+}
+
+// ---------------------------------------------------------------------------
+// Cover sources
+// ---------------------------------------------------------------------------
+
+/// Remember where `game_id`'s cover was downloaded from, marking it checked
+/// now. Nothing is saved unless `source.cover` is still the game's cover.
+pub fn set_cover_source(conn: &Connection, game_id: i64, source: &CoverSource) -> Result<()> {
+    conn.execute(
+        "INSERT INTO cover_sources (game_id, url, cover, etag, last_modified, hash, checked_at)
+         SELECT id, ?2, ?3, ?4, ?5, ?6, ?7 FROM games WHERE id = ?1 AND cover_art_path = ?3
+         ON CONFLICT(game_id) DO UPDATE SET
+            url = excluded.url, cover = excluded.cover, etag = excluded.etag,
+            last_modified = excluded.last_modified, hash = excluded.hash, checked_at = excluded.checked_at",
+        params![
+            game_id,
+            source.url,
+            source.cover,
+            source.etag,
+            source.last_modified,
+            source.hash,
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Cover sources last checked before `checked_before` (RFC 3339), oldest
+/// first, leaving out games whose cover has been replaced since.
+pub fn get_stale_cover_sources(conn: &Connection, checked_before: &str) -> Result<Vec<(i64, CoverSource)>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.game_id, s.url, s.cover, s.etag, s.last_modified, s.hash
+         FROM cover_sources s JOIN games g ON g.id = s.game_id AND g.cover_art_path = s.cover
+         WHERE s.checked_at < ?1
+         ORDER BY s.checked_at, s.game_id",
+    )?;
+    let sources = stmt
+        .query_map(params![checked_before], |row| {
+            Ok((row.get(0)?, CoverSource {
+                url:           row.get(1)?,
+                cover:         row.get(2)?,
+                etag:          row.get(3)?,
+                last_modified: row.get(4)?,
+                hash:          row.get(5)?,
+            }))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(sources)
+}
+
+/// Swap a game's cover `previous` for the one `source` now describes.
+/// Returns false (and changes nothing) if the cover was replaced meanwhile.
+pub fn replace_cover(conn: &Connection, game_id: i64, previous: &str, source: &CoverSource) -> Result<bool> {
+    atomically(conn, || {
+        let now = Utc::now().to_rfc3339();
+        let changed = conn.execute(
+            "UPDATE games SET cover_art_path = ?3, updated_at = ?4 WHERE id = ?1 AND cover_art_path = ?2",
+            params![game_id, previous, source.cover, now],
+        )?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        store_cover_details(conn, game_id, Some(&source.cover))?;
+        set_cover_source(conn, game_id, source)?;
+        record_edit(conn, game_id, &["cover_art_path".to_string()], &now)?;
+        Ok(true)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Metadata refreshes
+// ---------------------------------------------------------------------------
+
+/// Store the manifest of a finished refresh and fill in its id.
+pub fn add_refresh_manifest(conn: &Connection, manifest: &mut RefreshManifest) -> Result<()> {
+    let providers = serde_json::to_string(&manifest.providers)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO metadata_refreshes (started_at, finished_at, providers) VALUES (?1, ?2, ?3)",
+        params![manifest.started_at, manifest.finished_at, providers],
+    )?;
+    manifest.id = conn.last_insert_rowid();
+    // Only recent runs are interesting; keep the table from growing forever.
+    conn.execute(
+        "DELETE FROM metadata_refreshes WHERE id <= ?1 - ?2",
+        params![manifest.id, REFRESH_MANIFESTS_TO_KEEP],
+    )?;
+    Ok(())
+}
+
+/// How many refresh manifests are kept.
+const REFRESH_MANIFESTS_TO_KEEP: i64 = 50;
+
+/// The most recent refresh manifests, newest first.
+pub fn get_refresh_manifests(conn: &Connection, limit: u32) -> Result<Vec<RefreshManifest>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, started_at, finished_at, providers FROM metadata_refreshes ORDER BY id DESC LIMIT ?1",
+    )?;
+    let manifests = stmt
+        .query_map([limit], |row| {
+            let providers: String = row.get(3)?;
+            Ok(RefreshManifest {
+                id:          row.get(0)?,
+                started_at:  row.get(1)?,
+                finished_at: row.get(2)?,
+                providers:   serde_json::from_str(&providers).unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(manifests)
+}
+
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+/// How many finished jobs are kept in the history.
+const JOBS_TO_KEEP: i64 = 200;
+
+/// Record a newly queued job and return it.
+pub fn add_job(conn: &Connection, kind: &str) -> Result<Job> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO jobs (kind, status, created_at) VALUES (?1, ?2, ?3)",
+        params![kind, JobStatus::Queued.as_str(), now],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "DELETE FROM jobs WHERE id <= ?1 - ?2 AND status IN ('Succeeded', 'Failed', 'Cancelled')",
+        params![id, JOBS_TO_KEEP],
+    )?;
+    get_job(conn, id)
+}
+
+pub fn get_job(conn: &Connection, id: i64) -> Result<Job> {
+    conn.query_row(&format!("{JOB_SELECT} WHERE id = ?1"), [id], row_to_job)
+}
+
+/// Jobs newest first, optionally only those with `status`.
+pub fn get_jobs(conn: &Connection, status: Option<JobStatus>, limit: u32) -> Result<Vec<Job>> {
+    let mut stmt = conn.prepare(&format!("{JOB_SELECT} WHERE (?1 IS NULL OR status = ?1) ORDER BY id DESC LIMIT ?2"))?;
+    let jobs = stmt
+        .query_map(params![status.map(|s| s.as_str()), limit], row_to_job)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(jobs)
+}
+
+/// Save a job's status and progress. `started_at` / `finished_at` are set the
+/// first time the job is seen running / finished.
+pub fn update_job(conn: &Connection, job: &Job) -> Result<()> {
+    let result = job.result.as_ref().map(|r| r.to_string());
+    conn.execute(
+        "UPDATE jobs SET status = ?2, done = ?3, total = ?4, message = ?5, result = ?6, error = ?7,
+                         started_at = ?8, finished_at = ?9
+         WHERE id = ?1",
+        params![
+            job.id,
+            job.status.as_str(),
+            job.done as i64,
+            job.total as i64,
+            job.message,
+            result,
+            job.error,
+            job.started_at,
+            job.finished_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Mark jobs left queued or running by a previous run of the app as failed.
+pub fn fail_interrupted_jobs(conn: &Connection) -> Result<usize> {
+    let n = conn.execute(
+        "UPDATE jobs SET status = 'Failed', error = 'Interrupted: the app closed before the job finished',
+                         finished_at = ?1
+         WHERE status IN ('Queued', 'Running')",
+        [Utc::now().to_rfc3339()],
+    )?;
+    Ok(n)
+}
 
-    let final_sql = format!(
-        "SELECT DISTINCT g.id FROM games g {where_str} {order_clause}"
-    );
+const JOB_SELECT: &str =
+    "SELECT id, kind, status, done, total, message, result, error, created_at, started_at, finished_at FROM jobs";
 
-    let _ = sql; // suppress unused warning on the earlier draft
-    let mut stmt = conn.prepare(&final_sql)?;
+fn row_to_job(row: &rusqlite::Row) -> Result<Job> {
+    let result: Option<String> = row.get(6)?;
+    Ok(Job {
+        id:          row.get(0)?,
+        kind:        row.get(1)?,
+        status:      JobStatus::parse(&row.get::<_, String>(2)?),
+        done:        row.get::<_, i64>(3)? as usize,
+        total:       row.get::<_, i64>(4)? as usize,
+        message:     row.get(5)?,
+        result:      result.and_then(|r| serde_json::from_str(&r).ok()),
+        error:       row.get(7)?,
+        created_at:  row.get(8)?,
+        started_at:  row.get(9)?,
+        finished_at: row.get(10)?,
+    })
+}
 
-    // Convert Vec<Box<dyn ToSql>> to a slice of references for rusqlite
-    let params_ref: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
-    let ids: Vec<i64> = stmt
-        .query_map(params_ref.as_slice(), |row| row.get(0))?
-        .collect::<Result<Vec<i64>>>()?;
+// ---------------------------------------------------------------------------
+// Installs
+// ---------------------------------------------------------------------------
+
+/// Every folder the library knows a game by: install folders and save
+/// locations.
+pub fn get_game_directories(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT install_path FROM game_installs UNION SELECT path FROM save_locations")?;
+    let dirs = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<String>>>()?;
+    Ok(dirs)
+}
+
+/// Match scanned installs to library games: by launcher link first, then by
+/// title (ignoring case and punctuation). Title matches are linked so the next
+/// scan doesn't have to guess. Sets `game_id` on every matched install.
+pub fn match_installs(conn: &Connection, installs: &mut [DetectedInstall]) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, title FROM games")?;
+    let by_title: std::collections::HashMap<String, i64> = stmt
+        .query_map([], |row| Ok((crate::metadata::normalize_title(&row.get::<_, String>(1)?), row.get(0)?)))?
+        .collect::<Result<_>>()?;
+
+    for install in installs.iter_mut() {
+        install.game_id = match find_linked_game(conn, &install.launcher, &install.external_id)? {
+            Some(id) => Some(id),
+            None => {
+                let id = by_title.get(&crate::metadata::normalize_title(&install.title)).copied();
+                if let Some(id) = id {
+                    if get_external_ids(conn, Some(id), Some(&install.launcher))?.is_empty() {
+                        link_external_id(conn, id, &install.launcher, &install.external_id)?;
+                    }
+                }
+                id
+            }
+        };
+    }
+    Ok(())
+}
+
+/// Replace the stored install list with the matched installs from a fresh scan.
+/// Games no longer found are no longer installed. Returns every game id whose
+/// install state may have changed (previously or now installed).
+pub fn replace_installs(conn: &Connection, installs: &[DetectedInstall]) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT game_id FROM game_installs")?;
+    let mut touched: Vec<i64> = stmt.query_map([], |row| row.get(0))?.collect::<Result<_>>()?;
 
-    let mut games = Vec::new();
-    for id in ids {
-        if let Some(game) = fetch_game_by_id(conn, id)? {
-            games.push(game);
+    let now = Utc::now().to_rfc3339();
+    conn.execute("DELETE FROM game_installs", [])?;
+    for install in installs {
+        let Some(game_id) = install.game_id else { continue };
+        conn.execute(
+            "INSERT OR REPLACE INTO game_installs (game_id, launcher, install_path, size_bytes, detected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![game_id, install.launcher, install.install_path, install.size_bytes.map(|b| b as i64), now],
+        )?;
+        touched.push(game_id);
+    }
+    touched.sort_unstable();
+    touched.dedup();
+    info!(installed = installs.iter().filter(|i| i.game_id.is_some()).count(), "Stored installed games");
+    Ok(touched)
+}
+
+// ---------------------------------------------------------------------------
+// Achievements
+// ---------------------------------------------------------------------------
+
+/// Insert or refresh a game's achievements from `source`.
+/// Returns how many were newly unlocked compared to what was stored.
+pub fn upsert_achievements(
+    conn: &Connection,
+    game_id: i64,
+    source: &str,
+    achievements: &[RemoteAchievement],
+) -> Result<usize> {
+    let mut newly_unlocked = 0;
+    for a in achievements {
+        let was_unlocked: Option<bool> = conn
+            .query_row(
+                "SELECT unlocked_at IS NOT NULL FROM achievements
+                 WHERE game_id = ?1 AND source = ?2 AND external_id = ?3",
+                params![game_id, source, a.external_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if a.unlocked_at.is_some() && was_unlocked != Some(true) {
+            newly_unlocked += 1;
         }
+
+        conn.execute(
+            "INSERT INTO achievements (game_id, source, external_id, name, description, icon_url,
+                                       kind, points, hidden, unlocked_at, rarity_percent)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(game_id, source, external_id) DO UPDATE SET
+                name = excluded.name, description = excluded.description,
+                icon_url = excluded.icon_url, kind = excluded.kind, points = excluded.points,
+                hidden = excluded.hidden, unlocked_at = excluded.unlocked_at,
+                rarity_percent = excluded.rarity_percent",
+            params![
+                game_id, source, a.external_id, a.name, a.description, a.icon_url,
+                a.kind, a.points, a.hidden, a.unlocked_at, a.rarity_percent,
+            ],
+        )?;
     }
-    Ok(games)
+    debug!(game_id, source, total = achievements.len(), newly_unlocked, "Stored achievements");
+    Ok(newly_unlocked)
 }
 
-fn build_order_clause(filter: &SearchFilter) -> String {
-    let asc = filter.sort_asc.unwrap_or(true);
-    let dir = if asc { "ASC" } else { "DESC" };
-    let col = match &filter.sort_by {
-        Some(SortField::Title)               => "g.title",
-        Some(SortField::ReleaseDate)         => "g.release_date",
-        Some(SortField::Rating)              => "g.rating",
-        Some(SortField::PlaytimeHours)       => "g.playtime_hours",
-        Some(SortField::ProgressPercent)     => "g.progress_percent",
-        Some(SortField::SequenceInFranchise) => "g.sequence_in_franchise",
-        Some(SortField::UpdatedAt) | None    => "g.updated_at",
-    };
-    format!("ORDER BY {col} {dir} NULLS LAST")
+/// A game's achievements: unlocked ones first (newest first), then locked ones by name.
+pub fn get_achievements(conn: &Connection, game_id: i64) -> Result<Vec<Achievement>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, game_id, source, external_id, name, description, icon_url, kind, points,
+                hidden, unlocked_at, rarity_percent
+         FROM achievements WHERE game_id = ?1
+         ORDER BY unlocked_at IS NULL, unlocked_at DESC, name",
+    )?;
+    let achievements = stmt
+        .query_map(params![game_id], |row| {
+            Ok(Achievement {
+                id:             row.get(0)?,
+                game_id:        row.get(1)?,
+                source:         row.get(2)?,
+                external_id:    row.get(3)?,
+                name:           row.get(4)?,
+                description:    row.get(5)?,
+                icon_url:       row.get(6)?,
+                kind:           row.get(7)?,
+                points:         row.get(8)?,
+                hidden:         row.get(9)?,
+                unlocked_at:    row.get(10)?,
+                rarity_percent: row.get(11)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(achievements)
+}
+
+/// Unlocked / total counts and points for a game, one row per service.
+pub fn get_achievement_summaries(conn: &Connection, game_id: i64) -> Result<Vec<AchievementSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT source, COUNT(*), COUNT(unlocked_at),
+                COALESCE(SUM(points), 0),
+                COALESCE(SUM(CASE WHEN unlocked_at IS NOT NULL THEN points END), 0)
+         FROM achievements WHERE game_id = ?1
+         GROUP BY source ORDER BY source",
+    )?;
+    let summaries = stmt
+        .query_map(params![game_id], |row| {
+            Ok(AchievementSummary {
+                source:          row.get(0)?,
+                total:           row.get(1)?,
+                unlocked:        row.get(2)?,
+                points_total:    row.get(3)?,
+                points_unlocked: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(summaries)
+}
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// Load settings. Missing keys fall back to their defaults; if the stored
+/// values can't be read at all, every setting falls back.
+pub fn get_settings(conn: &Connection) -> Result<Settings> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let mut map = serde_json::Map::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (key, value) = row?;
+        if let Ok(value) = serde_json::from_str(&value) {
+            map.insert(key, value);
+        }
+    }
+    Ok(serde_json::from_value(serde_json::Value::Object(map)).unwrap_or_else(|e| {
+        warn!(error = %e, "Stored settings are invalid, using defaults");
+        Settings::default()
+    }))
+}
+
+/// One raw settings row as a string, e.g. a field older versions had.
+pub fn get_setting_string(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()?;
+    Ok(value.and_then(|v| serde_json::from_str::<Option<String>>(&v).ok().flatten()))
+}
+
+pub fn delete_setting(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute("DELETE FROM settings WHERE key = ?1", [key])?;
+    Ok(())
+}
+
+/// Save every field of `settings`.
+pub fn save_settings(conn: &Connection, settings: &Settings) -> Result<()> {
+    let value = serde_json::to_value(settings)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    if let serde_json::Value::Object(fields) = value {
+        for (key, value) in fields {
+            conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![key, value.to_string()],
+            )?;
+        }
+    }
+    info!("Saved settings");
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Change tracking
+// ---------------------------------------------------------------------------
+//
+// Every edit bumps the game's `revision` and stamps it with this device and
+// the time; each field that changed gets the same stamp in
+// `game_field_versions`. Merging two copies of the library can then keep, per
+// field, whichever side edited it last (see filesync.rs).
+
+static DEVICE_ID: OnceLock<String> = OnceLock::new();
+
+/// Tell the DB layer which device edits are made on. Call once at startup;
+/// until then edits are recorded without a device.
+pub fn set_device_id(id: String) {
+    let _ = DEVICE_ID.set(id);
+}
+
+fn device_id() -> Option<&'static str> {
+    DEVICE_ID.get().map(String::as_str)
+}
+
+/// Stamp an edit to `fields` of game `id`. Nothing to do if no field changed.
+fn record_edit(conn: &Connection, id: i64, fields: &[String], now: &str) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    conn.execute(
+        "UPDATE games SET revision = revision + 1, device_id = ?2, modified_at = ?3 WHERE id = ?1",
+        params![id, device_id(), now],
+    )?;
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO game_field_versions (game_id, field, revision, device_id, modified_at)
+         SELECT id, ?2, revision, device_id, modified_at FROM games WHERE id = ?1
+         ON CONFLICT(game_id, field) DO UPDATE SET
+            revision    = excluded.revision,
+            device_id   = excluded.device_id,
+            modified_at = excluded.modified_at",
+    )?;
+    for field in fields {
+        stmt.execute(params![id, field])?;
+    }
+    Ok(())
+}
+
+/// The game's revision and who last edited it.
+pub fn get_record_version(conn: &Connection, id: i64) -> Result<Option<RecordVersion>> {
+    conn.query_row(
+        "SELECT revision, device_id, COALESCE(modified_at, updated_at) FROM games WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(RecordVersion {
+                revision:    row.get(0)?,
+                device_id:   row.get(1)?,
+                modified_at: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// When each of the game's fields was last edited. Fields not edited since
+/// change tracking began (or since the game was added) aren't listed.
+pub fn get_field_versions(conn: &Connection, id: i64) -> Result<HashMap<String, RecordVersion>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT field, revision, device_id, modified_at FROM game_field_versions WHERE game_id = ?1",
+    )?;
+    let versions = stmt
+        .query_map(params![id], |row| {
+            Ok((row.get(0)?, RecordVersion {
+                revision:    row.get(1)?,
+                device_id:   row.get(2)?,
+                modified_at: row.get(3)?,
+            }))
+        })?
+        .collect::<Result<HashMap<_, _>>>()?;
+    Ok(versions)
+}
+
+/// Overwrite the stamps of the given fields, e.g. with another copy's after
+/// a merge took its values — the edit is that device's, not this one's.
+pub fn set_field_versions<'a>(
+    conn: &Connection,
+    id: i64,
+    versions: impl IntoIterator<Item = (&'a String, &'a RecordVersion)>,
+) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO game_field_versions (game_id, field, revision, device_id, modified_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(game_id, field) DO UPDATE SET
+            revision    = excluded.revision,
+            device_id   = excluded.device_id,
+            modified_at = excluded.modified_at",
+    )?;
+    for (field, version) in versions {
+        stmt.execute(params![id, field, version.revision, version.device_id, version.modified_at])?;
+    }
+    Ok(())
+}
+
+/// Every tombstone: each deleted game's title and the stamp of its delete.
+pub fn get_tombstones(conn: &Connection) -> Result<Vec<(String, RecordVersion)>> {
+    let mut stmt = conn.prepare("SELECT title, revision, device_id, deleted_at FROM game_tombstones")?;
+    let tombstones = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, RecordVersion {
+                revision:    row.get(1)?,
+                device_id:   row.get(2)?,
+                modified_at: row.get(3)?,
+            }))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(tombstones)
+}
+
+/// The tombstone of a game titled `title`, if one was deleted.
+pub fn get_tombstone(conn: &Connection, title: &str) -> Result<Option<RecordVersion>> {
+    conn.query_row(
+        "SELECT revision, device_id, deleted_at FROM game_tombstones WHERE normalized_title = ?1",
+        params![titles::normalize(title)],
+        |row| {
+            Ok(RecordVersion {
+                revision:    row.get(0)?,
+                device_id:   row.get(1)?,
+                modified_at: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Record that the game titled `title` was deleted, unless a later delete of
+/// it already is.
+pub fn set_tombstone(conn: &Connection, title: &str, deleted: &RecordVersion) -> Result<()> {
+    if get_tombstone(conn, title)?.is_some_and(|known| !deleted.is_newer_than(&known)) {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO game_tombstones (normalized_title, title, revision, device_id, deleted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(normalized_title) DO UPDATE SET
+            title      = excluded.title,
+            revision   = excluded.revision,
+            device_id  = excluded.device_id,
+            deleted_at = excluded.deleted_at",
+        params![titles::normalize(title), title, deleted.revision, deleted.device_id, deleted.modified_at],
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// File sync & merge conflicts
+// ---------------------------------------------------------------------------
+
+/// The file's current claim; generation 0 if no app has claimed it yet.
+pub fn get_db_generation(conn: &Connection) -> Result<DbGeneration> {
+    let generation = conn
+        .query_row("SELECT generation, device, written_at FROM db_generation WHERE id = 1", [], |row| {
+            Ok(DbGeneration {
+                generation: row.get(0)?,
+                device:     row.get(1)?,
+                written_at: row.get(2)?,
+            })
+        })
+        .optional()?;
+    Ok(generation.unwrap_or_default())
+}
+
+/// Bump the generation and sign it with `device`; returns the new claim.
+pub fn claim_db_generation(conn: &Connection, device: &str) -> Result<DbGeneration> {
+    conn.execute(
+        "INSERT INTO db_generation (id, generation, device, written_at) VALUES (1, 1, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET
+            generation = generation + 1,
+            device     = excluded.device,
+            written_at = excluded.written_at",
+        params![device, Utc::now().to_rfc3339()],
+    )?;
+    get_db_generation(conn)
+}
+
+/// Record that a merge kept `game_id`'s values over conflicting ones. An
+/// open conflict the game already had is replaced: the new one is current.
+pub fn add_sync_conflict(conn: &Connection, game_id: i64, source: &str, fields: &[FieldConflict]) -> Result<i64> {
+    let fields = serde_json::to_string(fields).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute("DELETE FROM sync_conflicts WHERE game_id = ?1 AND resolved_at IS NULL", params![game_id])?;
+    conn.execute(
+        "INSERT INTO sync_conflicts (game_id, source, fields, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![game_id, source, fields, Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Conflicts, newest first; only open ones unless `include_resolved`.
+pub fn get_sync_conflicts(conn: &Connection, include_resolved: bool) -> Result<Vec<SyncConflict>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.game_id, g.title, c.source, c.fields, c.created_at, c.resolved_at, c.resolution
+         FROM sync_conflicts c JOIN games g ON g.id = c.game_id
+         WHERE ?1 OR c.resolved_at IS NULL
+         ORDER BY c.id DESC",
+    )?;
+    let conflicts = stmt
+        .query_map(params![include_resolved], |row| {
+            Ok(SyncConflict {
+                id:          row.get(0)?,
+                game_id:     row.get(1)?,
+                title:       row.get(2)?,
+                source:      row.get(3)?,
+                fields:      serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                created_at:  row.get(5)?,
+                resolved_at: row.get(6)?,
+                resolution:  row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(conflicts)
+}
+
+pub fn mark_sync_conflict_resolved(conn: &Connection, id: i64, resolution: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE sync_conflicts SET resolved_at = ?2, resolution = ?3 WHERE id = ?1",
+        params![id, Utc::now().to_rfc3339(), resolution],
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// WebDAV sync
+// ---------------------------------------------------------------------------
+
+/// What the last WebDAV sync saw; all `None` before the first one.
+pub fn get_webdav_state(conn: &Connection) -> Result<WebDavState> {
+    let state = conn
+        .query_row("SELECT remote_version, library_hash, synced_at FROM webdav_sync WHERE id = 1", [], |row| {
+            Ok(WebDavState {
+                remote_version: row.get(0)?,
+                library_hash:   row.get(1)?,
+                synced_at:      row.get(2)?,
+            })
+        })
+        .optional()?;
+    Ok(state.unwrap_or_default())
+}
+
+pub fn set_webdav_state(conn: &Connection, state: &WebDavState) -> Result<()> {
+    conn.execute(
+        "INSERT INTO webdav_sync (id, remote_version, library_hash, synced_at) VALUES (1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET
+            remote_version = excluded.remote_version,
+            library_hash   = excluded.library_hash,
+            synced_at      = excluded.synced_at",
+        params![state.remote_version, state.library_hash, state.synced_at],
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Library dump
+// ---------------------------------------------------------------------------
+
+/// A table's rows as column → value maps, as written to a library export.
+pub type TableRows = Vec<serde_json::Map<String, serde_json::Value>>;
+
+/// Every game in the library, archived ones included.
+pub fn count_games(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0))
+}
+
+/// How many MIGRATIONS this database has applied.
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Every game id, oldest first.
+pub fn get_game_ids(conn: &Connection) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT id FROM games ORDER BY id")?;
+    let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<i64>>>()?;
+    Ok(ids)
+}
+
+/// Every table in the database except SQLite's own and those in `skip`,
+/// in creation order — so parents come before the tables referencing them.
+pub fn library_tables(conn: &Connection, skip: &[&str]) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+    )?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>>>()?;
+    Ok(names.into_iter().filter(|name| !skip.contains(&name.as_str())).collect())
+}
+
+/// Every row of `table`. Only call with names from `library_tables`.
+pub fn dump_table(conn: &Connection, table: &str) -> Result<TableRows> {
+    let mut stmt = conn.prepare(&format!("SELECT * FROM \"{table}\" ORDER BY rowid"))?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let rows = stmt
+        .query_map([], |row| {
+            let mut map = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                let value = match row.get::<_, Value>(i)? {
+                    Value::Null       => serde_json::Value::Null,
+                    Value::Integer(n) => n.into(),
+                    Value::Real(x)    => x.into(),
+                    Value::Text(t)    => t.into(),
+                    Value::Blob(b)    => b.into(),
+                };
+                map.insert(column.clone(), value);
+            }
+            Ok(map)
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// Replace the contents of `tables` (names from `library_tables`, in that
+/// order) with the given rows, all or nothing. Returns the rows written.
+///
+/// Columns the table doesn't have (a dump from a newer version) are dropped;
+/// columns the dump doesn't have take their defaults.
+pub fn replace_tables(conn: &Connection, tables: &[(String, &TableRows)]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    // Rows reference each other across tables; check once everything is in.
+    tx.pragma_update(None, "defer_foreign_keys", true)?;
+    for (table, _) in tables.iter().rev() {
+        tx.execute(&format!("DELETE FROM \"{table}\""), [])?;
+    }
+
+    let mut written = 0;
+    for (table, rows) in tables {
+        let mut stmt = tx.prepare(&format!("SELECT name FROM pragma_table_info('{table}')"))?;
+        let known: HashSet<String> = stmt.query_map([], |row| row.get(0))?.collect::<Result<_>>()?;
+        for row in rows.iter() {
+            let (columns, values): (Vec<&String>, Vec<Value>) = row
+                .iter()
+                .filter(|(column, _)| known.contains(*column))
+                .map(|(column, value)| (column, json_to_sql(value)))
+                .unzip();
+            if columns.is_empty() {
+                continue;
+            }
+            let sql = format!(
+                "INSERT INTO \"{table}\" ({}) VALUES ({})",
+                columns.iter().map(|c| format!("\"{c}\"")).collect::<Vec<_>>().join(", "),
+                vec!["?"; columns.len()].join(", "),
+            );
+            tx.prepare_cached(&sql)?.execute(params_from_iter(values))?;
+            written += 1;
+        }
+    }
+    tx.commit()?;
+    info!(tables = tables.len(), rows = written, "Replaced library tables");
+    Ok(written)
+}
+
+fn json_to_sql(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null      => Value::Null,
+        serde_json::Value::Bool(b)   => Value::Integer(i64::from(*b)),
+        serde_json::Value::Number(n) => n.as_i64().map(Value::Integer).unwrap_or_else(|| Value::Real(n.as_f64().unwrap_or_default())),
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        // Blobs are written as arrays of bytes.
+        serde_json::Value::Array(a)  => Value::Blob(a.iter().filter_map(|b| b.as_u64().map(|b| b as u8)).collect()),
+        serde_json::Value::Object(_) => Value::Text(value.to_string()),
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Stats
 // ---------------------------------------------------------------------------
 
-pub fn get_stats(conn: &Connection) -> Result<GameStats> {
-    // Status breakdown
+/// Games being played (status Playing, not archived), the most recently
+/// active first: the later of the last session and the last edit.
+pub fn get_now_playing(conn: &Connection) -> Result<Vec<NowPlaying>> {
     let mut stmt = conn.prepare(
-        "SELECT status, COUNT(*) FROM games GROUP BY status"
+        "SELECT g.id, g.title, g.cover_art_path, g.platform, g.progress_percent, g.playtime_minutes,
+                MAX(g.updated_at, COALESCE((SELECT MAX(s.started_at) FROM play_sessions s WHERE s.game_id = g.id), '')) AS active
+         FROM games g
+         WHERE g.status = 'Playing' AND g.archived = 0
+         ORDER BY active DESC, g.id",
     )?;
+    let games = stmt
+        .query_map([], |row| {
+            Ok(NowPlaying {
+                game_id:          row.get(0)?,
+                title:            row.get(1)?,
+                cover_art_path:   row.get::<_, Option<String>>(2)?.map(images::resolve),
+                platform:         row.get(3)?,
+                progress_percent: row.get(4)?,
+                playtime_minutes: row.get(5)?,
+                last_activity:    row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(games)
+}
+
+/// How many games are in Playing (archived ones aside), not counting `except`.
+pub fn count_playing(conn: &Connection, except: Option<i64>) -> Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM games WHERE status = 'Playing' AND archived = 0 AND (?1 IS NULL OR id != ?1)",
+        params![except],
+        |row| row.get(0),
+    )
+}
+
+/// Stats cover the games in the library's views, so archived games are left
+/// out: every query runs with `games` shadowed by the unarchived ones.
+pub fn get_stats(conn: &Connection) -> Result<GameStats> {
+    // Status breakdown
+    let mut stmt = conn.prepare(&listed("SELECT status, COUNT(*) FROM games GROUP BY status"))?;
     let mut breakdown = StatusBreakdown {
         not_started: 0, playing: 0, completed: 0,
         dropped: 0, backlog: 0, wishlist: 0,
@@ -444,14 +3283,14 @@ pub fn get_stats(conn: &Connection) -> Result<GameStats> {
         }
     }
 
-    let total: i64 = conn.query_row("SELECT COUNT(*) FROM games", [], |r| r.get(0))?;
+    let total: i64 = conn.query_row(&listed("SELECT COUNT(*) FROM games"), [], |r| r.get(0))?;
 
-    let total_playtime: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(playtime_hours), 0.0) FROM games", [], |r| r.get(0)
+    let total_playtime: i64 = conn.query_row(
+        &listed("SELECT COALESCE(SUM(playtime_minutes), 0) FROM games"), [], |r| r.get(0)
     )?;
 
     let avg_rating: Option<f64> = conn.query_row(
-        "SELECT AVG(rating) FROM games WHERE rating IS NOT NULL", [], |r| r.get(0)
+        &listed("SELECT AVG(rating) FROM games WHERE rating IS NOT NULL"), [], |r| r.get(0)
     ).ok().flatten();
 
     // Completion rate = completed / (total - wishlist) * 100
@@ -462,13 +3301,14 @@ pub fn get_stats(conn: &Connection) -> Result<GameStats> {
         0.0
     };
 
-    let games_by_platform = count_by(conn, "SELECT platform, COUNT(*) FROM games GROUP BY platform ORDER BY COUNT(*) DESC")?;
-    let games_by_franchise = count_by(conn, "SELECT franchise, COUNT(*) FROM games WHERE franchise IS NOT NULL GROUP BY franchise ORDER BY COUNT(*) DESC LIMIT 20")?;
+    let games_by_platform = count_by(conn, &listed("SELECT platform, COUNT(*) FROM games GROUP BY platform ORDER BY COUNT(*) DESC"))?;
+    let games_by_franchise = count_by(conn, &listed("SELECT franchise, COUNT(*) FROM games WHERE franchise IS NOT NULL GROUP BY franchise ORDER BY COUNT(*) DESC LIMIT 20"))?;
 
     // Genre counts come from the many-to-many table
-    let mut stmt = conn.prepare(
-        "SELECT genre, COUNT(*) AS cnt FROM game_genres GROUP BY genre ORDER BY cnt DESC LIMIT 20"
-    )?;
+    let mut stmt = conn.prepare(&listed(
+        "SELECT genre, COUNT(*) AS cnt FROM game_genres JOIN games g ON g.id = game_genres.game_id
+         GROUP BY genre ORDER BY cnt DESC LIMIT 20"
+    ))?;
     let games_by_genre = stmt
         .query_map([], |row| {
             Ok(CountEntry { name: row.get(0)?, count: row.get(1)? })
@@ -476,26 +3316,261 @@ pub fn get_stats(conn: &Connection) -> Result<GameStats> {
         .collect::<Result<Vec<_>>>()?;
 
     // 5 most recently completed games
-    let mut stmt = conn.prepare(
-        "SELECT title FROM games WHERE status = 'Completed' ORDER BY updated_at DESC LIMIT 5"
-    )?;
+    let mut stmt = conn.prepare(&listed(
+        "SELECT title FROM games WHERE status = 'Completed' ORDER BY completed_at DESC LIMIT 5"
+    ))?;
     let recent_completions: Vec<String> = stmt
         .query_map([], |row| row.get(0))?
         .collect::<Result<Vec<_>>>()?;
 
+    // Replays: every playthrough after a game's first one
+    let most_replayed = count_by(conn, &listed(
+        "SELECT g.title, COUNT(*) - 1 AS replays FROM playthroughs p JOIN games g ON g.id = p.game_id
+         GROUP BY g.id HAVING replays > 0 ORDER BY replays DESC, g.title LIMIT 10"))?;
+    let new_game_plus_runs: i64 = conn.query_row(
+        &listed("SELECT COUNT(*) FROM playthroughs p JOIN games g ON g.id = p.game_id WHERE p.new_game_plus = 1"),
+        [],
+        |r| r.get(0),
+    )?;
+
+    let challenge_runs = count_by(conn, &listed(
+        "SELECT p.challenge, COUNT(*) FROM playthroughs p JOIN games g ON g.id = p.game_id
+         WHERE p.challenge IS NOT NULL
+         GROUP BY p.challenge COLLATE NOCASE ORDER BY COUNT(*) DESC"))?;
+
+    // Disk usage of installed games, biggest first
+    let (installed_games, install_size): (i64, i64) = conn.query_row(
+        &listed("SELECT COUNT(DISTINCT i.game_id), COALESCE(SUM(i.size_bytes), 0)
+                 FROM game_installs i JOIN games g ON g.id = i.game_id"),
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let mut stmt = conn.prepare(&listed(
+        "SELECT g.id, g.title, g.status, SUM(i.size_bytes) AS size, g.playtime_minutes
+         FROM game_installs i JOIN games g ON g.id = i.game_id
+         WHERE i.size_bytes IS NOT NULL
+         GROUP BY g.id ORDER BY size DESC LIMIT 10"
+    ))?;
+    let largest_installs = stmt
+        .query_map([], |row| {
+            Ok(InstallUsage {
+                game_id:          row.get(0)?,
+                title:            row.get(1)?,
+                status:           GameStatus::from_str(&row.get::<_, String>(2)?),
+                size_bytes:       row.get::<_, i64>(3)? as u64,
+                playtime_minutes: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    // Pinned games lead the dashboard
+    let mut stmt = conn.prepare(&listed("SELECT id FROM games WHERE favorite = 1 ORDER BY title COLLATE NOCASE"))?;
+    let favorite_ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<i64>>>()?;
+    let favorites = fetch_summaries(conn, &favorite_ids)?;
+
+    debug!(total_games = total, "Computed stats");
     Ok(GameStats {
+        favorites,
         total_games: total,
         by_status: breakdown,
-        total_playtime_hours: total_playtime,
+        total_playtime_minutes: total_playtime,
         average_rating: avg_rating,
         completion_rate,
         games_by_platform,
         games_by_genre,
         games_by_franchise,
         recent_completions,
+        most_replayed,
+        new_game_plus_runs,
+        challenge_runs,
+        installed_games,
+        install_size_bytes: install_size as u64,
+        largest_installs,
     })
 }
 
+/// The dashboard's top-10s and superlatives. Ties go to the title first
+/// alphabetically.
+pub fn get_highlights(conn: &Connection) -> Result<Highlights> {
+    let most_played = highlights(conn, &listed(
+        "SELECT id, playtime_minutes FROM games WHERE playtime_minutes > 0
+         ORDER BY playtime_minutes DESC, title COLLATE NOCASE LIMIT 10"
+    ))?;
+
+    // Rating ties go to the game played longest.
+    let mut stmt = conn.prepare(&listed(
+        "SELECT genre, id FROM (
+             SELECT gg.genre, g.id, ROW_NUMBER() OVER (
+                 PARTITION BY gg.genre
+                 ORDER BY g.rating DESC, COALESCE(g.playtime_minutes, 0) DESC, g.title COLLATE NOCASE
+             ) AS place
+             FROM game_genres gg JOIN games g ON g.id = gg.game_id
+             WHERE g.rating IS NOT NULL
+         )
+         WHERE place = 1 ORDER BY genre COLLATE NOCASE"
+    ))?;
+    let best = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    let mut top_rated_by_genre = Vec::with_capacity(best.len());
+    for (genre, id) in best {
+        top_rated_by_genre.extend(fetch_summaries(conn, &[id])?.pop().map(|game| GenreHighlight { genre, game }));
+    }
+
+    // A game's wait starts when it was first bought, or else when it was
+    // added; only the dates count, so time zones don't shift a day.
+    let since = "julianday(substr(COALESCE(
+                     (SELECT MIN(p.purchased_at) FROM purchases p WHERE p.game_id = g.id), g.created_at), 1, 10))";
+    let longest_to_finish = highlights(conn, &listed(&format!(
+        "SELECT id, days FROM (
+             SELECT g.id, g.title, CAST(julianday(substr(g.completed_at, 1, 10)) - {since} AS INTEGER) AS days
+             FROM games g WHERE g.status = 'Completed' AND g.completed_at IS NOT NULL
+         )
+         WHERE days >= 0 ORDER BY days DESC, title COLLATE NOCASE LIMIT 1"
+    )))?.pop();
+    let oldest_backlog = highlights(conn, &listed(&format!(
+        "SELECT g.id, CAST(julianday('now') - {since} AS INTEGER) AS days
+         FROM games g
+         WHERE g.status IN ('Backlog', 'NotStarted') AND COALESCE(g.playtime_minutes, 0) = 0
+           AND NOT EXISTS (SELECT 1 FROM play_sessions s WHERE s.game_id = g.id)
+         ORDER BY days DESC, g.title COLLATE NOCASE LIMIT 1"
+    )))?.pop();
+
+    let shortest_completed = highlights(conn, &listed(
+        "SELECT id, playtime_minutes FROM games WHERE status = 'Completed' AND playtime_minutes > 0
+         ORDER BY playtime_minutes, title COLLATE NOCASE LIMIT 1"
+    ))?.pop();
+
+    Ok(Highlights { most_played, top_rated_by_genre, longest_to_finish, oldest_backlog, shortest_completed })
+}
+
+/// Add one to today's `counter` (today on this computer).
+pub fn count_usage(conn: &Connection, counter: UsageCounter) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO usage_counters (day, kind, count) VALUES (?1, ?2, 1)
+         ON CONFLICT(day, kind) DO UPDATE SET count = count + 1",
+    )?
+    .execute(params![Local::now().date_naive().to_string(), counter.as_str()])?;
+    Ok(())
+}
+
+/// The usage counters in total and for the `weeks` weeks up to the one
+/// `today` is in.
+pub fn get_usage_insights(conn: &Connection, today: NaiveDate, weeks: u32) -> Result<UsageInsights> {
+    let mut insights = conn.query_row(
+        "SELECT MIN(day), COUNT(DISTINCT CASE WHEN kind = 'launch' THEN day END),
+                COALESCE(SUM(CASE WHEN kind = 'launch'     THEN count END), 0),
+                COALESCE(SUM(CASE WHEN kind = 'game_added' THEN count END), 0),
+                COALESCE(SUM(CASE WHEN kind = 'edit'       THEN count END), 0)
+         FROM usage_counters",
+        [],
+        |row| {
+            Ok(UsageInsights {
+                tracking_since: row.get(0)?,
+                days_used:      row.get(1)?,
+                launches:       row.get(2)?,
+                games_added:    row.get(3)?,
+                edits:          row.get(4)?,
+                weeks:          vec![],
+            })
+        },
+    )?;
+
+    let this_week = today - chrono::Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let first_week = this_week - chrono::Duration::weeks(i64::from(weeks.max(1)) - 1);
+    insights.weeks = (0..weeks.max(1))
+        .map(|i| UsageWeek {
+            week_start: (first_week + chrono::Duration::weeks(i64::from(i))).to_string(),
+            ..UsageWeek::default()
+        })
+        .collect();
+    let mut stmt = conn.prepare("SELECT day, kind, count FROM usage_counters WHERE day >= ?1")?;
+    let rows = stmt.query_map(params![first_week.to_string()], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (day, kind, count) = row?;
+        let Ok(day) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") else { continue };
+        let Some(week) = usize::try_from((day - first_week).num_days() / 7).ok().and_then(|i| insights.weeks.get_mut(i)) else {
+            continue;
+        };
+        match kind.as_str() {
+            "launch"     => week.launches += count,
+            "game_added" => week.games_added += count,
+            "edit"       => week.edits += count,
+            _            => {}
+        }
+    }
+    Ok(insights)
+}
+
+/// What was completed, started or added on `date`'s month and day in the
+/// years before it, most recent first. Dates are compared as stored, so a
+/// timestamp counts on its UTC day.
+pub fn get_on_this_day(conn: &Connection, date: NaiveDate) -> Result<Vec<OnThisDay>> {
+    let mut stmt = conn.prepare(&listed(
+        "SELECT game_id, event, day FROM (
+             SELECT id AS game_id, 'Completed' AS event, substr(completed_at, 1, 10) AS day
+             FROM games WHERE status = 'Completed' AND completed_at IS NOT NULL
+             UNION
+             SELECT p.game_id, 'Completed', substr(p.finished_at, 1, 10)
+             FROM playthroughs p JOIN games g ON g.id = p.game_id WHERE p.finished_at IS NOT NULL
+             UNION
+             SELECT p.game_id, 'Started', substr(p.started_at, 1, 10)
+             FROM playthroughs p JOIN games g ON g.id = p.game_id WHERE p.started_at IS NOT NULL
+             UNION
+             SELECT s.game_id, 'Started', substr(MIN(s.started_at), 1, 10)
+             FROM play_sessions s JOIN games g ON g.id = s.game_id GROUP BY s.game_id
+             UNION
+             SELECT id, 'Added', substr(created_at, 1, 10) FROM games
+         )
+         WHERE substr(day, 6, 5) = ?1 AND substr(day, 1, 4) < ?2
+         ORDER BY day DESC, CASE event WHEN 'Completed' THEN 0 WHEN 'Started' THEN 1 ELSE 2 END"
+    ))?;
+    let rows = stmt
+        .query_map(params![date.format("%m-%d").to_string(), date.format("%Y").to_string()], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut days = Vec::with_capacity(rows.len());
+    for (id, event, day) in rows {
+        let event = match event.as_str() {
+            "Completed" => DayEvent::Completed,
+            "Started"   => DayEvent::Started,
+            _           => DayEvent::Added,
+        };
+        let year: i32 = day.get(..4).and_then(|y| y.parse().ok()).unwrap_or(date.year());
+        days.extend(fetch_summaries(conn, &[id])?.pop().map(|game| OnThisDay {
+            game,
+            event,
+            date: day,
+            years_ago: date.year() - year,
+        }));
+    }
+    Ok(days)
+}
+
+/// Run `sql`, which selects (game id, value) pairs, into highlights.
+fn highlights(conn: &Connection, sql: &str) -> Result<Vec<Highlight>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    let mut highlights = Vec::with_capacity(rows.len());
+    for (id, value) in rows {
+        highlights.extend(fetch_summaries(conn, &[id])?.pop().map(|game| Highlight { game, value }));
+    }
+    Ok(highlights)
+}
+
+/// `sql` with `games` meaning just the unarchived games.
+/// RUST NOTE: a WITH clause's name wins over a table of the same name, so the
+/// query itself doesn't change; `main.games` is the real table.
+fn listed(sql: &str) -> String {
+    format!("WITH games AS (SELECT * FROM main.games WHERE archived = 0) {sql}")
+}
+
 fn count_by(conn: &Connection, sql: &str) -> Result<Vec<CountEntry>> {
     let mut stmt = conn.prepare(sql)?;
     let x = stmt.query_map([], |row| {
@@ -505,4 +3580,69 @@ fn count_by(conn: &Connection, sql: &str) -> Result<Vec<CountEntry>> {
         })
     })?
     .collect::<Result<Vec<_>>>(); x
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Each table's columns, and every index.
+    fn schema(conn: &Connection) -> (BTreeMap<String, Vec<String>>, Vec<String>) {
+        let mut tables = BTreeMap::new();
+        for table in library_tables(conn, &[]).unwrap() {
+            let mut stmt = conn.prepare(&format!("SELECT name FROM pragma_table_info('{table}') ORDER BY name")).unwrap();
+            let columns = stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<Vec<String>>>().unwrap();
+            tables.insert(table, columns);
+        }
+        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND name NOT LIKE 'sqlite_%' ORDER BY name").unwrap();
+        let indexes = stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<Vec<String>>>().unwrap();
+        (tables, indexes)
+    }
+
+    fn latest() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn a_new_database_starts_fully_migrated() {
+        let conn = latest();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len() as i64);
+        // Opening it again changes nothing.
+        init_db(&conn).unwrap();
+        assert_eq!(schema(&conn), schema(&latest()));
+    }
+
+    /// A database from before the first migration, with `missing` tables not
+    /// created yet, upgrades to exactly the schema a new one gets.
+    fn upgrade(missing: &[&str]) {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(UNMIGRATED_TABLES).unwrap();
+        for table in missing {
+            conn.execute_batch(&format!("DROP TABLE {table}")).unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO games (id, title, playtime_hours, created_at, updated_at)
+             VALUES (1, 'Hades', 1.5, '2024-01-01', '2024-01-01');",
+        )
+        .unwrap();
+
+        init_db(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len() as i64);
+        assert_eq!(schema(&conn), schema(&latest()));
+        let game = get_game(&conn, 1).unwrap().unwrap();
+        assert_eq!(game.playtime_minutes, Some(90));
+    }
+
+    #[test]
+    fn an_unmigrated_database_upgrades() {
+        upgrade(&[]);
+    }
+
+    #[test]
+    fn an_unmigrated_database_missing_tables_upgrades() {
+        upgrade(&["play_sessions", "playtime_adjustments", "active_session", "playthroughs"]);
+    }
+}
@@ -0,0 +1,72 @@
+// diagnostics.rs — The state of the app in one payload, for the about screen
+// and for pasting into a bug report.
+//
+// Everything here is read-only and cheap: file sizes, a few COUNT(*)s and
+// whether each credential is set. Secrets themselves never appear — a
+// provider is only reported as configured or not — and the proxy is shown
+// without its password (that's a credential too).
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+use crate::cache;
+use crate::credentials;
+use crate::db;
+use crate::error::CmdResult;
+use crate::images;
+use crate::library;
+use crate::models::{BackupStatus, Diagnostics};
+
+/// Gather the diagnostics. The database is only locked for its own part.
+pub fn collect(app: &AppHandle, db: &Mutex<Connection>) -> CmdResult<Diagnostics> {
+    let (settings, schema_version, game_count, webdav, save_backups) = {
+        let conn = db::lock(db)?;
+        (
+            db::get_settings(&conn)?,
+            db::schema_version(&conn)?,
+            db::count_games(&conn)?,
+            db::get_webdav_state(&conn)?,
+            db::get_save_backup_totals(&conn)?,
+        )
+    };
+
+    let db_path = db::get_db_path(app);
+    let images_dir = images::get_images_dir(app)?;
+    let (image_count, images_size_bytes) = images::folder_usage(&images_dir)?;
+    let auto_export_dir = settings.auto_export_dir.clone().filter(|d| !d.trim().is_empty());
+    let backups = BackupStatus {
+        last_snapshot_at:    auto_export_dir.as_deref().and_then(|dir| library::last_snapshot(Path::new(dir))).map(|t| t.to_rfc3339()),
+        auto_export_dir,
+        webdav_url:          settings.webdav_url.clone().filter(|url| !url.trim().is_empty()),
+        last_webdav_sync_at: webdav.synced_at,
+        save_backup_count:   save_backups.0,
+        save_backup_bytes:   save_backups.1,
+        last_save_backup_at: save_backups.2,
+    };
+
+    Ok(Diagnostics {
+        app_version:       env!("CARGO_PKG_VERSION").to_string(),
+        os:                format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        db_size_bytes:     file_size(&db_path) + file_size(&db_path.with_extension("db-wal")),
+        db_path:           db_path.to_string_lossy().into_owned(),
+        schema_version,
+        game_count,
+        images_dir:        images_dir.to_string_lossy().into_owned(),
+        image_count,
+        images_size_bytes,
+        cache:             cache::stats(),
+        backups,
+        metadata_provider: settings.metadata_provider,
+        providers:         credentials::statuses(),
+        proxy:             settings.proxy_url.filter(|url| !url.trim().is_empty()),
+    })
+}
+
+/// Size of the file at `path`; 0 if there isn't one.
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
@@ -0,0 +1,154 @@
+// error.rs — The error type every Tauri command returns.
+//
+// RUST NOTE: Tauri commands must return `Result<T, E>` where E implements
+// `serde::Serialize` so errors can be sent back to JavaScript as JSON.
+// `rusqlite::Error` doesn't implement Serialize, so we wrap it in our own type.
+//
+// Each variant serializes as `{ "code": "NOT_FOUND", "message": "…" }` (plus a
+// `field` for validation errors), so the frontend can branch on `err.code`
+// instead of matching on message text:
+//
+//   try { await invoke("add_game", { input }); }
+//   catch (err) { if (err.code === "VALIDATION_FAILED") highlight(err.field); }
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// RUST NOTE: `thiserror` derives `std::fmt::Display` (and `std::error::Error`)
+/// from the `#[error("…")]` attribute on each variant.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    /// The requested record doesn't exist.
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// Input was rejected before touching the database.
+    #[error("Invalid {field}: {message}")]
+    ValidationFailed { field: String, message: String },
+
+    /// The write clashes with existing data (e.g. a UNIQUE constraint).
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// SQLite couldn't get a lock — retrying usually works.
+    #[error("The database is busy, please try again")]
+    DbBusy,
+
+    /// Any other SQLite failure.
+    #[error("Database error: {0}")]
+    Database(String),
+
+    #[error("IO error: {0}")]
+    Io(String),
+
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// The user cancelled the job doing the work.
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// Something went wrong inside the app itself (poisoned lock, crashed task, …).
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl CommandError {
+    /// Shorthand for building a `ValidationFailed` error.
+    pub fn validation(field: &str, message: impl Into<String>) -> Self {
+        CommandError::ValidationFailed { field: field.to_string(), message: message.into() }
+    }
+
+    /// Stable, machine-readable identifier sent to the frontend.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NotFound(_)              => "NOT_FOUND",
+            CommandError::ValidationFailed { .. }  => "VALIDATION_FAILED",
+            CommandError::Conflict(_)              => "CONFLICT",
+            CommandError::DbBusy                   => "DB_BUSY",
+            CommandError::Database(_)              => "DATABASE",
+            CommandError::Io(_)                    => "IO",
+            CommandError::Http(_)                  => "HTTP",
+            CommandError::Cancelled                => "CANCELLED",
+            CommandError::Internal(_)              => "INTERNAL",
+        }
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let field = match self {
+            CommandError::ValidationFailed { field, .. } => Some(field),
+            _ => None,
+        };
+        let mut s = serializer.serialize_struct("CommandError", if field.is_some() { 3 } else { 2 })?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        if let Some(field) = field {
+            s.serialize_field("field", field)?;
+        }
+        s.end()
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(e: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::NotFound("Record".to_string()),
+            rusqlite::Error::SqliteFailure(err, msg) => match err.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => CommandError::DbBusy,
+                ErrorCode::ConstraintViolation => {
+                    CommandError::Conflict(msg.clone().unwrap_or_else(|| e.to_string()))
+                }
+                _ => CommandError::Database(e.to_string()),
+            },
+            _ => CommandError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        CommandError::Io(e.to_string())
+    }
+}
+
+impl From<csv::Error> for CommandError {
+    fn from(e: csv::Error) -> Self {
+        CommandError::Io(e.to_string())
+    }
+}
+
+impl From<ureq::Error> for CommandError {
+    fn from(e: ureq::Error) -> Self {
+        CommandError::Http(e.to_string())
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for CommandError {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        CommandError::Io(e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for CommandError {
+    fn from(e: zip::result::ZipError) -> Self {
+        CommandError::Io(e.to_string())
+    }
+}
+
+impl From<crate::images::ImageError> for CommandError {
+    fn from(e: crate::images::ImageError) -> Self {
+        use crate::images::ImageError;
+        match e {
+            ImageError::IoError(e)       => CommandError::Io(e.to_string()),
+            ImageError::HttpError(msg)   => CommandError::Http(msg),
+            ImageError::InvalidPath(msg) => CommandError::validation("path", msg),
+            ImageError::Decode(msg)      => CommandError::validation("path", msg),
+            ImageError::Rejected(msg)    => CommandError::validation("path", msg),
+        }
+    }
+}
+
+// Shorthand type alias — `CmdResult<T>` is `Result<T, CommandError>`
+pub type CmdResult<T> = Result<T, CommandError>;
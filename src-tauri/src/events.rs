@@ -0,0 +1,63 @@
+// events.rs — Events pushed from Rust to the frontend.
+//
+// Commands return their result to the window that called them, but other
+// windows (or other views in the same window) also need to know the library
+// changed. After every mutation we broadcast an event that any webview can
+// subscribe to:
+//
+//   const { listen } = window.__TAURI__.event;
+//   await listen("games://changed", (e) => console.log(e.payload.action, e.payload.ids));
+//   await listen("jobs://updated", (e) => showProgress(e.payload.id, e.payload.done, e.payload.total));
+//   await listen("sync://db-changed", (e) => askHowToResolve(e.payload));
+//   await listen("sessions://tick", (e) => showTimer(e.payload.title, e.payload.elapsedSeconds));
+
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+use crate::models::{ActiveSession, ChangeAction, FileSyncStatus, GamesChanged, Job};
+
+/// Emitted after games are added, updated, deleted or imported.
+pub const GAMES_CHANGED: &str = "games://changed";
+
+/// Emitted when a job is queued, starts, reports progress or finishes.
+pub const JOBS_UPDATED: &str = "jobs://updated";
+
+/// Emitted when games.db was replaced on disk while open (sync-safe mode).
+pub const DB_CHANGED: &str = "sync://db-changed";
+
+/// Emitted once a minute while the session timer is open.
+pub const SESSION_TICK: &str = "sessions://tick";
+
+/// Broadcast a `games://changed` event to every window.
+///
+/// A failed emit is only logged — the write itself already succeeded, so the
+/// calling command shouldn't report an error because of it.
+pub fn games_changed(app: &AppHandle, action: ChangeAction, ids: Vec<i64>) {
+    if ids.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(GAMES_CHANGED, GamesChanged { action, ids }) {
+        warn!(error = %e, "Failed to emit {GAMES_CHANGED}");
+    }
+}
+
+/// Broadcast a `jobs://updated` event with the job's current state.
+pub fn job_updated(app: &AppHandle, job: &Job) {
+    if let Err(e) = app.emit(JOBS_UPDATED, job) {
+        warn!(error = %e, "Failed to emit {JOBS_UPDATED}");
+    }
+}
+
+/// Broadcast a `sync://db-changed` event so the user can pick a resolution.
+pub fn db_changed(app: &AppHandle, status: &FileSyncStatus) {
+    if let Err(e) = app.emit(DB_CHANGED, status) {
+        warn!(error = %e, "Failed to emit {DB_CHANGED}");
+    }
+}
+
+/// Broadcast a `sessions://tick` event with the open session's elapsed time.
+pub fn session_tick(app: &AppHandle, session: &ActiveSession) {
+    if let Err(e) = app.emit(SESSION_TICK, session) {
+        warn!(error = %e, "Failed to emit {SESSION_TICK}");
+    }
+}
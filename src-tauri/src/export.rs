@@ -0,0 +1,652 @@
+// export.rs — Turn the library into files people can use outside the app.
+//
+// Each exporter takes the games to write (usually chosen by a SearchFilter)
+// and produces the file contents. The Tauri commands in commands.rs decide
+// which games to pass in and where the output goes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use base64::Engine as _;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::Connection;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::models::{
+    ExportColumn, Game, GameStatus, HtmlCoverMode, HtmlExportOptions, MarkdownGrouping, PlaySession, PlaytimeUnit,
+    SearchFilter, Soundtrack,
+};
+
+/// Separator used when a list field (genres, screenshots) is flattened into one cell.
+const LIST_SEPARATOR: &str = "; ";
+
+/// Load the games an export should contain: everything, or just the search results.
+pub fn select_games(conn: &Connection, filter: Option<SearchFilter>) -> CmdResult<Vec<Game>> {
+    let games = match filter {
+        Some(filter) => db::search_games(conn, filter)?,
+        None => db::get_all_games(conn)?,
+    };
+    Ok(games)
+}
+
+// ---------------------------------------------------------------------------
+// CSV
+// ---------------------------------------------------------------------------
+
+/// Render games as CSV with the given columns (all columns if the list is empty).
+///
+/// Numbers are written unformatted and missing values as empty cells, so
+/// spreadsheets detect the column types on their own.
+pub fn to_csv(games: &[Game], columns: &[ExportColumn]) -> CmdResult<String> {
+    let columns = if columns.is_empty() { &ExportColumn::ALL[..] } else { columns };
+
+    // RUST NOTE: `csv::Writer` handles quoting/escaping of commas, quotes and
+    // newlines inside notes — never build CSV by joining strings by hand.
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns.iter().map(|c| c.header()))?;
+    for game in games {
+        writer.write_record(columns.iter().map(|c| cell(game, *c)))?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| CommandError::Internal(e.to_string()))
+}
+
+/// The text for one column of one game.
+fn cell(game: &Game, column: ExportColumn) -> String {
+    fn opt<T: ToString>(v: &Option<T>) -> String {
+        v.as_ref().map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    match column {
+        ExportColumn::Id                  => game.id.to_string(),
+        ExportColumn::Title               => game.title.clone(),
+        ExportColumn::Franchise           => opt(&game.franchise),
+        ExportColumn::SequenceInFranchise => opt(&game.sequence_in_franchise),
+        ExportColumn::ReleaseDate         => opt(&game.release_date),
+        ExportColumn::Platform            => game.platform.clone(),
+        ExportColumn::Status              => game.status.as_str().to_string(),
+        ExportColumn::ProgressPercent     => opt(&game.progress_percent),
+        ExportColumn::PlaytimeMinutes     => opt(&game.playtime_minutes),
+        ExportColumn::Rating              => opt(&game.rating),
+        ExportColumn::Notes               => opt(&game.notes),
+        ExportColumn::CoverArtPath        => opt(&game.cover_art_path),
+        ExportColumn::Screenshots         => game.screenshots.join(LIST_SEPARATOR),
+        ExportColumn::Developer           => opt(&game.developer),
+        ExportColumn::Publisher           => opt(&game.publisher),
+        ExportColumn::Genres              => game.genres.join(LIST_SEPARATOR),
+        ExportColumn::CreatedAt           => game.created_at.clone(),
+        ExportColumn::UpdatedAt           => game.updated_at.clone(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Backloggd
+// ---------------------------------------------------------------------------
+
+/// Render games as CSV using Backloggd's vocabulary, so the library can be
+/// mirrored there.
+///
+/// Backloggd (like IGDB, where its data comes from) differs from GameTrc in
+/// three ways: a game is Played / Playing / Backlog / Wishlist, and a played
+/// game also has a play type (Completed, Abandoned, …); ratings are 0.5 – 5
+/// stars in half steps; platforms use IGDB's full names.
+pub fn to_backloggd_csv(games: &[Game]) -> CmdResult<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "Name", "Platform", "Status", "Play Type", "Rating", "Hours Played", "Release Date", "Review",
+    ])?;
+    for game in games {
+        let (status, play_type) = backloggd_status(&game.status);
+        writer.write_record([
+            game.title.as_str(),
+            backloggd_platform(&game.platform),
+            status,
+            play_type,
+            &game.rating.map(backloggd_stars).unwrap_or_default(),
+            &game.playtime_minutes.map(|m| format!("{:.1}", m as f64 / 60.0)).unwrap_or_default(),
+            game.release_date.as_deref().unwrap_or_default(),
+            game.notes.as_deref().unwrap_or_default(),
+        ])?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| CommandError::Internal(e.to_string()))
+}
+
+/// GameTrc status → (Backloggd status, play type).
+fn backloggd_status(status: &GameStatus) -> (&'static str, &'static str) {
+    match status {
+        GameStatus::Playing    => ("Playing", ""),
+        GameStatus::Completed  => ("Played", "Completed"),
+        GameStatus::Dropped    => ("Played", "Abandoned"),
+        GameStatus::Backlog    => ("Backlog", ""),
+        GameStatus::NotStarted => ("Backlog", ""),
+        GameStatus::Wishlist   => ("Wishlist", ""),
+    }
+}
+
+/// 1 – 10 → 0.5 – 5 stars, rounded to the nearest half star.
+fn backloggd_stars(rating: f64) -> String {
+    (rating.clamp(1.0, 10.0).round() / 2.0).to_string()
+}
+
+/// Map the short names people type ("PS5", "Switch") to IGDB platform names.
+/// Anything unrecognised is passed through unchanged.
+fn backloggd_platform(platform: &str) -> &str {
+    match platform.trim().to_lowercase().as_str() {
+        "pc" | "windows"                    => "PC (Microsoft Windows)",
+        "mac" | "macos"                     => "Mac",
+        "linux"                             => "Linux",
+        "switch" | "nintendo switch"        => "Nintendo Switch",
+        "ps5" | "playstation 5"             => "PlayStation 5",
+        "ps4" | "playstation 4"             => "PlayStation 4",
+        "ps3" | "playstation 3"             => "PlayStation 3",
+        "ps2" | "playstation 2"             => "PlayStation 2",
+        "ps1" | "psx" | "playstation"       => "PlayStation",
+        "psp"                               => "PlayStation Portable",
+        "vita" | "ps vita"                  => "PlayStation Vita",
+        "xbox series x" | "xbox series x|s" | "xbox series" | "xsx" => "Xbox Series X|S",
+        "xbox one" | "xone"                 => "Xbox One",
+        "xbox 360" | "x360"                 => "Xbox 360",
+        "xbox"                              => "Xbox",
+        "3ds"                               => "Nintendo 3DS",
+        "ds" | "nds"                        => "Nintendo DS",
+        "wii u"                             => "Wii U",
+        "wii"                               => "Wii",
+        "gamecube" | "gcn"                  => "Nintendo GameCube",
+        "n64"                               => "Nintendo 64",
+        "snes"                              => "Super Nintendo Entertainment System",
+        "nes"                               => "Nintendo Entertainment System",
+        "gba"                               => "Game Boy Advance",
+        "ios"                               => "iOS",
+        "android"                           => "Android",
+        _                                   => platform,
+    }
+}
+
+/// Render soundtracks as CSV, one row per soundtrack. Favorite tracks share
+/// one cell, separated like genres in the game CSV.
+pub fn soundtracks_to_csv(soundtracks: &[Soundtrack]) -> CmdResult<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["Game", "Soundtrack", "Owned", "Format", "Favorite Tracks", "Link"])?;
+    for s in soundtracks {
+        writer.write_record([
+            s.game_title.as_str(),
+            s.title.as_str(),
+            if s.owned { "Yes" } else { "No" },
+            s.format.map_or("", |f| f.as_str()),
+            &s.favorite_tracks.join(LIST_SEPARATOR),
+            s.link.as_deref().unwrap_or_default(),
+        ])?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| CommandError::Internal(e.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Markdown
+// ---------------------------------------------------------------------------
+
+/// Render games as a Markdown document with one `##` section per group.
+///
+/// The YAML front matter makes the file a proper note in Obsidian and similar
+/// tools; each game gets a `###` heading so it shows up in the outline.
+pub fn to_markdown(games: &[Game], group_by: MarkdownGrouping, unit: PlaytimeUnit) -> String {
+    let mut out = String::new();
+    let today = Utc::now().format("%Y-%m-%d");
+
+    // RUST NOTE: `writeln!` into a String can't fail, so the Results are ignored.
+    let _ = writeln!(out, "---\nexported: {today}\ngames: {}\n---\n", games.len());
+    let _ = writeln!(out, "# Game Library\n");
+
+    for (heading, members) in group(games, group_by) {
+        let _ = writeln!(out, "## {heading} ({})\n", members.len());
+        for game in members {
+            write_game(&mut out, game, group_by, unit);
+        }
+    }
+    out
+}
+
+/// Split games into ordered (heading, games) sections.
+fn group(games: &[Game], group_by: MarkdownGrouping) -> Vec<(String, Vec<&Game>)> {
+    match group_by {
+        MarkdownGrouping::Status => GameStatus::DISPLAY_ORDER
+            .iter()
+            .map(|status| {
+                let members: Vec<&Game> = games.iter().filter(|g| g.status == *status).collect();
+                (status.label().to_string(), members)
+            })
+            .filter(|(_, members)| !members.is_empty())
+            .collect(),
+        MarkdownGrouping::Franchise => {
+            // BTreeMap keeps franchises alphabetical; standalone games go last.
+            let mut by_franchise: BTreeMap<&str, Vec<&Game>> = BTreeMap::new();
+            let mut standalone: Vec<&Game> = Vec::new();
+            for game in games {
+                match game.franchise.as_deref().filter(|f| !f.trim().is_empty()) {
+                    Some(f) => by_franchise.entry(f).or_default().push(game),
+                    None => standalone.push(game),
+                }
+            }
+            let mut sections: Vec<(String, Vec<&Game>)> = by_franchise
+                .into_iter()
+                .map(|(name, mut members)| {
+                    members.sort_by_key(|g| (g.sequence_in_franchise.unwrap_or(i32::MAX), g.title.to_lowercase()));
+                    (name.to_string(), members)
+                })
+                .collect();
+            if !standalone.is_empty() {
+                sections.push(("Standalone".to_string(), standalone));
+            }
+            sections
+        }
+    }
+}
+
+fn write_game(out: &mut String, game: &Game, group_by: MarkdownGrouping, unit: PlaytimeUnit) {
+    let _ = writeln!(out, "### {}\n", game.title);
+
+    let mut facts = vec![format!("**Platform:** {}", game.platform)];
+    // The section heading already says the status or franchise — don't repeat it.
+    if group_by != MarkdownGrouping::Status {
+        facts.push(format!("**Status:** {}", game.status.label()));
+    }
+    if let Some(rating) = game.rating {
+        facts.push(format!("**Rating:** {rating}/10"));
+    }
+    if let Some(minutes) = game.playtime_minutes {
+        facts.push(format!("**Playtime:** {}", unit.format(minutes)));
+    }
+    if let Some(progress) = game.progress_percent {
+        facts.push(format!("**Progress:** {progress}%"));
+    }
+    let _ = writeln!(out, "- {}", facts.join(" · "));
+
+    if group_by != MarkdownGrouping::Franchise {
+        if let Some(franchise) = &game.franchise {
+            let _ = writeln!(out, "- **Franchise:** {franchise}");
+        }
+    }
+    if let Some(date) = &game.release_date {
+        let _ = writeln!(out, "- **Released:** {date}");
+    }
+    if !game.genres.is_empty() {
+        let _ = writeln!(out, "- **Genres:** {}", game.genres.join(", "));
+    }
+
+    if let Some(notes) = game.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        out.push('\n');
+        for line in notes.lines() {
+            let _ = writeln!(out, "> {line}");
+        }
+    }
+    out.push('\n');
+}
+
+// ---------------------------------------------------------------------------
+// Excel
+// ---------------------------------------------------------------------------
+
+/// Write an .xlsx workbook with "Games", "Sessions" and "Stats" sheets.
+///
+/// Unlike CSV, cells are typed: dates are real Excel dates and numbers are
+/// numbers, so pivot tables and formulas work without any cleanup. Sessions
+/// of games not in `games` are skipped, and the stats are computed from
+/// `games`, so every sheet describes the same selection.
+pub fn write_xlsx(path: &Path, games: &[Game], sessions: &[PlaySession]) -> CmdResult<()> {
+    let mut workbook = Workbook::new();
+    games_sheet(workbook.add_worksheet(), games)?;
+    sessions_sheet(workbook.add_worksheet(), games, sessions)?;
+    stats_sheet(workbook.add_worksheet(), games)?;
+    workbook.save(path)?;
+    Ok(())
+}
+
+fn games_sheet(sheet: &mut Worksheet, games: &[Game]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+
+    sheet.set_name("Games")?;
+    for (col, column) in ExportColumn::ALL.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, column.header(), &bold)?;
+    }
+
+    for (i, game) in games.iter().enumerate() {
+        let row = i as u32 + 1;
+        for (col, column) in ExportColumn::ALL.iter().enumerate() {
+            let col = col as u16;
+            // Empty values stay blank cells rather than "" or 0.
+            match column {
+                ExportColumn::Id => {
+                    sheet.write_number(row, col, game.id as f64)?;
+                }
+                ExportColumn::SequenceInFranchise => {
+                    if let Some(n) = game.sequence_in_franchise {
+                        sheet.write_number(row, col, n)?;
+                    }
+                }
+                ExportColumn::ProgressPercent | ExportColumn::PlaytimeMinutes | ExportColumn::Rating => {
+                    let value = match column {
+                        ExportColumn::ProgressPercent => game.progress_percent,
+                        ExportColumn::PlaytimeMinutes => game.playtime_minutes.map(|m| m as f64),
+                        _                             => game.rating,
+                    };
+                    if let Some(n) = value {
+                        sheet.write_number(row, col, n)?;
+                    }
+                }
+                ExportColumn::ReleaseDate => {
+                    let parsed = game
+                        .release_date
+                        .as_deref()
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                    match (parsed, &game.release_date) {
+                        (Some(d), _)    => { sheet.write_datetime_with_format(row, col, d, &date)?; }
+                        (None, Some(s)) => { sheet.write_string(row, col, s)?; }
+                        (None, None)    => {}
+                    }
+                }
+                ExportColumn::CreatedAt | ExportColumn::UpdatedAt => {
+                    let text = if *column == ExportColumn::CreatedAt { &game.created_at } else { &game.updated_at };
+                    match DateTime::parse_from_rfc3339(text) {
+                        Ok(t)  => { sheet.write_datetime_with_format(row, col, t.naive_utc(), &datetime)?; }
+                        Err(_) => { sheet.write_string(row, col, text)?; }
+                    }
+                }
+                _ => {
+                    let text = cell(game, *column);
+                    if !text.is_empty() {
+                        sheet.write_string(row, col, &text)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if !games.is_empty() {
+        sheet.autofilter(0, 0, games.len() as u32, ExportColumn::ALL.len() as u16 - 1)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn sessions_sheet(sheet: &mut Worksheet, games: &[Game], sessions: &[PlaySession]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+    let hours = Format::new().set_num_format("0.00");
+
+    sheet.set_name("Sessions")?;
+    for (col, header) in ["game_id", "title", "started_at", "minutes", "hours", "source", "game_version"].iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, &bold)?;
+    }
+
+    let titles: HashMap<i64, &str> = games.iter().map(|g| (g.id, g.title.as_str())).collect();
+    let mut row = 0;
+    for session in sessions {
+        let Some(title) = titles.get(&session.game_id) else { continue };
+        row += 1;
+        sheet.write_number(row, 0, session.game_id as f64)?;
+        sheet.write_string(row, 1, *title)?;
+        match DateTime::parse_from_rfc3339(&session.started_at) {
+            Ok(t)  => { sheet.write_datetime_with_format(row, 2, t.naive_utc(), &datetime)?; }
+            Err(_) => { sheet.write_string(row, 2, &session.started_at)?; }
+        }
+        sheet.write_number(row, 3, session.duration_minutes as f64)?;
+        sheet.write_number_with_format(row, 4, session.duration_minutes as f64 / 60.0, &hours)?;
+        sheet.write_string(row, 5, session.source.as_str())?;
+        if let Some(version) = &session.game_version {
+            sheet.write_string(row, 6, version)?;
+        }
+    }
+
+    if row > 0 {
+        sheet.autofilter(0, 0, row, 6)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn stats_sheet(sheet: &mut Worksheet, games: &[Game]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let one_decimal = Format::new().set_num_format("0.0");
+    let percent = Format::new().set_num_format("0.0%");
+
+    sheet.set_name("Stats")?;
+
+    // Summary block
+    let rated: Vec<f64> = games.iter().filter_map(|g| g.rating).collect();
+    let counted = games.iter().filter(|g| g.status != GameStatus::Wishlist).count();
+    let completed = games.iter().filter(|g| g.status == GameStatus::Completed).count();
+
+    sheet.write_string_with_format(0, 0, "Summary", &bold)?;
+    sheet.write_string(1, 0, "Games")?;
+    sheet.write_number(1, 1, games.len() as f64)?;
+    sheet.write_string(2, 0, "Total playtime (h)")?;
+    let total_minutes: i64 = games.iter().filter_map(|g| g.playtime_minutes).sum();
+    sheet.write_number_with_format(2, 1, total_minutes as f64 / 60.0, &one_decimal)?;
+    sheet.write_string(3, 0, "Average rating")?;
+    if !rated.is_empty() {
+        sheet.write_number_with_format(3, 1, rated.iter().sum::<f64>() / rated.len() as f64, &one_decimal)?;
+    }
+    sheet.write_string(4, 0, "Completion rate")?;
+    if counted > 0 {
+        sheet.write_number_with_format(4, 1, completed as f64 / counted as f64, &percent)?;
+    }
+
+    // Breakdown tables, side by side: Status | Platform | Genre | Franchise
+    let by_status: Vec<(String, usize)> = GameStatus::DISPLAY_ORDER
+        .iter()
+        .map(|s| (s.label().to_string(), games.iter().filter(|g| g.status == *s).count()))
+        .collect();
+    let by_platform = tally(games.iter().map(|g| g.platform.as_str()));
+    let by_genre = tally(games.iter().flat_map(|g| g.genres.iter().map(String::as_str)));
+    let by_franchise = tally(games.iter().filter_map(|g| g.franchise.as_deref()));
+
+    let tables = [("Status", by_status), ("Platform", by_platform), ("Genre", by_genre), ("Franchise", by_franchise)];
+    for (i, (name, counts)) in tables.iter().enumerate() {
+        let col = i as u16 * 3;
+        sheet.write_string_with_format(6, col, *name, &bold)?;
+        sheet.write_string_with_format(6, col + 1, "Games", &bold)?;
+        for (j, (label, count)) in counts.iter().enumerate() {
+            sheet.write_string(7 + j as u32, col, label)?;
+            sheet.write_number(7 + j as u32, col + 1, *count as f64)?;
+        }
+    }
+
+    sheet.autofit();
+    Ok(())
+}
+
+/// Count occurrences, most common first (ties alphabetical). Blank names are skipped.
+fn tally<'a>(names: impl Iterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for name in names.filter(|n| !n.trim().is_empty()) {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().map(|(n, c)| (n.to_string(), c)).collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+/// Write a standalone HTML page (plus a covers folder in `Link` mode) to `path`.
+pub fn write_html(path: &Path, games: &[Game], options: &HtmlExportOptions, unit: PlaytimeUnit) -> CmdResult<()> {
+    let covers = match options.covers {
+        HtmlCoverMode::Embed => embed_covers(games),
+        HtmlCoverMode::Link  => link_covers(path, games)?,
+        HtmlCoverMode::None  => HashMap::new(),
+    };
+    fs::write(path, render_html(games, options, &covers, unit))?;
+    Ok(())
+}
+
+/// game id → data: URI. Covers that can't be read are skipped, not fatal.
+fn embed_covers(games: &[Game]) -> HashMap<i64, String> {
+    games
+        .iter()
+        .filter_map(|g| {
+            let cover = g.cover_art_path.as_deref()?;
+            let bytes = fs::read(cover).ok()?;
+            let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+            Some((g.id, format!("data:{};base64,{data}", mime_for(cover))))
+        })
+        .collect()
+}
+
+/// Copy covers to `<page name>_covers/` and return game id → relative URL.
+fn link_covers(page: &Path, games: &[Game]) -> CmdResult<HashMap<i64, String>> {
+    let stem = page.file_stem().and_then(|s| s.to_str()).unwrap_or("library");
+    let dir_name = format!("{stem}_covers");
+    let dir = page.with_file_name(&dir_name);
+    fs::create_dir_all(&dir)?;
+
+    let mut links = HashMap::new();
+    for game in games {
+        let Some(cover) = game.cover_art_path.as_deref() else { continue };
+        let Some(file_name) = Path::new(cover).file_name().and_then(|n| n.to_str()) else { continue };
+        if fs::copy(cover, dir.join(file_name)).is_ok() {
+            links.insert(game.id, format!("{dir_name}/{file_name}"));
+        }
+    }
+    Ok(links)
+}
+
+fn mime_for(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("png")  => "image/png",
+        Some("gif")  => "image/gif",
+        Some("webp") => "image/webp",
+        _            => "image/jpeg",
+    }
+}
+
+/// Render the page. `covers` maps game id → image URL (data: URI or relative path).
+pub fn render_html(games: &[Game], options: &HtmlExportOptions, covers: &HashMap<i64, String>, unit: PlaytimeUnit) -> String {
+    let mut cards = String::new();
+    for game in games {
+        let cover = match covers.get(&game.id) {
+            Some(src) => format!(r#"<img src="{}" alt="" loading="lazy">"#, escape(src)),
+            None => format!(r#"<div class="placeholder">{}</div>"#, escape(&initials(&game.title))),
+        };
+
+        let mut meta = vec![escape(&game.platform)];
+        if let Some(rating) = game.rating {
+            meta.push(format!("★ {rating}"));
+        }
+        if let Some(minutes) = game.playtime_minutes {
+            meta.push(unit.format(minutes));
+        }
+
+        let genres = if game.genres.is_empty() {
+            String::new()
+        } else {
+            format!(r#"<div class="genres">{}</div>"#, escape(&game.genres.join(" · ")))
+        };
+        let notes = match game.notes.as_deref().filter(|n| options.include_notes && !n.trim().is_empty()) {
+            Some(n) => format!(r#"<p class="notes">{}</p>"#, escape(n)),
+            None => String::new(),
+        };
+
+        let _ = writeln!(
+            cards,
+            r#"<article class="card" data-search="{search}"><div class="cover">{cover}</div><div class="body"><span class="status s-{status}">{label}</span><h2>{title}</h2><div class="meta">{meta}</div>{genres}{notes}</div></article>"#,
+            search = escape(&format!("{} {} {}", game.title, game.platform, game.genres.join(" ")).to_lowercase()),
+            status = game.status.as_str(),
+            label = game.status.label(),
+            title = escape(&game.title),
+            meta = meta.join(" · "),
+        );
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="GameTrc">
+<title>{title}</title>
+<style>{HTML_CSS}</style>
+</head>
+<body>
+<header><h1>{title}</h1><p>{count} games · exported {date}</p><input id="q" type="search" placeholder="Filter…"></header>
+<main id="grid">
+{cards}</main>
+<script>{HTML_JS}</script>
+</body>
+</html>
+"#,
+        title = escape(&options.title),
+        count = games.len(),
+        date = Utc::now().format("%Y-%m-%d"),
+    )
+}
+
+/// Escape text for use in HTML element content and quoted attributes.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&'  => out.push_str("&amp;"),
+            '<'  => out.push_str("&lt;"),
+            '>'  => out.push_str("&gt;"),
+            '"'  => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c    => out.push(c),
+        }
+    }
+    out
+}
+
+/// "The Legend of Zelda" → "TL" — shown when a game has no cover.
+fn initials(title: &str) -> String {
+    title.split_whitespace().filter_map(|w| w.chars().next()).take(2).collect::<String>().to_uppercase()
+}
+
+/// Same dark palette as the app (see src/styles.css).
+const HTML_CSS: &str = "
+*{box-sizing:border-box;margin:0;padding:0}
+body{background:#0d0f14;color:#e8eaf0;font-family:system-ui,-apple-system,'Segoe UI',sans-serif;padding:32px}
+header{display:flex;flex-wrap:wrap;align-items:baseline;gap:8px 16px;margin-bottom:24px}
+h1{font-size:28px}header p{color:#9098ab;flex:1}
+#q{background:#1a1e28;border:1px solid #ffffff18;border-radius:6px;color:inherit;padding:8px 12px;min-width:220px}
+#grid{display:grid;grid-template-columns:repeat(auto-fill,minmax(180px,1fr));gap:16px}
+.card{background:#13161d;border:1px solid #ffffff0f;border-radius:10px;overflow:hidden}
+.cover{aspect-ratio:3/4;background:#1a1e28}
+.cover img{width:100%;height:100%;object-fit:cover;display:block}
+.placeholder{height:100%;display:flex;align-items:center;justify-content:center;font-size:40px;color:#5a6170}
+.body{padding:12px}h2{font-size:15px;margin:6px 0 4px}
+.meta,.genres{color:#9098ab;font-size:12px}.genres{margin-top:4px}
+.notes{font-size:12px;margin-top:8px;white-space:pre-wrap;color:#e8eaf0cc}
+.status{font-size:10px;text-transform:uppercase;letter-spacing:.05em;padding:2px 6px;border-radius:4px;background:#ffffff0f}
+.s-Playing{color:#3b82f6}.s-Completed{color:#22c55e}.s-Dropped{color:#ef4444}
+.s-Backlog{color:#f59e0b}.s-Wishlist{color:#a855f7}.s-NotStarted{color:#9098ab}
+";
+
+/// Tiny client-side filter so friends can search the page.
+const HTML_JS: &str = "
+document.getElementById('q').addEventListener('input',function(e){
+  var q=e.target.value.trim().toLowerCase();
+  document.querySelectorAll('.card').forEach(function(c){c.style.display=c.dataset.search.indexOf(q)<0?'none':''});
+});
+";
@@ -0,0 +1,566 @@
+// filesync.rs — Sync-safe mode for a games.db kept in a cloud-drive folder.
+//
+// Dropbox, Google Drive, OneDrive and Syncthing sync files, not databases.
+// Two things go wrong when games.db lives in one of their folders:
+//
+//   1. Both devices edit offline → the client keeps one file and saves the
+//      other next to it as "games (Sam's conflicted copy 2024-05-01).db".
+//   2. The client replaces games.db while the app has it open → the app
+//      keeps writing to the old file, and those edits vanish.
+//
+// With the `sync_safe` setting on, every app that opens the file bumps a
+// generation counter in it and signs it with its device id (`db_generation`).
+// On open we note whether another device used the file since this one last
+// did and look for conflicted copies; while running, a watcher compares the
+// claim our connection sees with the one in the file on disk. Any difference
+// means case 2, and the frontend is told (`sync://db-changed`) so the user
+// can reload, merge or keep their version — never a silent overwrite.
+// Conflicted copies are merged in with `merge_database`.
+//
+// A merge goes field by field. Every edit is stamped with its device and time
+// (db.rs, "Change tracking"), so for a field edited on both sides the later
+// edit wins, rather than one whole game overwriting the other. Where this
+// library kept a value the other copy had differently, the game gets a row in
+// `sync_conflicts` and the user can still pick per game (`resolve_sync_conflict`).
+//
+// Deletes are stamped too, in a tombstone per deleted game. A game deleted on
+// one side stays deleted unless the other side edited it afterwards.
+//
+// Point the app at a database in a synced folder with the GAMETRC_DB
+// environment variable (the CLI reads the same one).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::db::{self, ImportOutcome};
+use crate::error::{CmdResult, CommandError};
+use crate::events;
+use crate::import;
+use crate::models::{
+    ConflictResolution, ConflictStrategy, FieldConflict, FileSyncResolution, FileSyncStatus, Game, GameInput, MergeReport,
+    RecordVersion,
+};
+
+/// How often the watcher compares the open database with the file on disk.
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What this device remembers about the file between runs. Kept outside the
+/// synced folder, in app_local_data_dir.
+#[derive(Serialize, Deserialize, Default)]
+struct LocalState {
+    db_path:    String,
+    generation: i64,   // the claim this device made last time it opened db_path
+}
+
+pub struct FileSync {
+    db_path:   PathBuf,
+    state_dir: PathBuf,
+    status:    Mutex<FileSyncStatus>,
+}
+
+impl FileSync {
+    /// Check the freshly opened database and, in sync-safe mode, claim it.
+    /// `state_dir` is a folder that isn't synced between devices. Problems
+    /// are logged; they shouldn't keep the app from starting.
+    pub fn open(conn: &Connection, db_path: PathBuf, state_dir: PathBuf) -> Arc<FileSync> {
+        let mut status = FileSyncStatus {
+            db_path: db_path.to_string_lossy().into_owned(),
+            device:  device_id(&state_dir).unwrap_or_else(|e| {
+                warn!(error = %e, "Couldn't save this device's id");
+                Uuid::new_v4().to_string()
+            }),
+            ..FileSyncStatus::default()
+        };
+        if let Err(e) = claim_on_open(conn, &db_path, &state_dir, &mut status) {
+            warn!(error = %e, "Couldn't check the database file for sync");
+        }
+        Arc::new(FileSync { db_path, state_dir, status: Mutex::new(status) })
+    }
+
+    pub fn status(&self) -> CmdResult<FileSyncStatus> {
+        let mut status = lock(&self.status)?.clone();
+        if status.enabled {
+            status.conflicted_copies = conflicted_copies(&self.db_path);
+        }
+        Ok(status)
+    }
+
+    /// Settle a file that changed on disk while open (see FileSyncResolution).
+    /// `strategy` decides how a Merge treats games both sides have.
+    pub fn resolve(
+        &self,
+        db: &Mutex<Connection>,
+        resolution: FileSyncResolution,
+        strategy: ConflictStrategy,
+    ) -> CmdResult<MergeReport> {
+        let mut conn = db::lock(db)?;
+        let mut report = MergeReport::default();
+        match resolution {
+            FileSyncResolution::Reload => *conn = self.reopen()?,
+            FileSyncResolution::Merge => {
+                let mine = std::mem::replace(&mut *conn, self.reopen()?);
+                report = merge(&conn, &mine, strategy, "Edits made while the file changed on disk")?;
+            }
+            FileSyncResolution::KeepMine => {
+                // Write a copy next to the file, then swap it in: the cloud
+                // client sees one complete new file rather than partial writes.
+                let copy = self.db_path.with_extension("db.keep");
+                let _ = fs::remove_file(&copy);
+                conn.execute("VACUUM INTO ?1", [copy.to_string_lossy()])?;
+                // Windows won't replace a file that's still open.
+                drop(std::mem::replace(&mut *conn, Connection::open_in_memory()?));
+                fs::rename(&copy, &self.db_path)?;
+                *conn = self.reopen()?;
+            }
+        }
+
+        let mut status = lock(&self.status)?;
+        status.generation = db::claim_db_generation(&conn, &status.device)?;
+        status.changed_on_disk = false;
+        write_local_state(&self.state_dir, &LocalState { db_path: status.db_path.clone(), generation: status.generation.generation });
+        info!(?resolution, generation = status.generation.generation, "Resolved database change on disk");
+        Ok(report)
+    }
+
+    /// Compare the claim our connection sees with the one in the file on
+    /// disk; true the first time they differ.
+    fn check(&self, db: &Mutex<Connection>) -> CmdResult<bool> {
+        if lock(&self.status)?.changed_on_disk {
+            return Ok(false);
+        }
+        let ours = db::get_db_generation(&*db::lock(db)?)?;
+        let on_disk = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|disk| db::get_db_generation(&disk));
+        // The file may be missing for a moment while the client swaps it.
+        let Ok(on_disk) = on_disk else { return Ok(false) };
+        if on_disk == ours {
+            return Ok(false);
+        }
+        warn!(ours = ours.generation, on_disk = on_disk.generation, device = ?on_disk.device, "Database changed on disk while open");
+        lock(&self.status)?.changed_on_disk = true;
+        Ok(true)
+    }
+
+    fn reopen(&self) -> CmdResult<Connection> {
+        let conn = Connection::open(&self.db_path)?;
+        db::init_db(&conn)?;
+        keep_in_one_file(&conn)?;
+        Ok(conn)
+    }
+}
+
+fn claim_on_open(conn: &Connection, db_path: &Path, state_dir: &Path, status: &mut FileSyncStatus) -> CmdResult<()> {
+    status.enabled = db::get_settings(conn)?.sync_safe;
+    if !status.enabled {
+        return Ok(());
+    }
+    keep_in_one_file(conn)?;
+    let found = db::get_db_generation(conn)?;
+    let local = read_local_state(state_dir).filter(|s| s.db_path == status.db_path);
+    status.opened_elsewhere = local.is_some_and(|l| found.generation > l.generation);
+    status.conflicted_copies = conflicted_copies(db_path);
+    status.generation = db::claim_db_generation(conn, &status.device)?;
+    write_local_state(state_dir, &LocalState { db_path: status.db_path.clone(), generation: status.generation.generation });
+    info!(
+        generation = status.generation.generation,
+        opened_elsewhere = status.opened_elsewhere,
+        conflicted_copies = status.conflicted_copies.len(),
+        "Opened database in sync-safe mode"
+    );
+    Ok(())
+}
+
+/// WAL mode keeps recent writes in games.db-wal, which a cloud client syncs
+/// separately (or not at all); the rollback journal keeps everything in
+/// games.db once each write is done.
+fn keep_in_one_file(conn: &Connection) -> CmdResult<()> {
+    conn.query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// In sync-safe mode, check the file on disk every `WATCH_INTERVAL` and emit
+/// `sync://db-changed` (with the status) when it changed underneath the app.
+pub fn spawn_watcher(app: AppHandle, db: Arc<Mutex<Connection>>, sync: Arc<FileSync>) {
+    if !sync.status.lock().is_ok_and(|s| s.enabled) {
+        return;
+    }
+    thread::spawn(move || loop {
+        thread::sleep(WATCH_INTERVAL);
+        match sync.check(&db) {
+            Ok(false) => {}
+            Ok(true) => {
+                if let Ok(status) = sync.status() {
+                    events::db_changed(&app, &status);
+                }
+            }
+            Err(e) => warn!(error = %e, "Couldn't check the database file"),
+        }
+    });
+}
+
+/// Merge the games of the database at `path` (e.g. a conflicted copy) into
+/// `conn`, matching games by title. The file itself isn't changed.
+pub fn merge_file(conn: &Connection, path: &Path, strategy: ConflictStrategy) -> CmdResult<MergeReport> {
+    if !path.is_file() {
+        return Err(CommandError::validation("path", "No such database file"));
+    }
+    // Work on a copy brought up to the current schema: the file may come
+    // from an older version, and migrating it in place would change it.
+    let copy = std::env::temp_dir().join(format!("gametrc-merge-{}.db", Uuid::new_v4()));
+    fs::copy(path, &copy)?;
+    let result = Connection::open(&copy)
+        .map_err(CommandError::from)
+        .and_then(|other| {
+            db::init_db(&other)?;
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            merge(conn, &other, strategy, &name)
+        });
+    let _ = fs::remove_file(&copy);
+    result
+}
+
+/// Import every game in `from` into `into`, plus the external ids the
+/// matching game doesn't have yet. A game matched by title is merged field by
+/// field (see merge_game); where it kept values that differ from `from`'s, a
+/// sync conflict is recorded under `source` for the user to settle. Deletes
+/// win over edits made before them, on either side.
+fn merge(into: &Connection, from: &Connection, strategy: ConflictStrategy, source: &str) -> CmdResult<MergeReport> {
+    let games = db::get_all_games(from)?;
+    let mut report = MergeReport::default();
+    let tx = into.unchecked_transaction()?;
+    for game in &games {
+        let remote = import::game_to_input(game);
+        let remote_versions = db::get_field_versions(from, game.id)?;
+        let matched = match strategy {
+            ConflictStrategy::Duplicate => None,
+            _ => db::find_game_by_title(&tx, &remote.title)?,
+        };
+        if matched.is_none() && deleted_since(&tx, &remote.title, db::get_record_version(from, game.id)?)? {
+            debug!(title = %remote.title, "Skipped a game deleted here since it was last edited");
+            report.unchanged += 1;
+            continue;
+        }
+        let (id, outcome) = match matched {
+            Some(id) => (id, merge_game(&tx, id, game, &remote, &remote_versions, strategy)?),
+            None => {
+                let (id, outcome) = db::import_game(&tx, remote.clone(), None, strategy)?;
+                for name in import::FLAGS {
+                    db::write_merged_flag(&tx, id, name, import::flag(game, name))?;
+                }
+                db::set_field_versions(&tx, id, &remote_versions)?;
+                (id, outcome)
+            }
+        };
+        match outcome {
+            ImportOutcome::Created   => report.created_ids.push(id),
+            ImportOutcome::Updated   => report.updated_ids.push(id),
+            ImportOutcome::Unchanged => report.unchanged += 1,
+        }
+        if outcome != ImportOutcome::Created {
+            let local = db::get_game(&tx, id)?.ok_or_else(|| CommandError::NotFound(format!("Game {id}")))?;
+            let fields = import::conflicting_fields(&import::game_to_input(&local), &remote);
+            if !fields.is_empty() {
+                report.conflict_ids.push(db::add_sync_conflict(&tx, id, source, &fields)?);
+            }
+        }
+        for link in db::get_external_ids(from, Some(game.id), None)? {
+            if db::get_external_ids(&tx, Some(id), Some(&link.source))?.is_empty() {
+                db::link_external_id(&tx, id, &link.source, &link.external_id)?;
+            }
+        }
+    }
+
+    for (title, deleted) in db::get_tombstones(from)? {
+        // `from` deleted the game, then got it back.
+        if db::find_game_by_title(from, &title)?.is_some() {
+            continue;
+        }
+        if let Some(id) = db::find_game_by_title(&tx, &title)? {
+            if db::get_record_version(&tx, id)?.is_none_or(|edited| deleted.is_newer_than(&edited)) {
+                db::delete_game_with(&tx, id, &deleted)?;
+                report.deleted_ids.push(id);
+            }
+        }
+        db::set_tombstone(&tx, &title, &deleted)?;
+    }
+    tx.commit()?;
+    info!(
+        games = games.len(),
+        created = report.created_ids.len(),
+        updated = report.updated_ids.len(),
+        deleted = report.deleted_ids.len(),
+        conflicts = report.conflict_ids.len(),
+        "Merged database"
+    );
+    Ok(report)
+}
+
+/// Whether `conn` deleted a game titled `title` after `edited`, the last edit
+/// of that game in the other copy.
+fn deleted_since(conn: &Connection, title: &str, edited: Option<RecordVersion>) -> CmdResult<bool> {
+    let Some(deleted) = db::get_tombstone(conn, title)? else { return Ok(false) };
+    Ok(edited.is_none_or(|edited| deleted.is_newer_than(&edited)))
+}
+
+/// Merge `remote` (`remote_game` as GameInput) into library game `id`, last
+/// writer wins per field and flag (import::FLAGS): a field only one side
+/// edited since change tracking began takes that side's value, one both
+/// edited takes the later edit, and one neither did is left to `strategy`. Fields taken from `remote` keep its edit stamps; the merge
+/// itself isn't stamped as an edit on this device.
+fn merge_game(
+    conn: &Connection,
+    id: i64,
+    remote_game: &Game,
+    remote: &GameInput,
+    remote_versions: &HashMap<String, RecordVersion>,
+    strategy: ConflictStrategy,
+) -> CmdResult<ImportOutcome> {
+    let game = db::get_game(conn, id)?.ok_or_else(|| CommandError::NotFound(format!("Game {id}")))?;
+    let local = import::game_to_input(&game);
+    let local_versions = db::get_field_versions(conn, id)?;
+
+    let mut fields = import::changed_fields(&local, remote);
+    fields.extend(import::changed_flags(&game, remote_game));
+    let (mut take, mut keep) = (vec![], vec![]);
+    for field in fields {
+        let remote_wins = match (local_versions.get(&field), remote_versions.get(&field)) {
+            (None, None) => continue,
+            (Some(local), Some(remote)) => remote.is_newer_than(local),
+            (local, _) => local.is_none(),
+        };
+        if remote_wins { take.push(field) } else { keep.push(field) }
+    }
+    let merged = import::merge_input(&game, remote.clone(), strategy).unwrap_or_else(|| local.clone());
+    let merged = import::copy_fields(&merged, remote, &take)?;
+    let merged = import::copy_fields(&merged, &local, &keep)?;
+    let flags: Vec<&String> = take.iter().filter(|field| import::FLAGS.contains(&field.as_str())).collect();
+    if merged == local && flags.is_empty() {
+        return Ok(ImportOutcome::Unchanged);
+    }
+
+    let changed = import::changed_fields(&local, &merged);
+    let taken = take.iter().filter(|field| changed.contains(field) || flags.contains(field));
+    if merged != local {
+        db::write_merged_game(conn, id, merged)?;
+    }
+    for name in &flags {
+        db::write_merged_flag(conn, id, name, import::flag(remote_game, name))?;
+    }
+    db::set_field_versions(conn, id, taken.filter_map(|field| remote_versions.get_key_value(field)))?;
+    debug!(id, taken = take.len(), kept = keep.len(), "Merged game field by field");
+    Ok(ImportOutcome::Updated)
+}
+
+/// Settle a sync conflict: keep the game as it is, or take the other copy's
+/// values for every conflicting field or just some. Returns the game.
+pub fn resolve_conflict(conn: &Connection, id: i64, resolution: &ConflictResolution) -> CmdResult<Game> {
+    let conflict = db::get_sync_conflicts(conn, true)?
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| CommandError::NotFound(format!("Sync conflict {id}")))?;
+    if conflict.resolved_at.is_some() {
+        return Err(CommandError::validation("id", "This conflict is already resolved"));
+    }
+    let take: Vec<FieldConflict> = match resolution {
+        ConflictResolution::KeepLocal => vec![],
+        ConflictResolution::KeepRemote => conflict.fields,
+        ConflictResolution::MergeFields(fields) => {
+            if let Some(unknown) = fields.iter().find(|f| !conflict.fields.iter().any(|c| &c.field == *f)) {
+                return Err(CommandError::validation("fields", format!("{unknown} isn't one of the conflicting fields")));
+            }
+            conflict.fields.into_iter().filter(|c| fields.contains(&c.field)).collect()
+        }
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    let game = db::get_game(&tx, conflict.game_id)?.ok_or_else(|| CommandError::NotFound(format!("Game {}", conflict.game_id)))?;
+    let game = match take.is_empty() {
+        true => game,
+        false => db::update_game(&tx, game.id, import::with_fields(&import::game_to_input(&game), &take)?)?,
+    };
+    db::mark_sync_conflict_resolved(&tx, id, resolution.as_str())?;
+    tx.commit()?;
+    info!(id, game_id = game.id, resolution = resolution.as_str(), fields = take.len(), "Resolved sync conflict");
+    Ok(game)
+}
+
+/// Copies the cloud client set aside next to `db_path`: same name, ".db",
+/// and "conflict" somewhere in between (Dropbox, Drive, OneDrive and
+/// Syncthing all say so).
+fn conflicted_copies(db_path: &Path) -> Vec<String> {
+    let (Some(dir), Some(stem)) = (db_path.parent(), db_path.file_stem().and_then(|s| s.to_str())) else {
+        return vec![];
+    };
+    let Ok(entries) = fs::read_dir(dir) else { return vec![] };
+    let mut copies: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { return false };
+            path.extension().is_some_and(|e| e == "db")
+                && name.starts_with(stem)
+                && name.to_lowercase().contains("conflict")
+        })
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    copies.sort();
+    copies
+}
+
+/// This device's id, made up the first time it's needed.
+pub fn device_id(state_dir: &Path) -> CmdResult<String> {
+    let path = state_dir.join("device_id");
+    if let Ok(id) = fs::read_to_string(&path) {
+        if !id.trim().is_empty() {
+            return Ok(id.trim().to_string());
+        }
+    }
+    let id = Uuid::new_v4().to_string();
+    fs::create_dir_all(state_dir)?;
+    fs::write(&path, &id)?;
+    Ok(id)
+}
+
+fn read_local_state(state_dir: &Path) -> Option<LocalState> {
+    serde_json::from_slice(&fs::read(state_dir.join("filesync.json")).ok()?).ok()
+}
+
+fn write_local_state(state_dir: &Path, state: &LocalState) {
+    let result = serde_json::to_vec(state)
+        .map_err(|e| std::io::Error::other(e.to_string()))
+        .and_then(|json| fs::write(state_dir.join("filesync.json"), json));
+    if let Err(e) = result {
+        warn!(error = %e, "Couldn't save file sync state");
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> CmdResult<std::sync::MutexGuard<'_, T>> {
+    mutex.lock().map_err(|_| CommandError::Internal("File sync lock poisoned".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::titles;
+    use rusqlite::params;
+
+    /// A library holding one game, Hades (id 1), with this rating and developer.
+    fn library(rating: Option<f64>, developer: Option<&str>) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO games (id, title, normalized_title, status, rating, developer, created_at, updated_at, modified_at)
+             VALUES (1, 'Hades', ?1, 'Playing', ?2, ?3, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            params![titles::normalize("Hades"), rating, developer],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn stamp(conn: &Connection, field: &str, device: &str, modified_at: &str) {
+        let version = RecordVersion { revision: 2, device_id: Some(device.to_string()), modified_at: modified_at.to_string() };
+        db::set_field_versions(conn, 1, [(&field.to_string(), &version)]).unwrap();
+    }
+
+    /// Merge `remote`'s Hades into `local`'s and return the result.
+    fn merged(local: &Connection, remote: &Connection, strategy: ConflictStrategy) -> (ImportOutcome, Game) {
+        let remote_game = db::get_game(remote, 1).unwrap().unwrap();
+        let remote_versions = db::get_field_versions(remote, 1).unwrap();
+        let input = import::game_to_input(&remote_game);
+        let outcome = merge_game(local, 1, &remote_game, &input, &remote_versions, strategy).unwrap();
+        (outcome, db::get_game(local, 1).unwrap().unwrap())
+    }
+
+    #[test]
+    fn a_field_edited_on_one_side_takes_that_side() {
+        let (local, remote) = (library(Some(7.0), Some("Supergiant")), library(Some(9.0), Some("Supergiant Games")));
+        stamp(&remote, "rating", "b", "2024-03-01T00:00:00Z");
+        stamp(&local, "developer", "a", "2024-02-01T00:00:00Z");
+
+        let (outcome, game) = merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(outcome, ImportOutcome::Updated);
+        assert_eq!(game.rating, Some(9.0));
+        assert_eq!(game.developer.as_deref(), Some("Supergiant"));
+        // The taken field keeps the other device's stamp.
+        assert_eq!(db::get_field_versions(&local, 1).unwrap()["rating"].device_id.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn a_field_edited_on_both_sides_takes_the_later_edit() {
+        let (local, remote) = (library(Some(7.0), Some("Supergiant")), library(Some(9.0), Some("Supergiant Games")));
+        stamp(&local, "rating", "a", "2024-02-01T00:00:00Z");
+        stamp(&remote, "rating", "b", "2024-03-01T00:00:00Z");
+        stamp(&local, "developer", "a", "2024-05-01T00:00:00Z");
+        stamp(&remote, "developer", "b", "2024-04-01T00:00:00Z");
+
+        let (_, game) = merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(game.rating, Some(9.0));
+        assert_eq!(game.developer.as_deref(), Some("Supergiant"));
+        let versions = db::get_field_versions(&local, 1).unwrap();
+        assert_eq!(versions["rating"].modified_at, "2024-03-01T00:00:00Z");
+        assert_eq!(versions["developer"].modified_at, "2024-05-01T00:00:00Z");
+    }
+
+    #[test]
+    fn an_unstamped_field_is_left_to_the_strategy() {
+        let (local, remote) = (library(None, Some("Supergiant")), library(Some(9.0), Some("Supergiant Games")));
+
+        let (_, game) = merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(game.rating, Some(9.0));
+        assert_eq!(game.developer.as_deref(), Some("Supergiant"));
+    }
+
+    #[test]
+    fn a_merge_is_not_stamped_as_a_local_edit() {
+        let (local, remote) = (library(Some(7.0), None), library(Some(9.0), None));
+        stamp(&remote, "rating", "b", "2024-03-01T00:00:00Z");
+        let before = db::get_record_version(&local, 1).unwrap();
+
+        merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(db::get_record_version(&local, 1).unwrap(), before);
+        assert_eq!(db::get_field_versions(&local, 1).unwrap().into_keys().collect::<Vec<_>>(), ["rating"]);
+    }
+
+    #[test]
+    fn flags_merge_like_fields() {
+        let (local, remote) = (library(None, None), library(None, None));
+        db::set_archived(&remote, &[1], true).unwrap();
+
+        let (outcome, game) = merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(outcome, ImportOutcome::Updated);
+        assert!(game.archived);
+    }
+
+    #[test]
+    fn nothing_to_take_leaves_the_game_unchanged() {
+        let (local, remote) = (library(Some(9.0), None), library(Some(7.0), None));
+        stamp(&local, "rating", "a", "2024-03-01T00:00:00Z");
+        stamp(&remote, "rating", "b", "2024-02-01T00:00:00Z");
+
+        let (outcome, game) = merged(&local, &remote, ConflictStrategy::FillEmpty);
+        assert_eq!(outcome, ImportOutcome::Unchanged);
+        assert_eq!(game.rating, Some(9.0));
+    }
+
+    #[test]
+    fn a_delete_wins_over_earlier_edits_on_either_side() {
+        let (local, remote) = (library(None, None), library(None, None));
+        db::delete_game(&local, 1).unwrap();
+
+        let report = merge(&local, &remote, ConflictStrategy::FillEmpty, "test").unwrap();
+        assert!(report.created_ids.is_empty());
+        let report = merge(&remote, &local, ConflictStrategy::FillEmpty, "test").unwrap();
+        assert_eq!(report.deleted_ids, [1]);
+        assert!(db::get_all_games(&remote).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+// hltb.rs — Read a HowLongToBeat list export (CSV); see trackers.rs.
+//
+// HowLongToBeat lets you download your lists from your profile
+// (Options → Export). Every game is one row; the lists it's on are marked
+// in their own columns, and the times you submitted are "H:MM:SS":
+//
+//   Title,Platform,Storefront,Playing,Backlog,Replay,Custom Tab,Completed,Retired,
+//   Progress,Main Story,Main + Extras,Completionist,Review,Review Notes,General Notes,
+//   Start Date,Completion Date,…
+//   Hades,PC,Steam,,,,,X,,61:12:00,22:30:00,,,90,Loved it,,2021-01-02,2021-02-14
+//
+// Each row maps to a game:
+//
+//   lists      → status: Playing, then Completed (or Replay), Retired (Dropped), Backlog
+//   Progress   → playtime; without it, the longest of the submitted times
+//   Review     → rating, HLTB's 0 – 100 scaled to 1 – 10
+//   notes      → the review and general notes, one after the other
+//   dates      → a playthrough
+
+use crate::import;
+use crate::models::GameStatus;
+use crate::trackers::{self, Row, TrackerEntry, TrackerExport};
+
+pub struct Hltb;
+
+impl TrackerExport for Hltb {
+    fn name(&self) -> &'static str {
+        "HowLongToBeat"
+    }
+
+    fn title_columns(&self) -> &'static [&'static str] {
+        &["title", "name"]
+    }
+
+    fn entry(&self, row: &Row, fallback_platform: &str) -> Option<TrackerEntry> {
+        let title = row.get(self.title_columns())?;
+        let status = if row.flag("playing") {
+            GameStatus::Playing
+        } else if row.flag("completed") || row.flag("replay") {
+            GameStatus::Completed
+        } else if row.flag("retired") {
+            GameStatus::Dropped
+        } else if row.flag("backlog") {
+            GameStatus::Backlog
+        } else {
+            GameStatus::NotStarted
+        };
+        let playtime = row.get(&["progress", "time played"]).and_then(trackers::parse_duration).or_else(|| {
+            ["main story", "main + extras", "completionist", "speed any%", "speed 100%"]
+                .iter()
+                .filter_map(|name| row.get(&[name]).and_then(trackers::parse_duration))
+                .max()
+        });
+        // Scores are out of 100; very old exports used 10.
+        let rating = row.get(&["review", "rating"]).and_then(|score| {
+            let out_of = if score.parse::<f64>().is_ok_and(|s| s > 10.0) { 100.0 } else { 10.0 };
+            trackers::parse_rating(score, out_of)
+        });
+        let notes: Vec<&str> = [row.get(&["review notes"]), row.get(&["general notes", "notes"])].into_iter().flatten().collect();
+
+        let mut input = import::remote_title_input(title, row.get(&["platform"]).unwrap_or(fallback_platform), status);
+        input.playtime_minutes = playtime.filter(|m| *m > 0);
+        input.rating = rating;
+        input.notes = (!notes.is_empty()).then(|| notes.join("\n\n"));
+        Some(TrackerEntry {
+            started_at:  row.get(&["start date", "started"]).and_then(trackers::parse_date),
+            finished_at: row.get(&["completion date", "completed date", "finished"]).and_then(trackers::parse_date),
+            ..TrackerEntry::new(input)
+        })
+    }
+}
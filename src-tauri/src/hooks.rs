@@ -0,0 +1,346 @@
+// hooks.rs — Run the user's own commands when something happens in the library.
+//
+// A hook is a program (with arguments) registered for one event:
+//
+//   GameAdded       a game was added — by hand, bulk or by any import
+//   GameCompleted   a game's status was changed to Completed
+//   SessionEnded    a play session was recorded: the timer stopped (or was
+//                   closed after a crash) or one was logged by hand
+//
+// The program gets the event as one line of JSON on stdin, and its name in
+// the GAMETRC_EVENT environment variable:
+//
+//   { "event": "session_ended", "at": "2024-05-01T21:14:03+00:00",
+//     "game": { "id": 12, "title": "Hades", … }, "session": { "duration_minutes": 95, … } }
+//
+// "game" is the whole Game record; "session" (a PlaySession) is only there
+// for SessionEnded. Something like this appends every finished session to a
+// journal:
+//
+//   { "event": "SessionEnded", "command": "sh",
+//     "args": ["-c", "jq -c '{at, title: .game.title, minutes: .session.duration_minutes}' >> ~/journal.jsonl"] }
+//
+// Hooks run one at a time, in the order events happened, on a worker thread
+// of their own — the command that caused the event never waits for them. A
+// hook that runs longer than TIMEOUT is killed; failures and anything a hook
+// writes are logged. Unlike importer plugins (plugins.rs), hooks are the
+// user's own commands and run with their full environment.
+//
+// Hooks are kept per device in app_local_data_dir/hooks.json, not in
+// games.db: the database travels (WebDAV, cloud-drive folders, library
+// exports), and a command mustn't start running on a machine because it
+// arrived with a library.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rusqlite::Connection;
+use serde_json::json;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::models::{Game, Hook, HookEvent, HookInput, HookRun, PlaySession, SessionSource};
+use crate::plugins;
+
+const HOOKS_FILE: &str = "hooks.json";
+
+/// How long a hook may run before it's killed.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for a hook's output once it has exited. Only a program
+/// the hook left running in the background (`notify-send … &`) keeps the
+/// pipes open that long; its output is dropped rather than stalling the
+/// hooks queued after it.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// How much of a hook's stdout and stderr is kept.
+const MAX_OUTPUT: usize = 64 * 1024;
+
+/// Something that happened, queued for the worker.
+enum Fired {
+    GamesAdded(Vec<i64>),
+    GameCompleted(i64),
+    SessionEnded(PlaySession),
+}
+
+struct Registry {
+    path:    PathBuf,
+    hooks:   Mutex<Vec<Hook>>,
+    queue:   Sender<Fired>,
+    pending: Mutex<Option<Receiver<Fired>>>,   // taken by `spawn_worker`
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Load this device's hooks from `state_dir`. Call once at startup; events
+/// fired before `spawn_worker` wait for it.
+pub fn init(state_dir: &Path) {
+    let path = state_dir.join(HOOKS_FILE);
+    let hooks = match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!(error = %e, path = %path.display(), "Stored hooks are invalid, none will run");
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    };
+    let (queue, pending) = mpsc::channel();
+    let registry = Registry { path, hooks: Mutex::new(hooks), queue, pending: Mutex::new(Some(pending)) };
+    if REGISTRY.set(registry).is_err() {
+        warn!("Hooks were already loaded");
+    }
+}
+
+/// Start the thread that runs hooks as events come in.
+pub fn spawn_worker(db: Arc<Mutex<Connection>>) {
+    let Some(receiver) = REGISTRY.get().and_then(|r| r.pending.lock().ok()?.take()) else { return };
+    thread::spawn(move || {
+        for fired in receiver {
+            for (event, payload) in payloads(&db, fired) {
+                for hook in enabled(event) {
+                    match run(&hook, &payload) {
+                        Ok(result) if result.exit_code == Some(0) => {
+                            info!(hook = %hook.id, event = event.as_str(), ms = result.duration_ms, "Ran hook");
+                        }
+                        Ok(result) => warn!(
+                            hook = %hook.id,
+                            event = event.as_str(),
+                            exit_code = ?result.exit_code,
+                            timed_out = result.timed_out,
+                            stderr = %result.stderr.trim(),
+                            "Hook failed"
+                        ),
+                        Err(e) => warn!(hook = %hook.id, event = event.as_str(), error = %e, "Couldn't run hook"),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// The enabled hooks for `event`, in the order they were added.
+fn enabled(event: HookEvent) -> Vec<Hook> {
+    list().into_iter().filter(|h| h.enabled && h.event == event).collect()
+}
+
+fn fire(event: HookEvent, fired: Fired) {
+    // Nothing to look up or queue unless a hook is waiting for it.
+    if enabled(event).is_empty() {
+        return;
+    }
+    if let Some(registry) = REGISTRY.get() {
+        let _ = registry.queue.send(fired);
+    }
+}
+
+/// Games were added by the user or an import. Not for a library restored or
+/// pulled as a whole — those games aren't new.
+pub fn games_added(ids: &[i64]) {
+    fire(HookEvent::GameAdded, Fired::GamesAdded(ids.to_vec()));
+}
+
+/// Game `id` was just moved to Completed.
+pub fn game_completed(id: i64) {
+    fire(HookEvent::GameCompleted, Fired::GameCompleted(id));
+}
+
+/// `session` was recorded.
+pub fn session_ended(session: &PlaySession) {
+    fire(HookEvent::SessionEnded, Fired::SessionEnded(session.clone()));
+}
+
+/// The JSON each hook gets for `fired` — one per game for GamesAdded. Games
+/// are read when their hooks run; ones deleted in the meantime are left out.
+fn payloads(db: &Mutex<Connection>, fired: Fired) -> Vec<(HookEvent, serde_json::Value)> {
+    let game = |id: i64| -> Option<Game> {
+        let conn = db::lock(db).ok()?;
+        db::get_game(&conn, id).ok().flatten()
+    };
+    let at = Utc::now().to_rfc3339();
+    match fired {
+        Fired::GamesAdded(ids) => ids
+            .into_iter()
+            .filter_map(game)
+            .map(|game| (HookEvent::GameAdded, json!({ "event": HookEvent::GameAdded.as_str(), "at": at, "game": game })))
+            .collect(),
+        Fired::GameCompleted(id) => game(id)
+            .map(|game| (HookEvent::GameCompleted, json!({ "event": HookEvent::GameCompleted.as_str(), "at": at, "game": game })))
+            .into_iter()
+            .collect(),
+        Fired::SessionEnded(session) => game(session.game_id)
+            .map(|game| {
+                let payload = json!({ "event": HookEvent::SessionEnded.as_str(), "at": at, "game": game, "session": session });
+                (HookEvent::SessionEnded, payload)
+            })
+            .into_iter()
+            .collect(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Managing hooks
+// ---------------------------------------------------------------------------
+
+fn registry() -> CmdResult<&'static Registry> {
+    REGISTRY.get().ok_or_else(|| CommandError::Internal("Hooks aren't loaded".into()))
+}
+
+/// Every hook on this device.
+pub fn list() -> Vec<Hook> {
+    REGISTRY.get().and_then(|r| r.hooks.lock().ok().map(|hooks| hooks.clone())).unwrap_or_default()
+}
+
+/// Change the hooks with `f` and save them.
+fn edit<T>(f: impl FnOnce(&mut Vec<Hook>) -> CmdResult<T>) -> CmdResult<T> {
+    let registry = registry()?;
+    let mut hooks = registry.hooks.lock().map_err(|_| CommandError::Internal("hooks lock poisoned".into()))?;
+    let mut edited = hooks.clone();
+    let result = f(&mut edited)?;
+    let json = serde_json::to_vec_pretty(&edited).map_err(|e| CommandError::Internal(e.to_string()))?;
+    if let Some(dir) = registry.path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&registry.path, json)?;
+    *hooks = edited;
+    Ok(result)
+}
+
+fn check(input: &HookInput) -> CmdResult<()> {
+    let command = input.command.trim();
+    if command.is_empty() {
+        return Err(CommandError::validation("command", "must not be empty"));
+    }
+    // A bare name is looked up on PATH when the hook runs; a path must exist.
+    if (command.contains('/') || command.contains('\\')) && !Path::new(command).is_file() {
+        return Err(CommandError::validation("command", "no such file"));
+    }
+    Ok(())
+}
+
+pub fn add(input: HookInput) -> CmdResult<Hook> {
+    check(&input)?;
+    let hook = Hook {
+        id:         Uuid::new_v4().to_string(),
+        event:      input.event,
+        command:    input.command.trim().to_string(),
+        args:       input.args,
+        enabled:    input.enabled.unwrap_or(true),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    edit(|hooks| {
+        hooks.push(hook.clone());
+        Ok(())
+    })?;
+    info!(hook = %hook.id, event = hook.event.as_str(), "Added hook");
+    Ok(hook)
+}
+
+pub fn update(id: &str, input: HookInput) -> CmdResult<Hook> {
+    check(&input)?;
+    edit(|hooks| {
+        let hook = hooks.iter_mut().find(|h| h.id == id).ok_or_else(|| CommandError::NotFound(format!("Hook {id}")))?;
+        hook.event = input.event;
+        hook.command = input.command.trim().to_string();
+        hook.args = input.args;
+        hook.enabled = input.enabled.unwrap_or(true);
+        Ok(hook.clone())
+    })
+}
+
+pub fn delete(id: &str) -> CmdResult<()> {
+    edit(|hooks| {
+        let before = hooks.len();
+        hooks.retain(|h| h.id != id);
+        if hooks.len() == before {
+            return Err(CommandError::NotFound(format!("Hook {id}")));
+        }
+        Ok(())
+    })?;
+    info!(hook = id, "Deleted hook");
+    Ok(())
+}
+
+/// Run hook `id` now with `game` as the event's game (a sample session is
+/// made up for SessionEnded), whether it's enabled or not. Waits for it.
+pub fn test(id: &str, game: &Game) -> CmdResult<HookRun> {
+    let hook = list().into_iter().find(|h| h.id == id).ok_or_else(|| CommandError::NotFound(format!("Hook {id}")))?;
+    let mut payload = json!({ "event": hook.event.as_str(), "at": Utc::now().to_rfc3339(), "game": game, "test": true });
+    if hook.event == HookEvent::SessionEnded {
+        let now = Utc::now();
+        payload["session"] = json!(PlaySession {
+            id:               0,
+            game_id:          game.id,
+            started_at:       (now - chrono::Duration::minutes(60)).to_rfc3339(),
+            duration_minutes: 60,
+            source:           SessionSource::Manual,
+            game_version:     None,
+            idle_minutes:     0,
+            people:           vec![],
+            created_at:       now.to_rfc3339(),
+        });
+    }
+    run(&hook, &payload)
+}
+
+// ---------------------------------------------------------------------------
+// Running
+// ---------------------------------------------------------------------------
+
+fn run(hook: &Hook, payload: &serde_json::Value) -> CmdResult<HookRun> {
+    let started = Instant::now();
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        .env("GAMETRC_EVENT", hook.event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| CommandError::Io(format!("Couldn't start {}: {e}", hook.command)))?;
+
+    let stdout = plugins::capture(child.stdout.take(), MAX_OUTPUT);
+    let stderr = plugins::capture(child.stderr.take(), MAX_OUTPUT);
+    if let Some(mut stdin) = child.stdin.take() {
+        let line = format!("{payload}\n");
+        // Its own thread: a game with long notes can be more than a pipe holds.
+        thread::spawn(move || {
+            // A hook that doesn't read stdin closes the pipe; that's fine.
+            let _ = stdin.write_all(line.as_bytes());
+        });
+    }
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() > TIMEOUT {
+            timed_out = true;
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let exited = Instant::now();
+    let text = |output: mpsc::Receiver<(Vec<u8>, bool)>| {
+        output
+            .recv_timeout(OUTPUT_GRACE.saturating_sub(exited.elapsed()))
+            .map(|(bytes, _)| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    };
+    Ok(HookRun {
+        exit_code:   status.and_then(|s| s.code()),
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+        stdout:      text(stdout),
+        stderr:      text(stderr),
+    })
+}
@@ -0,0 +1,264 @@
+// humble.rs — Import the games bought through Humble Bundle.
+//
+// Humble has no public API, but the library page is built from a JSON API
+// that answers to the session cookie (`_simpleauth_sess`) of a signed-in
+// browser:
+//
+//   /api/v1/user/order                      → [{ "gamekey": "…" }, …]
+//   /api/v1/orders?gamekeys=…&gamekeys=…    → { "<gamekey>": order, … }
+//
+// Library export scripts save those same orders to a file, so a JSON export
+// (a list of orders, or orders keyed by gamekey) imports the same way without
+// handing GameTrc the cookie.
+//
+// An order is one purchase — a bundle, a Humble Choice month or a store
+// purchase — and holds:
+//
+//   subproducts   DRM-free downloads (and ebooks, soundtracks, …)
+//   all_tpks      keys for other stores (Steam, GOG, Origin, …)
+//
+// Every game among them becomes (or is matched to) a library game linked
+// through `game_external_ids` (source "Humble", id = the product's machine
+// name), and the order is recorded as a purchase with its bundle name and
+// date (see `get_bundles`). A game with a DRM-free download is marked so and
+// linked to the order's download page.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::credentials;
+use crate::db::{self, ImportOutcome};
+use crate::error::{CmdResult, CommandError};
+use crate::http;
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::metadata::normalize_title;
+use crate::models::{ConflictStrategy, GameStatus, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for Humble rows in `game_external_ids` and `purchases`.
+pub const SOURCE: &str = "Humble";
+
+const API_BASE: &str = "https://www.humblebundle.com/api/v1";
+const DOWNLOADS_URL: &str = "https://www.humblebundle.com/downloads";
+
+/// Orders fetched per request; the site's own library page asks for 40.
+const ORDERS_PER_REQUEST: usize = 40;
+
+#[derive(Deserialize)]
+struct OrderKey {
+    gamekey: String,
+}
+
+#[derive(Deserialize)]
+struct Order {
+    gamekey:     String,
+    created:     Option<String>,   // "2019-05-01T17:00:00.000000"
+    product:     Product,
+    #[serde(default)]
+    subproducts: Vec<Subproduct>,
+    tpkd_dict:   Option<TpkdDict>,
+}
+
+#[derive(Deserialize)]
+struct Product {
+    human_name: String,
+    #[serde(default)]
+    category:   String,   // "bundle", "subscriptioncontent", "storefront", …
+}
+
+#[derive(Deserialize)]
+struct Subproduct {
+    machine_name: String,
+    human_name:   String,
+    #[serde(default)]
+    downloads:    Vec<Download>,
+    payee:        Option<Payee>,
+}
+
+#[derive(Deserialize)]
+struct Download {
+    platform: String,   // "windows", "mac", "linux", "android", "ebook", "audio", …
+}
+
+#[derive(Deserialize)]
+struct Payee {
+    human_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TpkdDict {
+    #[serde(default)]
+    all_tpks: Vec<Tpk>,
+}
+
+#[derive(Deserialize)]
+struct Tpk {
+    machine_name: String,
+    human_name:   String,
+}
+
+/// A library export: the orders as a list, or keyed by gamekey.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Export {
+    List(Vec<Order>),
+    ByKey(HashMap<String, Order>),
+}
+
+/// One game from an order.
+struct HumbleGame {
+    id:        String,
+    title:     String,
+    platform:  &'static str,
+    drm_free:  bool,
+    developer: Option<String>,
+}
+
+fn get<T: serde::de::DeserializeOwned>(session: &str, request: ureq::Request) -> CmdResult<T> {
+    http::read_json(request.set("Cookie", &format!("_simpleauth_sess={}", session.trim())))
+}
+
+/// Import the orders in a library export file (JSON). With `dry_run` nothing
+/// is saved; the report's `preview` says what would change.
+pub fn import_export(
+    db: &Mutex<Connection>,
+    path: &Path,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let export: Export = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| CommandError::validation("path", format!("Not a Humble library export: {e}")))?;
+    let orders = match export {
+        Export::List(orders) => orders,
+        Export::ByKey(orders) => orders.into_values().collect(),
+    };
+    import_orders(db, orders, dry_run, on_conflict, job)
+}
+
+/// Fetch every order on the account and import it. Needs the
+/// `humble_session` credential.
+pub fn sync(db: &Mutex<Connection>, dry_run: bool, on_conflict: ConflictStrategy, job: &JobHandle) -> CmdResult<SyncReport> {
+    let session = credentials::require(credentials::HUMBLE_SESSION)?;
+    let keys: Vec<OrderKey> = get(&session, http::agent().get(&format!("{API_BASE}/user/order")))?;
+    let chunks: Vec<&[OrderKey]> = keys.chunks(ORDERS_PER_REQUEST).collect();
+    let mut orders = Vec::with_capacity(keys.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, chunks.len(), "Fetching Humble orders");
+        let request = chunk
+            .iter()
+            .fold(http::agent().get(&format!("{API_BASE}/orders")).query("all_tpkds", "true"), |request, key| {
+                request.query("gamekeys", &key.gamekey)
+            });
+        let by_key: HashMap<String, Order> = get(&session, request)?;
+        orders.extend(by_key.into_values());
+    }
+    import_orders(db, orders, dry_run, on_conflict, job)
+}
+
+fn import_orders(
+    db: &Mutex<Connection>,
+    mut orders: Vec<Order>,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    // Oldest first, so a game in several orders is created from the first.
+    orders.sort_by(|a, b| a.created.cmp(&b.created));
+
+    let mut report = SyncReport::default();
+    let mut conn = db::lock(db)?;
+    let mut recorder = dry_run.then(|| PreviewRecorder::start(&conn)).transpose()?;
+    let tx = conn.transaction()?;
+    let total = orders.len();
+    for (i, order) in orders.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, total, order.product.human_name.as_str());
+        // A store purchase isn't a bundle; a bundle or Choice month is.
+        let bundle = (order.product.category != "storefront").then_some(order.product.human_name.trim());
+        let purchased_at = order.created.as_deref().and_then(|c| c.get(..10));
+
+        for game in games(order) {
+            report.checked += 1;
+            let mut input = import::remote_title_input(&game.title, game.platform, GameStatus::Backlog);
+            input.developer = game.developer;
+            let (game_id, outcome) = db::import_game(&tx, input, Some((SOURCE, game.id.as_str())), on_conflict)?;
+            if game.drm_free {
+                let url = format!("{DOWNLOADS_URL}?key={}", order.gamekey);
+                db::set_external_page(&tx, game_id, SOURCE, Some(&url), true)?;
+            }
+            let purchased = db::add_purchase(&tx, game_id, SOURCE, &order.gamekey, bundle, purchased_at)?;
+
+            let seen = report.imported_ids.contains(&game_id) || report.updated_ids.contains(&game_id);
+            match outcome {
+                _ if seen => {}
+                ImportOutcome::Created => report.imported_ids.push(game_id),
+                ImportOutcome::Updated => report.updated_ids.push(game_id),
+                ImportOutcome::Unchanged if purchased => report.updated_ids.push(game_id),
+                ImportOutcome::Unchanged => {}
+            }
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(&tx, game_id)?;
+            }
+        }
+    }
+    if let Some(recorder) = recorder {
+        // Dropping `tx` without committing rolls the import back.
+        info!(orders = total, checked = report.checked, "Humble import dry run");
+        return Ok(report.into_dry_run(recorder.finish()));
+    }
+    tx.commit()?;
+
+    info!(
+        orders = total,
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        "Imported Humble library"
+    );
+    Ok(report)
+}
+
+/// The games in an order: DRM-free downloads first, then store keys for
+/// games that don't have one. Ebooks, soundtracks and the like are skipped.
+fn games(order: &Order) -> Vec<HumbleGame> {
+    let mut games: Vec<HumbleGame> = Vec::new();
+    for product in &order.subproducts {
+        let has = |platform: &str| product.downloads.iter().any(|d| d.platform == platform);
+        let platform = if has("windows") || has("mac") || has("linux") {
+            "PC"
+        } else if has("android") {
+            "Android"
+        } else {
+            continue;
+        };
+        games.push(HumbleGame {
+            id:        product.machine_name.clone(),
+            title:     product.human_name.clone(),
+            platform,
+            drm_free:  true,
+            developer: product.payee.as_ref().and_then(|p| p.human_name.clone()),
+        });
+    }
+    for key in order.tpkd_dict.iter().flat_map(|d| &d.all_tpks) {
+        // The same game as a download and a Steam key is one game.
+        let title = normalize_title(&key.human_name);
+        if games.iter().any(|g| normalize_title(&g.title) == title) {
+            continue;
+        }
+        games.push(HumbleGame {
+            id:        key.machine_name.clone(),
+            title:     key.human_name.clone(),
+            platform:  "PC",
+            drm_free:  false,
+            developer: None,
+        });
+    }
+    games
+}
@@ -0,0 +1,68 @@
+// idle.rs — How long since the user last touched the keyboard, mouse or pad.
+//
+// The session timer (timer.rs) asks this once a minute, so walking away from
+// a game doesn't count as playing it. Every OS exposes the idle time
+// differently; like installs.rs reading the registry through `reg`, we ask a
+// stock command rather than take on a dependency per platform:
+//
+//   Windows  PowerShell calling GetLastInputInfo             (milliseconds)
+//   macOS    `ioreg -c IOHIDSystem`, the HIDIdleTime entry     (nanoseconds)
+//   Linux    `xprintidle` on X11                              (milliseconds)
+//            GNOME's Mutter IdleMonitor over D-Bus on Wayland (milliseconds)
+//
+// None means the idle time couldn't be read (e.g. a Wayland desktop other
+// than GNOME); the timer then only notices the machine being suspended.
+
+use std::process::Command;
+
+const WINDOWS_SCRIPT: &str = r#"Add-Type 'using System; using System.Runtime.InteropServices;
+public static class Idle {
+    [StructLayout(LayoutKind.Sequential)] struct Info { public uint Size; public uint Time; }
+    [DllImport("user32.dll")] static extern bool GetLastInputInfo(ref Info info);
+    public static uint Millis() { var i = new Info(); i.Size = 8; GetLastInputInfo(ref i); return (uint)Environment.TickCount - i.Time; }
+}'; [Idle]::Millis()"#;
+
+/// Seconds since the last keyboard or mouse input, if the OS will say.
+pub fn idle_seconds() -> Option<u64> {
+    let millis = if cfg!(windows) {
+        run("powershell", &["-NoProfile", "-NonInteractive", "-Command", WINDOWS_SCRIPT]).and_then(|out| out.trim().parse().ok())
+    } else if cfg!(target_os = "macos") {
+        run("ioreg", &["-c", "IOHIDSystem"]).and_then(|out| hid_idle_millis(&out))
+    } else {
+        run("xprintidle", &[]).and_then(|out| out.trim().parse().ok()).or_else(mutter_idle_millis)
+    };
+    millis.map(|ms: u64| ms / 1000)
+}
+
+/// `"HIDIdleTime" = 1234567890` → milliseconds.
+fn hid_idle_millis(ioreg: &str) -> Option<u64> {
+    let line = ioreg.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+    let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+    Some(nanos / 1_000_000)
+}
+
+/// GNOME on Wayland; answers `(uint64 1234,)`.
+fn mutter_idle_millis() -> Option<u64> {
+    let out = run("gdbus", &[
+        "call", "--session",
+        "--dest", "org.gnome.Mutter.IdleMonitor",
+        "--object-path", "/org/gnome/Mutter/IdleMonitor/Core",
+        "--method", "org.gnome.Mutter.IdleMonitor.GetIdletime",
+    ])?;
+    out.trim().trim_start_matches("(uint64").trim_end_matches(",)").trim().parse().ok()
+}
+
+/// A command's stdout, or None if it's missing or failed.
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    // RUST NOTE: a GUI app starting a console program gets a console window
+    // flashing up once a minute unless it asks for none (CREATE_NO_WINDOW).
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0800_0000);
+    }
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -4,19 +4,52 @@
 //   1. Local file paths  → copy to app_data_dir/images/ with a unique name
 //   2. Remote URLs       → download and save to app_data_dir/images/
 //
-// Both cases return a relative path that gets stored in the database.
+// What's saved depends on the image's kind (see `profile`): covers are
+// cropped to the 2:3 portrait shape the library grid shows them in and
+// shrunk to a sensible size; screenshots are kept at full resolution.
+// Animated GIFs and WebPs are kept as they are, once they've been checked
+// (see `animation_frames`); list views can show a still poster frame instead.
+//
+// Both cases return the saved image's absolute path. The database only keeps
+// the file name (see `to_stored` / `resolve`), so a library copied to another
+// machine or OS still finds its images.
+//
+// The folder can be capped in size (`max_images_mb`); `evict` then deletes
+// images nothing in the library uses any more, oldest first. Images the
+// library does use are never deleted, since they can't be fetched again.
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPDecoder;
+use image::imageops::{self, FilterType};
+use image::{AnimationDecoder, DynamicImage, Frames, ImageFormat, RgbaImage};
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
 use tauri::AppHandle;
 use uuid::Uuid;
 use tauri::Manager;
+use tracing::{info, warn};
+
+use crate::cache;
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::http;
+use crate::jobs::JobHandle;
+use crate::models::{CropRect, ImageEvictionReport, ImageKind, ImageSize, RelinkReport};
 
 #[derive(Debug)]
 pub enum ImageError {
     IoError(std::io::Error),
     HttpError(String),
     InvalidPath(String),
+    Decode(String),
+    Rejected(String),
 }
 
 impl std::fmt::Display for ImageError {
@@ -25,6 +58,8 @@ impl std::fmt::Display for ImageError {
             ImageError::IoError(e) => write!(f, "IO error: {}", e),
             ImageError::HttpError(e) => write!(f, "HTTP error: {}", e),
             ImageError::InvalidPath(e) => write!(f, "Invalid path: {}", e),
+            ImageError::Decode(e) => write!(f, "Not a readable image: {}", e),
+            ImageError::Rejected(e) => write!(f, "{}", e),
         }
     }
 }
@@ -52,6 +87,42 @@ pub fn get_images_dir(app: &AppHandle) -> Result<PathBuf, ImageError> {
     Ok(images_dir)
 }
 
+// ---------------------------------------------------------------------------
+// Stored paths
+// ---------------------------------------------------------------------------
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Tell the DB layer where the images folder is. Call once at startup; until
+/// then paths are stored and returned exactly as given.
+pub fn init(images_dir: PathBuf) {
+    let _ = DIR.set(images_dir);
+}
+
+/// The form of `path` to keep in the database: just the file name for an
+/// image inside the images folder, the path unchanged for anything else.
+pub fn to_stored(path: &str) -> String {
+    let Some(dir) = DIR.get() else { return path.to_string() };
+    let path = Path::new(path);
+    match (path.parent(), path.file_name().and_then(|n| n.to_str())) {
+        (Some(parent), Some(name)) if parent == dir => name.to_string(),
+        _ => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// Turn a stored path back into one the frontend can load: a bare file name
+/// is joined onto the images folder.
+pub fn resolve(stored: String) -> String {
+    match DIR.get() {
+        Some(dir) if is_file_name(&stored) => dir.join(stored).to_string_lossy().into_owned(),
+        _ => stored,
+    }
+}
+
+fn is_file_name(stored: &str) -> bool {
+    !stored.is_empty() && !stored.contains(['/', '\\'])
+}
+
 /// Detect if a string is a remote URL or a local file path
 fn is_remote_url(input: &str) -> bool {
     input.starts_with("http://") || input.starts_with("https://")
@@ -93,53 +164,537 @@ fn copy_local_file(source: &Path, dest: &Path) -> Result<(), ImageError> {
 
 /// Download a remote image and save it to the images directory
 fn download_remote_image(url: &str, dest: &Path) -> Result<(), ImageError> {
-    // Use ureq for a simple blocking HTTP client (no async needed for this use case)
-    let response = ureq::get(url)
-        .call()
+    // Through the response cache, so importing the same cover twice (or
+    // while offline) doesn't need the network
+    let bytes = http::get_bytes_cached(url, cache::IMAGE_TTL)
         .map_err(|e| ImageError::HttpError(format!("Failed to download: {}", e)))?;
     
-    // Check that we got a successful response
-    if response.status() != 200 {
-        return Err(ImageError::HttpError(format!(
-            "HTTP {} from {}",
-            response.status(),
-            url
-        )));
-    }
-    
-    // Read the response body into a byte buffer
-    let mut bytes = Vec::new();
-    response
-        .into_reader()
-        .read_to_end(&mut bytes)
-        .map_err(|e| ImageError::IoError(e))?;
-    
     // Write to disk
     fs::write(dest, bytes)?;
     Ok(())
 }
 
+/// Extensions of the image files the app can show.
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif"];
+
+/// The image files directly in `folder`, sorted by file name.
+pub fn images_in(folder: &Path) -> Result<Vec<PathBuf>, ImageError> {
+    let mut images: Vec<PathBuf> = fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    images.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(images)
+}
+
 /// Main entry point: process an image (local path or URL) and return the saved path.
 ///
 /// Returns an absolute path to the saved image in app_data_dir/images/.
 /// The caller should store this path in the database.
-pub fn process_image(app: &AppHandle, input: &str) -> Result<String, ImageError> {
+pub fn process_image(app: &AppHandle, input: &str, kind: ImageKind) -> Result<String, ImageError> {
     let images_dir = get_images_dir(app)?;
     let filename = generate_filename(input);
     let dest_path = images_dir.join(&filename);
     
-    if is_remote_url(input) {
+    let result = if is_remote_url(input) {
         // Download from URL
-        download_remote_image(input, &dest_path)?;
+        download_remote_image(input, &dest_path)
     } else {
         // Copy from local filesystem
         let source_path = Path::new(input);
-        copy_local_file(source_path, &dest_path)?;
+        copy_local_file(source_path, &dest_path)
+    };
+    if let Err(e) = &result {
+        warn!(input, error = %e, "Image processing failed");
     }
+    result?;
+    let animated = match animation_frames(&dest_path) {
+        Ok(frames) => frames.is_some(),
+        Err(e) => {
+            warn!(input, error = %e, "Rejected animated image");
+            let _ = fs::remove_file(&dest_path);
+            return Err(e);
+        }
+    };
+    // Re-encoding an animation would flatten it, so it's kept as it came, as
+    // is a format `decode` can't read.
+    let processed = if animated { Ok(dest_path.clone()) } else { apply_profile(&dest_path, profile(kind)) };
+    let dest_path = match processed {
+        Ok(path) => path,
+        Err(e) => {
+            warn!(input, error = %e, "Couldn't resize image; keeping the original");
+            dest_path
+        }
+    };
+    info!(input, ?kind, saved = %dest_path.display(), "Saved image");
     
     // Return the absolute path as a string
     dest_path
         .to_str()
         .ok_or_else(|| ImageError::InvalidPath("Invalid UTF-8 in path".to_string()))
         .map(|s| s.to_string())
-}
\ No newline at end of file
+}
+
+// ---------------------------------------------------------------------------
+// Profiles
+// ---------------------------------------------------------------------------
+
+/// How images of one kind are stored.
+pub struct ImageProfile {
+    /// Largest size kept; bigger images are scaled down. None keeps the
+    /// original file untouched.
+    pub max_size: Option<(u32, u32)>,
+    /// Crop to `max_size`'s shape rather than just fit inside it.
+    pub crop:     bool,
+    /// JPEG quality (1 – 100) a resized image is saved at.
+    pub quality:  u8,
+}
+
+/// 600 × 900 stays sharp in the detail panel on a 2× screen.
+const COVER: ImageProfile = ImageProfile { max_size: Some((600, 900)), crop: true, quality: 85 };
+/// Screenshots are looked at full screen.
+const SCREENSHOT: ImageProfile = ImageProfile { max_size: None, crop: false, quality: 92 };
+
+pub fn profile(kind: ImageKind) -> &'static ImageProfile {
+    match kind {
+        ImageKind::Cover      => &COVER,
+        ImageKind::Screenshot => &SCREENSHOT,
+    }
+}
+
+/// Bring the image at `path` within `profile`, saving the result as a JPEG
+/// beside it in place of the original. Returns where the image is now. Small
+/// images are never scaled up, only cropped to shape.
+fn apply_profile(path: &Path, profile: &ImageProfile) -> Result<PathBuf, ImageError> {
+    if profile.max_size.is_none() {
+        return Ok(path.to_path_buf());
+    }
+    let Some(resized) = shape(decode(&path.to_string_lossy())?, profile) else { return Ok(path.to_path_buf()) };
+    let jpeg = path.with_extension("jpg");
+    save_jpeg(&jpeg, resized, profile.quality)?;
+    if jpeg != path {
+        fs::remove_file(path)?;
+    }
+    Ok(jpeg)
+}
+
+/// `image` brought within `profile`; None if it already is.
+fn shape(image: RgbaImage, profile: &ImageProfile) -> Option<RgbaImage> {
+    let (max_width, max_height) = profile.max_size?;
+    let (width, height) = image.dimensions();
+    if profile.crop {
+        let scale = (width as f32 / max_width as f32).min(height as f32 / max_height as f32).min(1.0);
+        let target = (((max_width as f32 * scale).round() as u32).max(1), ((max_height as f32 * scale).round() as u32).max(1));
+        (target != (width, height)).then(|| fill(&image, target.0, target.1))
+    } else if width > max_width || height > max_height {
+        Some(DynamicImage::ImageRgba8(image).resize(max_width, max_height, FilterType::Lanczos3).into_rgba8())
+    } else {
+        None
+    }
+}
+
+fn save_jpeg(path: &Path, image: RgbaImage, quality: u8) -> Result<(), ImageError> {
+    let rgb = DynamicImage::ImageRgba8(image).into_rgb8();
+    JpegEncoder::new_with_quality(io::BufWriter::new(fs::File::create(path)?), quality)
+        .encode_image(&rgb)
+        .map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// The largest width or height `transform` will produce, in pixels.
+const MAX_SIDE: u32 = 16_384;
+
+/// Crop `stored` to `crop` (in the image's own pixels), then scale it to
+/// `size` — cropping evenly if the shapes differ. The result is saved as a new
+/// image, like a cover; the original is left alone, since a game may still
+/// use it. Returns the new image's absolute path.
+pub fn transform(app: &AppHandle, stored: &str, crop: Option<CropRect>, size: Option<ImageSize>) -> CmdResult<String> {
+    let mut image = decode(stored)?;
+    if let Some(crop) = crop {
+        let inside = u64::from(crop.x) + u64::from(crop.width) <= u64::from(image.width())
+            && u64::from(crop.y) + u64::from(crop.height) <= u64::from(image.height());
+        if crop.width == 0 || crop.height == 0 || !inside {
+            return Err(CommandError::validation(
+                "crop_rect",
+                format!("must be a non-empty area within the {} × {} image", image.width(), image.height()),
+            ));
+        }
+        image = imageops::crop_imm(&image, crop.x, crop.y, crop.width, crop.height).to_image();
+    }
+    if let Some(size) = size {
+        if !(1..=MAX_SIDE).contains(&size.width) || !(1..=MAX_SIDE).contains(&size.height) {
+            return Err(CommandError::validation("target_size", format!("sides must be between 1 and {MAX_SIDE} pixels")));
+        }
+        if (size.width, size.height) != image.dimensions() {
+            image = fill(&image, size.width, size.height);
+        }
+    }
+
+    let dest = get_images_dir(app)?.join(format!("{}.jpg", Uuid::new_v4()));
+    save_jpeg(&dest, image, COVER.quality)?;
+    info!(source = stored, saved = %dest.display(), "Transformed image");
+    dest.to_str()
+        .map(str::to_string)
+        .ok_or_else(|| ImageError::InvalidPath("Invalid UTF-8 in path".to_string()).into())
+}
+
+/// Scale `image` to cover `width` × `height` and crop the overflow evenly.
+pub fn fill(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let scale = (width as f32 / image.width() as f32).max(height as f32 / image.height() as f32);
+    let scaled_width = ((image.width() as f32 * scale).ceil() as u32).max(width);
+    let scaled_height = ((image.height() as f32 * scale).ceil() as u32).max(height);
+    let scaled = imageops::resize(image, scaled_width, scaled_height, FilterType::Lanczos3);
+    imageops::crop_imm(&scaled, (scaled_width - width) / 2, (scaled_height - height) / 2, width, height).to_image()
+}
+
+// ---------------------------------------------------------------------------
+// Animation
+// ---------------------------------------------------------------------------
+
+/// Animated images larger than this are turned away; an animated cover is
+/// loaded for every card it's on.
+const MAX_ANIMATED_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_FRAMES: usize = 1000;
+
+/// The frames of a GIF or animated WebP; None for anything else.
+fn frames(path: &Path) -> Result<Option<Frames<'static>>, ImageError> {
+    let format = image::ImageReader::open(path)?.with_guessed_format()?.format();
+    let reader = io::BufReader::new(fs::File::open(path)?);
+    let decode_error = |e: image::ImageError| ImageError::Decode(format!("{}: {e}", path.display()));
+    Ok(match format {
+        Some(ImageFormat::Gif) => Some(GifDecoder::new(reader).map_err(decode_error)?.into_frames()),
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(reader).map_err(decode_error)?;
+            decoder.has_animation().then(|| decoder.into_frames())
+        }
+        _ => None,
+    })
+}
+
+/// How many frames the image at `path` has if it's animated (a GIF or WebP
+/// with more than one); None for a still image. An animation is checked
+/// frame by frame, and turned away if one doesn't decode or it's bigger than
+/// `MAX_ANIMATED_BYTES` or `MAX_FRAMES`.
+fn animation_frames(path: &Path) -> Result<Option<usize>, ImageError> {
+    let Some(frames) = frames(path)? else { return Ok(None) };
+    let mut count = 0;
+    for frame in frames {
+        frame.map_err(|e| ImageError::Decode(format!("{}: {e}", path.display())))?;
+        count += 1;
+        if count == 2 {
+            let bytes = fs::metadata(path)?.len();
+            if bytes > MAX_ANIMATED_BYTES {
+                return Err(ImageError::Rejected(format!(
+                    "Animated images can be at most {} MB; this one is {:.1} MB",
+                    MAX_ANIMATED_BYTES / (1024 * 1024),
+                    bytes as f64 / (1024.0 * 1024.0)
+                )));
+            }
+        }
+        if count > MAX_FRAMES {
+            return Err(ImageError::Rejected(format!("Animated images can have at most {MAX_FRAMES} frames")));
+        }
+    }
+    Ok((count > 1).then_some(count))
+}
+
+/// Whether a stored image is an animation. Only the first two frames are
+/// read.
+pub fn is_animated(stored: &str) -> bool {
+    let path = PathBuf::from(resolve(stored.to_string()));
+    frames(&path).ok().flatten().is_some_and(|frames| frames.take(2).count() > 1)
+}
+
+/// Save an animated cover's first frame beside it as a still JPEG
+/// ("…-poster.jpg"), shaped like any cover, for list views. Returns the
+/// poster's stored form.
+pub fn make_poster(stored: &str) -> Result<String, ImageError> {
+    let path = PathBuf::from(resolve(stored.to_string()));
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("cover");
+    let poster = path.with_file_name(format!("{stem}-poster.jpg"));
+    let first = decode(stored)?;
+    let first = match shape(first.clone(), &COVER) {
+        Some(shaped) => shaped,
+        None => first,
+    };
+    save_jpeg(&poster, first, COVER.quality)?;
+    Ok(to_stored(&poster.to_string_lossy()))
+}
+
+// ---------------------------------------------------------------------------
+// Colours & placeholders
+// ---------------------------------------------------------------------------
+
+/// What's worked out from a cover when it's set (see `cover_details`).
+pub struct CoverDetails {
+    pub dominant: String,
+    pub accent:   String,
+    pub blurhash: Option<String>,
+}
+
+/// A cover's colours and blurhash, from one decode.
+pub fn cover_details(stored: &str) -> Result<CoverDetails, ImageError> {
+    let image = decode(stored)?;
+    let (dominant, accent) = cover_colors(&image);
+    Ok(CoverDetails { dominant, accent, blurhash: blurhash(&image) })
+}
+
+/// A cover's dominant colour — the one most of it is — and an accent — the
+/// vivid colour that stands out most — each as '#rrggbb'. A cover with no
+/// vivid colour uses the dominant one for both.
+///
+/// Colours are counted on a 64 × 64 thumbnail in buckets of 16 shades per
+/// channel; each bucket's colour is the average of the pixels in it.
+pub fn cover_colors(image: &RgbaImage) -> (String, String) {
+    let thumbnail = imageops::thumbnail(image, 64, 64);
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+    for pixel in thumbnail.pixels().filter(|p| p[3] >= 128) {
+        let (count, sum) = buckets.entry([pixel[0] >> 4, pixel[1] >> 4, pixel[2] >> 4]).or_default();
+        *count += 1;
+        for i in 0..3 {
+            sum[i] += u32::from(pixel[i]);
+        }
+    }
+    let colors: Vec<(u32, [u8; 3])> = buckets
+        .into_values()
+        .map(|(count, sum)| (count, sum.map(|channel| (channel / count) as u8)))
+        .collect();
+
+    let dominant = colors.iter().max_by_key(|(count, _)| *count).map(|(_, rgb)| *rgb).unwrap_or([0, 0, 0]);
+    // Vivid: saturated and bright — a dark navy is neither an accent nor
+    // readable on the dark theme.
+    let vividness = |rgb: &[u8; 3]| {
+        let (max, min) = (*rgb.iter().max().unwrap_or(&0), *rgb.iter().min().unwrap_or(&0));
+        let saturation = if max == 0 { 0.0 } else { f32::from(max - min) / f32::from(max) };
+        if saturation < 0.35 || max < 100 { 0.0 } else { saturation }
+    };
+    let accent = colors
+        .iter()
+        .map(|(count, rgb)| (*count as f32 * vividness(rgb), rgb))
+        .filter(|(score, _)| *score > 0.0)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(dominant, |(_, rgb)| *rgb);
+
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+    (hex(dominant), hex(accent))
+}
+
+/// A blurhash of `image` (see blurha.sh): a ~30 character string the
+/// frontend decodes into a blurry stand-in while the real image loads.
+/// Worked out from a 32 px thumbnail, since only the broad shapes survive
+/// anyway; 4 × 3 components along the longer side.
+pub fn blurhash(image: &RgbaImage) -> Option<String> {
+    let scale = 32.0 / image.width().max(image.height()).max(1) as f32;
+    let (width, height) = (
+        ((image.width() as f32 * scale).round() as u32).max(1),
+        ((image.height() as f32 * scale).round() as u32).max(1),
+    );
+    let thumbnail = imageops::thumbnail(image, width, height);
+    let (x, y) = if width >= height { (4, 3) } else { (3, 4) };
+    blurhash::encode(x, y, width, height, thumbnail.as_raw()).ok()
+}
+
+/// Work out the colours and blurhashes of images added before they were
+/// kept. Runs at startup; the database is only locked to read the lists and
+/// save each result, not while images are decoded. Unreadable images are
+/// skipped (and tried again next time).
+pub fn fill_image_details(db: &Mutex<Connection>, job: &JobHandle) -> CmdResult<usize> {
+    let covers = db::covers_without_details(&*db::lock(db)?)?;
+    let screenshots = db::screenshots_without_blurhash(&*db::lock(db)?)?;
+    let total = covers.len() + screenshots.len();
+    let mut filled = 0;
+    for (i, (id, cover)) in covers.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, total, "Reading covers");
+        let Ok(details) = cover_details(cover) else { continue };
+        db::set_cover_details(&*db::lock(db)?, *id, cover, &details)?;
+        filled += 1;
+    }
+    for (i, (id, path)) in screenshots.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(covers.len() + i, total, "Reading screenshots");
+        let Some(blurhash) = decode(path).ok().and_then(|image| blurhash(&image)) else { continue };
+        db::set_screenshot_blurhash(&*db::lock(db)?, *id, &blurhash)?;
+        filled += 1;
+    }
+    if filled > 0 {
+        info!(filled, "Filled in image colours and placeholders");
+    }
+    Ok(filled)
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+/// Decode a stored image (a file name in the images folder, or a path) to
+/// RGBA pixels. The format is read from the file itself, since a downloaded
+/// cover's extension is only a guess.
+pub fn decode(stored: &str) -> Result<RgbaImage, ImageError> {
+    let path = resolve(stored.to_string());
+    let decoded = image::ImageReader::open(&path)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| ImageError::Decode(format!("{path}: {e}")))?;
+    Ok(decoded.into_rgba8())
+}
+
+/// SHA-256 of a stored image's (or any local file's) bytes, as hex. Two
+/// screenshots with the same hash are the same picture, whatever their names.
+pub fn content_hash(stored: &str) -> Result<String, ImageError> {
+    Ok(bytes_hash(&fs::read(resolve(stored.to_string()))?))
+}
+
+/// Hex SHA-256 of `bytes`, as `content_hash` gives for a file.
+pub fn bytes_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// ---------------------------------------------------------------------------
+// Re-linking
+// ---------------------------------------------------------------------------
+
+/// Fix stored paths that point at a file that isn't there (typically the old
+/// images folder after moving app data to a new machine): if a file with the
+/// same name is in `images_dir`, store that name instead. Paths that still
+/// work are left alone; files found nowhere are reported.
+pub fn relink(conn: &Connection, images_dir: &Path) -> CmdResult<RelinkReport> {
+    let refs = db::get_image_refs(conn)?;
+    let mut report = RelinkReport { checked: refs.len(), ..RelinkReport::default() };
+
+    let tx = conn.unchecked_transaction()?;
+    for image in refs {
+        if Path::new(&resolve(image.path.clone())).exists() {
+            continue;
+        }
+        let name = file_name(&image.path);
+        if name.is_empty() || !images_dir.join(name).is_file() {
+            report.missing.push(image);
+            continue;
+        }
+        db::set_image_path(&tx, &image, name)?;
+        report.relinked += 1;
+        if report.updated_ids.last() != Some(&image.game_id) {
+            report.updated_ids.push(image.game_id);
+        }
+    }
+    tx.commit()?;
+
+    info!(checked = report.checked, relinked = report.relinked, missing = report.missing.len(), "Re-linked images");
+    Ok(report)
+}
+
+/// Rewrite absolute paths saved by older versions to bare file names: those
+/// inside `images_dir`, and missing ones whose file is found there. Safe to
+/// run on every start; returns how many paths changed.
+pub fn store_relative(conn: &Connection, images_dir: &Path) -> CmdResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut changed = 0;
+    for image in db::get_image_refs(&tx)? {
+        if is_file_name(&image.path) {
+            continue;
+        }
+        let path = Path::new(&image.path);
+        let name = file_name(&image.path);
+        let inside = path.parent() == Some(images_dir);
+        if name.is_empty() || !(inside || (!path.exists() && images_dir.join(name).is_file())) {
+            continue;
+        }
+        db::set_image_path(&tx, &image, name)?;
+        changed += 1;
+    }
+    tx.commit()?;
+    if changed > 0 {
+        info!(changed, "Stored image paths relative to the images folder");
+    }
+    Ok(changed)
+}
+
+/// The last component of a stored path. Splits on either separator: the path
+/// may come from another OS.
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Eviction
+// ---------------------------------------------------------------------------
+
+/// Images saved more recently than this are left alone: `process_cover_image`
+/// hands back a path before the game that uses it has been saved.
+const EVICTION_GRACE: Duration = Duration::from_secs(24 * 3600);
+
+/// Bring the images folder within the `max_images_mb` setting (nothing to do
+/// if it's 0).
+pub fn evict_unused(app: &AppHandle, db: &Mutex<Connection>) -> CmdResult<ImageEvictionReport> {
+    let (cap_mb, in_use) = {
+        let conn = db::lock(db)?;
+        (db::get_settings(&conn)?.max_images_mb, db::get_image_file_names(&conn)?)
+    };
+    if cap_mb == 0 {
+        return Ok(ImageEvictionReport::default());
+    }
+    Ok(evict(&get_images_dir(app)?, &in_use, u64::from(cap_mb) * 1024 * 1024)?)
+}
+
+/// How many files are in `images_dir` and how many bytes they take.
+pub fn folder_usage(images_dir: &Path) -> Result<(usize, u64), ImageError> {
+    let mut usage = (0, 0);
+    for entry in fs::read_dir(images_dir)? {
+        let meta = entry?.metadata()?;
+        if meta.is_file() {
+            usage.0 += 1;
+            usage.1 += meta.len();
+        }
+    }
+    Ok(usage)
+}
+
+/// Delete unused images from `images_dir`, oldest first, until the folder is
+/// within `cap_bytes`. `in_use` holds the file names the library references.
+/// If the images in use alone exceed the cap, the report says by how much.
+pub fn evict(images_dir: &Path, in_use: &HashSet<String>, cap_bytes: u64) -> Result<ImageEvictionReport, ImageError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(images_dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            files.push((entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+        }
+    }
+    let total_bytes: u64 = files.iter().map(|(_, size, _)| size).sum();
+    let mut report = ImageEvictionReport { total_bytes, cap_bytes, remaining_bytes: total_bytes, ..Default::default() };
+
+    let now = SystemTime::now();
+    let mut unused: Vec<_> = files
+        .into_iter()
+        .filter(|(path, _, modified)| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            !in_use.contains(name) && now.duration_since(*modified).unwrap_or_default() >= EVICTION_GRACE
+        })
+        .collect();
+    unused.sort_by_key(|(_, _, modified)| *modified);
+
+    for (path, size, _) in unused {
+        if report.remaining_bytes <= cap_bytes {
+            break;
+        }
+        fs::remove_file(&path)?;
+        report.remaining_bytes -= size;
+        report.freed_bytes += size;
+        report.removed.push(path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string());
+    }
+    report.over_cap_bytes = report.remaining_bytes.saturating_sub(cap_bytes);
+
+    info!(removed = report.removed.len(), freed = report.freed_bytes, over_cap = report.over_cap_bytes, "Evicted images");
+    if report.over_cap_bytes > 0 {
+        warn!(over_cap = report.over_cap_bytes, "Images in use exceed the size cap");
+    }
+    Ok(report)
+}
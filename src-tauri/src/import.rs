@@ -0,0 +1,254 @@
+// import.rs — Turn data from outside the app into GameInputs.
+//
+// Importers only parse and map; the commands in commands.rs do the network
+// lookups, image downloads and database writes, the same way export.rs leaves
+// file I/O to its callers.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::error::{CmdResult, CommandError};
+use crate::models::{ConflictStrategy, FieldConflict, Game, GameInput, GameStatus, TitleImportEntry};
+use crate::titles;
+
+// ---------------------------------------------------------------------------
+// Plain title lists
+// ---------------------------------------------------------------------------
+
+/// Split pasted text into titles: one per line, blank lines and `#` comments
+/// skipped, list bullets ("- ", "* ") stripped, duplicates (by normalized
+/// title) dropped.
+pub fn parse_title_list(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.lines()
+        .map(|line| line.trim())
+        .map(|line| line.trim_start_matches(['-', '*', '•']).trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| seen.insert(titles::normalize(line)))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build a Backlog entry from a previewed line and its (already saved) cover.
+pub fn title_entry_to_input(entry: TitleImportEntry, platform: &str, cover_art_path: Option<String>) -> GameInput {
+    let meta = entry.metadata;
+    GameInput {
+        title:                 meta.as_ref().map(|m| m.title.clone()).unwrap_or(entry.query),
+        franchise:             None,
+        sequence_in_franchise: None,
+        release_date:          meta.as_ref().and_then(|m| m.release_date.clone()),
+        platform:              platform.to_string(),
+        status:                GameStatus::Backlog,
+        progress_percent:      None,
+        playtime_minutes:      None,
+        rating:                None,
+        notes:                 None,
+        cover_art_path,
+        screenshots:           vec![],
+        developer:             meta.as_ref().and_then(|m| m.developer.clone()),
+        publisher:             meta.as_ref().and_then(|m| m.publisher.clone()),
+        genres:                meta.map(|m| m.genres).unwrap_or_default(),
+        controller_support:    None,
+        input_method:          None,
+        max_local_players:     None,
+        max_online_players:    None,
+        coop_mode:             None,
+        original_title:        None,
+        sort_title:            None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Account syncs (PSN, Xbox, …)
+// ---------------------------------------------------------------------------
+
+/// A bare entry for a title found on an external account but not yet in the library.
+pub fn remote_title_input(title: &str, platform: &str, status: GameStatus) -> GameInput {
+    GameInput {
+        title:                 title.trim().to_string(),
+        franchise:             None,
+        sequence_in_franchise: None,
+        release_date:          None,
+        platform:              platform.to_string(),
+        status,
+        progress_percent:      None,
+        playtime_minutes:      None,
+        rating:                None,
+        notes:                 None,
+        cover_art_path:        None,
+        screenshots:           vec![],
+        developer:             None,
+        publisher:             None,
+        genres:                vec![],
+        controller_support:    None,
+        input_method:          None,
+        max_local_players:     None,
+        max_online_players:    None,
+        coop_mode:             None,
+        original_title:        None,
+        sort_title:            None,
+    }
+}
+
+/// Status for a title that isn't in the library yet, from its achievement progress.
+pub fn status_from_progress(percent: u32) -> GameStatus {
+    match percent {
+        100.. => GameStatus::Completed,
+        0     => GameStatus::NotStarted,
+        _     => GameStatus::Playing,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Conflicts with games already in the library
+// ---------------------------------------------------------------------------
+
+/// What to save over `existing` when an import matches it, or None if the
+/// strategy leaves it alone (Skip, Duplicate) or nothing would change.
+pub fn merge_input(existing: &Game, incoming: GameInput, strategy: ConflictStrategy) -> Option<GameInput> {
+    let current = game_to_input(existing);
+    let overwrite = match strategy {
+        ConflictStrategy::Overwrite => true,
+        ConflictStrategy::FillEmpty => false,
+        ConflictStrategy::Skip | ConflictStrategy::Duplicate => return None,
+    };
+
+    // RUST NOTE: a closure can't be generic, so these are small generic fns.
+    // `pick` chooses per field: the import's value first when overwriting,
+    // the library's value first when only filling gaps.
+    fn pick<T>(overwrite: bool, current: Option<T>, incoming: Option<T>) -> Option<T> {
+        if overwrite { incoming.or(current) } else { current.or(incoming) }
+    }
+    fn pick_list<T>(overwrite: bool, current: Vec<T>, incoming: Vec<T>) -> Vec<T> {
+        match (overwrite, current.is_empty(), incoming.is_empty()) {
+            (true, _, false) | (false, true, _) => incoming,
+            _ => current,
+        }
+    }
+
+    let merged = GameInput {
+        // Title, platform and status are always set, so only Overwrite changes them.
+        title:                 if overwrite { incoming.title } else { current.title.clone() },
+        platform:              if overwrite { incoming.platform } else { current.platform.clone() },
+        status:                if overwrite { incoming.status } else { current.status.clone() },
+        franchise:             pick(overwrite, current.franchise.clone(), incoming.franchise),
+        sequence_in_franchise: pick(overwrite, current.sequence_in_franchise, incoming.sequence_in_franchise),
+        release_date:          pick(overwrite, current.release_date.clone(), incoming.release_date),
+        progress_percent:      pick(overwrite, current.progress_percent, incoming.progress_percent),
+        playtime_minutes:      pick(overwrite, current.playtime_minutes, incoming.playtime_minutes),
+        rating:                pick(overwrite, current.rating, incoming.rating),
+        notes:                 pick(overwrite, current.notes.clone(), incoming.notes),
+        cover_art_path:        pick(overwrite, current.cover_art_path.clone(), incoming.cover_art_path),
+        screenshots:           pick_list(overwrite, current.screenshots.clone(), incoming.screenshots),
+        developer:             pick(overwrite, current.developer.clone(), incoming.developer),
+        publisher:             pick(overwrite, current.publisher.clone(), incoming.publisher),
+        genres:                pick_list(overwrite, current.genres.clone(), incoming.genres),
+        controller_support:    pick(overwrite, current.controller_support, incoming.controller_support),
+        input_method:          pick(overwrite, current.input_method, incoming.input_method),
+        max_local_players:     pick(overwrite, current.max_local_players, incoming.max_local_players),
+        max_online_players:    pick(overwrite, current.max_online_players, incoming.max_online_players),
+        coop_mode:             pick(overwrite, current.coop_mode, incoming.coop_mode),
+        original_title:        pick(overwrite, current.original_title.clone(), incoming.original_title),
+        sort_title:            pick(overwrite, current.sort_title.clone(), incoming.sort_title),
+    };
+    (merged != current).then_some(merged)
+}
+
+/// The editable part of a game, e.g. to save it back with a few fields changed.
+pub fn game_to_input(game: &Game) -> GameInput {
+    GameInput {
+        title:                 game.title.clone(),
+        franchise:             game.franchise.clone(),
+        sequence_in_franchise: game.sequence_in_franchise,
+        release_date:          game.release_date.clone(),
+        platform:              game.platform.clone(),
+        status:                game.status.clone(),
+        progress_percent:      game.progress_percent,
+        playtime_minutes:      game.playtime_minutes,
+        rating:                game.rating,
+        notes:                 game.notes.clone(),
+        cover_art_path:        game.cover_art_path.clone(),
+        screenshots:           game.screenshots.clone(),
+        developer:             game.developer.clone(),
+        publisher:             game.publisher.clone(),
+        genres:                game.genres.clone(),
+        controller_support:    game.controller_support,
+        input_method:          game.input_method,
+        max_local_players:     game.max_local_players,
+        max_online_players:    game.max_online_players,
+        coop_mode:             game.coop_mode,
+        original_title:        game.original_title.clone(),
+        sort_title:            game.sort_title.clone(),
+    }
+}
+
+/// Fields `local` and `remote` both set, to different values — what a merge
+/// that keeps `local` would otherwise lose without a trace.
+pub fn conflicting_fields(local: &GameInput, remote: &GameInput) -> Vec<FieldConflict> {
+    let (Ok(Value::Object(local)), Ok(Value::Object(remote))) = (serde_json::to_value(local), serde_json::to_value(remote)) else {
+        return vec![];
+    };
+    let is_empty = |v: &Value| match v {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(a) => a.is_empty(),
+        _ => false,
+    };
+    local
+        .into_iter()
+        .filter_map(|(field, local)| {
+            let remote = remote.get(&field)?;
+            (!is_empty(&local) && !is_empty(remote) && local != *remote)
+                .then(|| FieldConflict { field, local, remote: remote.clone() })
+        })
+        .collect()
+}
+
+/// Game flags set outside GameInput (`set_archived`, `toggle_favorite`) that
+/// are stamped and merged like its fields.
+pub const FLAGS: &[&str] = &["archived", "favorite"];
+
+/// The value of one of the FLAGS on `game`.
+pub fn flag(game: &Game, name: &str) -> bool {
+    match name {
+        "archived" => game.archived,
+        "favorite" => game.favorite,
+        _ => false,
+    }
+}
+
+/// The FLAGS whose values differ between `before` and `after`.
+pub fn changed_flags(before: &Game, after: &Game) -> Vec<String> {
+    FLAGS.iter().filter(|name| flag(before, name) != flag(after, name)).map(|name| name.to_string()).collect()
+}
+
+/// The GameInput keys whose values differ between `before` and `after`.
+pub fn changed_fields(before: &GameInput, after: &GameInput) -> Vec<String> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) = (serde_json::to_value(before), serde_json::to_value(after)) else {
+        return vec![];
+    };
+    before.into_iter().filter(|(field, value)| after.get(field) != Some(value)).map(|(field, _)| field).collect()
+}
+
+/// `into` with `fields` set to `from`'s values. Names GameInput doesn't
+/// have, such as the FLAGS, are skipped.
+pub fn copy_fields(into: &GameInput, from: &GameInput, fields: &[String]) -> CmdResult<GameInput> {
+    let from = serde_json::to_value(from).map_err(|e| CommandError::Internal(e.to_string()))?;
+    let mut value = serde_json::to_value(into).map_err(|e| CommandError::Internal(e.to_string()))?;
+    for field in fields {
+        if let Some(from) = from.get(field.as_str()) {
+            value[field.as_str()] = from.clone();
+        }
+    }
+    serde_json::from_value(value).map_err(|e| CommandError::Internal(e.to_string()))
+}
+
+/// `input` with the given fields replaced, e.g. the remote side of conflicts.
+pub fn with_fields(input: &GameInput, fields: &[FieldConflict]) -> CmdResult<GameInput> {
+    let mut value = serde_json::to_value(input).map_err(|e| CommandError::Internal(e.to_string()))?;
+    for conflict in fields {
+        value[conflict.field.as_str()] = conflict.remote.clone();
+    }
+    serde_json::from_value(value).map_err(|e| CommandError::Internal(format!("Can't apply remote values: {e}")))
+}
@@ -0,0 +1,283 @@
+// installs.rs — Find games installed through Steam, Epic and GOG.
+//
+// Each launcher leaves a record of what it installed where:
+//   Steam — steamapps/libraryfolders.vdf lists library folders; each library
+//           has one steamapps/appmanifest_<appid>.acf per installed game.
+//   Epic  — one JSON .item manifest per game in the launcher's Manifests folder
+//           (or, on Linux, Heroic/Legendary's installed.json).
+//   GOG   — the registry on Windows, plus a goggame-<id>.info file in every
+//           install folder (which also covers Linux/Heroic installs).
+//
+// Scanning never changes the library on its own; `db::replace_installs`
+// stores what was found and the command reports installs with no matching game.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::models::DetectedInstall;
+use crate::steam;
+
+/// Everything installed through any supported launcher.
+pub fn scan(steam_path: Option<&str>) -> Vec<DetectedInstall> {
+    let mut found = scan_steam(steam_path);
+    found.extend(scan_epic());
+    found.extend(scan_gog());
+    debug!(count = found.len(), "Scanned launchers for installed games");
+    found
+}
+
+// ---------------------------------------------------------------------------
+// Steam
+// ---------------------------------------------------------------------------
+
+fn scan_steam(steam_path: Option<&str>) -> Vec<DetectedInstall> {
+    let Some(root) = steam::steam_roots(steam_path).into_iter().find(|r| r.join("steamapps").is_dir()) else {
+        return vec![];
+    };
+
+    // The main install is always a library, even if libraryfolders.vdf is missing.
+    let mut libraries = vec![root.clone()];
+    if let Ok(text) = fs::read_to_string(root.join("steamapps/libraryfolders.vdf")) {
+        for (key, value) in vdf_pairs(&text) {
+            // New format: "path" "D:\\SteamLibrary"; old format: "1" "D:\\SteamLibrary".
+            if key == "path" || key.parse::<u32>().is_ok() && value.contains(['/', '\\']) {
+                let library = PathBuf::from(value);
+                if !libraries.contains(&library) {
+                    libraries.push(library);
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for library in libraries {
+        let steamapps = library.join("steamapps");
+        let Ok(entries) = fs::read_dir(&steamapps) else { continue };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            let is_manifest = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"));
+            if !is_manifest {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            let fields: HashMap<String, String> = vdf_pairs(&text).into_iter().collect();
+            let (Some(app_id), Some(name)) = (fields.get("appid"), fields.get("name")) else { continue };
+            // Steam's own runtimes and redistributables aren't games.
+            if name.starts_with("Steamworks") || name.starts_with("Proton") || name.starts_with("Steam Linux Runtime") {
+                continue;
+            }
+            found.push(DetectedInstall {
+                launcher:     steam::SOURCE.to_string(),
+                external_id:  app_id.clone(),
+                title:        name.clone(),
+                install_path: steamapps.join("common").join(fields.get("installdir").map_or("", String::as_str)).to_string_lossy().into_owned(),
+                size_bytes:   fields.get("SizeOnDisk").and_then(|s| s.parse().ok()),
+                game_id:      None,
+            });
+        }
+    }
+    found
+}
+
+/// Every `"key" "value"` pair in a Valve KeyValues (VDF/ACF) file, in order.
+/// Nesting is ignored — the few keys we need are unique enough without it.
+fn vdf_pairs(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut quoted = line.split('"').skip(1).step_by(2);
+            let key = quoted.next()?;
+            let value = quoted.next()?;
+            Some((key.to_string(), value.replace("\\\\", "\\")))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Epic
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EpicManifest {
+    app_name:            String,
+    display_name:        String,
+    install_location:    String,
+    install_size:        Option<u64>,
+    main_game_app_name:  Option<String>,
+    #[serde(default)]
+    b_is_incomplete_install: bool,
+}
+
+#[derive(Deserialize)]
+struct LegendaryInstall {
+    title:        String,
+    install_path: String,
+    install_size: Option<u64>,
+    #[serde(default)]
+    is_dlc:       bool,
+}
+
+fn scan_epic() -> Vec<DetectedInstall> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let mut found = Vec::new();
+
+    let manifest_dirs = [
+        PathBuf::from(r"C:\ProgramData\Epic\EpicGamesLauncher\Data\Manifests"),
+        home.join("Library/Application Support/Epic/EpicGamesLauncher/Data/Manifests"),
+    ];
+    for dir in manifest_dirs {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            if path.extension().and_then(|e| e.to_str()) != Some("item") {
+                continue;
+            }
+            let Some(m) = fs::read_to_string(&path).ok().and_then(|t| serde_json::from_str::<EpicManifest>(&t).ok()) else {
+                continue;
+            };
+            // DLC manifests point at their base game.
+            let is_dlc = m.main_game_app_name.as_deref().is_some_and(|main| main != m.app_name);
+            if is_dlc || m.b_is_incomplete_install {
+                continue;
+            }
+            found.push(DetectedInstall {
+                launcher:     "Epic".to_string(),
+                external_id:  m.app_name,
+                title:        m.display_name,
+                install_path: m.install_location,
+                size_bytes:   m.install_size,
+                game_id:      None,
+            });
+        }
+    }
+
+    // Heroic (Linux / macOS) installs Epic games through Legendary.
+    let legendary = [
+        home.join(".config/heroic/legendaryConfig/legendary/installed.json"),
+        home.join(".config/legendary/installed.json"),
+    ];
+    if let Some(installed) = legendary
+        .iter()
+        .find_map(|p| serde_json::from_str::<HashMap<String, LegendaryInstall>>(&fs::read_to_string(p).ok()?).ok())
+    {
+        for (app_name, install) in installed.into_iter().filter(|(_, i)| !i.is_dlc) {
+            found.push(DetectedInstall {
+                launcher:     "Epic".to_string(),
+                external_id:  app_name,
+                title:        install.title,
+                install_path: install.install_path,
+                size_bytes:   install.install_size,
+                game_id:      None,
+            });
+        }
+    }
+    found
+}
+
+// ---------------------------------------------------------------------------
+// GOG
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GogInfo {
+    game_id:      String,
+    name:         String,
+    root_game_id: Option<String>,
+}
+
+fn scan_gog() -> Vec<DetectedInstall> {
+    let mut by_id: HashMap<String, (String, PathBuf)> = gog_registry();
+
+    let home = dirs::home_dir().unwrap_or_default();
+    let folders = [
+        PathBuf::from(r"C:\GOG Games"),
+        PathBuf::from(r"C:\Program Files (x86)\GOG Galaxy\Games"),
+        home.join("GOG Games"),
+        home.join("Games/Heroic"),
+    ];
+    for folder in folders {
+        let Ok(entries) = fs::read_dir(&folder) else { continue };
+        for dir in entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_dir()) {
+            if let Some(info) = gog_info(&dir) {
+                by_id.entry(info.game_id).or_insert((info.name, dir));
+            }
+        }
+    }
+
+    by_id
+        .into_iter()
+        .map(|(id, (name, path))| DetectedInstall {
+            launcher:     "GOG".to_string(),
+            external_id:  id,
+            title:        name,
+            size_bytes:   Some(dir_size(&path)),
+            install_path: path.to_string_lossy().into_owned(),
+            game_id:      None,
+        })
+        .collect()
+}
+
+/// The base game's goggame-<id>.info in an install folder (DLCs have their own
+/// files with a `rootGameId` pointing elsewhere).
+fn gog_info(dir: &Path) -> Option<GogInfo> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("goggame-") && n.ends_with(".info"))
+        })
+        .filter_map(|p| serde_json::from_str::<GogInfo>(&fs::read_to_string(p).ok()?).ok())
+        .find(|info| info.root_game_id.as_deref().is_none_or(|root| root == info.game_id))
+}
+
+/// GOG Galaxy's registry entries (Windows only). Reading them through `reg
+/// query` avoids a Windows-only dependency; elsewhere the command just fails.
+fn gog_registry() -> HashMap<String, (String, PathBuf)> {
+    let Ok(output) = Command::new("reg")
+        .args(["query", r"HKLM\SOFTWARE\WOW6432Node\GOG.com\Games", "/s"])
+        .output()
+    else {
+        return HashMap::new();
+    };
+
+    let mut games = HashMap::new();
+    let (mut id, mut name, mut path) = (None, None, None);
+    for line in String::from_utf8_lossy(&output.stdout).lines().chain(std::iter::once("")) {
+        // Each game is a block of "    <value name>    REG_SZ    <data>" lines.
+        let parts: Vec<&str> = line.split("    ").map(str::trim).filter(|p| !p.is_empty()).collect();
+        match parts[..] {
+            ["gameID", "REG_SZ", v]   => id = Some(v.to_string()),
+            ["gameName", "REG_SZ", v] => name = Some(v.to_string()),
+            ["path", "REG_SZ", v]     => path = Some(PathBuf::from(v)),
+            _ if line.trim().is_empty() || line.starts_with("HKEY_") => {
+                if let (Some(i), Some(n), Some(p)) = (id.take(), name.take(), path.take()) {
+                    games.insert(i, (n, p));
+                }
+            }
+            _ => {}
+        }
+    }
+    games
+}
+
+/// Total size of the files under `path`. Unreadable entries count as 0.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else { return 0 };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(_) => e.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
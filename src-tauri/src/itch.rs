@@ -0,0 +1,164 @@
+// itch.rs — Import the games bought (or claimed) on itch.io.
+//
+// itch.io's server-side API answers to a personal API key the user creates
+// under Settings → API keys; `/profile/owned-keys` lists every download key
+// on the account, 50 to a page, each with the game it unlocks.
+//
+// Each game becomes (or is matched to) a library game linked through
+// `game_external_ids` (source "itch.io", id = the itch.io game id). The link
+// also records the game's page, where the purchase is downloaded from, and
+// marks the copy DRM-free — everything sold on itch.io is. New games get the
+// cover from the store page; assets, tools, soundtracks and the like are
+// skipped.
+
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::covers;
+use crate::credentials;
+use crate::db::{self, ImportOutcome};
+use crate::error::CmdResult;
+use crate::http;
+use crate::images;
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::models::{ConflictStrategy, GameStatus, ImageKind, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for itch.io rows in `game_external_ids`.
+pub const SOURCE: &str = "itch.io";
+
+const API_BASE: &str = "https://api.itch.io";
+
+/// Everything on itch.io is downloaded for a computer.
+const PLATFORM: &str = "PC";
+
+#[derive(Deserialize)]
+struct OwnedKeys {
+    #[serde(default)]
+    owned_keys: Vec<OwnedKey>,
+    #[serde(default)]
+    per_page:   usize,
+}
+
+#[derive(Deserialize)]
+struct OwnedKey {
+    game: ItchGame,
+}
+
+#[derive(Deserialize)]
+struct ItchGame {
+    id:             i64,
+    title:          String,
+    url:            Option<String>,
+    cover_url:      Option<String>,
+    #[serde(default)]
+    classification: String,   // "game", "tool", "assets", "soundtrack", …
+    user:           Option<ItchUser>,
+}
+
+#[derive(Deserialize)]
+struct ItchUser {
+    display_name: Option<String>,
+    username:     Option<String>,
+}
+
+fn owned_keys(api_key: &str, page: usize) -> CmdResult<OwnedKeys> {
+    let request = http::agent()
+        .get(&format!("{API_BASE}/profile/owned-keys"))
+        .query("page", &page.to_string())
+        .set("Authorization", &format!("Bearer {}", api_key.trim()));
+    http::read_json(request)
+}
+
+/// Every game on the account, in purchase order, without duplicates (a game
+/// bought twice, or in two bundles, has two keys).
+fn owned_games(api_key: &str) -> CmdResult<Vec<ItchGame>> {
+    let mut games: Vec<ItchGame> = Vec::new();
+    for page in 1.. {
+        let keys = owned_keys(api_key, page)?;
+        let last = keys.owned_keys.len() < keys.per_page.max(1);
+        for key in keys.owned_keys {
+            let game = key.game;
+            if (game.classification.is_empty() || game.classification == "game") && !games.iter().any(|g| g.id == game.id) {
+                games.push(game);
+            }
+        }
+        if last {
+            break;
+        }
+    }
+    Ok(games)
+}
+
+/// Import the account's games. Needs the `itch_api_key` credential. With
+/// `dry_run` nothing is saved and no covers are downloaded; the report's
+/// `preview` says what would change. `on_conflict` decides what happens to
+/// unlinked library games with the same title.
+pub fn sync(
+    app: &AppHandle,
+    db: &Mutex<Connection>,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let api_key = credentials::require(credentials::ITCH_API_KEY)?;
+    let games = owned_games(&api_key)?;
+
+    let mut report = SyncReport { checked: games.len(), ..SyncReport::default() };
+    let mut recorder = if dry_run { Some(PreviewRecorder::start(&*db::lock(db)?)?) } else { None };
+    for (i, game) in games.iter().enumerate() {
+        // Games already committed stay; cancelling only stops the rest.
+        job.check_cancelled()?;
+        job.progress(i, report.checked, game.title.as_str());
+        let external_id = game.id.to_string();
+
+        let mut input = import::remote_title_input(&game.title, PLATFORM, GameStatus::Backlog);
+        input.developer = game.user.as_ref().and_then(|u| u.display_name.clone().or_else(|| u.username.clone()));
+        // Only for games not linked yet — a linked game keeps the cover it has.
+        // Downloaded before locking so the app isn't blocked on the network; a
+        // cover that fails to download just leaves the game without one.
+        let linked = db::find_linked_game(&*db::lock(db)?, SOURCE, &external_id)?.is_some();
+        let mut cover = None;
+        if !linked && !dry_run {
+            input.cover_art_path = game.cover_url.as_deref().and_then(|url| images::process_image(app, url, ImageKind::Cover).ok());
+            // Remembered so a metadata refresh can pick up a changed cover.
+            cover = game.cover_url.as_deref().zip(input.cover_art_path.as_deref()).map(|(url, path)| covers::source(url, path));
+        }
+
+        let mut conn = db::lock(db)?;
+        let tx = conn.transaction()?;
+        let (game_id, outcome) = db::import_game(&tx, input, Some((SOURCE, external_id.as_str())), on_conflict)?;
+        db::set_external_page(&tx, game_id, SOURCE, game.url.as_deref(), true)?;
+        if let Some(cover) = &cover {
+            db::set_cover_source(&tx, game_id, cover)?;
+        }
+        match recorder.as_mut() {
+            // Dropping `tx` without committing rolls this game back.
+            Some(recorder) => recorder.record(&tx, game_id)?,
+            None => tx.commit()?,
+        }
+
+        match outcome {
+            ImportOutcome::Created => report.imported_ids.push(game_id),
+            ImportOutcome::Updated => report.updated_ids.push(game_id),
+            ImportOutcome::Unchanged => {}
+        }
+    }
+
+    info!(
+        checked = report.checked,
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        dry_run,
+        "itch.io sync finished"
+    );
+    Ok(match recorder {
+        Some(recorder) => report.into_dry_run(recorder.finish()),
+        None => report,
+    })
+}
@@ -0,0 +1,256 @@
+// jobs.rs — One queue for imports, syncs and other long tasks.
+//
+// Long work used to run straight on the blocking thread pool, so nothing
+// outside the calling command knew it was happening. Now it goes through a
+// single worker thread that runs jobs one at a time:
+//
+//   enqueue ──► jobs table (Queued) ──► worker runs it (Running)
+//                                          │  job.progress(…) ──► "jobs://updated"
+//                                          ▼
+//                              Succeeded / Failed / Cancelled
+//
+// Every status change is saved to the `jobs` table and broadcast as a
+// `jobs://updated` event. Progress is kept in memory (the job usually holds
+// the DB lock while it reports) and saved when the job finishes.
+//
+// Commands that used to return a report still do: they enqueue the work and
+// wait for it, so the frontend gets the same result plus a job it can watch
+// or cancel.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::events;
+use crate::models::{Job, JobStatus};
+use crate::ratelimit;
+
+/// Progress events are sent at most this often per job (plus the final one).
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+type Work = Box<dyn FnOnce(&JobHandle) -> Result<serde_json::Value, String> + Send>;
+
+/// A job that's queued or running.
+struct LiveJob {
+    job:       Mutex<Job>,
+    cancelled: AtomicBool,
+}
+
+type LiveJobs = Arc<Mutex<HashMap<i64, Arc<LiveJob>>>>;
+
+pub struct JobQueue {
+    app:    AppHandle,
+    db:     Arc<Mutex<Connection>>,
+    sender: Mutex<Sender<(Arc<LiveJob>, Work)>>,
+    live:   LiveJobs,
+}
+
+impl JobQueue {
+    /// Start the worker thread. Jobs a previous run of the app left behind
+    /// are marked failed first, since their work is gone.
+    pub fn start(app: AppHandle, db: Arc<Mutex<Connection>>) -> Arc<JobQueue> {
+        match db::lock(&db).and_then(|conn| Ok(db::fail_interrupted_jobs(&conn)?)) {
+            Ok(0) => {}
+            Ok(n) => info!(count = n, "Marked interrupted jobs as failed"),
+            Err(e) => warn!(error = %e, "Couldn't clean up interrupted jobs"),
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let live: LiveJobs = Arc::default();
+        let worker_db = Arc::clone(&db);
+        let worker_live = Arc::clone(&live);
+        let worker_app = app.clone();
+        thread::spawn(move || run_worker(worker_app, worker_db, worker_live, receiver));
+        Arc::new(JobQueue { app, db, sender: Mutex::new(sender), live })
+    }
+
+    /// Queue `work` under `kind` (e.g. "psn_sync"). The ticket can be waited
+    /// on for the result or dropped to let the job run in the background.
+    pub fn enqueue<T, F>(&self, kind: &str, work: F) -> CmdResult<JobTicket<T>>
+    where
+        T: Serialize + Send + 'static,
+        F: FnOnce(&JobHandle) -> CmdResult<T> + Send + 'static,
+    {
+        let job = db::add_job(&*db::lock(&self.db)?, kind)?;
+        let (result_tx, result_rx) = mpsc::channel();
+        let work: Work = Box::new(move |handle| {
+            let result = work(handle);
+            let summary = match &result {
+                Ok(value) => serde_json::to_value(value).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            // The caller may have stopped waiting; the job still finished.
+            let _ = result_tx.send(result);
+            summary
+        });
+
+        let live = Arc::new(LiveJob { job: Mutex::new(job.clone()), cancelled: AtomicBool::new(false) });
+        lock(&self.live)?.insert(job.id, Arc::clone(&live));
+        lock(&self.sender)?
+            .send((live, work))
+            .map_err(|_| CommandError::Internal("The job queue has stopped".to_string()))?;
+        events::job_updated(&self.app, &job);
+        info!(id = job.id, kind, "Job queued");
+        Ok(JobTicket { job, result: result_rx })
+    }
+
+    /// Ask a queued or running job to stop. A queued job never starts; a
+    /// running one stops at its next check. Returns false if the job had
+    /// already finished.
+    pub fn cancel(&self, id: i64) -> CmdResult<bool> {
+        let Some(live) = lock(&self.live)?.get(&id).cloned() else {
+            db::get_job(&*db::lock(&self.db)?, id)?;   // NotFound for unknown ids
+            return Ok(false);
+        };
+        live.cancelled.store(true, Ordering::Relaxed);
+        info!(id, "Job cancellation requested");
+        Ok(true)
+    }
+
+    /// Job history, newest first, with live progress for unfinished jobs.
+    pub fn jobs(&self, status: Option<JobStatus>, limit: u32) -> CmdResult<Vec<Job>> {
+        let mut jobs = db::get_jobs(&*db::lock(&self.db)?, status, limit)?;
+        let live = lock(&self.live)?;
+        for job in &mut jobs {
+            if let Some(current) = live.get(&job.id) {
+                *job = lock(&current.job)?.clone();
+            }
+        }
+        Ok(jobs)
+    }
+}
+
+/// A queued job's id plus a way to wait for what it returns.
+pub struct JobTicket<T> {
+    pub job: Job,
+    result:  Receiver<CmdResult<T>>,
+}
+
+impl<T> JobTicket<T> {
+    /// Block until the job finishes. A job cancelled before it started
+    /// returns `CommandError::Cancelled`.
+    pub fn wait(self) -> CmdResult<T> {
+        self.result.recv().unwrap_or(Err(CommandError::Cancelled))
+    }
+}
+
+/// What a running job gets to report progress and notice cancellation.
+#[derive(Clone)]
+pub struct JobHandle {
+    live:       Arc<LiveJob>,
+    app:        Option<AppHandle>,
+    last_event: Arc<Mutex<Option<Instant>>>,
+}
+
+impl JobHandle {
+    /// Report that `done` of `total` items are finished, e.g. titles synced.
+    pub fn progress(&self, done: usize, total: usize, message: impl Into<String>) {
+        let Ok(mut job) = self.live.job.lock() else { return };
+        job.done = done;
+        job.total = total;
+        job.message = Some(message.into());
+
+        let Some(app) = &self.app else { return };
+        let Ok(mut last_event) = self.last_event.lock() else { return };
+        if done < total && last_event.is_some_and(|t| t.elapsed() < PROGRESS_EVENT_INTERVAL) {
+            return;
+        }
+        *last_event = Some(Instant::now());
+        events::job_updated(app, &job);
+    }
+
+    /// Say the job is paused for `provider`'s rate limit. Sent right away so
+    /// a long wait doesn't look like a hang.
+    pub fn waiting(&self, provider: &str, wait: Duration) {
+        let Ok(mut job) = self.live.job.lock() else { return };
+        job.message = Some(format!("Waiting {}s for the {provider} rate limit", wait.as_secs().max(1)));
+        if let Some(app) = &self.app {
+            events::job_updated(app, &job);
+        }
+    }
+
+    /// `Err(Cancelled)` once the user has cancelled the job. Call between
+    /// items; returning the error rolls back whatever transaction is open.
+    pub fn check_cancelled(&self) -> CmdResult<()> {
+        if self.live.cancelled.load(Ordering::Relaxed) {
+            return Err(CommandError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Run queued jobs one after another until the app exits.
+fn run_worker(app: AppHandle, db: Arc<Mutex<Connection>>, live: LiveJobs, receiver: Receiver<(Arc<LiveJob>, Work)>) {
+    let save = |job: &Job| {
+        match db::lock(&db).and_then(|conn| Ok(db::update_job(&conn, job)?)) {
+            Ok(()) => {}
+            Err(e) => warn!(id = job.id, error = %e, "Couldn't save job"),
+        }
+        events::job_updated(&app, job);
+    };
+
+    for (current, work) in receiver {
+        let handle = JobHandle { live: Arc::clone(&current), app: Some(app.clone()), last_event: Arc::default() };
+        let started = !current.cancelled.load(Ordering::Relaxed);
+
+        let outcome = if started {
+            let job = update(&current, |job| {
+                job.status = JobStatus::Running;
+                job.started_at = Some(Utc::now().to_rfc3339());
+            });
+            save(&job);
+            info!(id = job.id, kind = job.kind, "Job started");
+            let waiting = handle.clone();
+            let _waits = ratelimit::report_waits_to(move |provider, wait| waiting.waiting(provider, wait));
+            // RUST NOTE: release builds abort on panic (Cargo.toml), so there's
+            // no catching one here; jobs report failures as errors instead.
+            work(&handle)
+        } else {
+            // Dropping `work` unblocks anyone waiting with `Cancelled`.
+            drop(work);
+            Err(CommandError::Cancelled.to_string())
+        };
+
+        let cancelled = current.cancelled.load(Ordering::Relaxed);
+        let job = update(&current, |job| {
+            match outcome {
+                Ok(result) => {
+                    job.status = JobStatus::Succeeded;
+                    job.result = Some(result);
+                }
+                Err(error) => {
+                    job.status = if cancelled { JobStatus::Cancelled } else { JobStatus::Failed };
+                    job.error = Some(error);
+                }
+            }
+            job.finished_at = Some(Utc::now().to_rfc3339());
+        });
+        save(&job);
+        if let Ok(mut live) = live.lock() {
+            live.remove(&job.id);
+        }
+        info!(id = job.id, kind = job.kind, status = job.status.as_str(), "Job finished");
+    }
+}
+
+/// Change a live job and return a copy of it.
+fn update(live: &LiveJob, change: impl FnOnce(&mut Job)) -> Job {
+    let mut job = live.job.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    change(&mut job);
+    job.clone()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> CmdResult<std::sync::MutexGuard<'_, T>> {
+    mutex.lock().map_err(|_| CommandError::Internal("Job queue lock poisoned".to_string()))
+}
@@ -1,14 +1,357 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
+// lib.rs — The GameTrc library crate.
+//
+// Everything lives here so it can be shared by two binaries:
+//   - main.rs                 — the Tauri desktop app (just calls `run()`)
+//   - bin/gametrc-cli.rs      — a headless CLI for scripting / SSH access
+//
+// `run()` wires the desktop app together:
+//   1. Opens / creates the SQLite database
+//   2. Registers the Tauri commands so JavaScript can call them
+//   3. Starts the Tauri event loop
+
+// RUST NOTE: `mod` declares a module. Rust looks for either
+//   src/<name>.rs  or  src/<name>/mod.rs
+// These modules live in the src/ folder as separate .rs files.
+// `pub mod` makes them visible to the binaries that link this library.
+pub mod models;
+pub mod db;
+pub mod commands;
+pub mod cache;
+pub mod card;
+pub mod collage;
+pub mod compat;
+pub mod completionator;
+pub mod covers;
+pub mod credentials;
+pub mod darkadia;
+pub mod diagnostics;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod filesync;
+pub mod hltb;
+pub mod hooks;
+pub mod http;
+pub mod humble;
+pub mod idle;
+pub mod images;
+pub mod import;
+pub mod installs;
+pub mod itch;
+pub mod jobs;
+pub mod library;
+pub mod logging;
+pub mod metadata;
+pub mod plugins;
+pub mod preview;
+pub mod psn;
+pub mod ratelimit;
+pub mod refresh;
+pub mod registry;
+pub mod retroarch;
+pub mod saves;
+pub mod search;
+pub mod steam;
+pub mod timer;
+pub mod titles;
+pub mod trackers;
+pub mod webdav;
+pub mod xbox;
+
+use tauri::Manager;
+use std::sync::{Arc, Mutex};
+use rusqlite::Connection;
+
+// Re-export AppState from commands so db.rs can stay clean
+use commands::AppState;
 
+/// Build and run the desktop app. Blocks until the last window closes.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        // ── Plugins ──────────────────────────────────────────────────────────
+        // tauri-plugin-dialog lets Rust/JS open native file picker dialogs
+        .plugin(tauri_plugin_dialog::init())
+        // tauri-plugin-fs gives the frontend safe access to the filesystem
+        .plugin(tauri_plugin_fs::init())
+        // tauri-plugin-clipboard-manager puts copied images on the system clipboard
+        .plugin(tauri_plugin_clipboard_manager::init())
+
+        // ── One-time setup ───────────────────────────────────────────────────
+        .setup(|app| {
+            // Start logging first so everything below ends up in the log file.
+            // A logging failure shouldn't stop the app from starting.
+            if let Err(e) = logging::init(&logging::get_log_dir(app.handle())) {
+                eprintln!("Failed to initialise logging: {e}");
+            }
+
+            // Resolve the OS-standard data directory and open our SQLite DB
+            let db_path = db::get_db_path(app.handle());
+
+            // Create parent directories if they don't exist yet
+            // RUST NOTE: `unwrap()` panics if the Result is Err. During setup
+            // a panic is acceptable — if we can't create the data dir, the app
+            // cannot function at all.
+            std::fs::create_dir_all(db_path.parent().unwrap())
+                .expect("Failed to create app data directory");
+
+            let conn = Connection::open(&db_path)
+                .expect("Failed to open SQLite database");
+
+            // Run CREATE TABLE IF NOT EXISTS migrations
+            db::init_db(&conn)
+                .expect("Failed to initialise database schema");
+
+            // Cache provider responses so lookups work offline
+            cache::init(cache::get_cache_dir(app.handle()));
+
+            // Images are stored by file name and resolved against the images folder
+            match images::get_images_dir(app.handle()) {
+                Ok(images_dir) => {
+                    if let Err(e) = images::store_relative(&conn, &images_dir) {
+                        tracing::warn!(error = %e, "Couldn't rewrite image paths");
+                    }
+                    images::init(images_dir);
+                }
+                Err(e) => tracing::warn!(error = %e, "Couldn't resolve the images folder"),
+            }
+
+            // Older versions kept API keys in the settings table
+            if let Err(e) = credentials::migrate_from_settings(&conn) {
+                tracing::warn!(error = %e, "Couldn't migrate credentials");
+            }
+
+            // Apply the user's per-provider request limits and proxy before anything syncs
+            match db::get_settings(&conn) {
+                Ok(settings) => {
+                    ratelimit::configure(&settings.rate_limits);
+                    http::configure(&settings);
+                }
+                Err(e) => tracing::warn!(error = %e, "Couldn't read network settings, using defaults"),
+            }
+
+            // Stamp edits with this device; in sync-safe mode, claim the file and
+            // look for cloud-drive conflicts
+            let state_dir = app.path().app_local_data_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+            match filesync::device_id(&state_dir) {
+                Ok(device) => db::set_device_id(device),
+                Err(e) => tracing::warn!(error = %e, "Couldn't read the device id, edits won't name a device"),
+            }
+
+            // The user's own commands to run on library events, kept per device
+            hooks::init(&state_dir);
+            let filesync = filesync::FileSync::open(&conn, db_path.clone(), state_dir);
+
+            // A session timer still open means the last run crashed; close it
+            // where the app was last seen running
+            match timer::recover(&conn) {
+                Ok(Some(session)) => hooks::session_ended(&session),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(error = %e, "Couldn't close the session left open"),
+            }
+
+            // Local-only usage counters (get_usage_insights)
+            if let Err(e) = db::count_usage(&conn, models::UsageCounter::Launch) {
+                tracing::warn!(error = %e, "Couldn't count the launch");
+            }
+
+            // Register shared state — available in every command via State<AppState>
+            // RUST NOTE: `Mutex::new(conn)` wraps the Connection in a mutex so it
+            // can be safely shared across threads; `Arc` lets commands hand a
+            // reference to the blocking thread pool.
+            let db = Arc::new(Mutex::new(conn));
+            let jobs = jobs::JobQueue::start(app.handle().clone(), Arc::clone(&db));
+            app.manage(AppState { db: Arc::clone(&db), jobs: Arc::clone(&jobs), filesync: Arc::clone(&filesync) });
+
+            // Background jobs
+            steam::spawn_background_sync(app.handle().clone(), Arc::clone(&db), Arc::clone(&jobs));
+            refresh::spawn_background_refresh(app.handle().clone(), Arc::clone(&db), Arc::clone(&jobs));
+            filesync::spawn_watcher(app.handle().clone(), Arc::clone(&db), filesync);
+            timer::spawn_ticker(app.handle().clone(), Arc::clone(&db));
+            hooks::spawn_worker(Arc::clone(&db));
+
+            // Cover colours and placeholders for images added before they were kept
+            let details_db = Arc::clone(&db);
+            if let Err(e) = jobs.enqueue("image_details", move |job| images::fill_image_details(&details_db, job)) {
+                tracing::warn!(error = %e, "Couldn't queue image details");
+            }
+
+            // Trim the images folder to its size cap, if one is set
+            let handle = app.handle().clone();
+            if let Err(e) = jobs.enqueue("image_eviction", move |_| images::evict_unused(&handle, &db)) {
+                tracing::warn!(error = %e, "Couldn't queue image eviction");
+            }
+
+            tracing::info!(db = %db_path.display(), "GameTrc started");
+
+            Ok(())
+        })
+
+        // ── Register IPC commands ────────────────────────────────────────────
+        // Every function listed here can be called from JavaScript with:
+        //   import { invoke } from "@tauri-apps/api/core";
+        //   invoke("command_name", { arg: value })
+        .invoke_handler(tauri::generate_handler![
+            // CRUD
+            commands::get_all_games,
+            commands::get_game,
+            commands::add_game,
+            commands::update_game,
+            commands::delete_game,
+            commands::set_archived,
+            commands::toggle_favorite,
+            commands::add_game_alias,
+            commands::remove_game_alias,
+            // Search
+            commands::search_games,
+            commands::search_games_page,
+            commands::search_game_summaries,
+            commands::search_game_summaries_page,
+            commands::get_games_grouped,
+            // Stats
+            commands::get_stats,
+            commands::get_highlights,
+            commands::get_on_this_day,
+            commands::get_usage_insights,
+            commands::get_now_playing,
+            // Utility / dropdowns
+            commands::get_platforms,
+            commands::get_franchises,
+            commands::get_genres,
+            commands::reveal_in_folder,
+            commands::list_commands,
+            // Export
+            commands::export_csv,
+            commands::export_markdown,
+            commands::export_html,
+            commands::export_library,
+            commands::import_library,
+            commands::webdav_sync,
+            commands::get_file_sync_status,
+            commands::resolve_file_sync,
+            commands::merge_database,
+            commands::get_sync_conflicts,
+            commands::resolve_sync_conflict,
+            commands::export_xlsx,
+            commands::export_backloggd,
+            commands::export_soundtracks,
+            commands::export_collage,
+            commands::render_game_card,
+            // Import
+            commands::preview_title_list,
+            commands::import_title_list,
+            commands::preview_title_import,
+            commands::import_retroarch_playlist,
+            commands::import_tracker_export,
+            commands::list_importer_plugins,
+            commands::run_importer_plugin,
+            // Installed games
+            commands::scan_installed_games,
+            // Settings
+            commands::get_settings,
+            commands::update_settings,
+            commands::get_rate_limits,
+            // Credentials
+            commands::set_credential,
+            commands::get_credential,
+            commands::delete_credential,
+            commands::get_credential_status,
+            // Hooks
+            commands::get_hooks,
+            commands::add_hook,
+            commands::update_hook,
+            commands::delete_hook,
+            commands::test_hook,
+            // Response cache
+            commands::get_cache_stats,
+            commands::clear_metadata_cache,
+            // Play sessions
+            commands::log_play_session,
+            commands::get_play_sessions,
+            commands::set_session_game_version,
+            commands::set_session_people,
+            commands::adjust_playtime,
+            commands::get_playtime_adjustments,
+            commands::start_session,
+            commands::pause_session,
+            commands::resume_session,
+            commands::stop_session,
+            commands::get_active_session,
+            // Playthroughs
+            commands::add_playthrough,
+            commands::update_playthrough,
+            commands::delete_playthrough,
+            commands::get_playthroughs,
+            commands::get_challenges,
+            // Personal bests
+            commands::add_personal_best,
+            commands::update_personal_best,
+            commands::delete_personal_best,
+            commands::get_personal_bests,
+            // People
+            commands::add_person,
+            commands::delete_person,
+            commands::get_people,
+            commands::get_people_stats,
+            // Soundtracks
+            commands::add_soundtrack,
+            commands::update_soundtrack,
+            commands::delete_soundtrack,
+            commands::get_soundtracks,
+            commands::search_soundtracks,
+            // Save backups
+            commands::add_save_location,
+            commands::remove_save_location,
+            commands::get_save_locations,
+            commands::backup_saves,
+            commands::restore_saves,
+            commands::get_save_backups,
+            // External accounts
+            commands::link_external_id,
+            commands::unlink_external_id,
+            commands::get_external_ids,
+            commands::sync_steam_playtime,
+            commands::import_steam_screenshots,
+            commands::sync_protondb,
+            commands::sync_deck_status,
+            commands::refresh_stale_metadata,
+            commands::get_metadata_refreshes,
+            commands::sync_psn,
+            commands::sync_xbox,
+            commands::sync_itch,
+            commands::sync_humble,
+            commands::import_humble_export,
+            commands::get_purchases,
+            commands::get_bundles,
+            commands::get_achievements,
+            commands::get_achievement_summary,
+            // Image processing
+            commands::process_cover_image,
+            commands::process_images,
+            commands::transform_image,
+            commands::import_screenshots,
+            commands::copy_image_to_clipboard,
+            commands::evict_images,
+            commands::relink_images,
+            // Jobs
+            commands::get_jobs,
+            commands::cancel_job,
+            // Diagnostics
+            commands::get_recent_logs,
+            commands::get_diagnostics,
+        ])
+
+        // ── Start the event loop ─────────────────────────────────────────────
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Last chance to snapshot the library (the `auto_export_dir` setting)
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                let result = db::lock(&state.db).and_then(|conn| library::auto_export(&conn));
+                if let Err(e) = result {
+                    tracing::warn!(error = %e, "Couldn't write the exit snapshot");
+                }
+            }
+        });
 }
@@ -0,0 +1,534 @@
+// library.rs — Back up the whole library to one file, and restore it.
+//
+// An export is a JSON dump of every library table:
+//
+//   { "format": "gametrc-library", "version": 1, "schema_version": 7,
+//     "exported_at": "…", "tables": { "games": [{ "id": 1, "title": "Hades", … }], … } }
+//
+// or, with images included, a zip holding that dump plus the images folder:
+//
+//   library.json
+//   images/3f2a….png
+//
+// Image paths in the dump are file names (see images::to_stored), so a
+// restored library finds the restored images wherever app data now lives.
+//
+// Either can be encrypted with a passphrase, for backups kept on storage you
+// don't trust: the file is then
+//
+//   "GTRCENC1" | 16-byte salt | 12-byte nonce | AES-256-GCM(export)
+//
+// with the key derived from the passphrase by Argon2id. There's no way back
+// in without the passphrase.
+//
+// With the `auto_export_dir` setting, the app also writes a JSON snapshot
+// there every time it exits (`auto_export`). The file name never changes, so
+// a folder under Git or Syncthing versioning keeps the history.
+//
+// Tables describing this machine or file rather than the library (installs,
+// save backup archives, job history, sync bookkeeping, …) aren't exported.
+// Restoring replaces the library; it doesn't merge. The local tables are
+// left alone, except that rows belonging to a game (its installs, save
+// backups, sync conflicts, a running session) would go with the old games:
+// those move over to the restored game with the same title, and are lost
+// when there isn't exactly one.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::cache;
+use crate::db::{self, TableRows};
+use crate::error::{CmdResult, CommandError};
+use crate::models::LibraryReport;
+use crate::titles;
+
+const FORMAT: &str = "gametrc-library";
+const VERSION: u32 = 1;
+
+/// The dump's name inside a zip export.
+const DUMP_FILE: &str = "library.json";
+/// The folder images go in inside a zip export.
+const IMAGES_FOLDER: &str = "images";
+
+/// The exit snapshot's name in `auto_export_dir`.
+const SNAPSHOT_FILE: &str = "gametrc-library.json";
+
+/// What an encrypted export starts with.
+const ENCRYPTED_MAGIC: &[u8; 8] = b"GTRCENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Local tables whose rows belong to a game, carried over by `reattach`.
+const GAME_LOCAL_TABLES: &[&str] = &["game_installs", "save_backups", "sync_conflicts", "active_session"];
+
+/// Tables that only make sense on the machine that wrote them.
+const LOCAL_TABLES: &[&str] = &[
+    "game_installs", "save_backups", "jobs", "metadata_refreshes",
+    "webdav_sync", "db_generation", "sync_conflicts", "active_session", "usage_counters",
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct LibraryDump {
+    pub format:         String,
+    pub version:        u32,
+    pub schema_version: i64,
+    pub exported_at:    String,
+    pub tables:         BTreeMap<String, TableRows>,
+}
+
+impl LibraryDump {
+    fn report(&self, path: &Path, images: usize) -> LibraryReport {
+        LibraryReport {
+            path:       path.to_string_lossy().into_owned(),
+            games:      self.tables.get("games").map_or(0, Vec::len),
+            rows:       self.tables.values().map(Vec::len).sum(),
+            images,
+            size_bytes: fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+            encrypted:  false,
+        }
+    }
+}
+
+/// Read every library table.
+pub fn dump(conn: &Connection) -> CmdResult<LibraryDump> {
+    let mut tables = BTreeMap::new();
+    for table in db::library_tables(conn, LOCAL_TABLES)? {
+        let rows = db::dump_table(conn, &table)?;
+        tables.insert(table, rows);
+    }
+    Ok(LibraryDump {
+        format:         FORMAT.to_string(),
+        version:        VERSION,
+        schema_version: db::schema_version(conn)?,
+        exported_at:    Utc::now().to_rfc3339(),
+        tables,
+    })
+}
+
+/// Fail unless `dump` is a library export this version can read.
+fn check(dump: &LibraryDump) -> CmdResult<()> {
+    if dump.format != FORMAT {
+        return Err(CommandError::validation("path", "Not a GameTrc library export"));
+    }
+    if dump.version > VERSION {
+        return Err(CommandError::validation("path", "This export is from a newer version of GameTrc"));
+    }
+    Ok(())
+}
+
+/// Replace the library with `dump`. Tables the dump doesn't have (it's from
+/// an older version) end up empty.
+pub fn restore(conn: &Connection, dump: &LibraryDump) -> CmdResult<usize> {
+    check(dump)?;
+    // Exports from before playtime was kept in minutes still have hours.
+    let mut converted = BTreeMap::new();
+    if dump.schema_version < db::PLAYTIME_MINUTES_SCHEMA {
+        let renames: [(&str, &[(&str, &str)]); 2] = [
+            ("games", &[("playtime_hours", "playtime_minutes")]),
+            ("playtime_adjustments", &[("old_hours", "old_minutes"), ("new_hours", "new_minutes")]),
+        ];
+        for (table, columns) in renames {
+            if let Some(rows) = dump.tables.get(table) {
+                converted.insert(table.to_string(), hours_to_minutes(rows, columns));
+            }
+        }
+    }
+
+    let empty = TableRows::new();
+    let mut tables: Vec<(String, &TableRows)> = db::library_tables(conn, LOCAL_TABLES)?
+        .into_iter()
+        .map(|table| {
+            let rows = converted.get(&table).or_else(|| dump.tables.get(&table)).unwrap_or(&empty);
+            (table, rows)
+        })
+        .collect();
+    // Deleting the old games cascades to these, so they're written back too.
+    let reattached = reattach(conn, dump.tables.get("games").unwrap_or(&empty))?;
+    tables.extend(reattached.iter().map(|(table, rows)| (table.to_string(), rows)));
+    Ok(db::replace_tables(conn, &tables)?)
+}
+
+/// The rows of each `GAME_LOCAL_TABLES` table, moved to the game in `games`
+/// (the restored ones) that matches theirs: the same id with the same title,
+/// or else the only game with that title on both sides. Rows with no such
+/// game are dropped.
+fn reattach(conn: &Connection, games: &TableRows) -> CmdResult<Vec<(&'static str, TableRows)>> {
+    let old: HashMap<i64, String> =
+        db::game_titles(conn)?.into_iter().map(|(id, title)| (id, titles::normalize(&title))).collect();
+    let mut old_count: HashMap<&str, usize> = HashMap::new();
+    for title in old.values() {
+        *old_count.entry(title).or_default() += 1;
+    }
+    let mut restored: HashMap<String, Vec<i64>> = HashMap::new();
+    for game in games {
+        let id = game.get("id").and_then(serde_json::Value::as_i64);
+        let title = game.get("title").and_then(serde_json::Value::as_str);
+        if let (Some(id), Some(title)) = (id, title) {
+            restored.entry(titles::normalize(title)).or_default().push(id);
+        }
+    }
+
+    // RUST NOTE: the match is one-to-one, so two rows can't land on the same
+    // game and collide on a key like game_installs' (game_id, launcher).
+    let new_id = |old_id: i64| -> Option<i64> {
+        let title = old.get(&old_id)?;
+        match restored.get(title)?.as_slice() {
+            ids if ids.contains(&old_id) => Some(old_id),
+            [id] if old_count[title.as_str()] == 1 => Some(*id),
+            _ => None,
+        }
+    };
+
+    let mut reattached = Vec::new();
+    for &table in GAME_LOCAL_TABLES {
+        let rows: TableRows = db::dump_table(conn, table)?
+            .into_iter()
+            .filter_map(|mut row| {
+                let id = new_id(row.get("game_id")?.as_i64()?)?;
+                row.insert("game_id".to_string(), id.into());
+                Some(row)
+            })
+            .collect();
+        reattached.push((table, rows));
+    }
+    Ok(reattached)
+}
+
+/// `rows` with each (hours, minutes) column pair's hours turned into minutes.
+fn hours_to_minutes(rows: &TableRows, columns: &[(&str, &str)]) -> TableRows {
+    rows.iter()
+        .map(|row| {
+            let mut row = row.clone();
+            for (hours, minutes) in columns {
+                if let Some(value) = row.remove(*hours) {
+                    let value = value.as_f64().map_or(serde_json::Value::Null, |h| ((h * 60.0).round() as i64).into());
+                    row.insert(minutes.to_string(), value);
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Write the library to `path`: plain JSON, or a zip that also holds every
+/// file in `images_dir` when one is given — encrypted if there's a `passphrase`.
+pub fn export(conn: &Connection, path: &Path, images_dir: Option<&Path>, passphrase: Option<&str>) -> CmdResult<LibraryReport> {
+    let dump = dump(conn)?;
+    let Some(passphrase) = passphrase else { return write(&dump, path, images_dir) };
+    if passphrase.is_empty() {
+        return Err(CommandError::validation("passphrase", "must not be empty"));
+    }
+
+    // Built in memory: the plaintext never touches the disk.
+    let mut plain = Cursor::new(Vec::new());
+    let images = write_to(&dump, &mut plain, images_dir)?;
+    fs::write(path, encrypt(plain.get_ref(), passphrase)?)?;
+
+    let mut report = dump.report(path, images);
+    report.encrypted = true;
+    info!(path = %path.display(), games = report.games, "Encrypted the library export");
+    Ok(report)
+}
+
+/// Write an already-taken `dump` like `export` does.
+pub fn write(dump: &LibraryDump, path: &Path, images_dir: Option<&Path>) -> CmdResult<LibraryReport> {
+    let images = write_to(dump, File::create(path)?, images_dir)?;
+    let report = dump.report(path, images);
+    match images_dir {
+        Some(_) => info!(path = %path.display(), games = report.games, images, "Exported library archive"),
+        None => info!(path = %path.display(), games = report.games, "Exported library"),
+    }
+    Ok(report)
+}
+
+/// Write `dump` to `out` as JSON, or as a zip with the images in
+/// `images_dir`. Returns how many images went in.
+fn write_to<W: Write + Seek>(dump: &LibraryDump, out: W, images_dir: Option<&Path>) -> CmdResult<usize> {
+    let Some(images_dir) = images_dir else {
+        serde_json::to_writer_pretty(out, dump).map_err(|e| CommandError::Internal(e.to_string()))?;
+        return Ok(0);
+    };
+
+    let mut zip = ZipWriter::new(out);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(DUMP_FILE, options)?;
+    serde_json::to_writer(&mut zip, dump).map_err(|e| CommandError::Internal(e.to_string()))?;
+
+    // Images are already compressed; deflating them again only costs time.
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut images = 0;
+    for entry in fs::read_dir(images_dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else { continue };
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        zip.start_file(format!("{IMAGES_FOLDER}/{name}"), stored)?;
+        io::copy(&mut File::open(entry.path())?, &mut zip)?;
+        images += 1;
+    }
+    zip.finish()?;
+    Ok(images)
+}
+
+/// Write the exit snapshot to the `auto_export_dir` setting, if one is set.
+/// Skipped (None) when the library hasn't changed since the snapshot already
+/// there, so opening and closing the app doesn't churn the folder's history.
+pub fn auto_export(conn: &Connection) -> CmdResult<Option<LibraryReport>> {
+    let Some(dir) = db::get_settings(conn)?.auto_export_dir.filter(|d| !d.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let dir = Path::new(&dir);
+    let path = dir.join(SNAPSHOT_FILE);
+    let dump = dump(conn)?;
+    let previous = File::open(&path)
+        .ok()
+        .and_then(|file| serde_json::from_reader::<_, LibraryDump>(io::BufReader::new(file)).ok());
+    if previous.is_some_and(|previous| fingerprint(&previous) == fingerprint(&dump)) {
+        info!(path = %path.display(), "Library unchanged, kept the last snapshot");
+        return Ok(None);
+    }
+
+    // Written beside the snapshot and renamed over it, so a sync client never
+    // picks up half a file.
+    fs::create_dir_all(dir)?;
+    let partial = dir.join(format!(".{SNAPSHOT_FILE}.partial"));
+    write(&dump, &partial, None)?;
+    fs::rename(&partial, &path)?;
+    Ok(Some(dump.report(&path, 0)))
+}
+
+/// When the exit snapshot in `dir` (the `auto_export_dir` setting) was last
+/// written, if there is one.
+pub fn last_snapshot(dir: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(dir.join(SNAPSHOT_FILE)).and_then(|m| m.modified()).ok().map(DateTime::from)
+}
+
+/// Restore the library from an export written by `export`. A zip's images
+/// are unpacked beside `images_dir` and moved in once the library is
+/// restored, so a rejected export leaves the images as they were. An
+/// encrypted export needs its `passphrase`.
+pub fn import(conn: &Connection, path: &Path, images_dir: &Path, passphrase: Option<&str>) -> CmdResult<LibraryReport> {
+    if is_encrypted(path)? {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| CommandError::validation("passphrase", "This backup is encrypted; enter its passphrase"))?;
+        // Read from memory: the plaintext never touches the disk.
+        let plain = decrypt(&fs::read(path)?, passphrase)?;
+        let zipped = plain.starts_with(ZIP_MAGIC);
+        let (dump, images) = read_from(conn, Cursor::new(plain), zipped, images_dir)?;
+        let mut report = dump.report(path, images);
+        report.encrypted = true;
+        info!(path = %path.display(), games = report.games, images, "Imported encrypted library");
+        return Ok(report);
+    }
+
+    let zipped = is_zip(path)?;
+    let (dump, images) = read_from(conn, io::BufReader::new(File::open(path)?), zipped, images_dir)?;
+    let report = dump.report(path, images);
+    match zipped {
+        true => info!(path = %path.display(), games = report.games, images, "Imported library archive"),
+        false => info!(path = %path.display(), games = report.games, "Imported library"),
+    }
+    Ok(report)
+}
+
+/// Restore the export in `source` — a zip when `zipped`, else JSON. Returns
+/// the dump and how many images were copied into `images_dir`.
+fn read_from<R: Read + Seek>(conn: &Connection, source: R, zipped: bool, images_dir: &Path) -> CmdResult<(LibraryDump, usize)> {
+    if !zipped {
+        let dump: LibraryDump = serde_json::from_reader(source)
+            .map_err(|e| CommandError::validation("path", format!("Library export is invalid: {e}")))?;
+        restore(conn, &dump)?;
+        return Ok((dump, 0));
+    }
+
+    let mut zip = ZipArchive::new(source)?;
+    let dump: LibraryDump = serde_json::from_reader(zip.by_name(DUMP_FILE)?)
+        .map_err(|e| CommandError::validation("path", format!("Library export is invalid: {e}")))?;
+    check(&dump)?;
+
+    let staging = images_dir.with_file_name(format!(".images-restore-{}", Uuid::new_v4()));
+    let result = unpack_images(&mut zip, &staging).and_then(|images| {
+        restore(conn, &dump)?;
+        for entry in fs::read_dir(&staging)? {
+            let entry = entry?;
+            fs::rename(entry.path(), images_dir.join(entry.file_name()))?;
+        }
+        Ok(images)
+    });
+    let _ = fs::remove_dir_all(&staging);
+    Ok((dump, result?))
+}
+
+/// Copy the images in `zip` into `dir`. Returns how many there were.
+fn unpack_images<R: Read + Seek>(zip: &mut ZipArchive<R>, dir: &Path) -> CmdResult<usize> {
+    fs::create_dir_all(dir)?;
+    let mut images = 0;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        // RUST NOTE: `enclosed_name` rejects names like "../../evil" that
+        // would escape the target folder.
+        let Some(name) = entry.enclosed_name() else { continue };
+        let Ok(file) = name.strip_prefix(IMAGES_FOLDER) else { continue };
+        if entry.is_dir() || file.components().count() != 1 {
+            continue;
+        }
+        io::copy(&mut entry, &mut File::create(dir.join(file))?)?;
+        images += 1;
+    }
+    Ok(images)
+}
+
+/// A hash of the library's contents (not when it was dumped), to tell
+/// whether anything changed between two dumps.
+pub fn fingerprint(dump: &LibraryDump) -> String {
+    let json = serde_json::to_vec(&dump.tables).unwrap_or_default();
+    format!("{:016x}", cache::fnv1a(&json))
+}
+
+/// Encrypt `plain` with a key derived from `passphrase` (see the top of the file).
+fn encrypt(plain: &[u8], passphrase: &str) -> CmdResult<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let sealed = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| CommandError::Internal("Couldn't encrypt the export".to_string()))?;
+
+    let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.len());
+    out.extend_from_slice(ENCRYPTED_MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Undo `encrypt`. A wrong passphrase and a damaged file look the same: the
+/// authentication tag doesn't match.
+fn decrypt(sealed: &[u8], passphrase: &str) -> CmdResult<Vec<u8>> {
+    let header = ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if sealed.len() < header {
+        return Err(CommandError::validation("path", "The encrypted backup is truncated"));
+    }
+    let salt = &sealed[ENCRYPTED_MAGIC.len()..ENCRYPTED_MAGIC.len() + SALT_LEN];
+    let nonce = Nonce::from_slice(&sealed[ENCRYPTED_MAGIC.len() + SALT_LEN..header]);
+    Aes256Gcm::new(&derive_key(passphrase, salt)?)
+        .decrypt(nonce, &sealed[header..])
+        .map_err(|_| CommandError::validation("passphrase", "Wrong passphrase, or the backup is damaged"))
+}
+
+/// RUST NOTE: Argon2 is deliberately slow and memory-hungry, so guessing
+/// passphrases against a stolen backup is too.
+fn derive_key(passphrase: &str, salt: &[u8]) -> CmdResult<Key<Aes256Gcm>> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CommandError::Internal(format!("Couldn't derive the export key: {e}")))?;
+    Ok(key)
+}
+
+fn is_encrypted(path: &Path) -> CmdResult<bool> {
+    let mut magic = [0u8; 8];
+    let read = File::open(path)?.read(&mut magic)?;
+    Ok(read == magic.len() && magic == *ENCRYPTED_MAGIC)
+}
+
+/// Zip files start with "PK\x03\x04"; JSON never does.
+const ZIP_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+fn is_zip(path: &Path) -> CmdResult<bool> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)?.read(&mut magic)?;
+    Ok(read == 4 && magic == *ZIP_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+
+    fn rejected_field(result: CmdResult<Vec<u8>>) -> String {
+        match result {
+            Err(CommandError::ValidationFailed { field, .. }) => field,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn passphrase_round_trip() {
+        let plain = br#"{"format":"gametrc-library","tables":{}}"#;
+        let sealed = encrypt(plain, PASSPHRASE).unwrap();
+        assert!(sealed.starts_with(ENCRYPTED_MAGIC));
+        assert!(!sealed.windows(plain.len()).any(|w| w == plain));
+        assert_eq!(decrypt(&sealed, PASSPHRASE).unwrap(), plain);
+        // A fresh salt and nonce every time.
+        assert_ne!(encrypt(plain, PASSPHRASE).unwrap(), sealed);
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let sealed = encrypt(b"library", PASSPHRASE).unwrap();
+        assert_eq!(rejected_field(decrypt(&sealed, "Correct horse battery staple")), "passphrase");
+    }
+
+    #[test]
+    fn truncated_file_is_rejected() {
+        let sealed = encrypt(b"library", PASSPHRASE).unwrap();
+        // Cut inside the header: there's no salt or nonce to work with.
+        assert_eq!(rejected_field(decrypt(&sealed[..20], PASSPHRASE)), "path");
+        // Cut inside the ciphertext: the authentication tag no longer matches.
+        assert_eq!(rejected_field(decrypt(&sealed[..sealed.len() - 1], PASSPHRASE)), "passphrase");
+    }
+
+    /// A zip export of `conn`'s library holding one image, cover.jpg.
+    fn archive(conn: &Connection, version: u32, cover: &str) -> Cursor<Vec<u8>> {
+        let source = std::env::temp_dir().join(format!("gametrc-export-{}", Uuid::new_v4()));
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("cover.jpg"), cover).unwrap();
+        let mut dump = dump(conn).unwrap();
+        dump.version = version;
+        let mut zip = Cursor::new(Vec::new());
+        write_to(&dump, &mut zip, Some(&source)).unwrap();
+        fs::remove_dir_all(&source).unwrap();
+        zip.set_position(0);
+        zip
+    }
+
+    #[test]
+    fn images_are_only_replaced_by_a_restored_export() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        let root = std::env::temp_dir().join(format!("gametrc-import-{}", Uuid::new_v4()));
+        let images_dir = root.join("images");
+        fs::create_dir_all(&images_dir).unwrap();
+        fs::write(images_dir.join("cover.jpg"), "old").unwrap();
+
+        let newer = read_from(&conn, archive(&conn, VERSION + 1, "newer"), true, &images_dir);
+        assert!(matches!(newer, Err(CommandError::ValidationFailed { .. })));
+        assert_eq!(fs::read_to_string(images_dir.join("cover.jpg")).unwrap(), "old");
+
+        let (_, images) = read_from(&conn, archive(&conn, VERSION, "new"), true, &images_dir).unwrap();
+        assert_eq!(images, 1);
+        assert_eq!(fs::read_to_string(images_dir.join("cover.jpg")).unwrap(), "new");
+        // Nothing is left behind but the images folder.
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
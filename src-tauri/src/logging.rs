@@ -0,0 +1,127 @@
+// logging.rs — Structured logging to a rotating file in app data.
+//
+// Every module logs through the `tracing` macros (`info!`, `warn!`, …).
+// The subscriber set up here writes one JSON object per line to
+// app_data_dir/logs/gametrc.log.YYYY-MM-DD, keeping a week of files.
+// Because each line is JSON, `recent_logs` can filter by level without
+// guessing at a text format — that's what powers the in-app log viewer.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tauri::AppHandle;
+use tauri::Manager;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::models::LogEntry;
+
+/// Log file prefix — the appender adds a `.YYYY-MM-DD` suffix.
+const LOG_FILE_PREFIX: &str = "gametrc.log";
+
+/// How many daily files to keep before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+
+/// Resolve the logs directory: app_data_dir/logs/
+pub fn get_log_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("logs")
+}
+
+/// Install the global subscriber. Call once, as early as possible in setup.
+///
+/// Release builds record INFO and above. Debug builds also record DEBUG and
+/// echo human-readable lines to stderr so `cargo tauri dev` shows what's going on.
+pub fn init(log_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(log_dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+        .map_err(io::Error::other)?;
+
+    let file_layer = tracing_subscriber::fmt::layer()
+        .json()
+        .with_ansi(false)
+        .with_writer(appender);
+
+    let stderr_layer = cfg!(debug_assertions)
+        .then(|| tracing_subscriber::fmt::layer().compact().with_writer(io::stderr));
+
+    tracing_subscriber::registry()
+        .with(if cfg!(debug_assertions) { LevelFilter::DEBUG } else { LevelFilter::INFO })
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init()
+        .map_err(io::Error::other)
+}
+
+/// Read the newest `limit` log entries at `min_level` or more severe,
+/// returned oldest-first so they read like a normal log.
+pub fn recent_logs(log_dir: &Path, min_level: Option<&str>, limit: usize) -> io::Result<Vec<LogEntry>> {
+    let min_level = match min_level {
+        Some(l) => Level::from_str(l)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown log level: {l}")))?,
+        None => Level::TRACE,
+    };
+
+    // Rotated files sort chronologically by name thanks to the date suffix.
+    let mut files: Vec<PathBuf> = fs::read_dir(log_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+
+    // Walk files newest → oldest, collecting matching lines until we have enough.
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for file in files.iter().rev() {
+        let reader = BufReader::new(fs::File::open(file)?);
+        let mut from_file: Vec<LogEntry> = reader
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_line(&line))
+            .filter(|e| Level::from_str(&e.level).is_ok_and(|l| l <= min_level))
+            .collect();
+
+        let take = limit.saturating_sub(entries.len()).min(from_file.len());
+        let mut newest = from_file.split_off(from_file.len() - take);
+        newest.append(&mut entries);
+        entries = newest;
+
+        if entries.len() >= limit {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+/// Turn one JSON log line into a LogEntry. Malformed lines are skipped.
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let mut fields = value.get("fields")?.as_object()?.clone();
+    let message = fields
+        .remove("message")
+        .and_then(|m| m.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    Some(LogEntry {
+        timestamp: value.get("timestamp")?.as_str()?.to_string(),
+        level:     value.get("level")?.as_str()?.to_string(),
+        target:    value.get("target").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+        message,
+        fields,
+    })
+}
@@ -0,0 +1,151 @@
+// metadata.rs — Look up game details from online databases.
+//
+// Each source implements `MetadataProvider`. The rest of the app only ever asks
+// `provider(settings.metadata_provider)` for "the configured one", so adding a
+// new source means writing one impl and one enum variant.
+//
+// Lookups block on the network — always call them from `spawn_blocking`
+// (the `blocking` helper in commands.rs), never on the IPC thread.
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::cache;
+use crate::error::CmdResult;
+use crate::http;
+use crate::models::{GameMetadata, MetadataProviderKind};
+
+/// A source of game details.
+///
+/// RUST NOTE: `Send + Sync` lets a boxed provider be moved into a background
+/// task (`Box<dyn MetadataProvider>` is a trait object, like an interface).
+pub trait MetadataProvider: Send + Sync {
+    /// Human-readable name, stored in `GameMetadata::source`.
+    fn name(&self) -> &'static str;
+
+    /// Best match for `title`, or `None` if nothing plausible was found.
+    fn lookup(&self, title: &str) -> CmdResult<Option<GameMetadata>>;
+}
+
+/// The provider for a settings value, or `None` if lookups are turned off.
+pub fn provider(kind: MetadataProviderKind) -> Option<Box<dyn MetadataProvider>> {
+    match kind {
+        MetadataProviderKind::Steam => Some(Box::new(SteamStore)),
+        MetadataProviderKind::None  => None,
+    }
+}
+
+/// Lower-case and drop everything but letters and digits, so
+/// "DOOM (2016)" and "Doom 2016" compare equal.
+pub fn normalize_title(title: &str) -> String {
+    title.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Steam store
+// ---------------------------------------------------------------------------
+
+/// The public Steam storefront API. Keyless, but only knows games sold on Steam.
+pub struct SteamStore;
+
+const STEAM_SEARCH_URL: &str = "https://store.steampowered.com/api/storesearch/";
+const STEAM_DETAILS_URL: &str = "https://store.steampowered.com/api/appdetails";
+
+#[derive(Deserialize)]
+struct SteamSearch {
+    #[serde(default)]
+    items: Vec<SteamSearchItem>,
+}
+
+#[derive(Deserialize)]
+struct SteamSearchItem {
+    id:   u64,
+    name: String,
+}
+
+/// `appdetails` answers `{ "<appid>": { "success": true, "data": { … } } }`.
+#[derive(Deserialize)]
+struct SteamDetailsEnvelope {
+    success: bool,
+    data:    Option<SteamDetails>,
+}
+
+#[derive(Deserialize)]
+struct SteamDetails {
+    name:              String,
+    #[serde(default)]
+    developers:        Vec<String>,
+    #[serde(default)]
+    publishers:        Vec<String>,
+    #[serde(default)]
+    genres:            Vec<SteamGenre>,
+    release_date:      Option<SteamReleaseDate>,
+    short_description: Option<String>,
+    header_image:      Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SteamGenre {
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct SteamReleaseDate {
+    date: String,
+}
+
+impl MetadataProvider for SteamStore {
+    fn name(&self) -> &'static str {
+        "Steam"
+    }
+
+    fn lookup(&self, title: &str) -> CmdResult<Option<GameMetadata>> {
+        let search: SteamSearch =
+            http::get_json_cached(STEAM_SEARCH_URL, &[("term", title), ("l", "english"), ("cc", "US")], cache::SEARCH_TTL)?;
+
+        // Prefer an exact (normalized) name match; otherwise trust Steam's ranking.
+        let wanted = normalize_title(title);
+        let Some(item) = search
+            .items
+            .iter()
+            .find(|i| normalize_title(&i.name) == wanted)
+            .or_else(|| search.items.first())
+        else {
+            debug!(title, "No Steam match");
+            return Ok(None);
+        };
+
+        let app_id = item.id.to_string();
+        let mut envelope: std::collections::HashMap<String, SteamDetailsEnvelope> =
+            http::get_json_cached(STEAM_DETAILS_URL, &[("appids", &app_id), ("l", "english")], cache::DETAILS_TTL)?;
+        let Some(details) = envelope.remove(&app_id).filter(|e| e.success).and_then(|e| e.data) else {
+            return Ok(None);
+        };
+
+        // The tall library artwork matches the app's 3:4 cards, but older
+        // games don't have it — fall back to the wide header image.
+        let portrait = format!("https://cdn.akamai.steamstatic.com/steam/apps/{app_id}/library_600x900_2x.jpg");
+        let cover_url = if http::exists(&portrait) { Some(portrait) } else { details.header_image };
+
+        Ok(Some(GameMetadata {
+            source:       self.name().to_string(),
+            source_id:    app_id,
+            title:        details.name,
+            release_date: details.release_date.and_then(|d| parse_steam_date(&d.date)),
+            developer:    details.developers.into_iter().next(),
+            publisher:    details.publishers.into_iter().next(),
+            genres:       details.genres.into_iter().map(|g| g.description).collect(),
+            description:  details.short_description.filter(|d| !d.is_empty()),
+            cover_url,
+        }))
+    }
+}
+
+/// Steam writes dates as "Nov 10, 2011" or "10 Nov, 2011" depending on region.
+fn parse_steam_date(date: &str) -> Option<String> {
+    ["%b %d, %Y", "%d %b, %Y", "%B %d, %Y", "%d %B, %Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(date.trim(), fmt).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
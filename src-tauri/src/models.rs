@@ -6,7 +6,11 @@
 //   - `Debug`   lets you print them with `{:?}` for logging.
 //   - `Clone`   lets you duplicate a value (Rust moves by default, unlike most languages).
 
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::error::{CmdResult, CommandError};
 
 // ---------------------------------------------------------------------------
 // Enums
@@ -41,7 +45,28 @@ impl GameStatus {
         }
     }
 
+    /// Human-readable name for documents and exports ("Not Started").
+    pub fn label(&self) -> &str {
+        match self {
+            GameStatus::NotStarted => "Not Started",
+            GameStatus::Playing    => "Playing",
+            GameStatus::Completed  => "Completed",
+            GameStatus::Dropped    => "Dropped",
+            GameStatus::Backlog    => "Backlog",
+            GameStatus::Wishlist   => "Wishlist",
+        }
+    }
+
+    /// Order for status sections and groups — what you're playing first, wishes last.
+    pub const DISPLAY_ORDER: [GameStatus; 6] = [
+        GameStatus::Playing, GameStatus::Completed, GameStatus::Backlog,
+        GameStatus::NotStarted, GameStatus::Dropped, GameStatus::Wishlist,
+    ];
+
     /// Parse from a string coming out of SQLite.
+    /// Unknown values fall back to NotStarted, so this never fails — which is
+    /// why it isn't an implementation of `std::str::FromStr`.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "Playing"    => GameStatus::Playing,
@@ -65,22 +90,191 @@ impl GameStatus {
 pub struct Game {
     pub id:                       i64,
     pub title:                    String,
+    pub original_title:           Option<String>,   // e.g. the Japanese title
+    pub sort_title:               Option<String>,   // explicit sort key; None = title minus "The"/"A"/"An"
     pub franchise:                Option<String>,
     pub sequence_in_franchise:    Option<i32>,
     pub release_date:             Option<String>,   // stored as "YYYY-MM-DD"
     pub platform:                 String,
     pub status:                   GameStatus,
     pub progress_percent:         Option<f64>,      // 0.0 – 100.0
-    pub playtime_hours:           Option<f64>,
+    pub playtime_minutes:         Option<i64>,      // shown in the playtime_unit setting
     pub rating:                   Option<f64>,      // 1.0 – 10.0
     pub notes:                    Option<String>,
     pub cover_art_path:           Option<String>,
+    pub dominant_color:           Option<String>,   // '#rrggbb', the cover's main colour
+    pub accent_color:             Option<String>,   // '#rrggbb', its most striking vivid colour
+    pub cover_blurhash:           Option<String>,   // placeholder to show while the cover loads
+    pub cover_poster_path:        Option<String>,   // still first frame of an animated cover, for list views
     pub screenshots:              Vec<String>,       // list of file paths
+    pub screenshot_blurhashes:    Vec<Option<String>>,   // one per screenshot, same order
     pub developer:                Option<String>,
     pub publisher:                Option<String>,
     pub genres:                   Vec<String>,
+    pub aliases:                  Vec<String>,      // nicknames search also matches ("BotW")
+    pub installed:                bool,             // found by scan_installed_games
+    pub install_size_bytes:       Option<u64>,      // summed across launchers
+    pub protondb_tier:            Option<ProtonTier>,
+    pub deck_status:              Option<DeckStatus>,
+    pub controller_support:       Option<ControllerSupport>,
+    pub input_method:             Option<InputMethod>,   // what I actually played with
+    pub max_local_players:        Option<i32>,
+    pub max_online_players:       Option<i32>,
+    pub coop_mode:                Option<CoopMode>,
+    pub replay_count:             i64,              // playthroughs after the first
+    pub best_run:                 Option<BestRun>,  // fastest personal best, any category
     pub created_at:               String,           // ISO 8601
     pub updated_at:               String,
+    pub completed_at:             Option<String>,   // ISO 8601; set when the status becomes Completed
+    pub archived:                 bool,             // hidden from default views and stats
+    pub favorite:                 bool,             // pinned: listed first on the dashboard
+}
+
+/// A command result that is either the full records or, when the caller
+/// asked for specific `fields`, JSON with just those.
+/// RUST NOTE: `untagged` serializes whichever variant it is as its contents,
+/// so JS just sees the games (or the trimmed objects).
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Selected<T> {
+    All(T),
+    Fields(serde_json::Value),
+}
+
+/// The few fields a grid / list view shows, so big libraries don't pay for
+/// serializing notes, screenshots and stats they never display.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameSummary {
+    pub id:             i64,
+    pub title:          String,
+    pub cover_art_path: Option<String>,
+    pub cover_blurhash: Option<String>,
+    pub cover_poster_path: Option<String>,   // show this in lists when set; the cover is animated
+    pub status:         GameStatus,
+    pub rating:         Option<f64>,
+    pub platform:       String,
+    pub favorite:       bool,
+}
+
+impl From<&Game> for GameSummary {
+    fn from(game: &Game) -> Self {
+        GameSummary {
+            id:             game.id,
+            title:          game.title.clone(),
+            cover_art_path: game.cover_art_path.clone(),
+            cover_blurhash: game.cover_blurhash.clone(),
+            cover_poster_path: game.cover_poster_path.clone(),
+            status:         game.status.clone(),
+            rating:         game.rating,
+            platform:       game.platform.clone(),
+            favorite:       game.favorite,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Controls & multiplayer
+// ---------------------------------------------------------------------------
+
+/// How well a game supports controllers.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ControllerSupport {
+    Full,
+    Partial,
+    None,
+}
+
+impl ControllerSupport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControllerSupport::Full    => "Full",
+            ControllerSupport::Partial => "Partial",
+            ControllerSupport::None    => "None",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Full"    => Some(ControllerSupport::Full),
+            "Partial" => Some(ControllerSupport::Partial),
+            "None"    => Some(ControllerSupport::None),
+            _         => None,
+        }
+    }
+}
+
+/// The input device a game was played with.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum InputMethod {
+    KeyboardMouse,
+    Gamepad,
+    Wheel,
+    Touch,
+    Other,
+}
+
+impl InputMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputMethod::KeyboardMouse => "KeyboardMouse",
+            InputMethod::Gamepad       => "Gamepad",
+            InputMethod::Wheel         => "Wheel",
+            InputMethod::Touch         => "Touch",
+            InputMethod::Other         => "Other",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "KeyboardMouse" => Some(InputMethod::KeyboardMouse),
+            "Gamepad"       => Some(InputMethod::Gamepad),
+            "Wheel"         => Some(InputMethod::Wheel),
+            "Touch"         => Some(InputMethod::Touch),
+            "Other"         => Some(InputMethod::Other),
+            _               => None,
+        }
+    }
+}
+
+/// Where a game's co-op can be played.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum CoopMode {
+    None,
+    Local,            // couch / split-screen
+    Online,
+    LocalAndOnline,
+}
+
+impl CoopMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoopMode::None           => "None",
+            CoopMode::Local          => "Local",
+            CoopMode::Online         => "Online",
+            CoopMode::LocalAndOnline => "LocalAndOnline",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "None"           => Some(CoopMode::None),
+            "Local"          => Some(CoopMode::Local),
+            "Online"         => Some(CoopMode::Online),
+            "LocalAndOnline" => Some(CoopMode::LocalAndOnline),
+            _                => None,
+        }
+    }
+
+    /// Stored values that satisfy a filter for this mode: asking for Local
+    /// co-op also finds games with both local and online co-op.
+    pub fn matching(&self) -> &'static [&'static str] {
+        match self {
+            CoopMode::None           => &["None"],
+            CoopMode::Local          => &["Local", "LocalAndOnline"],
+            CoopMode::Online         => &["Online", "LocalAndOnline"],
+            CoopMode::LocalAndOnline => &["LocalAndOnline"],
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -88,7 +282,7 @@ pub struct Game {
 // ---------------------------------------------------------------------------
 
 /// Used when creating or updating a game. The frontend sends this JSON payload.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GameInput {
     pub title:                    String,
     pub franchise:                Option<String>,
@@ -97,7 +291,7 @@ pub struct GameInput {
     pub platform:                 String,
     pub status:                   GameStatus,
     pub progress_percent:         Option<f64>,
-    pub playtime_hours:           Option<f64>,
+    pub playtime_minutes:         Option<i64>,
     pub rating:                   Option<f64>,
     pub notes:                    Option<String>,
     pub cover_art_path:           Option<String>,
@@ -105,6 +299,49 @@ pub struct GameInput {
     pub developer:                Option<String>,
     pub publisher:                Option<String>,
     pub genres:                   Vec<String>,
+    #[serde(default)]
+    pub controller_support:       Option<ControllerSupport>,
+    #[serde(default)]
+    pub input_method:             Option<InputMethod>,
+    #[serde(default)]
+    pub max_local_players:        Option<i32>,
+    #[serde(default)]
+    pub max_online_players:       Option<i32>,
+    #[serde(default)]
+    pub coop_mode:                Option<CoopMode>,
+    #[serde(default)]
+    pub original_title:           Option<String>,
+    #[serde(default)]
+    pub sort_title:               Option<String>,
+}
+
+impl GameInput {
+    /// Reject obviously bad input with a field-specific error before SQLite's
+    /// CHECK constraints turn it into an opaque database failure.
+    pub fn validate(&self) -> CmdResult<()> {
+        if self.title.trim().is_empty() {
+            return Err(CommandError::validation("title", "must not be empty"));
+        }
+        if self.platform.trim().is_empty() {
+            return Err(CommandError::validation("platform", "must not be empty"));
+        }
+        if self.rating.is_some_and(|r| !(1.0..=10.0).contains(&r)) {
+            return Err(CommandError::validation("rating", "must be between 1 and 10"));
+        }
+        if self.progress_percent.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+            return Err(CommandError::validation("progress_percent", "must be between 0 and 100"));
+        }
+        if self.playtime_minutes.is_some_and(|m| m < 0) {
+            return Err(CommandError::validation("playtime_minutes", "must not be negative"));
+        }
+        if self.max_local_players.is_some_and(|p| p < 1) {
+            return Err(CommandError::validation("max_local_players", "must be at least 1"));
+        }
+        if self.max_online_players.is_some_and(|p| p < 1) {
+            return Err(CommandError::validation("max_online_players", "must be at least 1"));
+        }
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -112,27 +349,270 @@ pub struct GameInput {
 // ---------------------------------------------------------------------------
 
 /// All fields are optional — the frontend sends only the ones it wants to filter by.
-#[derive(Debug, Serialize, Deserialize)]
+/// `SearchFilter::default()` matches every game.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SearchFilter {
-    pub query:     Option<String>,      // searches title, franchise, notes
-    pub status:    Option<GameStatus>,
-    pub platform:  Option<String>,
-    pub franchise: Option<String>,
-    pub genre:     Option<String>,
-    pub min_rating: Option<f64>,
-    pub sort_by:   Option<SortField>,
-    pub sort_asc:  Option<bool>,
+    pub query:              Option<String>,      // searches `query_fields`
+    #[serde(default)]
+    pub query_fields:       Vec<QueryField>,     // empty = title, franchise and notes
+    pub fuzzy:              Option<bool>,        // typo-tolerant title match on `query`, best match first
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub status:             Vec<GameStatus>,     // any of these (OR); same for platform and genre
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub platform:           Vec<String>,
+    pub franchise:          Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub genre:              Vec<String>,
+    pub min_rating:         Option<f64>,
+    pub max_rating:         Option<f64>,
+    pub min_release_year:   Option<i32>,         // ranges are inclusive
+    pub max_release_year:   Option<i32>,
+    pub min_playtime_hours: Option<f64>,
+    pub max_playtime_hours: Option<f64>,
+    pub min_progress:       Option<f64>,         // percent
+    pub max_progress:       Option<f64>,
+    pub added_after:        Option<String>,      // "YYYY-MM-DD", inclusive; same for the other dates
+    pub added_before:       Option<String>,
+    pub completed_after:    Option<String>,
+    pub completed_before:   Option<String>,
+    #[serde(default)]
+    pub exclude_statuses:   Vec<GameStatus>,
+    #[serde(default)]
+    pub exclude_platforms:  Vec<String>,
+    #[serde(default)]
+    pub exclude_genres:     Vec<String>,         // drops games with any of these genres
+    pub installed:          Option<bool>,        // true = installed only, false = not installed
+    pub min_protondb_tier:  Option<ProtonTier>,  // e.g. Gold = Gold or Platinum
+    pub min_deck_status:    Option<DeckStatus>,  // e.g. Playable = Playable or Verified
+    pub controller_support: Option<ControllerSupport>,
+    pub input_method:       Option<InputMethod>,
+    pub coop_mode:          Option<CoopMode>,    // e.g. Local = local or local-and-online co-op
+    pub min_local_players:  Option<i32>,
+    pub min_online_players: Option<i32>,
+    pub played_with:        Option<String>,      // person's name
+    pub challenge:          Option<String>,      // has a playthrough with this challenge tag
+    #[serde(default)]
+    pub include_archived:   bool,                // archived games are left out unless this is set
+    pub favorite:           Option<bool>,        // true = favorites only, false = no favorites
+    #[serde(default)]
+    pub favorites_first:    bool,                // pinned games before the rest, each part in sort order
+    pub sort_by:            Option<SortField>,
+    pub sort_asc:           Option<bool>,
+    pub then_by:            Option<SortField>,   // secondary sort for ties in `sort_by`
+    pub then_asc:           Option<bool>,        // defaults to ascending
+    pub random_seed:        Option<i64>,         // for SortField::Random; same seed = same order
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Deserialize `null`, a single value or a list into a Vec, so callers that
+/// still send one status / platform / genre keep working.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    // RUST NOTE: `untagged` tries each variant in turn until one fits the JSON.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+    Ok(match Option::<OneOrMany<T>>::deserialize(deserializer)? {
+        None                        => vec![],
+        Some(OneOrMany::One(value)) => vec![value],
+        Some(OneOrMany::Many(list)) => list,
+    })
+}
+
+/// A field `SearchFilter.query` can be limited to. Title also covers the
+/// original title and aliases.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum QueryField {
+    Title,
+    Franchise,
+    Notes,
+    Developer,
+    Publisher,
+}
+
+impl QueryField {
+    /// What a query searches when no fields are picked.
+    pub const DEFAULT: [QueryField; 3] = [QueryField::Title, QueryField::Franchise, QueryField::Notes];
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum SortField {
     Title,
+    Franchise,
     ReleaseDate,
     Rating,
     PlaytimeHours,
     ProgressPercent,
     UpdatedAt,
+    CreatedAt,     // date added
+    CompletedAt,   // games that aren't completed sort last
     SequenceInFranchise,
+    Random,
+}
+
+/// One page of `search_games_page` (or, with `GameSummary`, of
+/// `search_game_summaries_page`). Pass `next_cursor` back to get the next
+/// page; it's `None` on the last one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GamePage<T = Game> {
+    pub games:       Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// What `get_games_grouped` buckets games by.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Status,
+    Platform,
+    Franchise,
+}
+
+/// One bucket of `get_games_grouped`: a header plus its games, in the
+/// filter's sort order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameGroup {
+    pub key:   Option<String>,   // raw value (e.g. "NotStarted"); None = games without a franchise
+    pub label: String,           // header text (e.g. "Not Started", "Standalone")
+    pub count: usize,
+    pub games: Vec<Game>,
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// A column that tabular exporters (CSV, …) can include.
+/// The frontend sends a list of these to pick which columns to write.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ExportColumn {
+    Id,
+    Title,
+    Franchise,
+    SequenceInFranchise,
+    ReleaseDate,
+    Platform,
+    Status,
+    ProgressPercent,
+    PlaytimeMinutes,
+    Rating,
+    Notes,
+    CoverArtPath,
+    Screenshots,
+    Developer,
+    Publisher,
+    Genres,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl ExportColumn {
+    /// Every column, in the same order as the Game struct.
+    pub const ALL: [ExportColumn; 18] = [
+        ExportColumn::Id, ExportColumn::Title, ExportColumn::Franchise,
+        ExportColumn::SequenceInFranchise, ExportColumn::ReleaseDate, ExportColumn::Platform,
+        ExportColumn::Status, ExportColumn::ProgressPercent, ExportColumn::PlaytimeMinutes,
+        ExportColumn::Rating, ExportColumn::Notes, ExportColumn::CoverArtPath,
+        ExportColumn::Screenshots, ExportColumn::Developer, ExportColumn::Publisher,
+        ExportColumn::Genres, ExportColumn::CreatedAt, ExportColumn::UpdatedAt,
+    ];
+
+    /// Header text written in the first row — matches the JSON field names.
+    pub fn header(&self) -> &'static str {
+        match self {
+            ExportColumn::Id                  => "id",
+            ExportColumn::Title               => "title",
+            ExportColumn::Franchise           => "franchise",
+            ExportColumn::SequenceInFranchise => "sequence_in_franchise",
+            ExportColumn::ReleaseDate         => "release_date",
+            ExportColumn::Platform            => "platform",
+            ExportColumn::Status              => "status",
+            ExportColumn::ProgressPercent     => "progress_percent",
+            ExportColumn::PlaytimeMinutes     => "playtime_minutes",
+            ExportColumn::Rating              => "rating",
+            ExportColumn::Notes               => "notes",
+            ExportColumn::CoverArtPath        => "cover_art_path",
+            ExportColumn::Screenshots         => "screenshots",
+            ExportColumn::Developer           => "developer",
+            ExportColumn::Publisher           => "publisher",
+            ExportColumn::Genres              => "genres",
+            ExportColumn::CreatedAt           => "created_at",
+            ExportColumn::UpdatedAt           => "updated_at",
+        }
+    }
+}
+
+/// How the Markdown export splits the library into sections.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MarkdownGrouping {
+    Status,
+    Franchise,
+}
+
+/// Which tracker a CSV export to import came from (see trackers.rs).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TrackerFormat {
+    HowLongToBeat,
+    Darkadia,
+    Completionator,
+}
+
+/// What the HTML export does with cover art.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HtmlCoverMode {
+    /// Inline every cover as a data: URI — one self-contained file.
+    Embed,
+    /// Copy covers into a folder next to the page and link to them — smaller
+    /// HTML, but the folder must be uploaded/sent along with it.
+    Link,
+    /// Leave covers out.
+    None,
+}
+
+/// Options for `export_html`. Every field is optional on the JS side.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HtmlExportOptions {
+    pub title:         String,
+    pub covers:        HtmlCoverMode,
+    pub include_notes: bool,          // notes can be personal — off by default
+}
+
+impl Default for HtmlExportOptions {
+    fn default() -> Self {
+        HtmlExportOptions {
+            title:         "My Game Library".to_string(),
+            covers:        HtmlCoverMode::Embed,
+            include_notes: false,
+        }
+    }
+}
+
+/// How `export_collage` lays covers out. Every field is optional on the JS side.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CollageLayout {
+    pub columns:    u32,              // rows follow from how many games match
+    pub tile_width: u32,              // pixels; tiles are 3:4, like box art
+    pub gap:        u32,              // pixels between and around the tiles
+    pub captions:   bool,             // each game's title under its cover
+    pub heading:    Option<String>,   // e.g. "Finished in 2024", above the grid
+}
+
+impl Default for CollageLayout {
+    fn default() -> Self {
+        CollageLayout {
+            columns:    6,
+            tile_width: 200,
+            gap:        8,
+            captions:   false,
+            heading:    None,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -141,15 +621,122 @@ pub enum SortField {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameStats {
+    pub favorites:            Vec<GameSummary>, // pinned games, by title
     pub total_games:          i64,
     pub by_status:            StatusBreakdown,
-    pub total_playtime_hours: f64,
+    pub total_playtime_minutes: i64,
     pub average_rating:       Option<f64>,
     pub completion_rate:      f64,              // % of non-wishlist games completed
     pub games_by_platform:    Vec<CountEntry>,
     pub games_by_genre:       Vec<CountEntry>,
     pub games_by_franchise:   Vec<CountEntry>,
     pub recent_completions:   Vec<String>,      // titles of recently completed games
+    pub most_replayed:        Vec<CountEntry>,  // title → replay count
+    pub new_game_plus_runs:   i64,
+    pub challenge_runs:       Vec<CountEntry>,  // challenge tag → playthroughs
+    pub installed_games:      i64,
+    pub install_size_bytes:   u64,              // disk used by all installed games
+    pub largest_installs:     Vec<InstallUsage>,
+}
+
+/// Top-10s and superlatives for the dashboard's widgets.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Highlights {
+    pub most_played:        Vec<Highlight>,       // top 10, value = minutes played
+    pub top_rated_by_genre: Vec<GenreHighlight>,  // each genre's best-rated game
+    pub longest_to_finish:  Option<Highlight>,    // value = days from purchase (or adding) to completion
+    pub oldest_backlog:     Option<Highlight>,    // never played; value = days waiting
+    pub shortest_completed: Option<Highlight>,    // value = minutes played
+}
+
+/// A game and the number it stands out for; `Highlights` says what it counts.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Highlight {
+    pub game:  GameSummary,
+    pub value: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenreHighlight {
+    pub genre: String,
+    pub game:  GameSummary,   // its rating is the genre's best
+}
+
+/// Something that happened to a game on this calendar date in an earlier
+/// year (see `get_on_this_day`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnThisDay {
+    pub game:      GameSummary,
+    pub event:     DayEvent,
+    pub date:      String,   // 'YYYY-MM-DD'
+    pub years_ago: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum DayEvent {
+    Completed,   // the game, or a replay of it, was finished
+    Started,     // a playthrough began, or the game was first played
+    Added,       // it went into the library
+}
+
+/// What a local usage counter counts (see `usage_counters` in db.rs).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UsageCounter {
+    Launch,      // the app was opened
+    GameAdded,   // a game went into the library, by hand or by an import
+    Edit,        // a game was saved from the edit form
+}
+
+impl UsageCounter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageCounter::Launch    => "launch",
+            UsageCounter::GameAdded => "game_added",
+            UsageCounter::Edit      => "edit",
+        }
+    }
+}
+
+/// "Your tracker habits": how the app itself gets used, from counters kept
+/// on this computer only.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UsageInsights {
+    pub tracking_since: Option<String>,   // first day anything was counted
+    pub days_used:      i64,              // days the app was opened
+    pub launches:       i64,
+    pub games_added:    i64,
+    pub edits:          i64,
+    pub weeks:          Vec<UsageWeek>,   // the most recent weeks, oldest first, empty ones included
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UsageWeek {
+    pub week_start:  String,   // the Monday, 'YYYY-MM-DD'
+    pub launches:    i64,
+    pub games_added: i64,
+    pub edits:       i64,
+}
+
+/// A game on the Now Playing shelf.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NowPlaying {
+    pub game_id:          i64,
+    pub title:            String,
+    pub cover_art_path:   Option<String>,
+    pub platform:         String,
+    pub progress_percent: Option<f64>,
+    pub playtime_minutes: Option<i64>,
+    pub last_activity:    String,   // latest session or edit, ISO 8601
+}
+
+/// An installed game and how much it takes up — for deciding what to uninstall.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallUsage {
+    pub game_id:        i64,
+    pub title:          String,
+    pub status:         GameStatus,
+    pub size_bytes:       u64,
+    pub playtime_minutes: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -167,4 +754,1179 @@ pub struct StatusBreakdown {
 pub struct CountEntry {
     pub name:  String,
     pub count: i64,
-}
\ No newline at end of file
+}
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// Where game details (developer, genres, cover…) are looked up.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum MetadataProviderKind {
+    /// The public Steam store — no API key needed.
+    #[default]
+    Steam,
+    /// Don't look anything up; imports create bare entries.
+    None,
+}
+
+/// How playtime is shown — in exports, the CLI and the app.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum PlaytimeUnit {
+    Hours,          // "12.1 h"
+    #[default]
+    HoursMinutes,   // "12h 05m"
+    Minutes,        // "725 min"
+}
+
+impl PlaytimeUnit {
+    pub fn format(&self, minutes: i64) -> String {
+        match self {
+            PlaytimeUnit::Hours        => format!("{:.1} h", minutes as f64 / 60.0),
+            PlaytimeUnit::HoursMinutes => format!("{}h {:02}m", minutes / 60, minutes % 60),
+            PlaytimeUnit::Minutes      => format!("{minutes} min"),
+        }
+    }
+}
+
+/// User preferences, stored one row per field in the `settings` table.
+/// Fields missing from the table (e.g. added in a newer version) use their default.
+/// API keys and tokens aren't settings; they live in the OS credential store
+/// (see credentials.rs).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub metadata_provider:           MetadataProviderKind,
+    pub steam_id:                    Option<String>,  // SteamID64, e.g. "76561197960287930"
+    pub steam_sync_interval_minutes: u32,             // 0 = background sync off
+    pub steam_path:                  Option<String>,  // Steam install folder, if not in the usual place
+    pub auto_backup_saves:           bool,            // back up saves whenever a session is logged
+    pub save_backups_to_keep:        u32,             // per game; 0 = keep every backup
+    pub metadata_refresh_hours:      u32,             // 0 = background metadata refresh off
+    pub protondb_stale_days:         u32,             // re-check ProtonDB tiers older than this
+    pub deck_status_stale_days:      u32,             // re-check Steam Deck status older than this
+    pub cover_stale_days:            u32,             // re-check downloaded covers older than this
+    pub rate_limits:                 BTreeMap<String, u32>,   // provider → requests per minute (0 = no limit); see get_rate_limits
+    pub max_images_mb:               u32,             // cap on the images folder; 0 = no cap
+    pub webdav_url:                  Option<String>,  // folder to sync the library to, e.g. a Nextcloud WebDAV URL
+    pub webdav_username:             Option<String>,  // the password is a credential
+    pub sync_safe:                   bool,            // games.db is in a cloud-drive folder; see filesync.rs (applies on restart)
+    pub auto_export_dir:             Option<String>,  // folder that gets a JSON snapshot of the library on exit
+    pub playing_limit:               u32,             // most games allowed in Playing at once; 0 = no limit
+    pub idle_threshold_minutes:      u32,             // timed sessions: no input this long counts as idle; 0 = don't check
+    pub subtract_idle_time:          bool,            // leave idle time out of timed sessions (otherwise only note it)
+    pub playtime_unit:               PlaytimeUnit,    // how playtime is shown; it's always stored in minutes
+    pub animated_cover_posters:      bool,            // save a still frame of animated covers for list views
+    pub proxy_url:                   Option<String>,  // send every request through this proxy, e.g. "http://proxy.corp:3128"
+    pub proxy_username:              Option<String>,  // the password is a credential
+    pub proxy_from_env:              bool,            // without `proxy_url`, use HTTPS_PROXY / HTTP_PROXY / ALL_PROXY
+    pub connect_timeout_secs:        u32,             // give up connecting to a server after this long
+    pub read_timeout_secs:           u32,             // give up on a server that sends nothing for this long
+    pub user_agent:                  Option<String>,  // sent instead of the default "GameTrc/<version> (+…)"
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            metadata_provider:           MetadataProviderKind::default(),
+            steam_id:                    None,
+            steam_sync_interval_minutes: 0,
+            steam_path:                  None,
+            auto_backup_saves:           false,
+            save_backups_to_keep:        10,
+            metadata_refresh_hours:      0,
+            protondb_stale_days:         7,
+            deck_status_stale_days:      30,
+            cover_stale_days:            30,
+            rate_limits:                 BTreeMap::new(),
+            max_images_mb:               0,
+            webdav_url:                  None,
+            webdav_username:             None,
+            sync_safe:                   false,
+            auto_export_dir:             None,
+            playing_limit:               0,
+            idle_threshold_minutes:      10,
+            subtract_idle_time:          true,
+            playtime_unit:               PlaytimeUnit::default(),
+            animated_cover_posters:      true,
+            proxy_url:                   None,
+            proxy_username:              None,
+            proxy_from_env:              true,
+            connect_timeout_secs:        10,
+            read_timeout_secs:           30,
+            user_agent:                  None,
+        }
+    }
+}
+
+/// Whether a secret is stored, for the settings screen (never the secret itself).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialStatus {
+    pub key:        String,   // "steam_api_key", "psn_npsso", …
+    pub provider:   String,   // "Steam", "PSN", …
+    pub label:      String,
+    pub configured: bool,
+}
+
+/// What an image eviction pass did. Sizes are in bytes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImageEvictionReport {
+    pub total_bytes:     u64,           // images folder before the pass
+    pub cap_bytes:       u64,
+    pub removed:         Vec<String>,   // file names of the deleted images
+    pub freed_bytes:     u64,
+    pub remaining_bytes: u64,
+    pub over_cap_bytes:  u64,           // still over the cap: everything left is in use
+}
+
+/// One IPC command, for the frontend's command palette (see registry.rs).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandInfo {
+    pub name:        String,            // what to pass to `invoke`
+    pub category:    String,            // its section of commands.rs, e.g. "Play sessions"
+    pub summary:     String,            // the doc comment's first paragraph
+    pub description: String,            // the whole doc comment, without the example
+    pub example:     Option<String>,    // the "Example JS call:" lines
+    pub args:        Vec<CommandArg>,
+    pub returns:     String,            // Rust type of the result, e.g. "Vec<Game>"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandArg {
+    pub name:      String,   // as `invoke` takes it, e.g. "gameId"
+    pub kind:      String,   // "integer", "number", "string", "boolean", "array" or "object"
+    pub rust_type: String,   // without the Option, e.g. "Vec<i64>"
+    pub optional:  bool,     // may be left out (or null)
+}
+
+/// The state of the app in one payload, for the about screen and bug
+/// reports. Sizes are in bytes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Diagnostics {
+    pub app_version:       String,
+    pub os:                String,                    // "linux x86_64"
+    pub db_path:           String,
+    pub db_size_bytes:     u64,                       // games.db and its write-ahead log
+    pub schema_version:    i64,                       // migrations applied
+    pub game_count:        i64,
+    pub images_dir:        String,
+    pub image_count:       usize,
+    pub images_size_bytes: u64,
+    pub cache:             CacheStats,
+    pub backups:           BackupStatus,
+    pub metadata_provider: MetadataProviderKind,
+    pub providers:         Vec<CredentialStatus>,     // which secrets are set (never the secrets)
+    pub proxy:             Option<String>,            // `proxy_url`, if requests go through one
+}
+
+/// Where copies of the library and saves are, and how recent they are.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BackupStatus {
+    pub auto_export_dir:     Option<String>,
+    pub last_snapshot_at:    Option<String>,   // when the exit snapshot there was written
+    pub webdav_url:          Option<String>,
+    pub last_webdav_sync_at: Option<String>,
+    pub save_backup_count:   i64,
+    pub save_backup_bytes:   i64,
+    pub last_save_backup_at: Option<String>,
+}
+
+/// Where a downloaded cover came from, for refreshing it (see covers.rs).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CoverSource {
+    pub url:           String,
+    pub cover:         String,           // the stored cover it was saved as
+    pub etag:          Option<String>,
+    pub last_modified: Option<String>,
+    pub hash:          Option<String>,   // SHA-256 of the downloaded bytes
+}
+
+/// A stored image path: a game's cover, or one of its screenshots.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImageRef {
+    pub game_id:       i64,
+    pub title:         String,
+    pub screenshot_id: Option<i64>,   // None = the cover
+    pub path:          String,
+}
+
+/// What `relink_images` did.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RelinkReport {
+    pub checked:      usize,
+    pub relinked:     usize,          // paths rewritten to the current images folder
+    pub missing:      Vec<ImageRef>,  // files found nowhere; paths left as they were
+    pub updated_ids:  Vec<i64>,
+}
+
+/// What an image is for; decides how it's stored (see images.rs).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum ImageKind {
+    #[default]
+    Cover,
+    Screenshot,
+}
+
+/// An area of an image, in its pixels from the top left.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct CropRect {
+    pub x:      u32,
+    pub y:      u32,
+    pub width:  u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ImageSize {
+    pub width:  u32,
+    pub height: u32,
+}
+
+/// One input's outcome in `process_images`: the saved path, or why it failed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessedImage {
+    pub input: String,
+    pub path:  Option<String>,
+    pub error: Option<String>,
+}
+
+/// Screenshots added to a game; exact copies of ones it already had are
+/// skipped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScreenshotImport {
+    pub game:    Game,
+    pub added:   usize,
+    pub skipped: usize,   // duplicates, by content
+}
+
+/// What the response cache holds (or what `clear_metadata_cache` removed).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CacheStats {
+    pub entries:    usize,
+    pub size_bytes: u64,
+    pub oldest:     Option<String>,   // when the oldest response was fetched
+}
+
+/// How fast requests to one service may go.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateLimit {
+    pub provider:           String,        // key for the `rate_limits` setting
+    pub hosts:              Vec<String>,
+    pub default_per_minute: u32,
+    pub per_minute:         u32,           // the limit in use (0 = no limit)
+}
+
+// ---------------------------------------------------------------------------
+// Metadata / import
+// ---------------------------------------------------------------------------
+
+/// Details about a game found by a metadata provider.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameMetadata {
+    pub source:       String,           // provider name, e.g. "Steam"
+    pub source_id:    String,           // the provider's own id (Steam app id, …)
+    pub title:        String,
+    pub release_date: Option<String>,   // "YYYY-MM-DD"
+    pub developer:    Option<String>,
+    pub publisher:    Option<String>,
+    pub genres:       Vec<String>,
+    pub description:  Option<String>,
+    pub cover_url:    Option<String>,
+}
+
+/// One line of a title list after lookup, shown to the user before importing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TitleImportEntry {
+    pub query:       String,                // the line as the user typed it
+    pub metadata:    Option<GameMetadata>,  // None if nothing matched
+    pub existing_id: Option<i64>,           // set if the game is already in the library
+}
+
+/// What an import does with an incoming game that matches a library game by
+/// title but isn't linked to the same source entry. (A linked game is the
+/// same game, not a conflict — syncs always update it.)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum ConflictStrategy {
+    #[default]
+    Skip,        // keep the library game as it is, just link it
+    Overwrite,   // imported values replace the library game's (fields the import lacks are kept)
+    FillEmpty,   // imported values only fill fields the library game leaves empty
+    Duplicate,   // add the import as a separate game
+}
+
+/// What an import would do, worked out by running it and rolling it back.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportPreview {
+    pub created: Vec<PreviewEntry>,
+    pub updated: Vec<PreviewEntry>,
+    pub skipped: Vec<PreviewEntry>,   // matched but unchanged, or left out (see `reason`)
+}
+
+/// One game in an ImportPreview.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviewEntry {
+    pub game_id: Option<i64>,          // None for games that would be created
+    pub title:   String,
+    pub changes: Vec<FieldChange>,     // for new games, every field that would be set
+    pub reason:  Option<String>,       // why it was skipped
+}
+
+/// A Game field an import would change. `achievements_unlocked` is included
+/// too, since syncs change it without touching the game row.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldChange {
+    pub field:  String,
+    pub before: serde_json::Value,     // null for new games
+    pub after:  serde_json::Value,
+}
+
+/// What `db::bulk_add_games` did. Rows that failed are skipped; the rest are
+/// still added.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BulkAddReport {
+    pub created_ids: Vec<i64>,        // in input order, failed rows left out
+    pub errors:      Vec<BulkAddError>,
+}
+
+/// Why one input row of a bulk add was skipped.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkAddError {
+    pub index:   usize,    // position in the input list
+    pub title:   String,
+    pub message: String,
+}
+
+/// An importer plugin found in the plugins folder (see plugins.rs).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImporterPlugin {
+    pub id:          String,        // the plugin's folder name
+    pub name:        String,
+    pub version:     String,
+    pub description: String,
+    pub input:       PluginInput,
+    pub file_types:  Vec<String>,   // extensions for the file picker, e.g. ["csv"]; empty = any
+    pub folder:      String,
+}
+
+/// What an importer plugin imports from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PluginInput {
+    File,   // a file the user picks
+    None,   // nothing; the plugin fetches the games itself
+}
+
+// ---------------------------------------------------------------------------
+// Play sessions / external accounts
+// ---------------------------------------------------------------------------
+
+/// Where a play session's minutes came from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SessionSource {
+    Manual,      // logged by the user
+    Timer,       // timed live with start_session / stop_session
+    Steam,       // playtime Steam reported beyond what was logged locally
+    Psn,         // same, from PlayStation Network
+    RetroArch,   // same, from RetroArch runtime logs
+}
+
+impl SessionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionSource::Manual    => "Manual",
+            SessionSource::Timer     => "Timer",
+            SessionSource::Steam     => "Steam",
+            SessionSource::Psn       => "PSN",
+            SessionSource::RetroArch => "RetroArch",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Timer"     => SessionSource::Timer,
+            "Steam"     => SessionSource::Steam,
+            "PSN"       => SessionSource::Psn,
+            "RetroArch" => SessionSource::RetroArch,
+            _           => SessionSource::Manual,
+        }
+    }
+}
+
+/// One stretch of playing a game.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaySession {
+    pub id:               i64,
+    pub game_id:          i64,
+    pub started_at:       String,   // RFC 3339
+    pub duration_minutes: i64,
+    pub source:           SessionSource,
+    pub game_version:     Option<String>,  // e.g. "1.6.2", "Patch 14"
+    pub idle_minutes:     i64,             // AFK time the timer noticed; left out of duration_minutes
+                                           // unless the subtract_idle_time setting is off
+    pub people:           Vec<Person>,     // who played along
+    pub created_at:       String,   // when it was recorded
+}
+
+/// A hand correction to a game's playtime.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaytimeAdjustment {
+    pub id:          i64,
+    pub game_id:     i64,
+    pub old_minutes: Option<i64>,
+    pub new_minutes: Option<i64>,
+    pub reason:      Option<String>,   // e.g. "Steam counted the game idling overnight"
+    pub created_at:  String,
+}
+
+/// The session the live timer is running (or holding paused).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveSession {
+    pub game_id:         i64,
+    pub title:           String,
+    pub started_at:      String,           // RFC 3339
+    pub paused_at:       Option<String>,   // Some = paused since then
+    pub paused_seconds:  i64,              // time spent paused before paused_at
+    pub game_version:    Option<String>,
+    pub heartbeat_at:    String,           // last moment the app was known to be running
+    pub idle_seconds:    i64,              // AFK or suspended time noticed so far
+    pub elapsed_seconds: i64,              // played so far, pauses (and idle time, if subtracted) excluded
+}
+
+/// One run through a game, from start to (optionally) finish.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Playthrough {
+    pub id:            i64,
+    pub game_id:       i64,
+    pub started_at:    Option<String>,   // "YYYY-MM-DD"
+    pub finished_at:   Option<String>,   // None = still going / abandoned
+    pub new_game_plus: bool,
+    pub challenge:     Option<String>,   // "No-hit", "Nuzlocke", "Pacifist", …
+    pub ruleset:       Option<String>,   // the challenge's rules, in my own words
+    pub game_version:  Option<String>,
+    pub notes:         Option<String>,
+    pub created_at:    String,
+}
+
+/// Used when creating or updating a playthrough.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaythroughInput {
+    pub game_id:       i64,
+    pub started_at:    Option<String>,
+    pub finished_at:   Option<String>,
+    #[serde(default)]
+    pub new_game_plus: bool,
+    pub challenge:     Option<String>,
+    pub ruleset:       Option<String>,
+    pub game_version:  Option<String>,
+    pub notes:         Option<String>,
+}
+
+impl PlaythroughInput {
+    pub fn validate(&self) -> CmdResult<()> {
+        for (field, date) in [("started_at", &self.started_at), ("finished_at", &self.finished_at)] {
+            if date.as_deref().is_some_and(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_err()) {
+                return Err(CommandError::validation(field, "must be a YYYY-MM-DD date"));
+            }
+        }
+        if self.ruleset.is_some() && self.challenge.as_deref().is_none_or(|c| c.trim().is_empty()) {
+            return Err(CommandError::validation("challenge", "a ruleset needs a challenge name"));
+        }
+        if let (Some(start), Some(end)) = (&self.started_at, &self.finished_at) {
+            if end < start {
+                return Err(CommandError::validation("finished_at", "must not be before started_at"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A speedrun personal best in one category.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersonalBest {
+    pub id:          i64,
+    pub game_id:     i64,
+    pub category:    String,           // "Any%", "100%", "Glitchless", …
+    pub time_ms:     i64,
+    pub achieved_on: Option<String>,   // "YYYY-MM-DD"
+    pub video_url:   Option<String>,
+    pub created_at:  String,
+}
+
+/// Used when creating or updating a personal best.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonalBestInput {
+    pub game_id:     i64,
+    pub category:    String,
+    pub time_ms:     i64,
+    pub achieved_on: Option<String>,
+    pub video_url:   Option<String>,
+}
+
+impl PersonalBestInput {
+    pub fn validate(&self) -> CmdResult<()> {
+        if self.category.trim().is_empty() {
+            return Err(CommandError::validation("category", "must not be empty"));
+        }
+        if self.time_ms <= 0 {
+            return Err(CommandError::validation("time_ms", "must be greater than 0"));
+        }
+        if self.achieved_on.as_deref().is_some_and(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_err()) {
+            return Err(CommandError::validation("achieved_on", "must be a YYYY-MM-DD date"));
+        }
+        if self.video_url.as_deref().is_some_and(|l| !(l.starts_with("http://") || l.starts_with("https://"))) {
+            return Err(CommandError::validation("video_url", "must be an http(s) URL"));
+        }
+        Ok(())
+    }
+}
+
+/// The headline personal best shown on a game.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BestRun {
+    pub category: String,
+    pub time_ms:  i64,
+}
+
+/// Someone I play games with.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Person {
+    pub id:   i64,
+    pub name: String,
+}
+
+/// How much I've played with one person.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersonStats {
+    pub id:             i64,
+    pub name:           String,
+    pub sessions:       i64,
+    pub total_minutes:  i64,
+    pub games:          i64,              // distinct games played together
+    pub top_game:       Option<String>,   // the game with the most time together
+}
+
+/// A folder holding a game's save files.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaveLocation {
+    pub id:         i64,
+    pub game_id:    i64,
+    pub path:       String,
+    pub created_at: String,
+}
+
+/// Why a save backup was taken.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BackupReason {
+    Manual,       // backup_saves
+    SessionEnd,   // automatic, after a play session was logged
+    PreRestore,   // the saves a restore was about to overwrite
+}
+
+impl BackupReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Manual     => "Manual",
+            BackupReason::SessionEnd => "SessionEnd",
+            BackupReason::PreRestore => "PreRestore",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "SessionEnd" => BackupReason::SessionEnd,
+            "PreRestore" => BackupReason::PreRestore,
+            _            => BackupReason::Manual,
+        }
+    }
+}
+
+/// One zip of a game's save folders.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaveBackup {
+    pub id:           i64,
+    pub game_id:      i64,
+    pub archive_path: String,
+    pub size_bytes:   u64,
+    pub file_count:   i64,
+    pub reason:       BackupReason,
+    pub created_at:   String,
+}
+
+/// A game's id on another service (e.g. its Steam app id), plus the last
+/// playtime numbers that service reported and, for store purchases, where to
+/// download it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalId {
+    pub game_id:                 i64,
+    pub source:                  String,          // "Steam", …
+    pub external_id:             String,
+    pub remote_playtime_minutes: Option<i64>,     // lifetime total at last sync
+    pub remote_recent_minutes:   Option<i64>,     // last two weeks, at last sync
+    pub remote_last_played:      Option<String>,  // RFC 3339
+    pub synced_at:               Option<String>,
+    pub url:                     Option<String>,  // the store page this copy is downloaded from
+    pub drm_free:                bool,
+}
+
+/// One order a game came in (see humble.rs).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Purchase {
+    pub id:           i64,
+    pub game_id:      i64,
+    pub store:        String,           // "Humble", …
+    pub order_id:     String,           // the store's id for the order
+    pub bundle:       Option<String>,   // e.g. "Humble Indie Bundle 20"; None for a single purchase
+    pub purchased_at: Option<String>,   // "YYYY-MM-DD"
+}
+
+/// A bundle bought, and how much of it has been played.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundleSummary {
+    pub store:        String,
+    pub bundle:       String,
+    pub purchased_at: Option<String>,
+    pub games:        i64,
+    pub played:       i64,
+}
+
+/// What a sync with an external account did.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncReport {
+    pub checked:       usize,      // remote titles / linked games looked at
+    pub imported_ids:  Vec<i64>,   // games created because they weren't in the library
+    pub updated_ids:   Vec<i64>,   // existing games whose playtime or achievements changed
+    pub added_minutes: i64,
+    pub preview:       Option<ImportPreview>,   // only set on dry runs, see `into_dry_run`
+}
+
+impl SyncReport {
+    /// Turn a finished run into a dry run's report. Its writes were rolled
+    /// back, so the ids are dropped (new games' ids were never kept) and the
+    /// details go in `preview`; `checked` and `added_minutes` still apply.
+    pub fn into_dry_run(self, preview: ImportPreview) -> Self {
+        SyncReport { imported_ids: vec![], updated_ids: vec![], preview: Some(preview), ..self }
+    }
+}
+
+/// What one metadata refresh did: per provider, how many stale games it
+/// re-checked and which of them changed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RefreshManifest {
+    pub id:          i64,
+    pub started_at:  String,
+    pub finished_at: String,
+    pub providers:   Vec<ProviderRefresh>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProviderRefresh {
+    pub provider:         String,          // "ProtonDB", "SteamDeck", …
+    pub stale_after_days: u32,
+    pub checked:          usize,           // stale games looked up successfully
+    pub updated_ids:      Vec<i64>,        // games whose value changed
+    pub error:            Option<String>,  // set if the provider failed as a whole
+}
+
+/// A trophy / achievement as stored for a game.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Achievement {
+    pub id:             i64,
+    pub game_id:        i64,
+    pub source:         String,          // "PSN", "Xbox", …
+    pub external_id:    String,          // the service's id for this achievement
+    pub name:           String,
+    pub description:    Option<String>,
+    pub icon_url:       Option<String>,
+    pub kind:           Option<String>,  // trophy grade: "Bronze", "Silver", "Gold", "Platinum"
+    pub points:         Option<i64>,     // e.g. Xbox gamerscore
+    pub hidden:         bool,
+    pub unlocked_at:    Option<String>,  // RFC 3339; None = still locked
+    pub rarity_percent: Option<f64>,     // share of players who unlocked it
+}
+
+/// Per-service achievement totals for one game (e.g. gamerscore earned / available).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AchievementSummary {
+    pub source:          String,
+    pub total:           i64,
+    pub unlocked:        i64,
+    pub points_total:    i64,
+    pub points_unlocked: i64,
+}
+
+/// An achievement as reported by a service, before it's tied to a game.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteAchievement {
+    pub external_id:    String,
+    pub name:           String,
+    pub description:    Option<String>,
+    pub icon_url:       Option<String>,
+    pub kind:           Option<String>,
+    pub points:         Option<i64>,
+    pub hidden:         bool,
+    pub unlocked_at:    Option<String>,
+    pub rarity_percent: Option<f64>,
+}
+
+// ---------------------------------------------------------------------------
+// Backup & sync
+// ---------------------------------------------------------------------------
+
+/// What `export_library` wrote or `import_library` restored.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LibraryReport {
+    pub path:       String,
+    pub games:      usize,
+    pub rows:       usize,   // across every table, games included
+    pub images:     usize,   // image files in a zip export
+    pub size_bytes: u64,
+    pub encrypted:  bool,    // passphrase-protected, see library.rs
+}
+
+/// Which way `webdav_sync` should go.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum WebDavDirection {
+    #[default]
+    Auto,   // push or pull, whichever side changed; stop if both did
+    Push,   // overwrite the server's copy with this library
+    Pull,   // replace this library with the server's copy
+}
+
+/// What a WebDAV sync did.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum WebDavAction {
+    Pushed,
+    Pulled,
+    UpToDate,
+    Conflict,   // both sides changed since the last sync; nothing was touched
+}
+
+/// What the last WebDAV sync saw, kept to tell which side changed since.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WebDavState {
+    pub remote_version: Option<String>,   // ETag, or Last-Modified if the server sends no ETag
+    pub library_hash:   Option<String>,   // see library::fingerprint
+    pub synced_at:      Option<String>,
+}
+
+/// What `webdav_sync` did.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebDavSyncReport {
+    pub action:     WebDavAction,
+    pub remote_url: String,
+    pub games:      usize,   // in the library pushed or pulled
+    pub size_bytes: u64,     // of the archive transferred
+    pub synced_at:  Option<String>,   // last successful sync
+}
+
+/// This app's claim on the database file (one row in `db_generation`).
+/// Every app that opens the file bumps `generation` and signs it, so a file
+/// replaced by a cloud-drive client can be told apart from the one open here.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DbGeneration {
+    pub generation: i64,
+    pub device:     Option<String>,   // device id of whoever opened it last
+    pub written_at: Option<String>,
+}
+
+/// What sync-safe mode found, for the frontend to prompt about.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FileSyncStatus {
+    pub enabled:           bool,           // the `sync_safe` setting
+    pub db_path:           String,
+    pub device:            String,         // this device's id
+    pub generation:        DbGeneration,   // the claim this app made on open
+    pub opened_elsewhere:  bool,           // another device used the file since this one last did
+    pub changed_on_disk:   bool,           // the file was replaced while open; resolve_file_sync
+    pub conflicted_copies: Vec<String>,    // "games (… conflicted copy …).db" next to the file; merge_database
+}
+
+/// How to settle a database file that changed on disk while the app had it open.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FileSyncResolution {
+    Reload,     // use the file on disk; edits made here since it changed are dropped
+    Merge,      // use the file on disk, then merge this app's games into it
+    KeepMine,   // overwrite the file on disk with this app's database
+}
+
+/// What merging another database's games into this one did.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MergeReport {
+    pub created_ids:  Vec<i64>,
+    pub updated_ids:  Vec<i64>,
+    pub deleted_ids:  Vec<i64>,   // games the other copy deleted after they were last edited here
+    pub unchanged:    usize,
+    pub conflict_ids: Vec<i64>,   // sync conflicts recorded; see get_sync_conflicts
+}
+
+/// When a game, or one of its fields, was last edited and on which device.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecordVersion {
+    pub revision:    i64,             // the game's revision after that edit
+    pub device_id:   Option<String>,  // None for edits made before devices were tracked
+    pub modified_at: String,
+}
+
+impl RecordVersion {
+    /// Whether this edit came after `other`. Timestamps are all UTC RFC 3339,
+    /// so they compare as strings; a tie goes to the higher device id so both
+    /// devices pick the same winner.
+    pub fn is_newer_than(&self, other: &RecordVersion) -> bool {
+        (&self.modified_at, &self.device_id) > (&other.modified_at, &other.device_id)
+    }
+}
+
+/// A field two copies of the library disagree on.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldConflict {
+    pub field:  String,              // GameInput field, e.g. "rating"
+    pub local:  serde_json::Value,   // the value this library kept
+    pub remote: serde_json::Value,   // the value the other copy had
+}
+
+/// A game a merge found edited differently on both sides. The merge kept the
+/// local values (they were newer, or neither side's edit time was known); the
+/// remote ones wait here until the user picks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncConflict {
+    pub id:          i64,
+    pub game_id:     i64,
+    pub title:       String,
+    pub source:      String,             // what found it, e.g. "games (conflicted copy).db"
+    pub fields:      Vec<FieldConflict>,
+    pub created_at:  String,
+    pub resolved_at: Option<String>,
+    pub resolution:  Option<String>,     // "KeepLocal", "KeepRemote" or "MergeFields"
+}
+
+/// How to settle a SyncConflict.
+/// RUST NOTE: serde writes a variant with data as `{ "MergeFields": [...] }`
+/// and one without as just `"KeepLocal"`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ConflictResolution {
+    KeepLocal,                // leave the game as it is
+    KeepRemote,               // take the other copy's value for every conflicting field
+    MergeFields(Vec<String>), // take the other copy's value for just these fields
+}
+
+impl ConflictResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictResolution::KeepLocal      => "KeepLocal",
+            ConflictResolution::KeepRemote     => "KeepRemote",
+            ConflictResolution::MergeFields(_) => "MergeFields",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compatibility
+// ---------------------------------------------------------------------------
+
+/// ProtonDB's rating of how well a Windows game runs through Proton, worst first.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+pub enum ProtonTier {
+    Borked,
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl ProtonTier {
+    pub const ALL: [ProtonTier; 5] = [
+        ProtonTier::Borked, ProtonTier::Bronze, ProtonTier::Silver, ProtonTier::Gold, ProtonTier::Platinum,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtonTier::Borked   => "Borked",
+            ProtonTier::Bronze   => "Bronze",
+            ProtonTier::Silver   => "Silver",
+            ProtonTier::Gold     => "Gold",
+            ProtonTier::Platinum => "Platinum",
+        }
+    }
+
+    /// Case-insensitive, so both stored values and ProtonDB's lowercase API
+    /// values parse. "pending" (too few reports) and anything unknown is None.
+    pub fn parse(s: &str) -> Option<Self> {
+        ProtonTier::ALL.into_iter().find(|t| t.as_str().eq_ignore_ascii_case(s))
+    }
+}
+
+/// Valve's Steam Deck compatibility review, worst first.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+pub enum DeckStatus {
+    Unsupported,
+    Playable,
+    Verified,
+}
+
+impl DeckStatus {
+    pub const ALL: [DeckStatus; 3] = [DeckStatus::Unsupported, DeckStatus::Playable, DeckStatus::Verified];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeckStatus::Unsupported => "Unsupported",
+            DeckStatus::Playable    => "Playable",
+            DeckStatus::Verified    => "Verified",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        DeckStatus::ALL.into_iter().find(|d| d.as_str().eq_ignore_ascii_case(s))
+    }
+
+    /// Steam's numeric `resolved_category`; 0 (not yet reviewed) is None.
+    pub fn from_category(category: u8) -> Option<Self> {
+        match category {
+            1 => Some(DeckStatus::Unsupported),
+            2 => Some(DeckStatus::Playable),
+            3 => Some(DeckStatus::Verified),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Soundtracks
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SoundtrackFormat {
+    Digital,
+    #[serde(rename = "CD")]
+    Cd,
+    Vinyl,
+    Cassette,
+    Other,
+}
+
+impl SoundtrackFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SoundtrackFormat::Digital  => "Digital",
+            SoundtrackFormat::Cd       => "CD",
+            SoundtrackFormat::Vinyl    => "Vinyl",
+            SoundtrackFormat::Cassette => "Cassette",
+            SoundtrackFormat::Other    => "Other",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Digital"  => SoundtrackFormat::Digital,
+            "CD"       => SoundtrackFormat::Cd,
+            "Vinyl"    => SoundtrackFormat::Vinyl,
+            "Cassette" => SoundtrackFormat::Cassette,
+            _          => SoundtrackFormat::Other,
+        }
+    }
+}
+
+/// A game's soundtrack / OST release.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Soundtrack {
+    pub id:              i64,
+    pub game_id:         i64,
+    pub game_title:      String,
+    pub title:           String,
+    pub owned:           bool,
+    pub format:          Option<SoundtrackFormat>,
+    pub favorite_tracks: Vec<String>,
+    pub link:            Option<String>,   // Bandcamp, Spotify, VGMdb, …
+    pub created_at:      String,
+    pub updated_at:      String,
+}
+
+/// Used when creating or updating a soundtrack.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoundtrackInput {
+    pub game_id:         i64,
+    pub title:           String,
+    #[serde(default)]
+    pub owned:           bool,
+    pub format:          Option<SoundtrackFormat>,
+    #[serde(default)]
+    pub favorite_tracks: Vec<String>,
+    pub link:            Option<String>,
+}
+
+impl SoundtrackInput {
+    pub fn validate(&self) -> CmdResult<()> {
+        if self.title.trim().is_empty() {
+            return Err(CommandError::validation("title", "must not be empty"));
+        }
+        if self.link.as_deref().is_some_and(|l| !(l.starts_with("http://") || l.starts_with("https://"))) {
+            return Err(CommandError::validation("link", "must be an http(s) URL"));
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Installed games
+// ---------------------------------------------------------------------------
+
+/// A game a launcher reports as installed on this machine.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DetectedInstall {
+    pub launcher:     String,        // "Steam", "Epic", "GOG"
+    pub external_id:  String,        // app id / AppName / GOG game id
+    pub title:        String,
+    pub install_path: String,
+    pub size_bytes:   Option<u64>,
+    pub game_id:      Option<i64>,   // matching library game, if any
+}
+
+/// Result of `scan_installed_games`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InstallScanReport {
+    pub installed: Vec<DetectedInstall>,   // matched to a library game (now marked installed)
+    pub missing:   Vec<DetectedInstall>,   // installed, but not in the library
+}
+
+// ---------------------------------------------------------------------------
+// Events
+// ---------------------------------------------------------------------------
+
+/// What kind of mutation a `games://changed` event describes.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ChangeAction {
+    Added,
+    Updated,
+    Deleted,
+}
+
+/// Payload of the `games://changed` event.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GamesChanged {
+    pub action: ChangeAction,
+    pub ids:    Vec<i64>,     // every game affected by the mutation
+}
+
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+/// Where a background job is in its life.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued    => "Queued",
+            JobStatus::Running   => "Running",
+            JobStatus::Succeeded => "Succeeded",
+            JobStatus::Failed    => "Failed",
+            JobStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Running"   => JobStatus::Running,
+            "Succeeded" => JobStatus::Succeeded,
+            "Failed"    => JobStatus::Failed,
+            "Cancelled" => JobStatus::Cancelled,
+            _           => JobStatus::Queued,
+        }
+    }
+
+    /// True once the job can't change any more.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// An import, sync or other long task run through the job queue. Also the
+/// payload of the `jobs://updated` event.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub id:          i64,
+    pub kind:        String,                      // "steam_sync", "psn_sync", "retroarch_import", …
+    pub status:      JobStatus,
+    pub done:        usize,                       // progress: items finished …
+    pub total:       usize,                       // … out of this many (0 = not known yet)
+    pub message:     Option<String>,              // what it's doing right now
+    pub result:      Option<serde_json::Value>,   // the command's return value, once it succeeded
+    pub error:       Option<String>,
+    pub created_at:  String,
+    pub started_at:  Option<String>,
+    pub finished_at: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Hooks
+// ---------------------------------------------------------------------------
+
+/// What a user hook runs on (see hooks.rs).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    GameAdded,       // a game went into the library, by hand or by an import
+    GameCompleted,   // a game's status was changed to Completed
+    SessionEnded,    // a play session was recorded
+}
+
+impl HookEvent {
+    /// The name hooks see, in the payload's "event" and GAMETRC_EVENT.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::GameAdded     => "game_added",
+            HookEvent::GameCompleted => "game_completed",
+            HookEvent::SessionEnded  => "session_ended",
+        }
+    }
+}
+
+/// A command the user registered to run on an event. Kept on this device
+/// only, in hooks.json.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Hook {
+    pub id:         String,
+    pub event:      HookEvent,
+    pub command:    String,        // a program on PATH, or the path to one
+    pub args:       Vec<String>,
+    pub enabled:    bool,
+    pub created_at: String,
+}
+
+/// Used when adding or changing a hook.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HookInput {
+    pub event:   HookEvent,
+    pub command: String,
+    #[serde(default)]
+    pub args:    Vec<String>,
+    pub enabled: Option<bool>,   // default true
+}
+
+/// How one run of a hook went.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HookRun {
+    pub exit_code:   Option<i32>,   // None if it was killed
+    pub timed_out:   bool,
+    pub duration_ms: u64,
+    pub stdout:      String,        // the first 64 KB of each
+    pub stderr:      String,
+}
+
+// ---------------------------------------------------------------------------
+// Diagnostics
+// ---------------------------------------------------------------------------
+
+/// One line from the log file, as shown in the in-app log viewer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,                                  // RFC 3339
+    pub level:     String,                                  // "ERROR", "WARN", "INFO", …
+    pub target:    String,                                  // module path, e.g. "gametrc::db"
+    pub message:   String,
+    pub fields:    serde_json::Map<String, serde_json::Value>, // structured extras
+}
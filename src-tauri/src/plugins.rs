@@ -0,0 +1,384 @@
+// plugins.rs — Importers for other services, shipped as plugins.
+//
+// Niche trackers and stores come and go faster than GameTrc can add an
+// importer for each, so the community can write their own. A plugin is a
+// folder in app_data_dir/plugins/ with a manifest and a program:
+//
+//   plugins/backloggd/
+//     plugin.json    { "name": "Backloggd", "version": "1.0.0",
+//                      "description": "…", "command": "bin/backloggd-import",
+//                      "input": "File", "file_types": ["csv"], "contract": 1 }
+//     bin/backloggd-import
+//
+// The folder name is the plugin's id. `command` is relative to the folder
+// (a script needs its shebang and the executable bit), `args` are passed as
+// given. Plugins written against another `contract` aren't loaded.
+//
+// The contract is JSON over stdin/stdout. GameTrc writes one request:
+//
+//   { "contract": 1, "path": "/home/me/export.csv", "platform": "PC" }
+//
+// `path` is the file the user picked (null for plugins whose `input` is
+// "None", which fetch from somewhere themselves). The plugin answers on
+// stdout and exits with 0:
+//
+//   { "games": [{ "id": "123", "title": "Hades", "platform": "PC",
+//                 "status": "Completed", "rating": 9.5, "playtime_minutes": 3672,
+//                 "started_at": "2021-01-02", "finished_at": "2021-02-14" }] }
+//
+// Only `title` is required; see PluginGame for the rest. Anything written to
+// stderr goes to the log. An `id` links the game (source "plugin:<id>"), so
+// importing again matches it instead of adding it twice.
+//
+// A plugin is an ordinary program running as the user, with everything the
+// user can reach: there is no sandbox, so installing one means trusting it
+// like any other program. The core trusts nothing it's handed back, though:
+//
+//   Limits       The program runs in its own folder with an emptied
+//                environment (only PATH and the few variables the OS needs),
+//                so it isn't handed credentials, proxy or home on a plate,
+//                nor any path but the file the user picked. It's killed
+//                after TIMEOUT, on cancel, or for writing more than
+//                MAX_OUTPUT.
+//   Validation   The whole answer is checked before anything is saved
+//                (`validate`): a bad game stops the import and names it.
+//   Import       Valid games go through `trackers::import_entries`, so
+//                conflicts, dry runs and progress work like any import.
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::{CmdResult, CommandError};
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::models::{ConflictStrategy, GameStatus, ImporterPlugin, PluginInput, SyncReport};
+use crate::trackers::{self, TrackerEntry};
+
+/// The contract version this build speaks.
+pub const CONTRACT: u32 = 1;
+
+const MANIFEST: &str = "plugin.json";
+
+/// How long a plugin may run.
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Most a plugin may write to stdout (its answer); stderr keeps less.
+const MAX_OUTPUT: usize = 32 * 1024 * 1024;
+const MAX_STDERR: usize = 64 * 1024;
+
+/// Environment variables a plugin still sees. Windows programs need the
+/// system ones to start at all.
+const PASSED_ENV: &[&str] = &["PATH", "SYSTEMROOT", "WINDIR", "TEMP", "TMP", "LANG"];
+
+#[derive(Deserialize)]
+struct Manifest {
+    name:        String,
+    #[serde(default)]
+    version:     String,
+    #[serde(default)]
+    description: String,
+    command:     String,
+    #[serde(default)]
+    args:        Vec<String>,
+    #[serde(default = "default_input")]
+    input:       PluginInput,
+    #[serde(default)]
+    file_types:  Vec<String>,
+    contract:    u32,
+}
+
+fn default_input() -> PluginInput {
+    PluginInput::File
+}
+
+/// A loaded plugin: what the frontend sees, plus how to run it.
+struct Plugin {
+    info:    ImporterPlugin,
+    program: PathBuf,
+    args:    Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    games: Vec<PluginGame>,
+}
+
+/// One game in a plugin's answer.
+#[derive(Deserialize)]
+struct PluginGame {
+    id:               Option<String>,
+    title:            String,
+    platform:         Option<String>,        // default: the platform the user chose
+    status:           Option<GameStatus>,    // default: Backlog
+    rating:           Option<f64>,           // 1 – 10
+    playtime_minutes: Option<i64>,
+    progress_percent: Option<f64>,
+    notes:            Option<String>,
+    developer:        Option<String>,
+    publisher:        Option<String>,
+    #[serde(default)]
+    genres:           Vec<String>,
+    release_date:     Option<String>,        // "YYYY-MM-DD"
+    started_at:       Option<String>,        // with finished_at, becomes a playthrough
+    finished_at:      Option<String>,
+    #[serde(default)]
+    favorite:         bool,
+}
+
+/// Resolve (and create) app_data_dir/plugins/.
+pub fn get_plugins_dir(app: &AppHandle) -> CmdResult<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| CommandError::Internal(e.to_string()))?
+        .join("plugins");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Every plugin in `dir` that loads, by name. One that doesn't is logged and
+/// left out.
+pub fn list(dir: &Path) -> CmdResult<Vec<ImporterPlugin>> {
+    Ok(discover(dir)?.into_iter().map(|p| p.info).collect())
+}
+
+fn discover(dir: &Path) -> CmdResult<Vec<Plugin>> {
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let folder = entry?.path();
+        if !folder.join(MANIFEST).is_file() {
+            continue;
+        }
+        match load(&folder) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => warn!(folder = %folder.display(), error = %e, "Skipping importer plugin"),
+        }
+    }
+    plugins.sort_by_key(|p| p.info.name.to_lowercase());
+    Ok(plugins)
+}
+
+fn load(folder: &Path) -> CmdResult<Plugin> {
+    let id = folder.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(CommandError::validation("id", "the folder name may only use letters, digits, '-' and '_'"));
+    }
+    let manifest: Manifest = serde_json::from_str(&std::fs::read_to_string(folder.join(MANIFEST))?)
+        .map_err(|e| CommandError::validation(MANIFEST, e.to_string()))?;
+    if manifest.contract != CONTRACT {
+        return Err(CommandError::validation("contract", format!("is {}, this GameTrc speaks {CONTRACT}", manifest.contract)));
+    }
+    if manifest.name.trim().is_empty() {
+        return Err(CommandError::validation("name", "must not be empty"));
+    }
+
+    // The program has to be the plugin's own — not something elsewhere on disk.
+    let command = Path::new(&manifest.command);
+    if command.as_os_str().is_empty() || !command.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(CommandError::validation("command", "must be a path inside the plugin folder"));
+    }
+    let program = folder.join(command).canonicalize()?;
+    if !program.starts_with(folder.canonicalize()?) || !program.is_file() {
+        return Err(CommandError::validation("command", "must be a file inside the plugin folder"));
+    }
+
+    Ok(Plugin {
+        info: ImporterPlugin {
+            id,
+            name:        manifest.name.trim().to_string(),
+            version:     manifest.version,
+            description: manifest.description,
+            input:       manifest.input,
+            file_types:  manifest.file_types,
+            folder:      folder.to_string_lossy().into_owned(),
+        },
+        program,
+        args: manifest.args,
+    })
+}
+
+/// Run plugin `id` and import what it returns. `path` is the file the user
+/// picked (required unless the plugin's input is None); games without a
+/// platform get `platform`. With `dry_run` nothing is saved.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    dir: &Path,
+    db: &Mutex<Connection>,
+    id: &str,
+    path: Option<&Path>,
+    platform: &str,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let plugin = discover(dir)?
+        .into_iter()
+        .find(|p| p.info.id == id)
+        .ok_or_else(|| CommandError::NotFound(format!("Importer plugin '{id}'")))?;
+    let path = match (plugin.info.input, path) {
+        (PluginInput::None, _) => None,
+        (PluginInput::File, Some(path)) if path.is_file() => Some(path),
+        (PluginInput::File, Some(_)) => return Err(CommandError::validation("path", "no such file")),
+        (PluginInput::File, None) => return Err(CommandError::validation("path", format!("{} imports from a file", plugin.info.name))),
+    };
+
+    job.progress(0, 1, format!("Running {}", plugin.info.name));
+    let request = serde_json::json!({ "contract": CONTRACT, "path": path, "platform": platform });
+    let output = execute(&plugin, request.to_string().as_bytes(), job)?;
+    let response: Response = serde_json::from_slice(&output)
+        .map_err(|e| CommandError::validation("output", format!("{} answered with invalid JSON: {e}", plugin.info.name)))?;
+    let entries = validate(response.games, platform).map_err(|e| match e {
+        CommandError::ValidationFailed { field, message } => CommandError::validation(&field, format!("{}: {message}", plugin.info.name)),
+        e => e,
+    })?;
+    if entries.is_empty() {
+        return Err(CommandError::validation("output", format!("{} found no games", plugin.info.name)));
+    }
+
+    info!(plugin = id, version = %plugin.info.version, games = entries.len(), "Importing from plugin");
+    trackers::import_entries(db, &format!("plugin:{id}"), entries, dry_run, on_conflict, job)
+}
+
+/// Run the program with `request` on stdin; its stdout once it exits with 0.
+fn execute(plugin: &Plugin, request: &[u8], job: &JobHandle) -> CmdResult<Vec<u8>> {
+    let mut command = Command::new(&plugin.program);
+    command
+        .args(&plugin.args)
+        .current_dir(&plugin.info.folder)
+        .env_clear()
+        .envs(PASSED_ENV.iter().filter_map(|name| std::env::var_os(name).map(|value| (name, value))))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command
+        .spawn()
+        .map_err(|e| CommandError::Io(format!("Couldn't start {}: {e}", plugin.info.name)))?;
+
+    // Read both pipes on their own threads so a chatty plugin never blocks
+    // on a full pipe while we wait for it.
+    let stdout = capture(child.stdout.take(), MAX_OUTPUT);
+    let stderr = capture(child.stderr.take(), MAX_STDERR);
+    if let Some(mut stdin) = child.stdin.take() {
+        // A plugin that doesn't read its request closes the pipe; that's its business.
+        let _ = stdin.write_all(request);
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let stop = if job.check_cancelled().is_err() {
+            Some(CommandError::Cancelled)
+        } else if started.elapsed() > TIMEOUT {
+            Some(CommandError::Io(format!("{} didn't finish within {} minutes", plugin.info.name, TIMEOUT.as_secs() / 60)))
+        } else {
+            None
+        };
+        if let Some(e) = stop {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    // The output still counts against the time limit.
+    let left = TIMEOUT.saturating_sub(started.elapsed());
+    let (stdout, overflowed) = stdout
+        .recv_timeout(left)
+        .map_err(|_| CommandError::Io(format!("{} exited, but something it started kept its output open", plugin.info.name)))?;
+    let (stderr, _) = stderr.recv_timeout(TIMEOUT.saturating_sub(started.elapsed())).unwrap_or_default();
+    let stderr = String::from_utf8_lossy(&stderr);
+    if !stderr.trim().is_empty() {
+        info!(plugin = %plugin.info.id, stderr = %stderr.trim(), "Plugin wrote to stderr");
+    }
+    if !status.success() {
+        let last = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("no details");
+        return Err(CommandError::Io(format!("{} failed ({status}): {last}", plugin.info.name)));
+    }
+    if overflowed {
+        return Err(CommandError::validation("output", format!("{} wrote more than {} MB", plugin.info.name, MAX_OUTPUT / (1024 * 1024))));
+    }
+    Ok(stdout)
+}
+
+/// Read `stream` to the end on a thread of its own, keeping the first
+/// `limit` bytes. The receiver gets them, and whether there was more, at the
+/// end of the stream — which can come long after the program exits if
+/// something it started in the background still holds the pipe, so wait on
+/// it with `recv_timeout`. User hooks (hooks.rs) read theirs this way too.
+pub fn capture(stream: Option<impl Read + Send + 'static>, limit: usize) -> mpsc::Receiver<(Vec<u8>, bool)> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut kept, mut overflowed) = (Vec::new(), false);
+        if let Some(mut stream) = stream {
+            let mut chunk = [0u8; 8192];
+            // Past the limit the rest is still drained, so the plugin can finish.
+            while let Ok(n @ 1..) = stream.read(&mut chunk) {
+                let room = limit.saturating_sub(kept.len());
+                kept.extend_from_slice(&chunk[..n.min(room)]);
+                overflowed |= n > room;
+            }
+        }
+        let _ = sender.send((kept, overflowed));
+    });
+    receiver
+}
+
+/// Turn a plugin's games into import entries, refusing the lot if any is
+/// bad. Errors name the game, e.g. field "games[3].rating".
+fn validate(games: Vec<PluginGame>, platform: &str) -> CmdResult<Vec<TrackerEntry>> {
+    let mut ids = HashSet::new();
+    let mut entries = Vec::with_capacity(games.len());
+    for (i, game) in games.into_iter().enumerate() {
+        let field = |name: &str| format!("games[{i}].{name}");
+        let date = |name: &str, value: Option<String>| -> CmdResult<Option<String>> {
+            value
+                .map(|v| trackers::parse_date(&v).ok_or_else(|| CommandError::validation(&field(name), format!("'{v}' isn't a date"))))
+                .transpose()
+        };
+
+        if let Some(id) = &game.id {
+            if id.trim().is_empty() || id.len() > 200 {
+                return Err(CommandError::validation(&field("id"), "must be 1 – 200 characters"));
+            }
+            if !ids.insert(id.clone()) {
+                return Err(CommandError::validation(&field("id"), format!("'{id}' is used twice")));
+            }
+        }
+        let platform = game.platform.as_deref().filter(|p| !p.trim().is_empty()).unwrap_or(platform);
+        let mut input = import::remote_title_input(&game.title, platform.trim(), game.status.unwrap_or(GameStatus::Backlog));
+        input.rating = game.rating;
+        input.playtime_minutes = game.playtime_minutes;
+        input.progress_percent = game.progress_percent;
+        input.notes = game.notes.filter(|n| !n.trim().is_empty());
+        input.developer = game.developer.filter(|d| !d.trim().is_empty());
+        input.publisher = game.publisher.filter(|p| !p.trim().is_empty());
+        input.genres = game.genres.into_iter().map(|g| g.trim().to_string()).filter(|g| !g.is_empty()).collect();
+        input.release_date = date("release_date", game.release_date)?;
+        input.validate().map_err(|e| match e {
+            CommandError::ValidationFailed { field: name, message } => CommandError::validation(&field(&name), message),
+            e => e,
+        })?;
+
+        entries.push(TrackerEntry {
+            started_at:  date("started_at", game.started_at)?,
+            finished_at: date("finished_at", game.finished_at)?,
+            favorite:    game.favorite,
+            external_id: game.id,
+            ..TrackerEntry::new(input)
+        });
+    }
+    Ok(entries)
+}
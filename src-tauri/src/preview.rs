@@ -0,0 +1,125 @@
+// preview.rs — Dry runs for importers.
+//
+// Rather than teaching every importer to predict its own writes, a dry run
+// does the real import inside a transaction, notes how each touched game
+// differs from before, and rolls back. The preview therefore can't disagree
+// with what a real import would do.
+//
+//   let mut recorder = PreviewRecorder::start(&conn)?;   // before any writes
+//   let tx = conn.transaction()?;
+//   … import, calling recorder.record(&tx, game_id) after each game …
+//   drop(tx);                                            // rolls back
+//   let preview = recorder.finish();
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::models::{FieldChange, Game, ImportPreview, PreviewEntry};
+
+/// Fields that change on every write, so they'd only be noise in a diff.
+const IGNORED_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+
+pub struct PreviewRecorder {
+    before:   HashMap<i64, Map<String, Value>>,   // every game, as it was at `start`
+    recorded: HashSet<i64>,                       // existing games already in the preview
+    preview:  ImportPreview,
+}
+
+impl PreviewRecorder {
+    /// Snapshot the library. Call before the import writes anything.
+    pub fn start(conn: &Connection) -> CmdResult<Self> {
+        let mut before = HashMap::new();
+        for game in db::get_all_games(conn)? {
+            before.insert(game.id, snapshot(conn, &game)?);
+        }
+        Ok(PreviewRecorder { before, recorded: HashSet::new(), preview: ImportPreview::default() })
+    }
+
+    /// Compare `game_id` as the import left it (inside its transaction) with
+    /// the snapshot. A game recorded twice only appears once.
+    pub fn record(&mut self, conn: &Connection, game_id: i64) -> CmdResult<()> {
+        let Some(game) = db::get_game(conn, game_id)? else { return Ok(()) };
+        let after = snapshot(conn, &game)?;
+
+        let Some(before) = self.before.get(&game_id) else {
+            // New game. Its id is only borrowed (the insert gets rolled back),
+            // so it isn't reported or used to spot repeats.
+            let changes = after
+                .into_iter()
+                .filter(|(field, value)| !IGNORED_FIELDS.contains(&field.as_str()) && !is_empty(value))
+                .map(|(field, after)| FieldChange { field, before: Value::Null, after })
+                .collect();
+            self.preview.created.push(PreviewEntry { game_id: None, title: game.title, changes, reason: None });
+            return Ok(());
+        };
+        if !self.recorded.insert(game_id) {
+            return Ok(());
+        }
+
+        let changes: Vec<FieldChange> = after
+            .into_iter()
+            .filter(|(field, value)| !IGNORED_FIELDS.contains(&field.as_str()) && before.get(field) != Some(value))
+            .map(|(field, after)| FieldChange {
+                before: before.get(&field).cloned().unwrap_or(Value::Null),
+                field,
+                after,
+            })
+            .collect();
+        let entry = PreviewEntry { game_id: Some(game_id), title: game.title, changes, reason: None };
+        if entry.changes.is_empty() {
+            self.preview.skipped.push(PreviewEntry { reason: Some("Already up to date".to_string()), ..entry });
+        } else {
+            self.preview.updated.push(entry);
+        }
+        Ok(())
+    }
+
+    /// Note something the import left out entirely, e.g. a linked game the
+    /// service no longer reports.
+    pub fn skip(&mut self, game_id: Option<i64>, title: &str, reason: &str) {
+        self.preview.skipped.push(PreviewEntry {
+            game_id,
+            title:   title.to_string(),
+            changes: vec![],
+            reason:  Some(reason.to_string()),
+        });
+    }
+
+    /// Title of a game as it was at `start`, for `skip`.
+    pub fn title_of(&self, game_id: i64) -> String {
+        self.before
+            .get(&game_id)
+            .and_then(|game| game.get("title")?.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub fn finish(self) -> ImportPreview {
+        self.preview
+    }
+}
+
+/// A game as a JSON object, plus its unlocked achievement count.
+fn snapshot(conn: &Connection, game: &Game) -> CmdResult<Map<String, Value>> {
+    let Value::Object(mut fields) = serde_json::to_value(game).map_err(|e| CommandError::Internal(e.to_string()))? else {
+        return Err(CommandError::Internal("Game didn't serialize to an object".to_string()));
+    };
+    let unlocked: i64 = db::get_achievement_summaries(conn, game.id)?.iter().map(|s| s.unlocked).sum();
+    fields.insert("achievements_unlocked".to_string(), unlocked.into());
+    Ok(fields)
+}
+
+/// Unset fields aren't listed for new games.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Bool(b) => !b,
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        _ => false,
+    }
+}
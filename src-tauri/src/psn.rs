@@ -0,0 +1,302 @@
+// psn.rs — PlayStation Network: owned titles, playtime and trophies.
+//
+// Sony has no public API, so this talks to the same endpoints the PlayStation
+// App uses. Authentication starts from an NPSSO token — a cookie value the
+// user copies from https://ca.account.sony.com/api/v1/ssocookie while signed
+// in on the web — which is exchanged for a short-lived access token:
+//
+//   NPSSO ──authorize──▶ code ──token──▶ access token (Bearer)
+//
+// Each trophy title becomes (or is matched to) a library game linked through
+// `game_external_ids` (source "PSN", id = npCommunicationId); its trophies go
+// into `achievements`. PS4/PS5 titles also report playtime, which is merged
+// with `db::merge_remote_playtime` just like Steam's.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::credentials;
+use crate::db::{self, ImportOutcome};
+use crate::error::{CmdResult, CommandError};
+use crate::http;
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::metadata::normalize_title;
+use crate::models::{ConflictStrategy, RemoteAchievement, SessionSource, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for PSN rows in `game_external_ids` and `achievements`.
+pub const SOURCE: &str = "PSN";
+
+const AUTHORIZE_URL: &str = "https://ca.account.sony.com/api/authz/v3/oauth/authorize";
+const TOKEN_URL: &str = "https://ca.account.sony.com/api/authz/v3/oauth/token";
+const API_BASE: &str = "https://m.np.playstation.com/api";
+
+// The PlayStation App's public client credentials.
+const CLIENT_ID: &str = "09515159-7237-4370-9b40-3806e67c0891";
+const CLIENT_BASIC_AUTH: &str = "Basic MDk1MTUxNTktNzIzNy00MzcwLTliNDAtMzgwNmU2N2MwODkxOnVjUGprYTV0bnRCMktxc1A=";
+const REDIRECT_URI: &str = "com.scee.psxandroid.scecompcall://redirect";
+
+// ---------------------------------------------------------------------------
+// Auth
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Trade an NPSSO token for an API access token.
+fn authenticate(npsso: &str) -> CmdResult<String> {
+    // The authorize step answers with a redirect whose Location holds the
+    // code, so this one request must not follow redirects.
+    let request = http::builder()
+        .redirects(0)
+        .build()
+        .get(AUTHORIZE_URL)
+        .query("access_type", "offline")
+        .query("client_id", CLIENT_ID)
+        .query("redirect_uri", REDIRECT_URI)
+        .query("response_type", "code")
+        .query("scope", "psn:mobile.v2.core psn:clientapp")
+        .set("Cookie", &format!("npsso={}", npsso.trim()));
+    let response = http::call(request)?;
+    let code = response
+        .header("location")
+        .and_then(|loc| loc.split(['?', '&']).find_map(|p| p.strip_prefix("code=")))
+        .map(str::to_string)
+        .ok_or_else(|| CommandError::validation("psn_npsso", "PSN rejected the NPSSO token — it may have expired"))?;
+
+    let request = http::agent().post(TOKEN_URL).set("Authorization", CLIENT_BASIC_AUTH);
+    let form = [
+        ("code", code.as_str()),
+        ("redirect_uri", REDIRECT_URI),
+        ("grant_type", "authorization_code"),
+        ("token_format", "jwt"),
+    ];
+    let body = http::send_form(request, &form)?.into_string()?;
+    let token: TokenResponse =
+        serde_json::from_str(&body).map_err(|e| CommandError::Http(format!("Unexpected PSN token response: {e}")))?;
+    Ok(token.access_token)
+}
+
+fn get<T: serde::de::DeserializeOwned>(token: &str, path: &str, query: &[(&str, &str)]) -> CmdResult<T> {
+    let mut request = http::agent()
+        .get(&format!("{API_BASE}{path}"))
+        .set("Authorization", &format!("Bearer {token}"));
+    for (key, value) in query {
+        request = request.query(key, value);
+    }
+    http::read_json(request)
+}
+
+// ---------------------------------------------------------------------------
+// API types
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrophyTitles {
+    #[serde(default)]
+    trophy_titles: Vec<TrophyTitle>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrophyTitle {
+    np_communication_id: String,
+    np_service_name:     String,     // "trophy" (PS3/PS4/Vita) or "trophy2" (PS5)
+    trophy_title_name:   String,
+    trophy_title_platform: String,   // "PS5", "PS4", "PS3,PSVITA", …
+    #[serde(default)]
+    progress:            u32,        // 0 – 100
+}
+
+#[derive(Deserialize)]
+struct Trophies {
+    #[serde(default)]
+    trophies: Vec<Trophy>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Trophy {
+    trophy_id:          i64,
+    trophy_type:        Option<String>,    // "bronze", "silver", "gold", "platinum"
+    trophy_name:        Option<String>,
+    trophy_detail:      Option<String>,
+    trophy_icon_url:    Option<String>,
+    #[serde(default)]
+    trophy_hidden:      bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EarnedTrophy {
+    trophy_id:          i64,
+    #[serde(default)]
+    earned:             bool,
+    earned_date_time:   Option<String>,
+    trophy_earned_rate: Option<String>,    // "12.3"
+}
+
+#[derive(Deserialize)]
+struct EarnedTrophies {
+    #[serde(default)]
+    trophies: Vec<EarnedTrophy>,
+}
+
+#[derive(Deserialize)]
+struct PlayedTitles {
+    #[serde(default)]
+    titles: Vec<PlayedTitle>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayedTitle {
+    name:                  String,
+    play_duration:         Option<String>,  // ISO 8601 duration, e.g. "PT12H3M"
+    last_played_date_time: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Sync
+// ---------------------------------------------------------------------------
+
+/// Import the account's trophy titles, their trophies and PS4/PS5 playtime.
+/// With `dry_run` nothing is saved; the report's `preview` says what would change.
+/// `on_conflict` decides what happens to unlinked library games with the same title.
+pub fn sync(db: &Mutex<Connection>, dry_run: bool, on_conflict: ConflictStrategy, job: &JobHandle) -> CmdResult<SyncReport> {
+    let npsso = credentials::require(credentials::PSN_NPSSO)?;
+    let token = authenticate(&npsso)?;
+
+    let titles: TrophyTitles = get(&token, "/trophy/v1/users/me/trophyTitles", &[("limit", "800")])?;
+
+    // Playtime is a nice extra — if the game list call fails, still sync trophies.
+    let played_query = [("categories", "ps4_game,ps5_native_game"), ("limit", "500")];
+    let played: HashMap<String, PlayedTitle> = match get::<PlayedTitles>(&token, "/gamelist/v2/users/me/titles", &played_query) {
+        Ok(p) => p.titles.into_iter().map(|t| (normalize_title(&t.name), t)).collect(),
+        Err(e) => {
+            warn!(error = %e, "Couldn't fetch PSN playtime");
+            HashMap::new()
+        }
+    };
+
+    let mut report = SyncReport { checked: titles.trophy_titles.len(), ..SyncReport::default() };
+    let mut recorder = if dry_run { Some(PreviewRecorder::start(&*db::lock(db)?)?) } else { None };
+    for (i, title) in titles.trophy_titles.iter().enumerate() {
+        // Titles already committed stay; cancelling only stops the rest.
+        job.check_cancelled()?;
+        job.progress(i, report.checked, title.trophy_title_name.as_str());
+        let achievements = fetch_trophies(&token, title)?;
+
+        // Lock per title so the app isn't blocked for the whole (slow) sync.
+        let mut conn = db::lock(db)?;
+        let tx = conn.transaction()?;
+        let platform = platform_name(&title.trophy_title_platform);
+        let input = import::remote_title_input(&title.trophy_title_name, &platform, import::status_from_progress(title.progress));
+        let link = Some((SOURCE, title.np_communication_id.as_str()));
+        let (game_id, outcome) = db::import_game(&tx, input, link, on_conflict)?;
+        let unlocked = db::upsert_achievements(&tx, game_id, SOURCE, &achievements)?;
+
+        let mut added = 0;
+        let played_title = played.get(&normalize_title(&title.trophy_title_name));
+        let minutes = played_title.and_then(|p| parse_duration_minutes(p.play_duration.as_deref()?));
+        let link = db::get_external_ids(&tx, Some(game_id), Some(SOURCE))?.into_iter().next();
+        if let (Some(minutes), Some(link)) = (minutes, link) {
+            let last_played = played_title.and_then(|p| p.last_played_date_time.clone());
+            added = db::merge_remote_playtime(&tx, &link, minutes, None, last_played, SessionSource::Psn)?;
+        }
+        match recorder.as_mut() {
+            // Dropping `tx` without committing rolls this title back.
+            Some(recorder) => recorder.record(&tx, game_id)?,
+            None => tx.commit()?,
+        }
+
+        if outcome == ImportOutcome::Created {
+            report.imported_ids.push(game_id);
+        } else if outcome == ImportOutcome::Updated || unlocked > 0 || added > 0 {
+            report.updated_ids.push(game_id);
+        }
+        report.added_minutes += added;
+    }
+
+    info!(
+        checked = report.checked,
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        dry_run,
+        "PSN sync finished"
+    );
+    Ok(match recorder {
+        Some(recorder) => report.into_dry_run(recorder.finish()),
+        None => report,
+    })
+}
+
+/// A title's trophy list merged with the user's progress on it.
+fn fetch_trophies(token: &str, title: &TrophyTitle) -> CmdResult<Vec<RemoteAchievement>> {
+    let service = [("npServiceName", title.np_service_name.as_str())];
+    let id = &title.np_communication_id;
+    let defined: Trophies = get(token, &format!("/trophy/v1/npCommunicationIds/{id}/trophyGroups/all/trophies"), &service)?;
+    let earned: EarnedTrophies =
+        get(token, &format!("/trophy/v1/users/me/npCommunicationIds/{id}/trophyGroups/all/trophies"), &service)?;
+    let earned: HashMap<i64, EarnedTrophy> = earned.trophies.into_iter().map(|t| (t.trophy_id, t)).collect();
+
+    Ok(defined
+        .trophies
+        .into_iter()
+        .map(|t| {
+            let progress = earned.get(&t.trophy_id);
+            RemoteAchievement {
+                external_id:    t.trophy_id.to_string(),
+                name:           t.trophy_name.unwrap_or_else(|| format!("Trophy {}", t.trophy_id)),
+                description:    t.trophy_detail,
+                icon_url:       t.trophy_icon_url,
+                kind:           t.trophy_type.as_deref().map(capitalize),
+                points:         None,
+                hidden:         t.trophy_hidden,
+                unlocked_at:    progress.filter(|p| p.earned).and_then(|p| p.earned_date_time.clone()),
+                rarity_percent: progress.and_then(|p| p.trophy_earned_rate.as_deref()?.parse().ok()),
+            }
+        })
+        .collect())
+}
+
+/// "PS3,PSVITA" → "PS3"; "PSVITA" → "PS Vita".
+fn platform_name(platforms: &str) -> String {
+    match platforms.split(',').next().unwrap_or("PS5").trim() {
+        "PSVITA" => "PS Vita".to_string(),
+        "PSPC"   => "PC".to_string(),
+        other    => other.to_string(),
+    }
+}
+
+/// "PT12H3M20S" → 723. Only hours/minutes/seconds appear in PSN durations.
+fn parse_duration_minutes(duration: &str) -> Option<i64> {
+    let rest = duration.strip_prefix("PT")?;
+    let (mut total_seconds, mut number) = (0i64, String::new());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'H' | 'M' | 'S' => {
+                let value: f64 = number.parse().ok()?;
+                let unit = match c { 'H' => 3600.0, 'M' => 60.0, _ => 1.0 };
+                total_seconds += (value * unit) as i64;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    Some(total_seconds / 60)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
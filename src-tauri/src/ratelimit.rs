@@ -0,0 +1,153 @@
+// ratelimit.rs — Keep bulk syncs within what each service tolerates.
+//
+// A sync can fire hundreds of requests at one service in a row, which is a
+// good way to get an API key or account throttled. Every request made through
+// `http` first waits here for a slot: requests to the same provider are
+// spaced evenly so they never exceed its requests-per-minute limit, across
+// every thread. A 429 answer pushes the provider's next slot back by the
+// server's Retry-After.
+//
+// Limits default to the values in `PROVIDERS` and can be overridden per
+// provider with the `rate_limits` setting. Hosts that aren't listed (image
+// CDNs, …) aren't limited. A new provider gets a row in `PROVIDERS`.
+//
+// Waiting is reported to whoever registered with `report_waits_to` on the
+// current thread — the job queue does, so a job's progress says how long it's
+// paused for.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use crate::models::RateLimit;
+
+struct ProviderLimit {
+    name:       &'static str,
+    hosts:      &'static [&'static str],   // also matches subdomains
+    per_minute: u32,
+}
+
+const PROVIDERS: &[ProviderLimit] = &[
+    // The Web API allows 100k calls a day; the storefront is much stricter.
+    ProviderLimit { name: "Steam",    hosts: &["steampowered.com"],                per_minute: 40 },
+    ProviderLimit { name: "ProtonDB", hosts: &["protondb.com"],                    per_minute: 60 },
+    ProviderLimit { name: "PSN",      hosts: &["playstation.com", "sony.com"],     per_minute: 60 },
+    // OpenXBL's free tier allows 150 requests an hour.
+    ProviderLimit { name: "Xbox",     hosts: &["xbl.io"],                          per_minute: 2 },
+];
+
+/// Waits shorter than this aren't worth telling anyone about.
+const REPORT_WAITS_OVER: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Limiter {
+    overrides: HashMap<String, u32>,              // provider → requests per minute, from settings
+    next_slot: HashMap<&'static str, Instant>,    // provider → earliest time of its next request
+}
+
+fn limiter() -> &'static Mutex<Limiter> {
+    static LIMITER: OnceLock<Mutex<Limiter>> = OnceLock::new();
+    LIMITER.get_or_init(Mutex::default)
+}
+
+type WaitReporter = Box<dyn Fn(&str, Duration)>;
+
+thread_local! {
+    static WAIT_REPORTER: RefCell<Option<WaitReporter>> = const { RefCell::new(None) };
+}
+
+/// Apply the `rate_limits` setting (provider → requests per minute, 0 = no
+/// limit). Providers it doesn't mention keep their default.
+pub fn configure(overrides: &BTreeMap<String, u32>) {
+    if let Ok(mut limiter) = limiter().lock() {
+        limiter.overrides = overrides.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    }
+}
+
+/// Every provider with its default and current limit, for the settings screen.
+pub fn limits() -> Vec<RateLimit> {
+    let overrides = limiter().lock().map(|l| l.overrides.clone()).unwrap_or_default();
+    PROVIDERS
+        .iter()
+        .map(|p| RateLimit {
+            provider:           p.name.to_string(),
+            hosts:              p.hosts.iter().map(|h| h.to_string()).collect(),
+            default_per_minute: p.per_minute,
+            per_minute:         overrides.get(p.name).copied().unwrap_or(p.per_minute),
+        })
+        .collect()
+}
+
+/// Block until a request to `url` is allowed.
+pub fn acquire(url: &str) {
+    let Some(provider) = provider_for(url) else { return };
+    let wait = {
+        let Ok(mut limiter) = limiter().lock() else { return };
+        let per_minute = limiter.overrides.get(provider.name).copied().unwrap_or(provider.per_minute);
+        if per_minute == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let slot = limiter.next_slot.get(provider.name).copied().filter(|s| *s > now).unwrap_or(now);
+        limiter.next_slot.insert(provider.name, slot + Duration::from_secs(60) / per_minute);
+        slot - now
+    };
+    pause(provider.name, wait);
+}
+
+/// The service answered 429: hold every request to it for `wait`, then
+/// carry on at the usual pace.
+pub fn back_off(url: &str, wait: Duration) {
+    let Some(provider) = provider_for(url) else {
+        pause("the server", wait);
+        return;
+    };
+    if let Ok(mut limiter) = limiter().lock() {
+        let resume = Instant::now() + wait;
+        let slot = limiter.next_slot.entry(provider.name).or_insert(resume);
+        *slot = (*slot).max(resume);
+    }
+}
+
+/// Call `report(provider, wait)` whenever a request on this thread has to
+/// wait, until the returned guard is dropped.
+pub fn report_waits_to(report: impl Fn(&str, Duration) + 'static) -> ReportGuard {
+    WAIT_REPORTER.with(|r| *r.borrow_mut() = Some(Box::new(report)));
+    ReportGuard
+}
+
+/// Stops wait reports on drop; see `report_waits_to`.
+pub struct ReportGuard;
+
+impl Drop for ReportGuard {
+    fn drop(&mut self) {
+        WAIT_REPORTER.with(|r| *r.borrow_mut() = None);
+    }
+}
+
+fn pause(provider: &str, wait: Duration) {
+    if wait.is_zero() {
+        return;
+    }
+    if wait >= REPORT_WAITS_OVER {
+        debug!(provider, wait_secs = wait.as_secs(), "Waiting for rate limit");
+        WAIT_REPORTER.with(|r| {
+            if let Some(report) = r.borrow().as_ref() {
+                report(provider, wait);
+            }
+        });
+    }
+    thread::sleep(wait);
+}
+
+fn provider_for(url: &str) -> Option<&'static ProviderLimit> {
+    let host = url.split("://").nth(1).unwrap_or(url);
+    let host = host.split(['/', ':', '?']).next().unwrap_or_default().to_ascii_lowercase();
+    PROVIDERS.iter().find(|p| {
+        p.hosts.iter().any(|h| host == *h || host.strip_suffix(h).is_some_and(|rest| rest.ends_with('.')))
+    })
+}
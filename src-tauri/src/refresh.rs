@@ -0,0 +1,131 @@
+// refresh.rs — Keep looked-up metadata from going stale.
+//
+// Ratings fetched from other services drift: ProtonDB tiers move as reports
+// come in, Valve re-reviews games for the Deck. A background job re-checks
+// whatever is older than its provider's staleness window (a setting per
+// provider) and records a manifest of what it looked at and changed. Covers
+// downloaded from a URL are re-checked the same way (see covers.rs).
+//
+// Each provider is one entry in `PROVIDERS`. Prices, HowLongToBeat times and
+// critic scores aren't fetched by anything yet; when they are, they get an
+// entry (and a `*_stale_days` setting) here.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rusqlite::Connection;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::compat;
+use crate::covers;
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::events;
+use crate::jobs::{JobHandle, JobQueue};
+use crate::models::{ChangeAction, ProviderRefresh, RefreshManifest, Settings, SyncReport};
+
+struct Provider {
+    name:       &'static str,
+    stale_days: fn(&Settings) -> u32,
+    // Re-check games last checked before the given RFC 3339 time.
+    refresh:    fn(&AppHandle, &Mutex<Connection>, &str, &JobHandle) -> CmdResult<SyncReport>,
+}
+
+const PROVIDERS: &[Provider] = &[
+    Provider {
+        name:       compat::PROTONDB,
+        stale_days: |s| s.protondb_stale_days,
+        refresh:    |_, db, before, job| compat::refresh_stale(db, compat::PROTONDB, before, job),
+    },
+    Provider {
+        name:       compat::STEAM_DECK,
+        stale_days: |s| s.deck_status_stale_days,
+        refresh:    |_, db, before, job| compat::refresh_stale(db, compat::STEAM_DECK, before, job),
+    },
+    Provider {
+        name:       covers::SOURCE,
+        stale_days: |s| s.cover_stale_days,
+        refresh:    covers::refresh_stale,
+    },
+];
+
+/// Re-check every provider's stale records and store the manifest. A provider
+/// that fails is noted in the manifest and the rest still run.
+pub fn refresh_stale(app: &AppHandle, db: &Mutex<Connection>, job: &JobHandle) -> CmdResult<RefreshManifest> {
+    let settings = db::get_settings(&*db::lock(db)?)?;
+    let mut manifest = RefreshManifest { started_at: Utc::now().to_rfc3339(), ..RefreshManifest::default() };
+
+    for provider in PROVIDERS {
+        let stale_after_days = (provider.stale_days)(&settings);
+        let before = (Utc::now() - chrono::Duration::days(i64::from(stale_after_days))).to_rfc3339();
+        let mut entry = ProviderRefresh { provider: provider.name.to_string(), stale_after_days, ..ProviderRefresh::default() };
+        match (provider.refresh)(app, db, &before, job) {
+            Ok(report) => {
+                entry.checked = report.checked;
+                entry.updated_ids = report.updated_ids;
+            }
+            Err(CommandError::Cancelled) => return Err(CommandError::Cancelled),
+            Err(e) => {
+                warn!(provider = provider.name, code = e.code(), error = %e, "Metadata refresh failed");
+                entry.error = Some(e.to_string());
+            }
+        }
+        manifest.providers.push(entry);
+    }
+
+    manifest.finished_at = Utc::now().to_rfc3339();
+    db::add_refresh_manifest(&*db::lock(db)?, &mut manifest)?;
+    info!(
+        id = manifest.id,
+        changed = manifest.providers.iter().map(|p| p.updated_ids.len()).sum::<usize>(),
+        "Metadata refreshed"
+    );
+    Ok(manifest)
+}
+
+/// Every game a manifest changed, once each.
+pub fn updated_ids(manifest: &RefreshManifest) -> Vec<i64> {
+    let mut ids: Vec<i64> = manifest.providers.iter().flat_map(|p| p.updated_ids.iter().copied()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Start a thread that queues `refresh_stale` as a "metadata_refresh" job
+/// every `metadata_refresh_hours` (checked once a minute, so changing the
+/// setting takes effect without a restart) and tells the frontend which
+/// games changed.
+pub fn spawn_background_refresh(app: AppHandle, db: Arc<Mutex<Connection>>, jobs: Arc<JobQueue>) {
+    thread::spawn(move || {
+        let mut last_run: Option<Instant> = None;
+        loop {
+            thread::sleep(Duration::from_secs(60));
+
+            let interval = match db::lock(&db).and_then(|conn| Ok(db::get_settings(&conn)?)) {
+                Ok(settings) => settings.metadata_refresh_hours,
+                Err(e) => {
+                    warn!(error = %e, "Couldn't read settings for metadata refresh");
+                    continue;
+                }
+            };
+            let due = last_run.is_none_or(|t| t.elapsed() >= Duration::from_secs(u64::from(interval) * 3600));
+            if interval == 0 || !due {
+                continue;
+            }
+
+            last_run = Some(Instant::now());
+            let (app, job_db) = (app.clone(), Arc::clone(&db));
+            let queued = jobs.enqueue("metadata_refresh", move |job| {
+                let manifest = refresh_stale(&app, &job_db, job)?;
+                events::games_changed(&app, ChangeAction::Updated, updated_ids(&manifest));
+                Ok(manifest)
+            });
+            if let Err(e) = queued {
+                warn!(code = e.code(), error = %e, "Couldn't queue background metadata refresh");
+            }
+        }
+    });
+}
@@ -0,0 +1,203 @@
+// registry.rs — Machine-readable descriptions of the IPC commands, for the
+// frontend's command palette and scripting console (`list_commands`).
+//
+// Rather than a hand-kept list that drifts from the code, the descriptions
+// are read from the source itself, embedded with `include_str!`:
+//
+//   commands.rs   each command's doc comment, section, arguments and result
+//   lib.rs        the `generate_handler!` list — only registered commands count
+//   models.rs     which argument types are enums (sent as strings)
+//
+// Arguments are named the way `invoke` takes them (camelCase). The ones Tauri
+// fills in itself — the app handle, managed state, the window — are left out.
+//
+// RUST NOTE: the parse runs once, the first time it's asked for; `OnceLock`
+// keeps the result for the rest of the run.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::models::{CommandArg, CommandInfo};
+
+const COMMANDS_SOURCE: &str = include_str!("commands.rs");
+const LIB_SOURCE: &str = include_str!("lib.rs");
+const MODELS_SOURCE: &str = include_str!("models.rs");
+
+/// Every registered command, in the order commands.rs defines them.
+pub fn commands() -> &'static [CommandInfo] {
+    static COMMANDS: OnceLock<Vec<CommandInfo>> = OnceLock::new();
+    COMMANDS.get_or_init(|| parse(COMMANDS_SOURCE, &registered(LIB_SOURCE), &enums(MODELS_SOURCE)))
+}
+
+/// Command names in lib.rs's `generate_handler!` list.
+fn registered(lib: &str) -> HashSet<&str> {
+    lib.lines()
+        .filter_map(|line| line.trim().strip_prefix("commands::")?.strip_suffix(','))
+        .collect()
+}
+
+fn enums(models: &str) -> HashSet<&str> {
+    models
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("pub enum ")?.split([' ', '{', '<']).next())
+        .collect()
+}
+
+fn is_divider(line: &str) -> bool {
+    line.starts_with("// ----")
+}
+
+fn parse(source: &str, registered: &HashSet<&str>, enums: &HashSet<&str>) -> Vec<CommandInfo> {
+    let lines: Vec<&str> = source.lines().map(str::trim).collect();
+    let mut commands = Vec::new();
+    let mut category = "";
+    let mut doc: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        // A section is a title between two dividers.
+        if is_divider(line) && lines.get(i + 2).is_some_and(|l| is_divider(l)) {
+            category = lines[i + 1].trim_start_matches('/').trim();
+            doc.clear();
+            i += 3;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix("///") {
+            doc.push(text.strip_prefix(' ').unwrap_or(text));
+        } else if line == "#[tauri::command]" {
+            // The signature runs up to the `{` that opens the body.
+            let mut signature = String::new();
+            while let Some(next) = lines.get(i + 1) {
+                i += 1;
+                signature.push_str(next);
+                signature.push(' ');
+                if next.ends_with('{') && signature.matches('(').count() == signature.matches(')').count() {
+                    break;
+                }
+            }
+            if let Some(command) = command(&signature, category, &doc, enums) {
+                if registered.contains(command.name.as_str()) {
+                    commands.push(command);
+                }
+            }
+            doc.clear();
+        } else if !line.starts_with("#[") {
+            doc.clear();
+        }
+        i += 1;
+    }
+    commands
+}
+
+/// Describe the command with this signature ("pub async fn name(…) -> CmdResult<T> {").
+fn command(signature: &str, category: &str, doc: &[&str], enums: &HashSet<&str>) -> Option<CommandInfo> {
+    let (_, rest) = signature.split_once("fn ")?;
+    let (name, rest) = rest.split_once('(')?;
+    let close = closing_paren(rest)?;
+    let (params, rest) = (&rest[..close], &rest[close + 1..]);
+    let returns = rest
+        .split_once("->")
+        .map(|(_, ty)| ty.trim().trim_end_matches('{').trim())
+        .map(|ty| ty.strip_prefix("CmdResult<").and_then(|t| t.strip_suffix('>')).unwrap_or(ty))
+        .unwrap_or("()");
+
+    let (text, example) = match doc.iter().position(|l| l.starts_with("Example JS call")) {
+        Some(at) => (&doc[..at], Some(doc[at + 1..].iter().map(|l| l.trim()).collect::<Vec<_>>().join("\n"))),
+        None => (doc, None),
+    };
+    let paragraphs: Vec<String> = text
+        .split(|l| l.trim().is_empty())
+        .map(|p| p.iter().map(|l| l.trim()).collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    Some(CommandInfo {
+        name:        name.trim().to_string(),
+        category:    category.to_string(),
+        summary:     paragraphs.first().cloned().unwrap_or_default(),
+        description: paragraphs.join("\n\n"),
+        example:     example.filter(|e| !e.is_empty()),
+        args:        split_top_level(params).filter_map(|param| arg(param, enums)).collect(),
+        returns:     returns.to_string(),
+    })
+}
+
+/// Where the parenthesis opened just before `text` closes.
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (at, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(at),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `text` split at the commas that aren't inside <…>, (…) or […].
+fn split_top_level(text: &str) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (at, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..at]);
+                start = at + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts.into_iter().map(str::trim).filter(|p| !p.is_empty())
+}
+
+/// One `name: Type` parameter; None for the ones Tauri supplies.
+fn arg(param: &str, enums: &HashSet<&str>) -> Option<CommandArg> {
+    let (name, ty) = param.split_once(": ")?;
+    let ty = ty.trim();
+    if ty.contains("AppHandle") || ty.starts_with("State<") || ty.contains("Window") {
+        return None;
+    }
+    let inner = ty.strip_prefix("Option<").and_then(|t| t.strip_suffix('>'));
+    let rust_type = inner.unwrap_or(ty);
+    Some(CommandArg {
+        name:      camel_case(name.trim().trim_start_matches("mut ")),
+        kind:      kind(rust_type, enums).to_string(),
+        rust_type: rust_type.to_string(),
+        optional:  inner.is_some(),
+    })
+}
+
+/// Tauri's default: `game_id` is passed as `gameId`.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                out.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The JSON kind a value of Rust type `ty` is sent as.
+fn kind(ty: &str, enums: &HashSet<&str>) -> &'static str {
+    match ty {
+        "i64" | "i32" | "i16" | "i8" | "u64" | "u32" | "u16" | "u8" | "usize" => "integer",
+        "f64" | "f32" => "number",
+        "String" | "&str" | "PathBuf" => "string",
+        "bool" => "boolean",
+        _ if ty.starts_with("Vec<") || ty.starts_with("HashSet<") || ty.starts_with('[') => "array",
+        _ if enums.contains(ty) => "string",
+        _ => "object",
+    }
+}
@@ -0,0 +1,263 @@
+// retroarch.rs — Import RetroArch playlists (.lpl) and their runtime logs.
+//
+// A playlist lists the ROMs RetroArch knows about for one system. Since 1.7.6
+// it's JSON:
+//
+//   { "version": "1.5",
+//     "items": [ { "path": "/roms/snes/Chrono Trigger (USA).sfc",
+//                  "label": "Chrono Trigger (USA)",
+//                  "db_name": "Nintendo - Super Nintendo Entertainment System.lpl", … } ] }
+//
+// Older playlists are plain text, six lines per entry (path, label, core path,
+// core name, crc, db name). Both are supported.
+//
+// With "Save runtime log" enabled, RetroArch also writes one small JSON file per
+// game — playlists/logs/<core>/<rom name>.lrtl, or playlists/logs/<rom name>.lrtl
+// when aggregated — holding the total time played:
+//
+//   { "runtime": "12:34:56", "last_played": "2024-01-31 20:15:00" }
+//
+// Each ROM is linked through `game_external_ids` (source "RetroArch", id = ROM
+// path) and its runtime merged with `db::merge_remote_playtime`, so importing
+// the same playlist again only adds the time played since.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::db::{self, ImportOutcome};
+use crate::error::{CmdResult, CommandError};
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::models::{ConflictStrategy, GameStatus, SessionSource, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for RetroArch rows in `game_external_ids`.
+pub const SOURCE: &str = "RetroArch";
+
+/// One ROM from a playlist.
+struct PlaylistEntry {
+    path:   String,
+    label:  String,
+    system: String,
+}
+
+#[derive(Deserialize)]
+struct JsonPlaylist {
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonItem {
+    #[serde(default)]
+    path:    String,
+    #[serde(default)]
+    label:   String,
+    #[serde(default)]
+    db_name: String,
+}
+
+#[derive(Deserialize)]
+struct RuntimeLog {
+    runtime:     String,           // "H:MM:SS"
+    last_played: Option<String>,   // "YYYY-MM-DD HH:MM:SS", local time
+}
+
+/// Import every ROM in `playlist`. `logs_dir` defaults to the `logs` folder
+/// next to the playlist, which is where RetroArch writes runtime logs. With
+/// `dry_run` nothing is saved; the report's `preview` says what would change.
+/// `on_conflict` decides what happens to unlinked library games with the same title.
+pub fn import_playlist(
+    db: &Mutex<Connection>,
+    playlist: &Path,
+    logs_dir: Option<&Path>,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let text = fs::read_to_string(playlist)?;
+    let fallback_system = playlist.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let entries = parse_playlist(&text, fallback_system);
+    if entries.is_empty() {
+        return Err(CommandError::validation("path", "No games found in the playlist"));
+    }
+    let logs_dir = logs_dir
+        .map(Path::to_path_buf)
+        .or_else(|| playlist.parent().map(|p| p.join("logs")));
+
+    let mut report = SyncReport { checked: entries.len(), ..SyncReport::default() };
+    let mut conn = db::lock(db)?;
+    let mut recorder = dry_run.then(|| PreviewRecorder::start(&conn)).transpose()?;
+    let tx = conn.transaction()?;
+    for (i, entry) in entries.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, entries.len(), entry.label.as_str());
+        let input = import::remote_title_input(&clean_label(&entry.label), &platform_name(&entry.system), GameStatus::Backlog);
+        let (game_id, outcome) = db::import_game(&tx, input, Some((SOURCE, entry.path.as_str())), on_conflict)?;
+
+        let mut added = 0;
+        let runtime = logs_dir.as_deref().and_then(|dir| read_runtime(dir, &entry.path));
+        let link = db::get_external_ids(&tx, Some(game_id), Some(SOURCE))?.into_iter().next();
+        if let (Some((minutes, last_played)), Some(link)) = (runtime, link) {
+            added = db::merge_remote_playtime(&tx, &link, minutes, None, last_played, SessionSource::RetroArch)?;
+        }
+
+        if outcome == ImportOutcome::Created {
+            report.imported_ids.push(game_id);
+        } else if outcome == ImportOutcome::Updated || added > 0 {
+            report.updated_ids.push(game_id);
+        }
+        report.added_minutes += added;
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(&tx, game_id)?;
+        }
+    }
+    if let Some(recorder) = recorder {
+        // Dropping `tx` without committing rolls the import back.
+        info!(playlist = %playlist.display(), checked = report.checked, "RetroArch playlist dry run");
+        return Ok(report.into_dry_run(recorder.finish()));
+    }
+    tx.commit()?;
+
+    info!(
+        playlist = %playlist.display(),
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        "Imported RetroArch playlist"
+    );
+    Ok(report)
+}
+
+fn parse_playlist(text: &str, fallback_system: &str) -> Vec<PlaylistEntry> {
+    let system_of = |db_name: &str| {
+        let name = db_name.trim_end_matches(".lpl");
+        if name.is_empty() || name == "DETECT" { fallback_system.to_string() } else { name.to_string() }
+    };
+
+    if let Ok(playlist) = serde_json::from_str::<JsonPlaylist>(text) {
+        return playlist
+            .items
+            .into_iter()
+            .filter(|i| !i.path.is_empty())
+            .map(|i| PlaylistEntry {
+                label:  if i.label.is_empty() { file_stem(&i.path) } else { i.label },
+                system: system_of(&i.db_name),
+                path:   i.path,
+            })
+            .collect();
+    }
+
+    // Pre-1.7.6: six lines per entry.
+    let lines: Vec<&str> = text.lines().collect();
+    lines
+        .chunks(6)
+        .filter(|c| c.len() == 6 && !c[0].trim().is_empty())
+        .map(|c| PlaylistEntry {
+            path:   c[0].trim().to_string(),
+            label:  if c[1].trim().is_empty() { file_stem(c[0]) } else { c[1].trim().to_string() },
+            system: system_of(c[5].trim()),
+        })
+        .collect()
+}
+
+/// Total minutes and last-played time from the ROM's runtime log(s), if any.
+///
+/// Prefers the aggregate log; otherwise adds up the per-core logs (a game
+/// played with two cores has one log in each core's folder).
+fn read_runtime(logs_dir: &Path, rom_path: &str) -> Option<(i64, Option<String>)> {
+    let log_name = format!("{}.lrtl", file_stem(rom_path));
+
+    let mut logs: Vec<PathBuf> = Vec::new();
+    let aggregate = logs_dir.join(&log_name);
+    if aggregate.is_file() {
+        logs.push(aggregate);
+    } else if let Ok(cores) = fs::read_dir(logs_dir) {
+        logs.extend(cores.filter_map(|e| e.ok()).map(|e| e.path().join(&log_name)).filter(|p| p.is_file()));
+    }
+
+    let parsed: Vec<RuntimeLog> = logs
+        .iter()
+        .filter_map(|p| serde_json::from_str(&fs::read_to_string(p).ok()?).ok())
+        .collect();
+    if parsed.is_empty() {
+        return None;
+    }
+    let minutes = parsed.iter().filter_map(|l| parse_runtime_minutes(&l.runtime)).sum();
+    let last_played = parsed
+        .iter()
+        .filter_map(|l| l.last_played.as_deref())
+        .max()
+        .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%d %H:%M:%S").ok())
+        .and_then(|t| Local.from_local_datetime(&t).single())
+        .map(|t| t.to_rfc3339());
+    Some((minutes, last_played))
+}
+
+/// "12:34:56" → 754 minutes.
+fn parse_runtime_minutes(runtime: &str) -> Option<i64> {
+    let parts: Vec<i64> = runtime.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [h, m, _] => Some(h * 60 + m),
+        [m, _]    => Some(m),
+        _         => None,
+    }
+}
+
+fn file_stem(path: &str) -> String {
+    // ROMs inside archives look like "/roms/game.zip#game.sfc".
+    let path = path.rsplit('#').next().unwrap_or(path);
+    Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or(path).to_string()
+}
+
+/// "Chrono Trigger (USA) [!]" → "Chrono Trigger". No-Intro / Redump names put
+/// region, revision and dump info in trailing brackets.
+fn clean_label(label: &str) -> String {
+    let mut title = label.trim();
+    while let Some(stripped) = title
+        .strip_suffix(')')
+        .and_then(|t| t.rfind('(').map(|i| &t[..i]))
+        .or_else(|| title.strip_suffix(']').and_then(|t| t.rfind('[').map(|i| &t[..i])))
+    {
+        if stripped.trim().is_empty() {
+            break;
+        }
+        title = stripped.trim_end();
+    }
+    title.to_string()
+}
+
+/// "Nintendo - Super Nintendo Entertainment System" → "SNES". Unknown systems
+/// keep their name without the manufacturer prefix.
+fn platform_name(system: &str) -> String {
+    let short = match system {
+        "Nintendo - Nintendo Entertainment System"       => "NES",
+        "Nintendo - Super Nintendo Entertainment System" => "SNES",
+        "Nintendo - Nintendo 64"                         => "N64",
+        "Nintendo - GameCube"                            => "GameCube",
+        "Nintendo - Wii"                                 => "Wii",
+        "Nintendo - Game Boy"                            => "Game Boy",
+        "Nintendo - Game Boy Color"                      => "Game Boy Color",
+        "Nintendo - Game Boy Advance"                    => "GBA",
+        "Nintendo - Nintendo DS"                         => "DS",
+        "Nintendo - Nintendo 3DS"                        => "3DS",
+        "Sega - Mega Drive - Genesis"                    => "Genesis",
+        "Sega - Master System - Mark III"                => "Master System",
+        "Sega - Saturn"                                  => "Saturn",
+        "Sega - Dreamcast"                               => "Dreamcast",
+        "Sega - Game Gear"                               => "Game Gear",
+        "Sony - PlayStation"                             => "PS1",
+        "Sony - PlayStation 2"                           => "PS2",
+        "Sony - PlayStation Portable"                    => "PSP",
+        "NEC - PC Engine - TurboGrafx 16"                => "TurboGrafx-16",
+        "Atari - 2600"                                   => "Atari 2600",
+        "MAME" | "FBNeo - Arcade Games"                  => "Arcade",
+        other => return other.split_once(" - ").map_or(other, |(_, name)| name).to_string(),
+    };
+    short.to_string()
+}
@@ -0,0 +1,141 @@
+// saves.rs — Zip a game's save folders into app data, and put them back.
+//
+// Each backup is one archive at app_data_dir/save_backups/<game_id>/<timestamp>.zip:
+//
+//   manifest.json        — { "locations": ["C:\\Users\\me\\Saved Games\\Hades", …] }
+//   0/Profile1.sav       — files from locations[0], relative to that folder
+//   1/…                  — files from locations[1]
+//
+// The manifest records where each numbered folder came from, so a backup can
+// be restored even after the game's save locations have been edited.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{CmdResult, CommandError};
+
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    locations: Vec<String>,
+}
+
+/// What `create_backup` wrote.
+pub struct ArchiveInfo {
+    pub path:       PathBuf,
+    pub size_bytes: u64,
+    pub file_count: i64,
+}
+
+/// Resolve (and create) app_data_dir/save_backups/<game_id>/.
+pub fn get_backups_dir(app: &AppHandle, game_id: i64) -> CmdResult<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| CommandError::Io(e.to_string()))?
+        .join("save_backups")
+        .join(game_id.to_string());
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Zip every file under `locations` into a new archive in `dir`.
+pub fn create_backup(dir: &Path, locations: &[String]) -> CmdResult<ArchiveInfo> {
+    // Microseconds keep two backups in the same second (e.g. manual + pre-restore) apart.
+    let path = dir.join(format!("{}.zip", chrono::Utc::now().format("%Y%m%d-%H%M%S%.6f")));
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST, options)?;
+    serde_json::to_writer(&mut zip, &Manifest { locations: locations.to_vec() })
+        .map_err(|e| CommandError::Internal(e.to_string()))?;
+
+    let mut file_count = 0;
+    for (index, location) in locations.iter().enumerate() {
+        let root = Path::new(location);
+        if !root.is_dir() {
+            return Err(CommandError::validation("path", format!("Save folder not found: {location}")));
+        }
+        file_count += add_dir(&mut zip, root, root, &index.to_string(), options)?;
+    }
+    zip.finish()?;
+
+    let size_bytes = fs::metadata(&path)?.len();
+    info!(path = %path.display(), file_count, size_bytes, "Backed up saves");
+    Ok(ArchiveInfo { path, size_bytes, file_count })
+}
+
+/// Recursively add `dir`'s files under `prefix/<path relative to root>`.
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    options: SimpleFileOptions,
+) -> CmdResult<i64> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            count += add_dir(zip, root, &path, prefix, options)?;
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        // Zip entry names always use forward slashes.
+        let name = format!("{prefix}/{}", relative.to_string_lossy().replace('\\', "/"));
+        zip.start_file(name, options)?;
+        io::copy(&mut File::open(&path)?, zip)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Replace the contents of each save folder recorded in `archive` with the
+/// files from the backup. Returns the folders that were restored.
+pub fn restore_backup(archive: &Path) -> CmdResult<Vec<String>> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    let manifest: Manifest = serde_json::from_reader(zip.by_name(MANIFEST)?)
+        .map_err(|e| CommandError::Internal(format!("Backup manifest is invalid: {e}")))?;
+
+    // Clear first so files created after the backup don't linger.
+    for location in &manifest.locations {
+        let root = Path::new(location);
+        if root.exists() {
+            fs::remove_dir_all(root)?;
+        }
+        fs::create_dir_all(root)?;
+    }
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        // RUST NOTE: `enclosed_name` rejects names like "../../evil" that
+        // would escape the target folder.
+        let Some(name) = entry.enclosed_name() else { continue };
+        let mut parts = name.components();
+        let Some(index) = parts.next().and_then(|c| c.as_os_str().to_str()?.parse::<usize>().ok()) else {
+            continue; // the manifest
+        };
+        let Some(location) = manifest.locations.get(index) else { continue };
+
+        let target = Path::new(location).join(parts.as_path());
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&target)?)?;
+    }
+
+    info!(archive = %archive.display(), "Restored saves");
+    Ok(manifest.locations)
+}
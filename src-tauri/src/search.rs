@@ -0,0 +1,730 @@
+// search.rs — Turn a SearchFilter into SQL.
+//
+// `SearchQuery` collects one WHERE condition per filter that is set, binding
+// every user-supplied value as a numbered parameter (?1, ?2, …) as it goes,
+// plus the ORDER BY keys. db.rs runs the result with `prepare_cached`: the
+// SQL only depends on which filters are set, not their values, so repeated
+// searches of the same shape reuse one compiled statement.
+//
+// Fuzzy ranking and the keyset-pagination cursor live here too, since both
+// are part of how a search is answered rather than of storing games.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rusqlite::types::Value;
+
+use crate::error::{CmdResult, CommandError};
+use crate::models::{DeckStatus, Game, ProtonTier, QueryField, SearchFilter, SortField};
+use crate::titles;
+
+/// SQL and parameters for one search.
+///
+/// Example:
+///   let query = SearchQuery::new(&filter).page(51, None);
+///   conn.prepare_cached(&query.sql())?.query_map(params_from_iter(query.params()), …)
+#[derive(Debug)]
+pub struct SearchQuery {
+    conditions:  Vec<String>,         // AND-ed together
+    params:      Vec<Value>,          // params[0] is ?1
+    sort_keys:   Vec<(String, bool)>, // (expression, ascending)
+    select_keys: bool,                // also return the sort keys (paging)
+    limit:       Option<u32>,
+}
+
+impl SearchQuery {
+    pub fn new(filter: &SearchFilter) -> Self {
+        let mut query = SearchQuery {
+            conditions:  Vec::new(),
+            params:      Vec::new(),
+            sort_keys:   sort_keys(filter),
+            select_keys: false,
+            limit:       None,
+        };
+        query.add_text_filters(filter);
+        query.add_range_filters(filter);
+        query.add_exclusions(filter);
+        query.add_feature_filters(filter);
+        query.add_activity_filters(filter);
+        query
+    }
+
+    /// Limit to `limit` rows sorting after `after` (the previous page's last
+    /// row's sort keys), and return each row's sort keys for the next cursor.
+    pub fn page(mut self, limit: u32, after: Option<&[Value]>) -> Self {
+        if let Some(after) = after {
+            let condition = self.keyset_condition(after);
+            self.conditions.push(condition);
+        }
+        self.select_keys = true;
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let key_columns: String = if self.select_keys {
+            self.sort_keys.iter().map(|(column, _)| format!(", {column}")).collect()
+        } else {
+            String::new()
+        };
+        let where_clause = if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.conditions.join(" AND "))
+        };
+        let order: Vec<String> = self
+            .sort_keys
+            .iter()
+            .map(|(column, asc)| format!("{column} {} NULLS LAST", if *asc { "ASC" } else { "DESC" }))
+            .collect();
+        let limit = self.limit.map(|n| format!(" LIMIT {n}")).unwrap_or_default();
+        format!(
+            "SELECT g.id{key_columns} FROM games g{where_clause} ORDER BY {}{limit}",
+            order.join(", ")
+        )
+    }
+
+    pub fn params(&self) -> &[Value] {
+        &self.params
+    }
+
+    /// Sort keys returned after the id in each row (0 unless paging).
+    pub fn key_count(&self) -> usize {
+        if self.select_keys { self.sort_keys.len() } else { 0 }
+    }
+
+    /// How many values a cursor for this sort holds.
+    pub fn sort_key_count(&self) -> usize {
+        self.sort_keys.len()
+    }
+
+    // -----------------------------------------------------------------------
+    // Building blocks
+    // -----------------------------------------------------------------------
+
+    /// Bind `value` as the next parameter and return its placeholder ("?3").
+    fn bind(&mut self, value: impl Into<Value>) -> String {
+        self.params.push(value.into());
+        format!("?{}", self.params.len())
+    }
+
+    /// Bind every value and return the placeholders for an `IN (...)` list.
+    fn bind_list<T: Into<Value>>(&mut self, values: impl IntoIterator<Item = T>) -> String {
+        let marks: Vec<String> = values.into_iter().map(|v| self.bind(v)).collect();
+        marks.join(", ")
+    }
+
+    fn add_text_filters(&mut self, filter: &SearchFilter) {
+        // A fuzzy query is scored in Rust after the other filters have run
+        // (see `rank_fuzzy`), so it doesn't become a LIKE condition.
+        if let Some(q) = filter.query.as_ref().filter(|_| filter.fuzzy != Some(true)) {
+            let mark = self.bind(format!("%{q}%"));
+            let fields = if filter.query_fields.is_empty() { &QueryField::DEFAULT[..] } else { &filter.query_fields };
+            let matches: Vec<String> = fields.iter().map(|f| query_field_condition(*f, &mark)).collect();
+            self.conditions.push(format!("({})", matches.join(" OR ")));
+        }
+        // Several values in one field are OR-ed (status IN (...)); fields are AND-ed.
+        if !filter.status.is_empty() {
+            let marks = self.bind_list(filter.status.iter().map(|s| s.as_str().to_string()));
+            self.conditions.push(format!("g.status IN ({marks})"));
+        }
+        if !filter.platform.is_empty() {
+            let marks = self.bind_list(filter.platform.iter().cloned());
+            self.conditions.push(format!("g.platform IN ({marks})"));
+        }
+        if let Some(f) = &filter.franchise {
+            let mark = self.bind(format!("%{f}%"));
+            self.conditions.push(format!("g.franchise LIKE {mark}"));
+        }
+        if !filter.genre.is_empty() {
+            let marks = self.bind_list(filter.genre.iter().cloned());
+            self.conditions.push(format!(
+                "EXISTS (SELECT 1 FROM game_genres gg WHERE gg.game_id = g.id AND gg.genre IN ({marks}))"
+            ));
+        }
+    }
+
+    fn add_range_filters(&mut self, filter: &SearchFilter) {
+        // Inclusive min / max ranges. Games with no value in a column never
+        // match a range on it (NULL comparisons are false).
+        let ranges = [
+            ("g.rating",           ">=", filter.min_rating),
+            ("g.rating",           "<=", filter.max_rating),
+            (RELEASE_YEAR,         ">=", filter.min_release_year.map(f64::from)),
+            (RELEASE_YEAR,         "<=", filter.max_release_year.map(f64::from)),
+            ("g.playtime_minutes", ">=", filter.min_playtime_hours.map(|h| h * 60.0)),
+            ("g.playtime_minutes", "<=", filter.max_playtime_hours.map(|h| h * 60.0)),
+            ("g.progress_percent", ">=", filter.min_progress),
+            ("g.progress_percent", "<=", filter.max_progress),
+        ];
+        for (column, op, value) in ranges {
+            let Some(value) = value else { continue };
+            let mark = self.bind(value);
+            self.conditions.push(format!("{column} {op} {mark}"));
+        }
+        // Date ranges compare the "YYYY-MM-DD" part of the stored timestamps.
+        let date_ranges = [
+            ("g.created_at",   ">=", &filter.added_after),
+            ("g.created_at",   "<=", &filter.added_before),
+            ("g.completed_at", ">=", &filter.completed_after),
+            ("g.completed_at", "<=", &filter.completed_before),
+        ];
+        for (column, op, date) in date_ranges {
+            let Some(date) = date else { continue };
+            let mark = self.bind(date.clone());
+            self.conditions.push(format!("substr({column}, 1, 10) {op} {mark}"));
+        }
+    }
+
+    fn add_exclusions(&mut self, filter: &SearchFilter) {
+        if !filter.exclude_statuses.is_empty() {
+            let marks = self.bind_list(filter.exclude_statuses.iter().map(|s| s.as_str().to_string()));
+            self.conditions.push(format!("g.status NOT IN ({marks})"));
+        }
+        if !filter.exclude_platforms.is_empty() {
+            let marks = self.bind_list(filter.exclude_platforms.iter().cloned());
+            self.conditions.push(format!("g.platform NOT IN ({marks})"));
+        }
+        if !filter.exclude_genres.is_empty() {
+            let marks = self.bind_list(filter.exclude_genres.iter().cloned());
+            self.conditions.push(format!(
+                "NOT EXISTS (SELECT 1 FROM game_genres gg WHERE gg.game_id = g.id AND gg.genre IN ({marks}))"
+            ));
+        }
+    }
+
+    fn add_feature_filters(&mut self, filter: &SearchFilter) {
+        if !filter.include_archived {
+            self.conditions.push("g.archived = 0".to_string());
+        }
+        if let Some(favorite) = filter.favorite {
+            self.conditions.push(format!("g.favorite = {}", i32::from(favorite)));
+        }
+        if let Some(installed) = filter.installed {
+            // No parameter needed — the flag only picks EXISTS or NOT EXISTS.
+            let not = if installed { "" } else { "NOT " };
+            self.conditions.push(format!("{not}EXISTS (SELECT 1 FROM game_installs gi WHERE gi.game_id = g.id)"));
+        }
+        if let Some(min) = filter.min_protondb_tier {
+            let tiers: Vec<&str> = ProtonTier::ALL.iter().filter(|t| **t >= min).map(|t| t.as_str()).collect();
+            self.conditions.push(compat_condition("ProtonDB", &tiers));
+        }
+        if let Some(min) = filter.min_deck_status {
+            let statuses: Vec<&str> = DeckStatus::ALL.iter().filter(|d| **d >= min).map(|d| d.as_str()).collect();
+            self.conditions.push(compat_condition("SteamDeck", &statuses));
+        }
+        if let Some(c) = filter.controller_support {
+            let mark = self.bind(c.as_str().to_string());
+            self.conditions.push(format!("g.controller_support = {mark}"));
+        }
+        if let Some(i) = filter.input_method {
+            let mark = self.bind(i.as_str().to_string());
+            self.conditions.push(format!("g.input_method = {mark}"));
+        }
+        if let Some(mode) = filter.coop_mode {
+            // Mode names come from the enum, so they're safe to inline.
+            let modes: Vec<String> = mode.matching().iter().map(|m| format!("'{m}'")).collect();
+            self.conditions.push(format!("g.coop_mode IN ({})", modes.join(", ")));
+        }
+        if let Some(p) = filter.min_local_players {
+            let mark = self.bind(p);
+            self.conditions.push(format!("g.max_local_players >= {mark}"));
+        }
+        if let Some(p) = filter.min_online_players {
+            let mark = self.bind(p);
+            self.conditions.push(format!("g.max_online_players >= {mark}"));
+        }
+    }
+
+    fn add_activity_filters(&mut self, filter: &SearchFilter) {
+        if let Some(name) = &filter.played_with {
+            let mark = self.bind(name.clone());
+            self.conditions.push(format!(
+                "EXISTS (SELECT 1 FROM play_sessions ps
+                         JOIN session_people sp ON sp.session_id = ps.id
+                         JOIN people p ON p.id = sp.person_id
+                         WHERE ps.game_id = g.id AND p.name = {mark} COLLATE NOCASE)"
+            ));
+        }
+        if let Some(challenge) = &filter.challenge {
+            let mark = self.bind(challenge.clone());
+            self.conditions.push(format!(
+                "EXISTS (SELECT 1 FROM playthroughs pt WHERE pt.game_id = g.id AND pt.challenge = {mark} COLLATE NOCASE)"
+            ));
+        }
+    }
+
+    /// "Sorts after the row whose keys were `after`", for keyset pagination:
+    ///   k1 > a1  OR  (k1 = a1 AND k2 > a2)  OR  …
+    /// with `>` flipped for descending keys. NULLs sort last, so a NULL key is
+    /// followed only by rows that tie on it, and any non-NULL value is
+    /// followed by the NULLs.
+    fn keyset_condition(&mut self, after: &[Value]) -> String {
+        let keys = self.sort_keys.clone();
+        let mut branches: Vec<String> = Vec::new();
+        for (i, ((column, asc), value)) in keys.iter().zip(after).enumerate() {
+            if *value == Value::Null {
+                continue;
+            }
+            let mut parts: Vec<String> = Vec::new();
+            for ((col, _), v) in keys[..i].iter().zip(after) {
+                let mark = self.bind(v.clone());
+                parts.push(format!("{col} IS {mark}"));
+            }
+            let op = if *asc { ">" } else { "<" };
+            let mark = self.bind(value.clone());
+            parts.push(format!("({column} {op} {mark} OR {column} IS NULL)"));
+            branches.push(format!("({})", parts.join(" AND ")));
+        }
+        if branches.is_empty() {
+            // Nothing sorts after a row that is NULL on every key but the id.
+            return "0".to_string();
+        }
+        format!("({})", branches.join(" OR "))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SQL fragments
+// ---------------------------------------------------------------------------
+
+/// Year part of `release_date` ("YYYY-MM-DD") as a number.
+const RELEASE_YEAR: &str = "CAST(substr(g.release_date, 1, 4) AS INTEGER)";
+
+/// Sort key for titles: the explicit `sort_title` if set, otherwise the title
+/// with a leading English article dropped, so "The Witcher 3" files under W.
+const SORT_TITLE: &str = "COALESCE(g.sort_title, CASE
+            WHEN g.title LIKE 'The %' THEN substr(g.title, 5)
+            WHEN g.title LIKE 'An %'  THEN substr(g.title, 4)
+            WHEN g.title LIKE 'A %'   THEN substr(g.title, 3)
+            ELSE g.title END) COLLATE NOCASE";
+
+/// "`field` contains the LIKE pattern bound to `mark`".
+fn query_field_condition(field: QueryField, mark: &str) -> String {
+    match field {
+        QueryField::Title => format!(
+            "g.title LIKE {mark} OR g.original_title LIKE {mark}
+             OR EXISTS (SELECT 1 FROM game_aliases a WHERE a.game_id = g.id AND a.alias LIKE {mark})"
+        ),
+        QueryField::Franchise => format!("g.franchise LIKE {mark}"),
+        QueryField::Notes     => format!("g.notes LIKE {mark}"),
+        QueryField::Developer => format!("g.developer LIKE {mark}"),
+        QueryField::Publisher => format!("g.publisher LIKE {mark}"),
+    }
+}
+
+/// "Has one of these ratings from `source`". The values come from enums, not
+/// the user, so they're safe to inline.
+fn compat_condition(source: &str, ratings: &[&str]) -> String {
+    let list: Vec<String> = ratings.iter().map(|r| format!("'{r}'")).collect();
+    format!(
+        "EXISTS (SELECT 1 FROM game_compat gc WHERE gc.game_id = g.id AND gc.source = '{source}' AND gc.rating IN ({}))",
+        list.join(", ")
+    )
+}
+
+/// The (column, ascending) pairs a search sorts by. The id always comes last
+/// so the order is total — keyset pagination depends on that.
+fn sort_keys(filter: &SearchFilter) -> Vec<(String, bool)> {
+    // With no sort at all, match get_all_games: most recently updated first.
+    let asc = filter.sort_asc.unwrap_or(filter.sort_by.is_some());
+    let mut keys = Vec::new();
+    if filter.favorites_first {
+        keys.push(("g.favorite".to_string(), false));
+    }
+    keys.push((sort_column(filter.sort_by.unwrap_or(SortField::UpdatedAt), filter.random_seed), asc));
+
+    // Tie-breaker, e.g. franchise then sequence_in_franchise. Ascending unless told otherwise.
+    if let Some(then_by) = filter.then_by {
+        keys.push((sort_column(then_by, filter.random_seed), filter.then_asc.unwrap_or(true)));
+    }
+    keys.push(("g.id".to_string(), asc));
+    keys
+}
+
+fn sort_column(field: SortField, random_seed: Option<i64>) -> String {
+    let col = match field {
+        SortField::Title               => SORT_TITLE,
+        SortField::Franchise           => "g.franchise COLLATE NOCASE",
+        SortField::ReleaseDate         => "g.release_date",
+        SortField::Rating              => "g.rating",
+        SortField::PlaytimeHours       => "g.playtime_minutes",
+        SortField::ProgressPercent     => "g.progress_percent",
+        SortField::SequenceInFranchise => "g.sequence_in_franchise",
+        SortField::UpdatedAt           => "g.updated_at",
+        SortField::CreatedAt           => "g.created_at",
+        SortField::CompletedAt         => "g.completed_at",
+        SortField::Random              => return random_order(random_seed),
+    };
+    col.to_string()
+}
+
+/// A shuffled order. SQLite's RANDOM() can't be seeded, so with a seed each
+/// id is hashed instead (two xor-shift-multiply rounds mixed with the seed):
+/// the same seed always gives the same order, so later pages don't reshuffle.
+fn random_order(seed: Option<i64>) -> String {
+    let Some(seed) = seed else { return "RANDOM()".to_string() };
+    // SQLite has no XOR operator: a ^ b = (a | b) - (a & b).
+    let xor = |a: &str, b: &str| format!("(({a} | {b}) - ({a} & {b}))");
+    // Everything stays below 2^32 before a multiply, so nothing overflows into
+    // a REAL. The seed is a number, so it's safe to inline.
+    let seed = seed.rem_euclid(1 << 31).to_string();
+    let round1 = format!("(({} * 2654435761) % 4294967296)", xor("g.id", &seed));
+    let mixed = xor(&round1, &format!("({round1} >> 15)"));
+    format!("(({mixed} * 73244475) % 4294967296)")
+}
+
+// ---------------------------------------------------------------------------
+// Fuzzy ranking
+// ---------------------------------------------------------------------------
+
+/// Lowest `titles::similarity` a game needs to show up in fuzzy results.
+const FUZZY_THRESHOLD: f64 = 0.45;
+
+/// Keep the games whose title, original title or an alias resembles `query`.
+/// Unless the caller asked for another sort, the closest match comes first.
+pub fn rank_fuzzy(games: Vec<Game>, query: &str, by_score: bool) -> Vec<Game> {
+    let mut scored: Vec<(f64, Game)> = games
+        .into_iter()
+        .map(|game| {
+            let score = std::iter::once(&game.title)
+                .chain(game.original_title.as_ref())
+                .chain(&game.aliases)
+                .map(|title| titles::similarity(query, title))
+                .fold(0.0, f64::max);
+            (score, game)
+        })
+        .filter(|(score, _)| *score >= FUZZY_THRESHOLD)
+        .collect();
+    if by_score {
+        // RUST NOTE: f64 isn't `Ord` (NaN), so `total_cmp` supplies an ordering.
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    }
+    scored.into_iter().map(|(_, game)| game).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Pagination cursors
+// ---------------------------------------------------------------------------
+
+/// Opaque cursor: the sort key values as JSON, base64-encoded so the
+/// frontend treats it as a token rather than something to build by hand.
+pub fn encode_cursor(keys: &[Value]) -> String {
+    let json: Vec<serde_json::Value> = keys
+        .iter()
+        .map(|v| match v {
+            Value::Integer(i) => serde_json::Value::from(*i),
+            Value::Real(f)    => serde_json::Value::from(*f),
+            Value::Text(t)    => serde_json::Value::from(t.as_str()),
+            Value::Null | Value::Blob(_) => serde_json::Value::Null,
+        })
+        .collect();
+    URL_SAFE_NO_PAD.encode(serde_json::Value::Array(json).to_string())
+}
+
+pub fn decode_cursor(cursor: &str) -> CmdResult<Vec<Value>> {
+    let invalid = || CommandError::validation("cursor", "Not a cursor returned by search_games_page");
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let json: Vec<serde_json::Value> = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    json.into_iter()
+        .map(|v| match v {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => Ok(Value::Text(s)),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None => n.as_f64().map(Value::Real).ok_or_else(invalid),
+            },
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::models::{ControllerSupport, CoopMode, GameStatus, InputMethod};
+    use rusqlite::Connection;
+
+    /// Five games that each filter splits differently.
+    fn library() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO games (id, title, original_title, franchise, release_date, platform, status, rating,
+                               playtime_minutes, progress_percent, developer, notes, controller_support,
+                               input_method, max_local_players, max_online_players, coop_mode,
+                               created_at, updated_at, completed_at) VALUES
+                (1, 'The Legend of Zelda: Breath of the Wild', 'ゼルダの伝説', 'Zelda', '2017-03-03', 'Switch',
+                 'Completed', 10, 9000, 100, 'Nintendo', 'Best open world', 'Full', 'Gamepad', 1, NULL, 'None',
+                 '2023-01-05T10:00:00Z', '2023-01-05T10:00:00Z', '2023-06-01T20:00:00Z'),
+                (2, 'Hollow Knight', NULL, NULL, '2017-02-24', 'PC', 'Playing', 9, 1800, 60, 'Team Cherry',
+                 'Also great on a Nintendo console', 'Full', 'KeyboardMouse', 1, NULL, 'None',
+                 '2024-02-01T10:00:00Z', '2024-02-01T10:00:00Z', NULL),
+                (3, 'It Takes Two', NULL, NULL, '2021-03-26', 'PC', 'Completed', 8, 840, 100, 'Hazelight', NULL,
+                 'Full', 'Gamepad', 2, 2, 'LocalAndOnline',
+                 '2023-03-10T10:00:00Z', '2023-03-10T10:00:00Z', '2023-04-02T20:00:00Z'),
+                (4, 'Stardew Valley', NULL, NULL, '2016-02-26', 'PC', 'Backlog', NULL, NULL, NULL, 'ConcernedApe',
+                 NULL, 'Partial', NULL, 1, 4, 'Online',
+                 '2024-05-20T10:00:00Z', '2024-05-20T10:00:00Z', NULL),
+                (5, 'FIFA 23', NULL, 'FIFA', '2022-09-30', 'PS5', 'Wishlist', 5, 120, 5, 'EA Sports', NULL,
+                 'Full', 'Gamepad', 4, 22, 'Local',
+                 '2024-06-01T10:00:00Z', '2024-06-01T10:00:00Z', NULL);
+             INSERT INTO game_genres (game_id, genre) VALUES
+                (1, 'Adventure'), (1, 'RPG'), (2, 'Metroidvania'), (3, 'Adventure'), (4, 'Simulation'), (5, 'Sports');
+             INSERT INTO game_aliases (game_id, alias) VALUES (1, 'BotW');
+             INSERT INTO game_installs (game_id, launcher, install_path, detected_at) VALUES
+                (2, 'Steam', '/games/hk', '2024-01-01'), (4, 'Steam', '/games/sdv', '2024-01-01');
+             INSERT INTO game_compat (game_id, source, rating, checked_at) VALUES
+                (2, 'ProtonDB', 'Platinum', '2024-01-01'), (3, 'ProtonDB', 'Bronze', '2024-01-01'),
+                (4, 'ProtonDB', 'Gold', '2024-01-01'),
+                (2, 'SteamDeck', 'Verified', '2024-01-01'), (4, 'SteamDeck', 'Playable', '2024-01-01');
+             INSERT INTO people (id, name, created_at) VALUES (1, 'Sam', '2024-01-01');
+             INSERT INTO play_sessions (id, game_id, started_at, duration_minutes, created_at) VALUES
+                (1, 3, '2023-03-11T19:00:00Z', 120, '2023-03-11');
+             INSERT INTO session_people (session_id, person_id) VALUES (1, 1);
+             INSERT INTO playthroughs (game_id, challenge, created_at) VALUES (2, 'Steel Soul', '2024-03-01');
+             UPDATE games SET favorite = 1 WHERE id IN (2, 5);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(conn: &Connection, filter: SearchFilter) -> Vec<i64> {
+        db::search_games(conn, filter).unwrap().iter().map(|g| g.id).collect()
+    }
+
+    fn sorted(mut ids: Vec<i64>) -> Vec<i64> {
+        ids.sort();
+        ids
+    }
+
+    /// (what the case sets, how, which games match). Cases setting the same
+    /// thing can't be combined.
+    type Case = (&'static str, fn(&mut SearchFilter), Vec<i64>);
+
+    fn cases() -> Vec<Case> {
+        vec![
+            ("query",            |f| f.query = Some("zelda".into()),                       vec![1]),
+            ("query",            |f| f.query = Some("botw".into()),                        vec![1]),
+            ("query",            |f| f.query = Some("ゼルダ".into()),                        vec![1]),
+            ("query",            |f| f.query = Some("nintendo".into()),                    vec![2]),
+            ("query",            |f| {
+                f.query = Some("nintendo".into());
+                f.query_fields = vec![QueryField::Developer];
+            },                                                                              vec![1]),
+            ("query",            |f| {
+                f.query = Some("Holow Knigt".into());
+                f.fuzzy = Some(true);
+            },                                                                              vec![2]),
+            ("status",           |f| f.status = vec![GameStatus::Completed],               vec![1, 3]),
+            ("status",           |f| f.status = vec![GameStatus::Playing, GameStatus::Backlog], vec![2, 4]),
+            ("platform",         |f| f.platform = vec!["PC".into()],                       vec![2, 3, 4]),
+            ("franchise",        |f| f.franchise = Some("zel".into()),                     vec![1]),
+            ("genre",            |f| f.genre = vec!["Adventure".into()],                   vec![1, 3]),
+            ("min_rating",       |f| f.min_rating = Some(9.0),                             vec![1, 2]),
+            ("max_rating",       |f| f.max_rating = Some(8.0),                             vec![3, 5]),
+            ("min_release_year", |f| f.min_release_year = Some(2017),                      vec![1, 2, 3, 5]),
+            ("max_release_year", |f| f.max_release_year = Some(2017),                      vec![1, 2, 4]),
+            ("min_playtime",     |f| f.min_playtime_hours = Some(20.0),                    vec![1, 2]),
+            ("max_playtime",     |f| f.max_playtime_hours = Some(20.0),                    vec![3, 5]),
+            ("min_progress",     |f| f.min_progress = Some(100.0),                         vec![1, 3]),
+            ("max_progress",     |f| f.max_progress = Some(60.0),                          vec![2, 5]),
+            ("added_after",      |f| f.added_after = Some("2024-02-01".into()),            vec![2, 4, 5]),
+            ("added_before",     |f| f.added_before = Some("2023-12-31".into()),           vec![1, 3]),
+            ("completed_after",  |f| f.completed_after = Some("2023-05-01".into()),        vec![1]),
+            ("completed_before", |f| f.completed_before = Some("2023-04-02".into()),       vec![3]),
+            ("exclude_statuses", |f| f.exclude_statuses = vec![GameStatus::Wishlist],      vec![1, 2, 3, 4]),
+            ("exclude_platforms",|f| f.exclude_platforms = vec!["PC".into()],              vec![1, 5]),
+            ("exclude_genres",   |f| f.exclude_genres = vec!["Adventure".into()],          vec![2, 4, 5]),
+            ("installed",        |f| f.installed = Some(true),                             vec![2, 4]),
+            ("installed",        |f| f.installed = Some(false),                            vec![1, 3, 5]),
+            ("protondb",         |f| f.min_protondb_tier = Some(ProtonTier::Gold),         vec![2, 4]),
+            ("deck",             |f| f.min_deck_status = Some(DeckStatus::Verified),       vec![2]),
+            ("controller",       |f| f.controller_support = Some(ControllerSupport::Partial), vec![4]),
+            ("input",            |f| f.input_method = Some(InputMethod::Gamepad),          vec![1, 3, 5]),
+            ("coop",             |f| f.coop_mode = Some(CoopMode::Local),                  vec![3, 5]),
+            ("coop",             |f| f.coop_mode = Some(CoopMode::Online),                 vec![3, 4]),
+            ("min_local",        |f| f.min_local_players = Some(2),                        vec![3, 5]),
+            ("min_online",       |f| f.min_online_players = Some(4),                       vec![4, 5]),
+            ("played_with",      |f| f.played_with = Some("sam".into()),                   vec![3]),
+            ("challenge",        |f| f.challenge = Some("steel soul".into()),              vec![2]),
+            ("favorite",         |f| f.favorite = Some(true),                              vec![2, 5]),
+            ("favorite",         |f| f.favorite = Some(false),                             vec![1, 3, 4]),
+        ]
+    }
+
+    #[test]
+    fn no_filter_matches_everything_most_recently_updated_first() {
+        let conn = library();
+        assert_eq!(ids(&conn, SearchFilter::default()), vec![5, 4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn each_filter_on_its_own() {
+        let conn = library();
+        for (name, apply, expected) in cases() {
+            let mut filter = SearchFilter::default();
+            apply(&mut filter);
+            assert_eq!(sorted(ids(&conn, filter)), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn every_pair_of_filters_matches_the_intersection() {
+        let conn = library();
+        let cases = cases();
+        for (a_name, a_apply, a_ids) in &cases {
+            for (b_name, b_apply, b_ids) in &cases {
+                if a_name == b_name {
+                    continue;
+                }
+                let mut filter = SearchFilter::default();
+                a_apply(&mut filter);
+                b_apply(&mut filter);
+                let expected: Vec<i64> = a_ids.iter().copied().filter(|id| b_ids.contains(id)).collect();
+                assert_eq!(sorted(ids(&conn, filter)), expected, "{a_name} + {b_name}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_filter_at_once_binds_parameters_in_order() {
+        let conn = library();
+        let filter = SearchFilter {
+            query:              Some("two".into()),
+            status:             vec![GameStatus::Completed],
+            platform:           vec!["PC".into()],
+            genre:              vec!["Adventure".into()],
+            min_rating:         Some(8.0),
+            max_rating:         Some(8.0),
+            min_release_year:   Some(2021),
+            max_release_year:   Some(2021),
+            min_playtime_hours: Some(10.0),
+            max_playtime_hours: Some(20.0),
+            min_progress:       Some(100.0),
+            max_progress:       Some(100.0),
+            added_after:        Some("2023-03-10".into()),
+            added_before:       Some("2023-03-10".into()),
+            completed_after:    Some("2023-04-02".into()),
+            completed_before:   Some("2023-04-02".into()),
+            exclude_statuses:   vec![GameStatus::Wishlist],
+            exclude_platforms:  vec!["PS5".into()],
+            exclude_genres:     vec!["Sports".into()],
+            installed:          Some(false),
+            min_protondb_tier:  Some(ProtonTier::Bronze),
+            controller_support: Some(ControllerSupport::Full),
+            input_method:       Some(InputMethod::Gamepad),
+            coop_mode:          Some(CoopMode::Local),
+            min_local_players:  Some(2),
+            min_online_players: Some(2),
+            played_with:        Some("Sam".into()),
+            ..Default::default()
+        };
+        let query = SearchQuery::new(&filter);
+        assert!(query.sql().contains(&format!("?{}", query.params().len())));
+        assert!(!query.sql().contains(&format!("?{}", query.params().len() + 1)));
+        assert_eq!(ids(&conn, filter), vec![3]);
+    }
+
+    #[test]
+    fn sql_depends_only_on_which_filters_are_set() {
+        // Same SQL = same cached statement.
+        let filter = |q: &str, rating: f64| SearchFilter {
+            query: Some(q.into()),
+            min_rating: Some(rating),
+            ..Default::default()
+        };
+        let a = SearchQuery::new(&filter("zelda", 8.0));
+        let b = SearchQuery::new(&filter("stardew", 3.0));
+        assert_eq!(a.sql(), b.sql());
+        assert_ne!(a.params(), b.params());
+    }
+
+    #[test]
+    fn sorts_with_nulls_last_and_tie_breakers() {
+        let conn = library();
+        let by = |sort_by, sort_asc, then_by| SearchFilter { sort_by: Some(sort_by), sort_asc, then_by, ..Default::default() };
+
+        // Leading articles are ignored: "The Legend of Zelda" files under L.
+        assert_eq!(ids(&conn, by(SortField::Title, None, None)), vec![5, 2, 3, 1, 4]);
+        assert_eq!(ids(&conn, by(SortField::Rating, Some(false), None)), vec![1, 2, 3, 5, 4]);
+        assert_eq!(ids(&conn, by(SortField::Rating, Some(true), None)), vec![5, 3, 2, 1, 4]);
+        assert_eq!(ids(&conn, by(SortField::CompletedAt, None, None)), vec![3, 1, 2, 4, 5]);
+        let pinned = SearchFilter { favorites_first: true, ..Default::default() };
+        assert_eq!(ids(&conn, pinned), vec![5, 2, 4, 3, 1]);
+
+        let mut franchise_then_rating = by(SortField::Franchise, None, Some(SortField::Rating));
+        franchise_then_rating.then_asc = Some(false);
+        assert_eq!(ids(&conn, franchise_then_rating), vec![5, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn seeded_random_sort_is_repeatable() {
+        let conn = library();
+        let shuffled = |seed| ids(&conn, SearchFilter {
+            sort_by: Some(SortField::Random),
+            random_seed: Some(seed),
+            ..Default::default()
+        });
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_eq!(sorted(shuffled(42)), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn pages_add_up_to_the_full_search() {
+        let conn = library();
+        let sorts: Vec<fn() -> SearchFilter> = vec![
+            SearchFilter::default,
+            || SearchFilter { sort_by: Some(SortField::Title), ..Default::default() },
+            || SearchFilter { sort_by: Some(SortField::Rating), sort_asc: Some(false), ..Default::default() },
+            || SearchFilter { sort_by: Some(SortField::CompletedAt), ..Default::default() },
+            || SearchFilter {
+                sort_by: Some(SortField::Franchise),
+                then_by: Some(SortField::PlaytimeHours),
+                then_asc: Some(false),
+                ..Default::default()
+            },
+            || SearchFilter { sort_by: Some(SortField::Random), random_seed: Some(7), ..Default::default() },
+            || SearchFilter { platform: vec!["PC".into()], sort_by: Some(SortField::ReleaseDate), ..Default::default() },
+        ];
+        for (i, filter) in sorts.iter().enumerate() {
+            let mut paged = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = db::search_games_page(&conn, filter(), 2, cursor.as_deref()).unwrap();
+                paged.extend(page.games.iter().map(|g| g.id));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(paged, ids(&conn, filter()), "sort #{i}");
+        }
+    }
+
+    #[test]
+    fn archived_games_only_show_up_when_asked_for() {
+        let conn = library();
+        db::set_archived(&conn, &[5], true).unwrap();
+        assert_eq!(sorted(ids(&conn, SearchFilter::default())), vec![1, 2, 3, 4]);
+        let with_archived = SearchFilter { include_archived: true, ..Default::default() };
+        assert_eq!(sorted(ids(&conn, with_archived)), vec![1, 2, 3, 4, 5]);
+        assert_eq!(db::get_stats(&conn).unwrap().total_games, 4);
+    }
+
+    #[test]
+    fn rejects_foreign_cursors() {
+        let conn = library();
+        assert!(db::search_games_page(&conn, SearchFilter::default(), 2, Some("not a cursor")).is_err());
+
+        let page = db::search_games_page(&conn, SearchFilter::default(), 2, None).unwrap();
+        let with_tie_breaker = SearchFilter { then_by: Some(SortField::Title), ..Default::default() };
+        assert!(db::search_games_page(&conn, with_tie_breaker, 2, page.next_cursor.as_deref()).is_err());
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let keys = vec![Value::Text("Zelda".into()), Value::Real(8.5), Value::Null, Value::Integer(3)];
+        assert_eq!(decode_cursor(&encode_cursor(&keys)).unwrap(), keys);
+    }
+}
@@ -0,0 +1,234 @@
+// steam.rs — Keep Steam-linked games' playtime up to date.
+//
+// Games are linked to Steam through `game_external_ids` (source "Steam",
+// external id = app id). A sync asks the Steam Web API for the account's owned
+// games and merges each linked game's lifetime playtime into the library with
+// `db::merge_remote_playtime`, which takes care of not double counting
+// sessions the user already logged by hand.
+//
+// The sync runs on demand (`sync_steam_playtime` command) and, if the user set
+// an interval, on a background thread started from lib.rs.
+//
+// The second half of the file finds screenshots the Steam client saved locally.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::DateTime;
+use rusqlite::Connection;
+use serde::Deserialize;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::credentials;
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::events;
+use crate::http;
+use crate::jobs::{JobHandle, JobQueue};
+use crate::models::{ChangeAction, SessionSource, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for Steam rows in `game_external_ids`.
+pub const SOURCE: &str = "Steam";
+
+const OWNED_GAMES_URL: &str = "https://api.steampowered.com/IPlayerService/GetOwnedGames/v1/";
+
+#[derive(Deserialize)]
+struct OwnedGamesResponse {
+    response: OwnedGames,
+}
+
+#[derive(Deserialize)]
+struct OwnedGames {
+    #[serde(default)]
+    games: Vec<OwnedGame>,
+}
+
+#[derive(Deserialize)]
+struct OwnedGame {
+    appid:             u64,
+    playtime_forever:  i64,           // minutes
+    playtime_2weeks:   Option<i64>,   // minutes; absent if not played recently
+    #[serde(default)]
+    rtime_last_played: i64,           // unix seconds; 0 = never
+}
+
+/// Pull playtime for every Steam-linked game and merge it into the library.
+///
+/// The DB lock is released while waiting on Steam, so the UI stays usable
+/// during a slow request. With `dry_run` nothing is saved; the report's
+/// `preview` says what would have changed.
+pub fn sync_playtime(db: &Mutex<Connection>, dry_run: bool, job: &JobHandle) -> CmdResult<SyncReport> {
+    let (settings, links) = {
+        let conn = db::lock(db)?;
+        (db::get_settings(&conn)?, db::get_external_ids(&conn, None, Some(SOURCE))?)
+    };
+    let key = credentials::require(credentials::STEAM_API_KEY)?;
+    let Some(steam_id) = settings.steam_id else {
+        return Err(CommandError::validation("steam_id", "Set your SteamID64 first"));
+    };
+    if links.is_empty() {
+        return Ok(SyncReport::default());
+    }
+
+    let owned: OwnedGamesResponse = http::get_json(
+        OWNED_GAMES_URL,
+        &[("key", &key), ("steamid", &steam_id), ("include_played_free_games", "1"), ("format", "json")],
+    )?;
+    let by_app: HashMap<String, OwnedGame> =
+        owned.response.games.into_iter().map(|g| (g.appid.to_string(), g)).collect();
+
+    let mut report = SyncReport { checked: links.len(), ..SyncReport::default() };
+    let mut conn = db::lock(db)?;
+    let mut recorder = dry_run.then(|| PreviewRecorder::start(&conn)).transpose()?;
+    let tx = conn.transaction()?;
+    for (i, link) in links.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, links.len(), "Merging Steam playtime");
+        // Not owned (or hidden by privacy settings) — nothing to merge.
+        let Some(game) = by_app.get(&link.external_id) else {
+            if let Some(recorder) = recorder.as_mut() {
+                let title = recorder.title_of(link.game_id);
+                recorder.skip(Some(link.game_id), &title, "Not in the Steam library");
+            }
+            continue;
+        };
+        let last_played = (game.rtime_last_played > 0)
+            .then(|| DateTime::from_timestamp(game.rtime_last_played, 0))
+            .flatten()
+            .map(|t| t.to_rfc3339());
+        let added = db::merge_remote_playtime(
+            &tx,
+            link,
+            game.playtime_forever,
+            game.playtime_2weeks,
+            last_played,
+            SessionSource::Steam,
+        )?;
+        if added > 0 {
+            report.updated_ids.push(link.game_id);
+            report.added_minutes += added;
+        }
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(&tx, link.game_id)?;
+        }
+    }
+    if let Some(recorder) = recorder {
+        // Dropping `tx` without committing rolls the sync back.
+        info!(checked = report.checked, "Steam playtime dry run");
+        return Ok(report.into_dry_run(recorder.finish()));
+    }
+    tx.commit()?;
+
+    info!(checked = report.checked, updated = report.updated_ids.len(), added_minutes = report.added_minutes, "Steam playtime synced");
+    Ok(report)
+}
+
+/// Start the background sync thread. It checks the interval setting once a
+/// minute, so changing it in settings takes effect without a restart. Each
+/// sync runs as a "steam_sync" job on `jobs`.
+pub fn spawn_background_sync(app: AppHandle, db: Arc<Mutex<Connection>>, jobs: Arc<JobQueue>) {
+    thread::spawn(move || {
+        let mut last_run: Option<Instant> = None;
+        loop {
+            thread::sleep(Duration::from_secs(60));
+
+            let interval = match db::lock(&db).and_then(|conn| Ok(db::get_settings(&conn)?)) {
+                Ok(settings) => settings.steam_sync_interval_minutes,
+                Err(e) => {
+                    warn!(error = %e, "Couldn't read settings for Steam sync");
+                    continue;
+                }
+            };
+            let due = last_run.is_none_or(|t| t.elapsed() >= Duration::from_secs(u64::from(interval) * 60));
+            if interval == 0 || !due {
+                continue;
+            }
+
+            last_run = Some(Instant::now());
+            let (app, job_db) = (app.clone(), Arc::clone(&db));
+            let queued = jobs.enqueue("steam_sync", move |job| {
+                let report = sync_playtime(&job_db, false, job)?;
+                events::games_changed(&app, ChangeAction::Updated, report.updated_ids.clone());
+                Ok(report)
+            });
+            if let Err(e) = queued {
+                warn!(code = e.code(), error = %e, "Couldn't queue background Steam sync");
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Local screenshots
+// ---------------------------------------------------------------------------
+
+/// SteamID64 = this + the 32-bit account id used for userdata folder names.
+const STEAM_ID64_BASE: u64 = 76_561_197_960_265_728;
+
+/// Folders the Steam client may be installed in: `steam_path` if the user set
+/// one, otherwise the usual places for this OS.
+pub fn steam_roots(steam_path: Option<&str>) -> Vec<PathBuf> {
+    if let Some(p) = steam_path.filter(|p| !p.trim().is_empty()) {
+        return vec![PathBuf::from(p)];
+    }
+    let home = dirs::home_dir().unwrap_or_default();
+    if cfg!(target_os = "windows") {
+        vec![
+            PathBuf::from(r"C:\Program Files (x86)\Steam"),
+            PathBuf::from(r"C:\Program Files\Steam"),
+        ]
+    } else if cfg!(target_os = "macos") {
+        vec![home.join("Library/Application Support/Steam")]
+    } else {
+        vec![
+            home.join(".steam/steam"),
+            home.join(".local/share/Steam"),
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"), // Flatpak
+        ]
+    }
+}
+
+/// Screenshots the Steam client saved for `app_id`, oldest first.
+///
+/// They live in `<steam>/userdata/<account id>/760/remote/<app id>/screenshots/`.
+/// If `steam_id` is set only that account is searched; otherwise every account
+/// on the machine is. `steam_path` overrides the install folder.
+pub fn find_screenshots(steam_path: Option<&str>, steam_id: Option<&str>, app_id: &str) -> CmdResult<Vec<PathBuf>> {
+    let Some(userdata) = steam_roots(steam_path).iter().map(|r| r.join("userdata")).find(|p| p.is_dir()) else {
+        return Err(CommandError::NotFound("Steam installation".to_string()));
+    };
+
+    let account = steam_id
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .and_then(|id| id.checked_sub(STEAM_ID64_BASE))
+        .map(|id| id.to_string());
+    let accounts: Vec<PathBuf> = match account {
+        Some(account) => vec![userdata.join(account)],
+        None => fs::read_dir(&userdata)?.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+    };
+
+    let mut shots: Vec<PathBuf> = Vec::new();
+    for account in accounts {
+        let dir = account.join("760/remote").join(app_id).join("screenshots");
+        // Missing folder just means no screenshots for this account.
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        shots.extend(entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| is_image(p)));
+    }
+    // Steam names files by date ("20240131123456_1.jpg"), so sorting by name is chronological.
+    shots.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(shots)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "jpg" | "jpeg" | "png"))
+}
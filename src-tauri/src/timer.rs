@@ -0,0 +1,311 @@
+// timer.rs — A live session timer.
+//
+// Instead of typing in "90 minutes" afterwards, the user can start a timer
+// when they sit down to play:
+//
+//   start_session → (pause_session ⇄ resume_session)* → stop_session
+//
+// Stopping records an ordinary play session (source "Timer") for the time
+// played, pauses left out. Only one timer runs at a time.
+//
+// The open session lives in the database (`active_session`), not just in
+// memory, so a crash or a killed process doesn't lose it. While it's open a
+// ticker thread emits `sessions://tick` once a minute for the UI and stamps
+// `heartbeat_at`. If the app starts and finds a session still open, it
+// closes it at that last heartbeat — the time the app wasn't running isn't
+// counted as play.
+//
+// Each tick also looks for time the player wasn't there:
+//
+//   - no keyboard or mouse input for `idle_threshold_minutes` (see idle.rs);
+//     the whole idle stretch counts, not just the part past the threshold
+//   - a gap between ticks much longer than a minute: the machine slept or
+//     the process was suspended
+//
+// That idle time is left out of the session when it's recorded, or, with
+// `subtract_idle_time` off, kept in and only noted on it (`idle_minutes`).
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::events;
+use crate::idle;
+use crate::models::{ActiveSession, PlaySession, SessionSource, Settings};
+
+/// How often the ticker emits `sessions://tick` and stamps the heartbeat.
+const TICK: Duration = Duration::from_secs(60);
+
+/// Start timing a session of `game_id`.
+pub fn start(conn: &Connection, game_id: i64, game_version: Option<&str>) -> CmdResult<ActiveSession> {
+    if let Some(open) = db::get_active_session(conn)? {
+        return Err(CommandError::validation("gameId", format!("A session of {} is already running", open.title)));
+    }
+    if db::get_game(conn, game_id)?.is_none() {
+        return Err(CommandError::NotFound(format!("Game {game_id}")));
+    }
+    let version = game_version.map(str::trim).filter(|v| !v.is_empty());
+    db::start_active_session(conn, game_id, &Utc::now().to_rfc3339(), version)?;
+    info!(game_id, "Session timer started");
+    current(conn)?.ok_or_else(|| CommandError::Internal("The session didn't start".into()))
+}
+
+/// Stop the clock without ending the session.
+pub fn pause(conn: &Connection) -> CmdResult<ActiveSession> {
+    let session = require(conn)?;
+    if session.paused_at.is_none() {
+        db::set_active_session_paused(conn, Some(&Utc::now().to_rfc3339()), 0)?;
+    }
+    require(conn)
+}
+
+/// Restart the clock of a paused session.
+pub fn resume(conn: &Connection) -> CmdResult<ActiveSession> {
+    let session = require(conn)?;
+    if let Some(paused_at) = session.paused_at.as_deref().and_then(parse) {
+        let paused = (Utc::now() - paused_at).num_seconds().max(0);
+        db::set_active_session_paused(conn, None, paused)?;
+    }
+    require(conn)
+}
+
+/// End the session and record it. Returns None (and records nothing) if
+/// less than a minute was played.
+pub fn stop(conn: &Connection) -> CmdResult<Option<PlaySession>> {
+    let session = require(conn)?;
+    close(conn, &session, Utc::now())
+}
+
+/// The open session with its elapsed time, if there is one.
+pub fn current(conn: &Connection) -> CmdResult<Option<ActiveSession>> {
+    let subtract_idle = db::get_settings(conn)?.subtract_idle_time;
+    Ok(db::get_active_session(conn)?.map(|mut session| {
+        session.elapsed_seconds = elapsed_seconds(&session, Utc::now(), subtract_idle);
+        session
+    }))
+}
+
+/// Close a session left open by a crash, at its last heartbeat. Call once at
+/// startup, before the ticker runs.
+pub fn recover(conn: &Connection) -> CmdResult<Option<PlaySession>> {
+    let Some(session) = db::get_active_session(conn)? else {
+        return Ok(None);
+    };
+    let end = parse(&session.heartbeat_at).unwrap_or_else(Utc::now);
+    let recorded = close(conn, &session, end)?;
+    warn!(game_id = session.game_id, heartbeat_at = session.heartbeat_at, "Closed a session left open by the last run");
+    Ok(recorded)
+}
+
+/// Every minute while a session is open: add up idle time, stamp the
+/// heartbeat and emit `sessions://tick`.
+pub fn spawn_ticker(app: AppHandle, db: Arc<Mutex<Connection>>) {
+    thread::spawn(move || {
+        // Idle time is counted up to here, so a long idle stretch spanning
+        // several ticks is only counted once.
+        let mut idle_counted_until: Option<DateTime<Utc>> = None;
+        loop {
+            thread::sleep(TICK);
+
+            // Asking the OS runs a command, which can take a while; every
+            // other command waits on the database lock, so don't hold it then.
+            let open = db::lock(&db).and_then(|conn| Ok(db::get_active_session(&conn)?.is_some()));
+            let idle_seconds = match open {
+                Ok(true) => idle::idle_seconds(),
+                _ => None,
+            };
+
+            let tick = db::lock(&db).and_then(|conn| {
+                let Some(session) = db::get_active_session(&conn)? else {
+                    idle_counted_until = None;
+                    return Ok(None);
+                };
+                let now = Utc::now();
+                let settings = db::get_settings(&conn)?;
+                let idle = idle_since_last_tick(&session, &settings, idle_seconds, now, &mut idle_counted_until);
+                db::touch_active_session(&conn, &now.to_rfc3339(), idle)?;
+                current(&conn)
+            });
+            match tick {
+                Ok(Some(session)) => events::session_tick(&app, &session),
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Couldn't update the session timer"),
+            }
+        }
+    });
+}
+
+/// Idle seconds to add to the session at this tick, given how long the OS
+/// says input has been idle (`idle::idle_seconds`).
+fn idle_since_last_tick(
+    session: &ActiveSession,
+    settings: &Settings,
+    idle_seconds: Option<u64>,
+    now: DateTime<Utc>,
+    counted_until: &mut Option<DateTime<Utc>>,
+) -> i64 {
+    // A paused session isn't counting anything, idle or not.
+    if session.paused_at.is_some() {
+        *counted_until = Some(now);
+        return 0;
+    }
+
+    // Far more than a minute since the last heartbeat: we were suspended.
+    let gap = parse(&session.heartbeat_at).map_or(0, |last| (now - last).num_seconds());
+    let tick = TICK.as_secs() as i64;
+    if gap > 2 * tick {
+        *counted_until = Some(now);
+        info!(game_id = session.game_id, seconds = gap - tick, "Session timer was suspended");
+        return gap - tick;
+    }
+
+    let threshold = u64::from(settings.idle_threshold_minutes) * 60;
+    match idle_seconds {
+        Some(idle) if threshold > 0 && idle >= threshold => {
+            // Count the stretch from when input stopped, but nothing before the
+            // session started or that an earlier tick already counted.
+            let idle_start = [Some(now - chrono::Duration::seconds(idle as i64)), *counted_until, parse(&session.started_at)]
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or(now);
+            *counted_until = Some(now);
+            (now - idle_start).num_seconds().max(0)
+        }
+        _ => 0,
+    }
+}
+
+/// Record the session as ended at `end` and remove the marker, together.
+fn close(conn: &Connection, session: &ActiveSession, end: DateTime<Utc>) -> CmdResult<Option<PlaySession>> {
+    let subtract_idle = db::get_settings(conn)?.subtract_idle_time;
+    let minutes = (elapsed_seconds(session, end, subtract_idle) + 30) / 60;
+    let idle_minutes = (session.idle_seconds + 30) / 60;
+    let tx = conn.unchecked_transaction()?;
+    let recorded = if minutes > 0 {
+        let version = session.game_version.as_deref();
+        let mut recorded = db::add_play_session(&tx, session.game_id, &session.started_at, minutes, SessionSource::Timer, version)?;
+        if idle_minutes > 0 {
+            db::set_session_idle_minutes(&tx, recorded.id, idle_minutes)?;
+            recorded.idle_minutes = idle_minutes;
+        }
+        Some(recorded)
+    } else {
+        None
+    };
+    db::clear_active_session(&tx)?;
+    tx.commit()?;
+    info!(game_id = session.game_id, minutes, idle_minutes, "Session timer stopped");
+    Ok(recorded)
+}
+
+fn require(conn: &Connection) -> CmdResult<ActiveSession> {
+    current(conn)?.ok_or_else(|| CommandError::validation("session", "No session is running"))
+}
+
+/// Seconds played between the start and `end`, not counting pauses (nor
+/// idle time, if `subtract_idle`). A paused session stopped counting at
+/// `paused_at`.
+fn elapsed_seconds(session: &ActiveSession, end: DateTime<Utc>, subtract_idle: bool) -> i64 {
+    let Some(started) = parse(&session.started_at) else {
+        return 0;
+    };
+    let end = session.paused_at.as_deref().and_then(parse).map_or(end, |paused| paused.min(end));
+    let idle = if subtract_idle { session.idle_seconds } else { 0 };
+    ((end - started).num_seconds() - session.paused_seconds - idle).max(0)
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        parse(&format!("2024-05-01T{time}Z")).unwrap()
+    }
+
+    /// A session started at 20:00, last heard from at `heartbeat`.
+    fn session(heartbeat: &str) -> ActiveSession {
+        ActiveSession {
+            game_id:         1,
+            title:           "Hades".to_string(),
+            started_at:      at("20:00:00").to_rfc3339(),
+            paused_at:       None,
+            paused_seconds:  0,
+            game_version:    None,
+            heartbeat_at:    at(heartbeat).to_rfc3339(),
+            idle_seconds:    0,
+            elapsed_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn elapsed_leaves_out_pauses_and_optionally_idle_time() {
+        let mut s = session("21:00:00");
+        assert_eq!(elapsed_seconds(&s, at("21:00:00"), true), 3600);
+
+        s.paused_seconds = 600;
+        s.idle_seconds = 300;
+        assert_eq!(elapsed_seconds(&s, at("21:00:00"), false), 3000);
+        assert_eq!(elapsed_seconds(&s, at("21:00:00"), true), 2700);
+
+        // Paused at 20:30: nothing after that counts, however late it ends.
+        s.paused_at = Some(at("20:30:00").to_rfc3339());
+        assert_eq!(elapsed_seconds(&s, at("23:00:00"), false), 1200);
+
+        s.idle_seconds = 5000;
+        assert_eq!(elapsed_seconds(&s, at("21:00:00"), true), 0);
+    }
+
+    #[test]
+    fn a_paused_session_counts_no_idle_time() {
+        let mut s = session("20:29:00");
+        s.paused_at = Some(at("20:10:00").to_rfc3339());
+        let mut counted = None;
+        assert_eq!(idle_since_last_tick(&s, &Settings::default(), Some(3600), at("20:30:00"), &mut counted), 0);
+        assert_eq!(counted, Some(at("20:30:00")));
+    }
+
+    #[test]
+    fn a_suspend_counts_the_gap_since_the_heartbeat() {
+        let s = session("20:00:00");
+        let mut counted = None;
+        // Half an hour without a tick: all but the one expected minute.
+        assert_eq!(idle_since_last_tick(&s, &Settings::default(), None, at("20:30:00"), &mut counted), 1740);
+    }
+
+    #[test]
+    fn idle_time_counts_from_when_input_stopped_and_only_once() {
+        let settings = Settings::default();   // idle after 10 minutes
+        let mut counted = None;
+
+        // Idle 5 minutes: not yet.
+        assert_eq!(idle_since_last_tick(&session("20:19:00"), &settings, Some(300), at("20:20:00"), &mut counted), 0);
+        // Idle 15 minutes at 20:30: since 20:15.
+        assert_eq!(idle_since_last_tick(&session("20:29:00"), &settings, Some(900), at("20:30:00"), &mut counted), 900);
+        // Still idle a minute later: only that minute is new.
+        assert_eq!(idle_since_last_tick(&session("20:30:00"), &settings, Some(960), at("20:31:00"), &mut counted), 60);
+
+        // Input stopped before the session started: counted from the start.
+        let mut counted = None;
+        assert_eq!(idle_since_last_tick(&session("20:29:00"), &settings, Some(7200), at("20:30:00"), &mut counted), 1800);
+    }
+
+    #[test]
+    fn idle_is_not_counted_when_off_or_unknown() {
+        let off = Settings { idle_threshold_minutes: 0, ..Settings::default() };
+        let mut counted = None;
+        assert_eq!(idle_since_last_tick(&session("20:29:00"), &off, Some(3600), at("20:30:00"), &mut counted), 0);
+        assert_eq!(idle_since_last_tick(&session("20:29:00"), &Settings::default(), None, at("20:30:00"), &mut counted), 0);
+    }
+}
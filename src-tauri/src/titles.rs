@@ -0,0 +1,118 @@
+// titles.rs — Normalize game titles so the same game written two ways matches.
+//
+// Importers and duplicate checks compare `normalize(title)` instead of the raw
+// title, so "Pokémon™ Sword", "POKEMON SWORD" and "Pokemon: Sword" are all the
+// same game, and so are "Final Fantasy VII" and "Final Fantasy 7".
+//
+// `similarity` builds on the same normalization for typo-tolerant search.
+//
+// The result of `normalize` is stored in the hidden `games.normalized_title` column (kept up
+// to date by db::add_game / db::update_game) so lookups can use an index.
+
+use std::collections::HashSet;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Comparison key for a title:
+///   - ™, ® and © are dropped
+///   - diacritics are folded ("é" → "e") and everything is lowercased
+///   - punctuation becomes a word break ("Half-Life" → "half life")
+///   - roman numerals become digits ("vii" → "7")
+///   - runs of whitespace collapse to one space
+pub fn normalize(title: &str) -> String {
+    // RUST NOTE: `nfkd()` splits "é" into "e" + a combining accent, which
+    // `is_combining_mark` then lets us drop. The K form also turns full-width
+    // letters and ligatures ("ﬁ") into their plain equivalents.
+    let folded: String = title
+        .chars()
+        .filter(|c| !matches!(c, '™' | '®' | '©'))
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    folded
+        .split_whitespace()
+        .map(|word| roman_to_arabic(word).map_or_else(|| word.to_string(), |n| n.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Value of a lowercase roman numeral made of i, v, x and l, up to 50.
+///
+/// Only well-formed numerals count ("iiii" and "vx" don't), and c/d/m are left
+/// alone so ordinary words like "mix" or "civ" aren't mistaken for numbers.
+fn roman_to_arabic(word: &str) -> Option<u32> {
+    let digit = |c: char| match c {
+        'i' => Some(1),
+        'v' => Some(5),
+        'x' => Some(10),
+        'l' => Some(50),
+        _   => None,
+    };
+    let digits: Vec<u32> = word.chars().map(digit).collect::<Option<_>>()?;
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut value = 0;
+    for (i, &d) in digits.iter().enumerate() {
+        match digits.get(i + 1) {
+            Some(&next) if next > d => value -= d as i32,
+            _ => value += d as i32,
+        }
+    }
+    // Round-trip to reject malformed numerals that still add up to something.
+    let value = u32::try_from(value).ok().filter(|v| (1..=50).contains(v))?;
+    (arabic_to_roman(value) == word).then_some(value)
+}
+
+fn arabic_to_roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 7] =
+        [(50, "l"), (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i")];
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Fuzzy matching
+// ---------------------------------------------------------------------------
+
+/// How well `query` matches `title`, from 0.0 (nothing in common) to 1.0.
+///
+/// Both are normalized and split into trigrams (three-letter windows of each
+/// word, like Postgres' pg_trgm). The score mixes how much of the query shows
+/// up in the title with how similar the two are overall, so a typo
+/// ("Wild Hnt") only costs a few trigrams and a partial query ("witcher")
+/// still scores well against the full title.
+pub fn similarity(query: &str, title: &str) -> f64 {
+    let query = trigrams(&normalize(query));
+    let title = trigrams(&normalize(title));
+    if query.is_empty() || title.is_empty() {
+        return 0.0;
+    }
+    let shared = query.intersection(&title).count() as f64;
+    let coverage = shared / query.len() as f64;
+    let dice = 2.0 * shared / (query.len() + title.len()) as f64;
+    0.7 * coverage + 0.3 * dice
+}
+
+fn trigrams(text: &str) -> HashSet<String> {
+    let mut grams = HashSet::new();
+    for word in text.split_whitespace() {
+        // Pad so short words and word starts still produce trigrams.
+        let padded: Vec<char> = format!("  {word} ").chars().collect();
+        for window in padded.windows(3) {
+            grams.insert(window.iter().collect());
+        }
+    }
+    grams
+}
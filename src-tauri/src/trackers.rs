@@ -0,0 +1,226 @@
+// trackers.rs — Import the CSV exports of other game trackers.
+//
+// People moving to GameTrc bring their library from somewhere else. Each
+// tracker exports a CSV with its own columns and vocabulary; each gets a
+// small module implementing `TrackerExport`, which maps one row to a
+// `TrackerEntry`:
+//
+//   hltb.rs            HowLongToBeat
+//   darkadia.rs        Darkadia
+//   completionator.rs  Completionator
+//
+// Everything else is shared and lives here: reading the CSV, looking columns
+// up by name, and the import itself — matching library games with
+// `on_conflict`, dry runs through PreviewRecorder, progress and cancellation
+// as a job — so every tracker behaves the same. Importer plugins (plugins.rs)
+// hand their games to the same `import_entries`.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::NaiveDate;
+use csv::StringRecord;
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::completionator::Completionator;
+use crate::darkadia::Darkadia;
+use crate::db::{self, ImportOutcome};
+use crate::error::{CmdResult, CommandError};
+use crate::hltb::Hltb;
+use crate::jobs::JobHandle;
+use crate::models::{ConflictStrategy, GameInput, PlaythroughInput, SyncReport, TrackerFormat};
+use crate::preview::PreviewRecorder;
+
+/// One tracker's export format.
+pub trait TrackerExport {
+    /// The tracker's name, for logs and messages.
+    fn name(&self) -> &'static str;
+
+    /// Header names the title may be under; an export without one is refused.
+    fn title_columns(&self) -> &'static [&'static str];
+
+    /// Map one row. None skips it (e.g. a row without a title).
+    fn entry(&self, row: &Row, fallback_platform: &str) -> Option<TrackerEntry>;
+}
+
+/// One game from an export, mapped.
+pub struct TrackerEntry {
+    pub input:       GameInput,
+    pub started_at:  Option<String>,   // "YYYY-MM-DD"; with finished_at, becomes a playthrough
+    pub finished_at: Option<String>,
+    pub favorite:    bool,
+    pub external_id: Option<String>,   // links the game under the import's source; CSV rows have none
+}
+
+impl TrackerEntry {
+    pub fn new(input: GameInput) -> Self {
+        TrackerEntry { input, started_at: None, finished_at: None, favorite: false, external_id: None }
+    }
+}
+
+/// The importer for a format.
+pub fn importer(format: TrackerFormat) -> &'static dyn TrackerExport {
+    match format {
+        TrackerFormat::HowLongToBeat  => &Hltb,
+        TrackerFormat::Darkadia       => &Darkadia,
+        TrackerFormat::Completionator => &Completionator,
+    }
+}
+
+/// Import every game in the export at `path`. Rows without a platform get
+/// `fallback_platform`. With `dry_run` nothing is saved; the report's
+/// `preview` says what would change.
+pub fn import(
+    db: &Mutex<Connection>,
+    format: TrackerFormat,
+    path: &Path,
+    fallback_platform: &str,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let tracker = importer(format);
+    let entries = parse(tracker, &std::fs::read_to_string(path)?, fallback_platform)?;
+    if entries.is_empty() {
+        return Err(CommandError::validation("path", format!("No games found in the {} export", tracker.name())));
+    }
+    info!(tracker = tracker.name(), path = %path.display(), games = entries.len(), "Importing tracker export");
+    import_entries(db, tracker.name(), entries, dry_run, on_conflict, job)
+}
+
+/// Import mapped games: each is matched with `on_conflict` (or by its
+/// `external_id`, linked under `source`) and new ones get their playthrough
+/// and favourite. The entries must be valid; the first that isn't stops the
+/// import. With `dry_run` nothing is saved.
+pub fn import_entries(
+    db: &Mutex<Connection>,
+    source: &str,
+    entries: Vec<TrackerEntry>,
+    dry_run: bool,
+    on_conflict: ConflictStrategy,
+    job: &JobHandle,
+) -> CmdResult<SyncReport> {
+    let mut report = SyncReport { checked: entries.len(), ..SyncReport::default() };
+    let mut conn = db::lock(db)?;
+    let mut recorder = dry_run.then(|| PreviewRecorder::start(&conn)).transpose()?;
+    let tx = conn.transaction()?;
+    for (i, entry) in entries.into_iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i, report.checked, entry.input.title.as_str());
+        entry.input.validate()?;
+        let link = entry.external_id.as_deref().map(|id| (source, id));
+        let (game_id, outcome) = db::import_game(&tx, entry.input, link, on_conflict)?;
+
+        match outcome {
+            ImportOutcome::Created => {
+                // Only for new games: importing again mustn't add the run twice.
+                if entry.started_at.is_some() || entry.finished_at.is_some() {
+                    db::add_playthrough(&tx, &PlaythroughInput {
+                        game_id,
+                        started_at:    entry.started_at,
+                        finished_at:   entry.finished_at,
+                        new_game_plus: false,
+                        challenge:     None,
+                        ruleset:       None,
+                        game_version:  None,
+                        notes:         None,
+                    })?;
+                }
+                if entry.favorite {
+                    db::toggle_favorite(&tx, game_id)?;
+                }
+                report.imported_ids.push(game_id);
+            }
+            ImportOutcome::Updated => report.updated_ids.push(game_id),
+            ImportOutcome::Unchanged => {}
+        }
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(&tx, game_id)?;
+        }
+    }
+    if let Some(recorder) = recorder {
+        // Dropping `tx` without committing rolls the import back.
+        info!(source, checked = report.checked, "Import dry run");
+        return Ok(report.into_dry_run(recorder.finish()));
+    }
+    tx.commit()?;
+
+    info!(
+        source,
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        "Imported games"
+    );
+    Ok(report)
+}
+
+fn parse(tracker: &dyn TrackerExport, text: &str, fallback_platform: &str) -> CmdResult<Vec<TrackerEntry>> {
+    // Excel-saved CSVs start with a byte order mark.
+    let text = text.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_lowercase()).collect();
+    if !tracker.title_columns().iter().any(|name| headers.iter().any(|h| h == name)) {
+        return Err(CommandError::validation("path", format!("Not a {} export: there's no title column", tracker.name())));
+    }
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        if let Some(entry) = tracker.entry(&Row { headers: &headers, record: &record }, fallback_platform) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Reading rows
+// ---------------------------------------------------------------------------
+
+/// One CSV row, read by column name.
+pub struct Row<'a> {
+    headers: &'a [String],   // lowercased
+    record:  &'a StringRecord,
+}
+
+impl Row<'_> {
+    /// The first non-empty value under any of `names` (lowercase).
+    pub fn get(&self, names: &[&str]) -> Option<&str> {
+        names.iter().find_map(|name| {
+            let i = self.headers.iter().position(|h| h == name)?;
+            self.record.get(i).map(str::trim).filter(|v| !v.is_empty())
+        })
+    }
+
+    /// A yes/no column: anything but empty, "0", "no" or "false" is yes.
+    pub fn flag(&self, name: &str) -> bool {
+        self.get(&[name]).is_some_and(|v| !matches!(v.to_lowercase().as_str(), "0" | "no" | "false"))
+    }
+}
+
+/// "61:12:00", "12:30" or "12.5" (hours) → minutes.
+pub fn parse_duration(value: &str) -> Option<i64> {
+    let parts: Vec<&str> = value.split(':').map(str::trim).collect();
+    match parts[..] {
+        [h, m, s] => Some(h.parse::<i64>().ok()? * 60 + m.parse::<i64>().ok()? + i64::from(s.parse::<i64>().ok()? >= 30)),
+        [h, m] => Some(h.parse::<i64>().ok()? * 60 + m.parse::<i64>().ok()?),
+        [hours] => hours.parse::<f64>().ok().filter(|h| *h >= 0.0).map(|h| (h * 60.0).round() as i64),
+        _ => None,
+    }
+}
+
+/// A score out of `out_of` → GameTrc's 1 – 10. 0 means unrated.
+pub fn parse_rating(value: &str, out_of: f64) -> Option<f64> {
+    let score: f64 = value.trim_end_matches('%').parse().ok()?;
+    (score > 0.0).then(|| (score * 10.0 / out_of).clamp(1.0, 10.0))
+}
+
+/// "2021-02-14", "2021-02-14 20:15:00" or "02/14/2021" → "2021-02-14".
+pub fn parse_date(value: &str) -> Option<String> {
+    let day = value.get(..10).unwrap_or(value);
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(day, "%m/%d/%Y"))
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
@@ -0,0 +1,213 @@
+// webdav.rs — Sync the library through a WebDAV server (e.g. Nextcloud).
+//
+// Opt-in: nothing happens until `webdav_url` is set. The library is kept on
+// the server as one zip export (see library.rs), images included:
+//
+//   <webdav_url>/gametrc-library.zip
+//
+// Each sync remembers the server file's ETag (or Last-Modified) and a hash of
+// the library as it was pushed or pulled. Next time, that tells which side
+// changed:
+//
+//   only this library  → push       only the server's copy → pull
+//   neither            → nothing    both                   → Conflict, nothing touched
+//
+// A push also sends If-Match / If-None-Match, so another device syncing at
+// the same moment gets a 412 instead of silently overwriting. A conflict is
+// settled by syncing again with an explicit Push or Pull.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use chrono::Utc;
+use rusqlite::Connection;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::credentials;
+use crate::db;
+use crate::error::{CmdResult, CommandError};
+use crate::http;
+use crate::jobs::JobHandle;
+use crate::library::{self, LibraryDump};
+use crate::models::{LibraryReport, WebDavAction, WebDavDirection, WebDavState, WebDavSyncReport};
+
+/// The library's file name in the WebDAV folder.
+const REMOTE_FILE: &str = "gametrc-library.zip";
+
+struct Remote {
+    url:  String,
+    auth: Option<String>,   // Authorization header value
+}
+
+impl Remote {
+    fn request(&self, method: &str) -> ureq::Request {
+        let request = http::agent().request(method, &self.url);
+        match &self.auth {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+
+    /// The server file's ETag (or Last-Modified), or None if there's no file yet.
+    fn version(&self) -> CmdResult<Option<String>> {
+        match http::call_or_status(self.request("HEAD"), None)? {
+            Ok(response) => Ok(version_of(&response)),
+            Err(404) => Ok(None),
+            Err(status) => Err(status_error(status)),
+        }
+    }
+}
+
+/// Sync the library with the WebDAV server, going `direction`.
+pub fn sync(db: &Mutex<Connection>, images_dir: &Path, direction: WebDavDirection, job: &JobHandle) -> CmdResult<WebDavSyncReport> {
+    let (remote, state, dump) = {
+        let conn = db::lock(db)?;
+        (remote(&conn)?, db::get_webdav_state(&conn)?, library::dump(&conn)?)
+    };
+    job.progress(0, 2, "Checking the server");
+    let remote_version = remote.version()?;
+
+    let local_changed = state.library_hash != Some(library::fingerprint(&dump));
+    let remote_changed = remote_version.is_some() && remote_version != state.remote_version;
+    let action = match direction {
+        WebDavDirection::Push => WebDavAction::Pushed,
+        WebDavDirection::Pull if remote_version.is_none() => {
+            return Err(CommandError::validation("direction", "There's no library on the server yet"));
+        }
+        WebDavDirection::Pull => WebDavAction::Pulled,
+        WebDavDirection::Auto => match (local_changed, remote_changed) {
+            _ if remote_version.is_none() => WebDavAction::Pushed,
+            (true, true)   => WebDavAction::Conflict,
+            (false, true)  => WebDavAction::Pulled,
+            (true, false)  => WebDavAction::Pushed,
+            (false, false) => WebDavAction::UpToDate,
+        },
+    };
+    let mut report = WebDavSyncReport {
+        action,
+        remote_url: remote.url.clone(),
+        games:      dump.tables.get("games").map_or(0, Vec::len),
+        size_bytes: 0,
+        synced_at:  state.synced_at,
+    };
+    if matches!(action, WebDavAction::UpToDate | WebDavAction::Conflict) {
+        info!(?action, "WebDAV sync: nothing to transfer");
+        return Ok(report);
+    }
+    job.check_cancelled()?;
+
+    let archive = std::env::temp_dir().join(format!("gametrc-webdav-{}.zip", Uuid::new_v4()));
+    let result = if action == WebDavAction::Pushed {
+        job.progress(1, 2, "Uploading the library");
+        // Only an explicit Push may replace a copy this device hasn't seen.
+        let expected = (direction == WebDavDirection::Auto).then_some(remote_version.as_deref());
+        push(&remote, &dump, images_dir, &archive, expected)
+    } else {
+        job.progress(1, 2, "Downloading the library");
+        pull(&remote, db, images_dir, &archive).map(Some)
+    };
+    let _ = fs::remove_file(&archive);
+    let Some((transferred, new_state)) = result? else {
+        // Another device pushed between our check and our upload.
+        report.action = WebDavAction::Conflict;
+        info!("WebDAV sync: the server copy changed during the sync");
+        return Ok(report);
+    };
+
+    db::set_webdav_state(&*db::lock(db)?, &new_state)?;
+    report.games = transferred.games;
+    report.size_bytes = transferred.size_bytes;
+    report.synced_at = new_state.synced_at;
+    job.progress(2, 2, "Synced");
+    info!(?action, games = report.games, size_bytes = report.size_bytes, "WebDAV sync finished");
+    Ok(report)
+}
+
+/// Upload the library. `expected` is the server version this device last saw
+/// (`Some(None)`: no file yet), sent as a precondition; `None` skips the
+/// check. Returns None if the precondition failed.
+fn push(
+    remote: &Remote,
+    dump: &LibraryDump,
+    images_dir: &Path,
+    archive: &Path,
+    expected: Option<Option<&str>>,
+) -> CmdResult<Option<(LibraryReport, WebDavState)>> {
+    let written = library::write(dump, archive, Some(images_dir))?;
+    let mut request = remote.request("PUT").set("Content-Type", "application/zip");
+    match expected {
+        Some(Some(etag)) if etag.starts_with('"') || etag.starts_with("W/") => request = request.set("If-Match", etag),
+        Some(Some(last_modified)) => request = request.set("If-Unmodified-Since", last_modified),
+        Some(None) => request = request.set("If-None-Match", "*"),
+        None => {}
+    }
+    let response = match http::call_or_status(request, Some(&fs::read(archive)?))? {
+        Ok(response) => response,
+        Err(412) => return Ok(None),
+        Err(status) => return Err(status_error(status)),
+    };
+    // Not every server returns the new ETag from a PUT; ask if it didn't.
+    let remote_version = match version_of(&response) {
+        Some(version) => Some(version),
+        None => remote.version()?,
+    };
+    let state = WebDavState {
+        remote_version,
+        library_hash: Some(library::fingerprint(dump)),
+        synced_at:    Some(Utc::now().to_rfc3339()),
+    };
+    Ok(Some((written, state)))
+}
+
+/// Download the server's library and replace this one with it.
+fn pull(remote: &Remote, db: &Mutex<Connection>, images_dir: &Path, archive: &Path) -> CmdResult<(LibraryReport, WebDavState)> {
+    let response = match http::call_or_status(remote.request("GET"), None)? {
+        Ok(response) => response,
+        Err(status) => return Err(status_error(status)),
+    };
+    let remote_version = version_of(&response);
+    io::copy(&mut response.into_reader(), &mut File::create(archive)?)?;
+
+    let conn = db::lock(db)?;
+    let imported = library::import(&conn, archive, images_dir, None)?;
+    let state = WebDavState {
+        remote_version,
+        library_hash: Some(library::fingerprint(&library::dump(&conn)?)),
+        synced_at:    Some(Utc::now().to_rfc3339()),
+    };
+    Ok((imported, state))
+}
+
+/// Where and as whom to sync, from the settings and credential store.
+fn remote(conn: &Connection) -> CmdResult<Remote> {
+    let settings = db::get_settings(conn)?;
+    let url = settings
+        .webdav_url
+        .filter(|u| !u.trim().is_empty())
+        .ok_or_else(|| CommandError::validation("webdav_url", "Set a WebDAV URL first"))?;
+    let auth = match settings.webdav_username.filter(|u| !u.trim().is_empty()) {
+        Some(username) => {
+            let password = credentials::require(credentials::WEBDAV_PASSWORD)?;
+            let token = base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+            Some(format!("Basic {token}"))
+        }
+        None => None,
+    };
+    Ok(Remote { url: format!("{}/{REMOTE_FILE}", url.trim().trim_end_matches('/')), auth })
+}
+
+fn version_of(response: &ureq::Response) -> Option<String> {
+    response.header("etag").or_else(|| response.header("last-modified")).map(str::to_string)
+}
+
+fn status_error(status: u16) -> CommandError {
+    match status {
+        401 | 403 => CommandError::validation("webdav_username", "The WebDAV server refused the username or password"),
+        404 | 409 => CommandError::validation("webdav_url", "The WebDAV folder doesn't exist"),
+        _ => CommandError::Http(format!("The WebDAV server answered {status}")),
+    }
+}
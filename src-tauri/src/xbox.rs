@@ -0,0 +1,229 @@
+// xbox.rs — Xbox library and achievements through OpenXBL (https://xbl.io).
+//
+// Microsoft's own Xbox Live API needs a registered app and a full OAuth flow;
+// OpenXBL wraps it behind a personal API key the user creates on xbl.io, which
+// is all GameTrc needs to store.
+//
+// Every game title on the account becomes (or is matched to) a library game
+// linked through `game_external_ids` (source "Xbox", id = titleId). Each
+// achievement's gamerscore goes into `achievements.points`, so a title's
+// earned / total gamerscore is just a sum over that table.
+
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::credentials;
+use crate::db::{self, ImportOutcome};
+use crate::error::CmdResult;
+use crate::http;
+use crate::import;
+use crate::jobs::JobHandle;
+use crate::models::{ConflictStrategy, RemoteAchievement, SyncReport};
+use crate::preview::PreviewRecorder;
+
+/// `source` value used for Xbox rows in `game_external_ids` and `achievements`.
+pub const SOURCE: &str = "Xbox";
+
+const API_BASE: &str = "https://xbl.io/api/v2";
+
+#[derive(Deserialize)]
+struct TitleList {
+    #[serde(default)]
+    titles: Vec<Title>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Title {
+    title_id:    String,
+    name:        String,
+    #[serde(rename = "type", default)]
+    kind:        String,          // "Game", "App", …
+    #[serde(default)]
+    devices:     Vec<String>,     // "XboxSeries", "XboxOne", "Xbox360", "PC", …
+    achievement: Option<TitleProgress>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TitleProgress {
+    #[serde(default)]
+    current_achievements: i64,
+    #[serde(default)]
+    progress_percentage:  u32,
+}
+
+#[derive(Deserialize)]
+struct AchievementList {
+    #[serde(default)]
+    achievements: Vec<XboxAchievement>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct XboxAchievement {
+    id:                 String,
+    name:               String,
+    description:        Option<String>,
+    locked_description: Option<String>,
+    #[serde(default)]
+    progress_state:     String,         // "Achieved", "InProgress", "NotStarted"
+    progression:        Option<Progression>,
+    #[serde(default)]
+    media_assets:       Vec<MediaAsset>,
+    #[serde(default)]
+    is_secret:          bool,
+    #[serde(default)]
+    rewards:            Vec<Reward>,
+    rarity:             Option<Rarity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Progression {
+    time_unlocked: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MediaAsset {
+    #[serde(rename = "type", default)]
+    kind: String,
+    url:  String,
+}
+
+#[derive(Deserialize)]
+struct Reward {
+    #[serde(rename = "type", default)]
+    kind:  String,
+    value: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rarity {
+    current_percentage: Option<f64>,
+}
+
+fn get<T: serde::de::DeserializeOwned>(api_key: &str, path: &str) -> CmdResult<T> {
+    let request = http::agent()
+        .get(&format!("{API_BASE}{path}"))
+        .set("X-Authorization", api_key)
+        .set("Accept", "application/json");
+    http::read_json(request)
+}
+
+/// Import the account's games and their achievements. With `dry_run` nothing
+/// is saved; the report's `preview` says what would change. `on_conflict` decides
+/// what happens to unlinked library games with the same title.
+///
+/// OpenXBL's free tier is rate limited, so a title's achievement list is only
+/// fetched when its unlocked count differs from what's already stored.
+pub fn sync(db: &Mutex<Connection>, dry_run: bool, on_conflict: ConflictStrategy, job: &JobHandle) -> CmdResult<SyncReport> {
+    let api_key = credentials::require(credentials::XBOX_API_KEY)?;
+
+    let list: TitleList = get(&api_key, "/achievements")?;
+    let games: Vec<&Title> = list.titles.iter().filter(|t| t.kind.is_empty() || t.kind == "Game").collect();
+
+    let mut report = SyncReport { checked: games.len(), ..SyncReport::default() };
+    let mut recorder = if dry_run { Some(PreviewRecorder::start(&*db::lock(db)?)?) } else { None };
+    for (i, title) in games.into_iter().enumerate() {
+        // Titles already committed stay; cancelling only stops the rest.
+        job.check_cancelled()?;
+        job.progress(i, report.checked, title.name.as_str());
+        let progress = title.achievement.as_ref();
+        let unlocked_remote = progress.map_or(0, |p| p.current_achievements);
+
+        // A title that isn't linked yet has nothing stored, so it's never up to date.
+        let up_to_date = {
+            let conn = db::lock(db)?;
+            match db::find_linked_game(&conn, SOURCE, &title.title_id)? {
+                Some(game_id) => db::get_achievement_summaries(&conn, game_id)?
+                    .into_iter()
+                    .any(|s| s.source == SOURCE && s.unlocked == unlocked_remote),
+                None => false,
+            }
+        };
+        // Fetched before locking so the app isn't blocked on the network.
+        let achievements: Option<Vec<RemoteAchievement>> = if up_to_date {
+            None
+        } else {
+            let list: AchievementList = get(&api_key, &format!("/achievements/title/{}", title.title_id))?;
+            Some(list.achievements.into_iter().map(to_remote).collect())
+        };
+
+        let mut conn = db::lock(db)?;
+        let tx = conn.transaction()?;
+        let input = import::remote_title_input(
+            &title.name,
+            platform_name(&title.devices),
+            import::status_from_progress(progress.map_or(0, |p| p.progress_percentage)),
+        );
+        let (game_id, outcome) = db::import_game(&tx, input, Some((SOURCE, title.title_id.as_str())), on_conflict)?;
+        let unlocked = match &achievements {
+            Some(achievements) => db::upsert_achievements(&tx, game_id, SOURCE, achievements)?,
+            None => 0,
+        };
+        match recorder.as_mut() {
+            // Dropping `tx` without committing rolls this title back.
+            Some(recorder) => recorder.record(&tx, game_id)?,
+            None => tx.commit()?,
+        }
+
+        if outcome == ImportOutcome::Created {
+            report.imported_ids.push(game_id);
+        } else if outcome == ImportOutcome::Updated || unlocked > 0 {
+            report.updated_ids.push(game_id);
+        }
+    }
+
+    info!(
+        checked = report.checked,
+        imported = report.imported_ids.len(),
+        updated = report.updated_ids.len(),
+        dry_run,
+        "Xbox sync finished"
+    );
+    Ok(match recorder {
+        Some(recorder) => report.into_dry_run(recorder.finish()),
+        None => report,
+    })
+}
+
+fn to_remote(a: XboxAchievement) -> RemoteAchievement {
+    let unlocked = a.progress_state == "Achieved";
+    RemoteAchievement {
+        external_id:    a.id,
+        name:           a.name,
+        // Secret achievements only show their real description once unlocked.
+        description:    if unlocked { a.description } else { a.locked_description.or(a.description) },
+        icon_url:       a.media_assets.into_iter().find(|m| m.kind == "Icon").map(|m| m.url),
+        kind:           None,
+        points:         a
+            .rewards
+            .iter()
+            .find(|r| r.kind == "Gamerscore")
+            .and_then(|r| r.value.as_deref()?.parse().ok()),
+        hidden:         a.is_secret,
+        unlocked_at:    if unlocked { a.progression.and_then(|p| p.time_unlocked) } else { None },
+        rarity_percent: a.rarity.and_then(|r| r.current_percentage),
+    }
+}
+
+/// Newest device the title runs on, as a platform name.
+fn platform_name(devices: &[String]) -> &'static str {
+    let has = |d: &str| devices.iter().any(|x| x == d);
+    if has("XboxSeries") {
+        "Xbox Series X|S"
+    } else if has("XboxOne") {
+        "Xbox One"
+    } else if has("Xbox360") {
+        "Xbox 360"
+    } else if has("PC") || has("Win32") {
+        "PC"
+    } else {
+        "Xbox"
+    }
+}
@@ -7,6 +7,7 @@
 // API surface used:
 //   window.__TAURI__.core.invoke(cmd, args)  — calls a Rust #[tauri::command]
 //   window.__TAURI__.dialog.open(options)    — native file-picker dialog (cover art)
+//   window.__TAURI__.event.listen(evt, cb)   — events broadcast from Rust (games://changed)
 
 // ── Grab globals once, fail loudly if Tauri isn't present ────────────────────
 if (!window.__TAURI__) {
@@ -18,6 +19,7 @@ if (!window.__TAURI__) {
 
 const { invoke, convertFileSrc } = window.__TAURI__.core;
 const { open: openDialog }       = window.__TAURI__.dialog;
+const { listen }                 = window.__TAURI__.event;
 
 
 // =============================================================================
@@ -40,6 +42,7 @@ const state = {
   formGenres: [],
   formRating: null,
   isListView: false,
+  playtimeUnit: "HoursMinutes",  // the playtime_unit setting
 };
 
 
@@ -65,7 +68,14 @@ function statusLabel(status) {
 }
 
 function fmtRating(r) { return r != null ? `★ ${r.toFixed(1)}` : "—"; }
-function fmtHours(h)  { return h != null ? `${h}h` : "—"; }
+function fmtPlaytime(minutes) {
+  if (minutes == null) return "—";
+  switch (state.playtimeUnit) {
+    case "Hours":   return `${(minutes / 60).toFixed(1)}h`;
+    case "Minutes": return `${minutes} min`;
+    default:        return `${Math.floor(minutes / 60)}h ${String(minutes % 60).padStart(2, "0")}m`;
+  }
+}
 function fmtDate(d)   {
   return d
     ? new Date(d).toLocaleDateString("en-US", { year: "numeric", month: "short", day: "numeric" })
@@ -125,11 +135,14 @@ async function loadGames() {
 
 async function loadMeta() {
   try {
-    [state.allPlatforms, state.allFranchises, state.allGenres] = await Promise.all([
+    let settings;
+    [state.allPlatforms, state.allFranchises, state.allGenres, settings] = await Promise.all([
       invoke("get_platforms"),
       invoke("get_franchises"),
       invoke("get_genres"),
+      invoke("get_settings"),
     ]);
+    state.playtimeUnit = settings.playtime_unit;
   } catch (e) {
     console.error("loadMeta failed:", e);
   }
@@ -182,8 +195,9 @@ function renderGames() {
 }
 
 function renderGridCard(g) {
+  // Animated covers show their still poster frame in lists.
   const coverHtml = g.cover_art_path
-    ? `<img src="${resolveCover(g.cover_art_path)}" alt="${g.title}" loading="lazy" />`
+    ? `<img src="${resolveCover(g.cover_poster_path || g.cover_art_path)}" alt="${g.title}" loading="lazy" />`
     : `<div class="card-cover-placeholder"><span class="cover-letter">${g.title.charAt(0).toUpperCase()}</span></div>`;
 
   const progressHtml = g.progress_percent != null
@@ -208,8 +222,9 @@ function renderGridCard(g) {
 }
 
 function renderListCard(g) {
+  // Animated covers show their still poster frame in lists.
   const coverHtml = g.cover_art_path
-    ? `<img src="${resolveCover(g.cover_art_path)}" alt="${g.title}" loading="lazy" />`
+    ? `<img src="${resolveCover(g.cover_poster_path || g.cover_art_path)}" alt="${g.title}" loading="lazy" />`
     : `<div class="card-cover-placeholder"><span class="cover-letter">${g.title.charAt(0).toUpperCase()}</span></div>`;
 
   return `
@@ -225,7 +240,7 @@ function renderListCard(g) {
       <div class="list-card-right">
         <span class="status-badge ${statusClass(g.status)}" style="position:static">${statusLabel(g.status)}</span>
         <span class="card-rating" style="font-family:var(--font-mono);font-size:11px">${fmtRating(g.rating)}</span>
-        <span class="card-rating" style="font-family:var(--font-mono);font-size:11px;color:var(--text-3)">${fmtHours(g.playtime_hours)}</span>
+        <span class="card-rating" style="font-family:var(--font-mono);font-size:11px;color:var(--text-3)">${fmtPlaytime(g.playtime_minutes)}</span>
         <div class="list-card-actions">
           <button class="icon-btn" data-edit="${g.id}" title="Edit">
             <svg viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="1.5">
@@ -260,7 +275,7 @@ function openDetail(id) {
   const genreTags      = game.genres.map(g => `<span class="detail-tag">${g}</span>`).join("");
   const screenshotsHtml = game.screenshots.length
     ? `<div class="detail-screenshots">${game.screenshots.map(s =>
-        `<div class="detail-screenshot"><img src="${resolveCover(s)}" /></div>`).join("")}</div>`
+        `<div class="detail-screenshot" data-path="${s}" title="Show in folder"><img src="${resolveCover(s)}" /></div>`).join("")}</div>`
     : "";
   const notesHtml = game.notes ? `<div class="detail-notes">${game.notes}</div>` : "";
 
@@ -283,7 +298,7 @@ function openDetail(id) {
     </div>` : ""}
     <div class="detail-meta-grid">
       <div class="detail-meta-item"><label>Platform</label><span>${game.platform}</span></div>
-      <div class="detail-meta-item"><label>Playtime</label><span>${fmtHours(game.playtime_hours)}</span></div>
+      <div class="detail-meta-item"><label>Playtime</label><span>${fmtPlaytime(game.playtime_minutes)}</span></div>
       <div class="detail-meta-item"><label>Release</label><span>${fmtDate(game.release_date)}</span></div>
       <div class="detail-meta-item"><label>Developer</label><span>${game.developer || "—"}</span></div>
       <div class="detail-meta-item"><label>Publisher</label><span>${game.publisher || "—"}</span></div>
@@ -304,6 +319,9 @@ function openDetail(id) {
     }
   });
 
+  // Theme the panel with the cover's colours; without them the defaults apply.
+  $("detailPanel").style.setProperty("--accent", game.accent_color || "");
+  $("detailPanel").style.setProperty("--cover-tint", game.dominant_color || "");
   $("detailPanel").classList.add("open");
   $("overlay").classList.add("active");
 
@@ -315,6 +333,29 @@ function openDetail(id) {
     closeDetail();
     promptDelete(id);
   });
+  if (game.cover_art_path) {
+    const cover = $("detailContent").querySelector(".detail-cover");
+    cover.title = "Copy image";
+    cover.addEventListener("click", async () => {
+      try {
+        await invoke("copy_image_to_clipboard", { path: game.cover_art_path });
+        showToast("Cover copied", "success");
+      } catch (e) {
+        console.error("Copy failed:", e);
+        showToast("Could not copy the cover", "error");
+      }
+    });
+  }
+  $("detailContent").querySelectorAll(".detail-screenshot").forEach(shot => {
+    shot.addEventListener("click", async () => {
+      try {
+        await invoke("reveal_in_folder", { path: shot.dataset.path });
+      } catch (e) {
+        console.error("Reveal failed:", e);
+        showToast("Could not show the screenshot in its folder", "error");
+      }
+    });
+  });
 }
 
 function closeDetail() {
@@ -389,6 +430,11 @@ async function renderStats() {
     grid.innerHTML = `<div class="stat-card"><div class="stat-label">Error loading stats</div></div>`;
     return;
   }
+  // Counted on this computer only; the card is simply left out if it fails.
+  const habits = await invoke("get_usage_insights", { weeks: 8 }).catch(e => {
+    console.error("get_usage_insights failed:", e);
+    return null;
+  });
 
   const barList = (items, maxCount) => items.map((item, i) => `
     <div class="chart-bar-item">
@@ -407,6 +453,11 @@ async function renderStats() {
   const maxPlatform  = Math.max(1, ...stats.games_by_platform.map(x => x.count));
   const maxGenre     = Math.max(1, ...stats.games_by_genre.map(x => x.count));
   const maxFranchise = Math.max(1, ...stats.games_by_franchise.map(x => x.count));
+  const habitWeeks   = habits ? habits.weeks.map(w => ({
+    name: "Week of " + new Date(w.week_start + "T00:00").toLocaleDateString(undefined, { month: "short", day: "numeric" }),
+    count: w.games_added,
+  })) : [];
+  const maxHabitWeek = Math.max(1, ...habitWeeks.map(x => x.count));
 
   grid.innerHTML = `
     <div class="stat-card" style="animation-delay:0s">
@@ -415,7 +466,7 @@ async function renderStats() {
     </div>
     <div class="stat-card" style="animation-delay:0.05s">
       <div class="stat-label">Total Playtime</div>
-      <div class="stat-value">${Math.round(stats.total_playtime_hours)}<span style="font-size:16px;color:var(--text-3)">h</span></div>
+      <div class="stat-value">${Math.round(stats.total_playtime_minutes / 60)}<span style="font-size:16px;color:var(--text-3)">h</span></div>
     </div>
     <div class="stat-card" style="animation-delay:0.1s">
       <div class="stat-label">Avg Rating</div>
@@ -469,6 +520,19 @@ async function renderStats() {
           </div>`).join("")}
       </div>
     </div>` : ""}
+
+    ${habits && habits.tracking_since ? `
+    <div class="stat-card stat-card--wide" style="animation-delay:0.45s">
+      <div class="stat-label">Your Tracker Habits</div>
+      <div class="status-breakdown">
+        <div class="breakdown-item"><span class="label">Days used</span>  <span class="count">${habits.days_used}</span></div>
+        <div class="breakdown-item"><span class="label">Launches</span>   <span class="count">${habits.launches}</span></div>
+        <div class="breakdown-item"><span class="label">Games added</span><span class="count">${habits.games_added}</span></div>
+        <div class="breakdown-item"><span class="label">Edits</span>      <span class="count">${habits.edits}</span></div>
+      </div>
+      <div class="stat-sub">Games added per week · counted on this computer only, since ${habits.tracking_since}</div>
+      <div class="chart-bar-list">${barList(habitWeeks, maxHabitWeek)}</div>
+    </div>` : ""}
   `;
 
   requestAnimationFrame(() => {
@@ -523,7 +587,7 @@ function openModal(id = null) {
     $("f_publisher").value        = game.publisher || "";
     $("f_progress").value         = game.progress_percent ?? 0;
     $("progressVal").textContent  = `${Math.round(game.progress_percent ?? 0)}%`;
-    $("f_playtime").value         = game.playtime_hours ?? "";
+    $("f_playtime").value         = game.playtime_minutes != null ? +(game.playtime_minutes / 60).toFixed(2) : "";
     $("f_notes").value            = game.notes || "";
     $("f_cover_art_path").value   = game.cover_art_path || "";
 
@@ -730,7 +794,7 @@ $("gameForm").addEventListener("submit", async e => {
     platform:              $("f_platform").value.trim(),
     status,
     progress_percent:      parseFloat($("f_progress").value),
-    playtime_hours:        $("f_playtime").value ? parseFloat($("f_playtime").value) : null,
+    playtime_minutes:      $("f_playtime").value ? Math.round(parseFloat($("f_playtime").value) * 60) : null,
     rating:                state.formRating,
     notes:                 $("f_notes").value.trim() || null,
     cover_art_path:        $("f_cover_art_path").value || null,
@@ -765,10 +829,11 @@ $("gameForm").addEventListener("submit", async e => {
       showToast("Game added!", "success");
     }
     closeModal();
-    await refresh(true);
+    // The list refreshes itself when Rust broadcasts games://changed (see Boot)
   } catch (err) {
     console.error("save failed:", err);
-    showToast("Failed to save game", "error");
+    // Rust errors arrive as { code, message, field? } — validation messages are user-friendly
+    showToast(err?.code === "VALIDATION_FAILED" ? err.message : "Failed to save game", "error");
   } finally {
     $("submitBtn").disabled = false;
     $("submitBtn").textContent = state.editingGameId ? "Save Changes" : "Save Game";
@@ -800,7 +865,6 @@ $("confirmDelete").addEventListener("click", async () => {
     showToast("Game deleted", "info");
     $("confirmBackdrop").classList.add("hidden");
     state.pendingDeleteId = null;
-    await refresh(true);
   } catch (e) {
     console.error("delete failed:", e);
     showToast("Failed to delete game", "error");
@@ -925,6 +989,12 @@ async function init() {
   renderGames();
   renderPlatformFilters();
   renderGenreFilters();
+
+  // Rust emits this after every add/update/delete/import — from this window or any other
+  await listen("games://changed", async () => {
+    await refresh(true);
+    if (state.activeView === "stats") renderStats();
+  });
 }
 
 init();
\ No newline at end of file